@@ -0,0 +1,44 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Compares [`ErrTreePkg::new`] against [`ErrTreePkg::new_cached`].
+//!
+//! Run with `cargo bench --bench pkg_cache --features pkg_cache,tracing`.
+
+use std::{hint::black_box, time::Instant};
+
+use bare_err_tree::ErrTreePkg;
+use tracing::info_span;
+use tracing_error::ErrorLayer;
+use tracing_subscriber::{layer::SubscriberExt, Registry};
+
+const ITERS: u32 = 1_000_000;
+
+fn time(name: &str, mut func: impl FnMut() -> ErrTreePkg) {
+    // Warm up the cache and the span registry
+    for _ in 0..1_000 {
+        black_box(func());
+    }
+
+    let start = Instant::now();
+    for _ in 0..ITERS {
+        black_box(func());
+    }
+    let per_call = start.elapsed() / ITERS;
+    println!("{name}: {per_call:?} per call");
+}
+
+fn main() {
+    let subscriber = Registry::default().with(ErrorLayer::default());
+    tracing::subscriber::with_default(subscriber, || {
+        let _outer = info_span!("outer", id = 1).entered();
+        let _middle = info_span!("middle", id = 2).entered();
+        let _inner = info_span!("inner", id = 3).entered();
+
+        time("new", ErrTreePkg::new);
+        time("new_cached", ErrTreePkg::new_cached);
+    });
+}
@@ -0,0 +1,50 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Constructs and discards errors, as a parser's lookahead would.
+//!
+//! Compare `boxed` against the same tracking features without it, e.g.
+//! `cargo bench --bench pkg_new --features source_line` and
+//! `cargo bench --bench pkg_new --features source_line,boxed`.
+
+use std::{hint::black_box, time::Instant};
+
+use bare_err_tree::{err_tree, ErrTreePkg};
+
+const ITERS: u32 = 10_000_000;
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("expected {expected}")]
+struct Lookahead {
+    expected: char,
+}
+
+fn time<T>(name: &str, mut func: impl FnMut(u32) -> T) {
+    for idx in 0..1_000 {
+        black_box(func(idx));
+    }
+
+    let start = Instant::now();
+    for idx in 0..ITERS {
+        black_box(func(idx));
+    }
+    let per_call = start.elapsed().as_secs_f64() * 1e9 / f64::from(ITERS);
+    println!("{name}: {per_call:.2}ns per call");
+}
+
+fn main() {
+    time("ErrTreePkg::new", |_| ErrTreePkg::new());
+    time("speculative error", |idx| {
+        // Most alternatives fail and are dropped immediately
+        let res = if idx % 16 == 0 {
+            Ok(idx)
+        } else {
+            Err(Lookahead::_tree('x'))
+        };
+        res.is_ok()
+    });
+}
@@ -0,0 +1,61 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Renders shallow trees, the common case for an error path.
+//!
+//! Run with `cargo bench --bench render`. `FRONT_MAX` is sized for a deep
+//! limit, as binaries tend to pick a generous one, so fixed per-render costs
+//! show up against the small amount of output.
+
+use std::{hint::black_box, time::Instant};
+
+use bare_err_tree::{err_tree, front_max_for_depth, print_tree};
+
+const ITERS: u32 = 2_000_000;
+const FRONT_MAX: usize = front_max_for_depth(256);
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("{msg}")]
+struct Node {
+    msg: &'static str,
+    #[tree_iter_err]
+    children: Vec<Node>,
+}
+
+fn time(name: &str, tree: &Node) {
+    let mut out = String::new();
+    let render = |out: &mut String| {
+        out.clear();
+        print_tree::<FRONT_MAX, _, _>(black_box(tree), &mut *out).unwrap();
+        black_box(out.len());
+    };
+
+    for _ in 0..1_000 {
+        render(&mut out);
+    }
+
+    let start = Instant::now();
+    for _ in 0..ITERS {
+        render(&mut out);
+    }
+    let per_call = start.elapsed().as_secs_f64() * 1e9 / f64::from(ITERS);
+    println!("{name}: {per_call:.2}ns per render");
+}
+
+fn main() {
+    let leaf = Node::_tree("unexpected end of file", vec![]);
+    time("single leaf", &leaf);
+
+    let three_levels = Node::_tree(
+        "failed to load config",
+        vec![Node::_tree(
+            "failed to parse",
+            vec![Node::_tree("unexpected end of file", vec![])],
+        )],
+    );
+    time("3 levels", &three_levels);
+}
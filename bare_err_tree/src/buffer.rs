@@ -4,7 +4,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-use core::iter::FusedIterator;
+use core::{iter::FusedIterator, mem::MaybeUninit};
 
 /// Stores the most recent item.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -17,6 +17,7 @@ impl<I> From<I> for IterBuffer<I>
 where
     I: Iterator,
 {
+    #[inline]
     fn from(value: I) -> Self {
         Self {
             iter: value,
@@ -31,10 +32,19 @@ where
 {
     type Item = I::Item;
 
+    #[inline]
     fn next(&mut self) -> Option<Self::Item> {
         self.buffer = Some(self.iter.next()?);
         self.buffer.clone()
     }
+
+    /// Stores the returned item, as [`Self::next`] does. Skipped items are
+    /// never stored, and running out keeps the previously stored item.
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.buffer = Some(self.iter.nth(n)?);
+        self.buffer.clone()
+    }
 }
 
 impl<I> FusedIterator for IterBuffer<I> where I: Iterator<Item: Clone> + FusedIterator {}
@@ -43,6 +53,7 @@ impl<I> ExactSizeIterator for IterBuffer<I>
 where
     I: Iterator<Item: Clone> + ExactSizeIterator,
 {
+    #[inline]
     fn len(&self) -> usize {
         self.iter.len()
     }
@@ -53,11 +64,13 @@ where
     I: Iterator,
 {
     /// Returns the stored last value, if any, and voids it.
+    #[inline]
     pub fn take_stored(&mut self) -> Option<I::Item> {
         self.buffer.take()
     }
 
     #[allow(dead_code)]
+    #[inline]
     pub fn is_empty(&mut self) -> bool {
         if self.buffer.is_some() {
             false
@@ -77,6 +90,7 @@ pub enum OneOrTwo<T> {
 }
 
 impl<T> OneOrTwo<T> {
+    #[inline]
     pub fn as_slice(&self) -> &[T] {
         match self {
             Self::One(x) => x.as_slice(),
@@ -92,6 +106,7 @@ where
     /// Combines [`Iterator::next`] and [`Self::take_stored`].
     ///
     /// Only returns a value if there is a next value from the iterator.
+    #[inline]
     pub fn take_stored_and_next(&mut self) -> Option<OneOrTwo<I::Item>> {
         let next_val = self.iter.next()?;
 
@@ -102,3 +117,120 @@ where
         }
     }
 }
+
+/// Fixed size buffer that is only initialized as far as it has been written.
+///
+/// Rendering buffers are sized for `FRONT_MAX`, but shallow trees only use the
+/// start of them. Filling the whole buffer up front would dominate small
+/// renders.
+pub struct LazyBuf<T, const N: usize> {
+    #[cfg(not(feature = "heap_buffer"))]
+    buf: [MaybeUninit<T>; N],
+    #[cfg(feature = "heap_buffer")]
+    buf: alloc::boxed::Box<[MaybeUninit<T>]>,
+    /// Length of the initialized prefix of `buf`
+    init: usize,
+}
+
+impl<T: Copy, const N: usize> LazyBuf<T, N> {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(not(feature = "heap_buffer"))]
+            buf: [const { MaybeUninit::uninit() }; N],
+            #[cfg(feature = "heap_buffer")]
+            buf: alloc::boxed::Box::new_uninit_slice(N),
+            init: 0,
+        }
+    }
+
+    /// The written values before `end`.
+    #[inline]
+    pub fn prefix(&self, end: usize) -> &[T] {
+        let init = &self.buf[..end.min(self.init)];
+        // SAFETY: every value before `self.init` has been written
+        unsafe { &*(init as *const [MaybeUninit<T>] as *const [T]) }
+    }
+
+    /// Writes `values` starting at `start`.
+    ///
+    /// Panics if `start` would leave unwritten values before it, or if the
+    /// values do not fit.
+    #[inline]
+    pub fn write(&mut self, start: usize, values: &[T]) {
+        assert!(start <= self.init, "LazyBuf writes must be contiguous");
+        for (slot, value) in self.buf[start..start + values.len()].iter_mut().zip(values) {
+            slot.write(*value);
+        }
+        self.init = self.init.max(start + values.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits() -> IterBuffer<core::ops::RangeInclusive<u8>> {
+        (1..=5).into()
+    }
+
+    #[test]
+    fn nth_then_take_stored() {
+        let mut iter = digits();
+        assert_eq!(iter.nth(1), Some(2));
+        assert_eq!(iter.take_stored(), Some(2));
+        assert_eq!(iter.take_stored(), None);
+        assert_eq!(iter.next(), Some(3));
+    }
+
+    #[test]
+    fn nth_then_take_stored_and_next() {
+        let mut iter = digits();
+        assert_eq!(iter.nth(1), Some(2));
+        assert_eq!(iter.take_stored_and_next(), Some(OneOrTwo::Two([2, 3])));
+        assert_eq!(iter.nth(1), Some(5));
+        assert_eq!(iter.take_stored_and_next(), None);
+        assert_eq!(iter.take_stored(), Some(5));
+
+        let mut iter = digits();
+        assert_eq!(iter.nth(2), Some(3));
+        assert_eq!(iter.take_stored(), Some(3));
+        assert_eq!(iter.take_stored_and_next(), Some(OneOrTwo::One([4])));
+    }
+
+    #[test]
+    fn nth_past_end_keeps_stored() {
+        let mut iter = digits();
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.nth(10), None);
+        assert_eq!(iter.take_stored(), Some(1));
+    }
+
+    #[test]
+    fn nth_then_last() {
+        // Matches `apply_to_last_source`, which takes the stored item after
+        // running the sources out
+        let mut iter = digits();
+        assert_eq!(iter.nth(2), Some(3));
+        let _ = iter.by_ref().last();
+        assert_eq!(iter.take_stored(), Some(5));
+
+        let mut iter = digits();
+        assert_eq!(iter.nth(4), Some(5));
+        assert_eq!(iter.by_ref().last(), None);
+        assert_eq!(iter.take_stored(), Some(5));
+    }
+
+    #[test]
+    fn take_stored_after_nth_matches_next() {
+        for n in 0..7 {
+            let mut by_nth = digits();
+            let mut by_next = digits();
+            let nth = by_nth.nth(n);
+            let next = (0..=n).map(|_| by_next.next()).last().flatten();
+            assert_eq!(nth, next, "n = {n}");
+            if nth.is_some() {
+                assert_eq!(by_nth.take_stored(), by_next.take_stored(), "n = {n}");
+            }
+        }
+    }
+}
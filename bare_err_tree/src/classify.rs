@@ -0,0 +1,213 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Root cause categories, for counting errors by kind.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    cmp::Reverse,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{AsErrTree, ErrTree};
+
+/// Category of a root cause that no [`Classifier`] rule matches.
+pub const UNCLASSIFIED: &str = "other";
+
+/// Sources followed by [`classify_tree`] before treating the error reached as
+/// the root cause. Bounds the walk for errors that are their own source.
+const MAX_DEPTH: usize = 256;
+
+type Rule = Box<dyn Fn(&(dyn Error + 'static)) -> bool + Send + Sync>;
+
+/// Ordered rules mapping an error to a category name.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{classify_tree, Classifier, UNCLASSIFIED};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("request failed")]
+/// struct Request(#[source] io::Error);
+///
+/// let classifier = Classifier::new().rule("network", |e| {
+///     e.downcast_ref::<io::Error>()
+///         .is_some_and(|e| e.kind() == io::ErrorKind::TimedOut)
+/// });
+///
+/// let err = Request(io::ErrorKind::TimedOut.into());
+/// assert_eq!(classify_tree(&(&err as &dyn Error), &classifier), "network");
+///
+/// let err = Request(io::ErrorKind::NotFound.into());
+/// assert_eq!(classify_tree(&(&err as &dyn Error), &classifier), UNCLASSIFIED);
+/// ```
+#[derive(Default)]
+pub struct Classifier {
+    rules: Vec<(&'static str, Rule)>,
+}
+
+impl fmt::Debug for Classifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.rules.iter().map(|(class, _)| class))
+            .finish()
+    }
+}
+
+impl Classifier {
+    /// A classifier without rules, which classifies everything as
+    /// [`UNCLASSIFIED`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a rule, checked after every rule before it.
+    pub fn rule<F>(mut self, class: &'static str, rule: F) -> Self
+    where
+        F: Fn(&(dyn Error + 'static)) -> bool + Send + Sync + 'static,
+    {
+        self.rules.push((class, Box::new(rule)));
+        self
+    }
+
+    /// The category of the first matching rule, or [`UNCLASSIFIED`].
+    pub fn classify(&self, err: &(dyn Error + 'static)) -> &'static str {
+        self.rules
+            .iter()
+            .find(|(_, rule)| rule(err))
+            .map_or(UNCLASSIFIED, |(class, _)| class)
+    }
+}
+
+/// Classifies the root cause of `tree`, found by following the first source
+/// of each error.
+///
+/// Rules can only see errors that are known to be `'static` for downcasting
+/// (see [`ErrTree::downcast_ref`]). A root cause held directly by an
+/// [`err_tree`][`crate::err_tree`] field is [`UNCLASSIFIED`], although its
+/// own sources are not.
+pub fn classify_tree<E: AsErrTree + ?Sized>(tree: &E, classifier: &Classifier) -> &'static str {
+    let mut class = UNCLASSIFIED;
+    tree.as_err_tree(&mut |tree| class = root_class(tree, classifier, 0));
+    class
+}
+
+fn root_class(tree: ErrTree<'_>, classifier: &Classifier, depth: usize) -> &'static str {
+    let downcast = tree.downcast();
+
+    if depth < MAX_DEPTH {
+        if let Some(source) = tree.sources().next() {
+            let mut class = UNCLASSIFIED;
+            source.as_err_tree(&mut |tree| class = root_class(tree, classifier, depth + 1));
+            return class;
+        }
+    }
+
+    downcast.map_or(UNCLASSIFIED, |err| classifier.classify(err))
+}
+
+/// Running counts of error categories, for a final summary.
+///
+/// ```rust
+/// use bare_err_tree::{ClassCounter, UNCLASSIFIED};
+///
+/// let mut counter = ClassCounter::new();
+/// counter.add("network");
+/// counter.add(UNCLASSIFIED);
+/// counter.add("config");
+/// counter.add("network");
+///
+/// assert_eq!(counter.count("network"), 2);
+/// assert_eq!(
+///     counter.summary().to_string(),
+///     "2 network errors, 1 config error, 1 other"
+/// );
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ClassCounter {
+    counts: Vec<(&'static str, usize)>,
+}
+
+impl ClassCounter {
+    /// A counter with no errors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts one error of `class`.
+    pub fn add(&mut self, class: &'static str) {
+        match self
+            .counts
+            .iter_mut()
+            .find(|(existing, _)| *existing == class)
+        {
+            Some((_, count)) => *count += 1,
+            None => self.counts.push((class, 1)),
+        }
+    }
+
+    /// Counts `tree` by [`classify_tree`], returning its category.
+    pub fn add_tree<E: AsErrTree + ?Sized>(
+        &mut self,
+        tree: &E,
+        classifier: &Classifier,
+    ) -> &'static str {
+        let class = classify_tree(tree, classifier);
+        self.add(class);
+        class
+    }
+
+    /// Errors counted for `class`.
+    pub fn count(&self, class: &str) -> usize {
+        self.counts
+            .iter()
+            .find(|(existing, _)| *existing == class)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// Errors counted across all categories.
+    pub fn total(&self) -> usize {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Displays every category as `<count> <class> error(s)`, most common
+    /// first, with [`UNCLASSIFIED`] last as `<count> other`.
+    ///
+    /// Displays `no errors` when nothing was counted.
+    pub fn summary(&self) -> ClassSummary<'_> {
+        ClassSummary(self)
+    }
+}
+
+/// Display of a [`ClassCounter`], produced by [`ClassCounter::summary`].
+#[derive(Debug, Clone, Copy)]
+pub struct ClassSummary<'a>(&'a ClassCounter);
+
+impl Display for ClassSummary<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut counts: Vec<_> = self.0.counts.iter().collect();
+        // Stable, so ties keep the order they were first counted in
+        counts.sort_by_key(|(class, count)| (*class == UNCLASSIFIED, Reverse(*count)));
+
+        if counts.is_empty() {
+            return f.write_str("no errors");
+        }
+
+        for (idx, (class, count)) in counts.into_iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+            if *class == UNCLASSIFIED {
+                write!(f, "{count} {class}")?;
+            } else {
+                let plural = if *count == 1 { "" } else { "s" };
+                write!(f, "{count} {class} error{plural}")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/*!
+Rendering for reports that are not [`Error`](core::error::Error)s.
+
+An already collected report (e.g. received from another process) can
+implement [`ErrTreeFormattable`] and render with [`fmt_tree`], without
+building stand-in error objects. With `tracing`, spans are passed to the
+formatter as [`TraceSpan`]s from [`ErrTreeFormattable::apply_trace`].
+
+```rust
+use core::{fmt, iter::Empty};
+
+use bare_err_tree::{
+    custom_fmt::{fmt_tree, ErrTreeFormattable},
+    front_max_for_depth,
+};
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+struct Report {
+    msg: String,
+    sources: Vec<Report>,
+}
+
+impl ErrTreeFormattable for Report {
+    fn apply_msg<W: fmt::Write>(&self, mut f: W) -> fmt::Result {
+        f.write_str(&self.msg)
+    }
+
+    type Source<'a> = &'a mut Report;
+
+    fn sources_empty(&mut self) -> bool {
+        self.sources.is_empty()
+    }
+
+    fn apply_to_leading_sources<F>(&mut self, func: F) -> fmt::Result
+    where
+        F: FnMut(Self::Source<'_>) -> fmt::Result,
+    {
+        let leading = self.sources.len().saturating_sub(1);
+        self.sources[..leading].iter_mut().try_for_each(func)
+    }
+
+    fn apply_to_last_source<F>(&mut self, func: F) -> fmt::Result
+    where
+        F: FnMut(Self::Source<'_>) -> fmt::Result,
+    {
+        self.sources.last_mut().map_or(Ok(()), func)
+    }
+
+    type TraceSpanId = ();
+    type TraceSpanIter<'a> = Empty<char>;
+}
+
+let report = Report {
+    msg: "worker failed".to_string(),
+    sources: vec![Report {
+        msg: "disk full".to_string(),
+        sources: vec![],
+    }],
+};
+
+let mut out = String::new();
+fmt_tree::<FRONT_MAX, _, _>(report, &mut out).unwrap();
+# #[cfg(not(feature = "unix_color"))]
+assert_eq!(out, "worker failed\n│\n╰─▶ disk full");
+```
+*/
+
+pub use crate::fmt_logic::{fmt_tree, ErrTreeFormattable};
+
+#[cfg(feature = "tracing")]
+pub use crate::fmt_logic::TraceSpan;
@@ -0,0 +1,207 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! GraphViz DOT and Mermaid diagrams of an error tree.
+
+use core::fmt::{self, Write};
+
+use crate::{snapshot::snapshot, AsErrTree, TreeSnapshot};
+
+/// Default maximum label length, in characters, for [`tree_to_dot`] and
+/// [`tree_to_mermaid`].
+pub const DEFAULT_LABEL_MAX: usize = 80;
+
+/// Renders the tree as a GraphViz DOT digraph.
+///
+/// Each error is a node labelled with the first line of its message, with its
+/// tracked location (`source_line`) as a tooltip. Edges run from each error to
+/// its sources. Node ids are pre-order indices, so the output is deterministic.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::tree_to_dot;
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+///
+/// let mut out = String::new();
+/// tree_to_dot(&(&err as &dyn Error), &mut out).unwrap();
+/// assert_eq!(
+///     out,
+///     "digraph {\n    \
+///         n0 [label=\"outer\"];\n    \
+///         n1 [label=\"unexpected end of file\"];\n    \
+///         n0 -> n1;\n\
+///     }"
+/// );
+/// ```
+pub fn tree_to_dot<E, W>(tree: &E, formatter: W) -> fmt::Result
+where
+    E: AsErrTree + ?Sized,
+    W: Write,
+{
+    tree_to_dot_with(tree, DEFAULT_LABEL_MAX, formatter)
+}
+
+/// [`tree_to_dot`], with labels cut to at most `label_max` characters.
+///
+/// A cut label ends with `…`.
+pub fn tree_to_dot_with<E, W>(tree: &E, label_max: usize, mut formatter: W) -> fmt::Result
+where
+    E: AsErrTree + ?Sized,
+    W: Write,
+{
+    formatter.write_str("digraph {\n")?;
+    walk(&snapshot(tree), &mut 0, None, &mut |id, parent, node| {
+        write!(formatter, "    n{id} [label=\"")?;
+        write_label(&mut DotEscape(&mut formatter), &node.msg, label_max)?;
+        formatter.write_char('"')?;
+        #[cfg(feature = "source_line")]
+        if let Some(location) = &node.location {
+            formatter.write_str(", tooltip=\"")?;
+            DotEscape(&mut formatter).write_str(location)?;
+            formatter.write_char('"')?;
+        }
+        formatter.write_str("];\n")?;
+
+        if let Some(parent) = parent {
+            writeln!(formatter, "    n{parent} -> n{id};")?;
+        }
+        Ok(())
+    })?;
+    formatter.write_char('}')
+}
+
+/// Renders the tree as a Mermaid `graph TD` flowchart.
+///
+/// Has the same nodes and edges as [`tree_to_dot`]. Mermaid has no plain
+/// tooltips, so a tracked location is a second line of the label instead.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::tree_to_mermaid;
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+///
+/// let mut out = String::new();
+/// tree_to_mermaid(&(&err as &dyn Error), &mut out).unwrap();
+/// assert_eq!(
+///     out,
+///     "graph TD\n    \
+///         n0[\"outer\"]\n    \
+///         n1[\"unexpected end of file\"]\n    \
+///         n0 --> n1"
+/// );
+/// ```
+pub fn tree_to_mermaid<E, W>(tree: &E, formatter: W) -> fmt::Result
+where
+    E: AsErrTree + ?Sized,
+    W: Write,
+{
+    tree_to_mermaid_with(tree, DEFAULT_LABEL_MAX, formatter)
+}
+
+/// [`tree_to_mermaid`], with labels cut to at most `label_max` characters.
+///
+/// A cut label ends with `…`.
+pub fn tree_to_mermaid_with<E, W>(tree: &E, label_max: usize, mut formatter: W) -> fmt::Result
+where
+    E: AsErrTree + ?Sized,
+    W: Write,
+{
+    formatter.write_str("graph TD")?;
+    walk(&snapshot(tree), &mut 0, None, &mut |id, parent, node| {
+        write!(formatter, "\n    n{id}[\"")?;
+        write_label(&mut MermaidEscape(&mut formatter), &node.msg, label_max)?;
+        #[cfg(feature = "source_line")]
+        if let Some(location) = &node.location {
+            formatter.write_str("<br/>at ")?;
+            MermaidEscape(&mut formatter).write_str(location)?;
+        }
+        formatter.write_str("\"]")?;
+
+        if let Some(parent) = parent {
+            write!(formatter, "\n    n{parent} --> n{id}")?;
+        }
+        Ok(())
+    })
+}
+
+/// Visits `node` and its sources in pre-order, with their ids and parent ids.
+fn walk(
+    node: &TreeSnapshot,
+    next_id: &mut usize,
+    parent: Option<usize>,
+    visit: &mut dyn FnMut(usize, Option<usize>, &TreeSnapshot) -> fmt::Result,
+) -> fmt::Result {
+    let id = *next_id;
+    *next_id += 1;
+    visit(id, parent, node)?;
+    node.children
+        .iter()
+        .try_for_each(|child| walk(child, next_id, Some(id), visit))
+}
+
+/// Writes the first line of `msg`, cut to `label_max` characters.
+fn write_label<W: Write>(formatter: &mut W, msg: &str, label_max: usize) -> fmt::Result {
+    let line = msg.lines().next().unwrap_or_default();
+    match line.char_indices().nth(label_max) {
+        None => formatter.write_str(line),
+        Some(_) => {
+            let end = line
+                .char_indices()
+                .nth(label_max.saturating_sub(1))
+                .map_or(line.len(), |(idx, _)| idx);
+            formatter.write_str(&line[..end])?;
+            formatter.write_char('…')
+        }
+    }
+}
+
+/// Escapes a DOT quoted string.
+struct DotEscape<W>(W);
+
+impl<W: Write> Write for DotEscape<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut rest = s;
+        while let Some(idx) = rest.find(['"', '\\']) {
+            self.0.write_str(&rest[..idx])?;
+            self.0.write_char('\\')?;
+            self.0.write_str(&rest[idx..=idx])?;
+            rest = &rest[idx + 1..];
+        }
+        self.0.write_str(rest)
+    }
+}
+
+/// Escapes a Mermaid quoted label with entity codes.
+struct MermaidEscape<W>(W);
+
+impl<W: Write> Write for MermaidEscape<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut rest = s;
+        while let Some(idx) = rest.find(['"', '#', '[', ']', '<', '>']) {
+            self.0.write_str(&rest[..idx])?;
+            self.0.write_str(match rest.as_bytes()[idx] {
+                b'"' => "#quot;",
+                b'#' => "#35;",
+                b'[' => "#91;",
+                b']' => "#93;",
+                b'<' => "#lt;",
+                _ => "#gt;",
+            })?;
+            rest = &rest[idx + 1..];
+        }
+        self.0.write_str(rest)
+    }
+}
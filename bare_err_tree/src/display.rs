@@ -0,0 +1,141 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! [`Display`] adapter for trees.
+
+use core::{
+    fmt::{self, Debug, Display, Formatter},
+    ops::Deref,
+};
+
+use crate::{print_tree, AsErrTree, DEFAULT_FRONT_MAX};
+
+/// Displays the wrapped error with [`print_tree`].
+///
+/// [`Debug`] displays the same tree, so this can be used where only
+/// [`Debug`] is printed (e.g. [`Result::expect`] and [`Result::unwrap`]).
+///
+/// ```rust
+/// # use std::io;
+/// use bare_err_tree::{front_max_for_depth, ErrTreeDisplay, WrapErr};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("request failed")]
+/// struct Request(#[source] io::Error);
+///
+/// let err = WrapErr(Request(io::ErrorKind::TimedOut.into()));
+/// let display = ErrTreeDisplay::<_, { front_max_for_depth(10) }>::new(err);
+/// assert!(display.to_string().ends_with("╰─▶ timed out"));
+/// assert_eq!(format!("{display:?}"), display.to_string());
+///
+/// let err: WrapErr<Request> = display.into_inner();
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ErrTreeDisplay<E, const FRONT_MAX: usize>(pub E);
+
+/// [`ErrTreeDisplay`] with [`DEFAULT_FRONT_MAX`].
+///
+/// ```rust
+/// # use std::io;
+/// use bare_err_tree::{DefaultTreeDisplay, WrapErr};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("request failed")]
+/// struct Request(#[source] io::Error);
+///
+/// let err = WrapErr(Request(io::ErrorKind::TimedOut.into()));
+/// let display = DefaultTreeDisplay::new(err);
+/// assert!(display.to_string().ends_with("╰─▶ timed out"));
+/// ```
+pub type DefaultTreeDisplay<E> = ErrTreeDisplay<E, DEFAULT_FRONT_MAX>;
+
+impl<E, const FRONT_MAX: usize> ErrTreeDisplay<E, FRONT_MAX> {
+    /// Takes ownership of `tree`.
+    pub fn new(tree: E) -> Self {
+        Self(tree)
+    }
+
+    /// Borrows `tree`, so it remains usable after display.
+    ///
+    /// ```rust
+    /// # use std::io;
+    /// use bare_err_tree::{front_max_for_depth, ErrTreeDisplay, WrapErr};
+    ///
+    /// #[derive(Debug, thiserror::Error)]
+    /// #[error("request failed")]
+    /// struct Request(#[source] io::Error);
+    ///
+    /// let err = WrapErr(Request(io::ErrorKind::TimedOut.into()));
+    /// let display = ErrTreeDisplay::<_, { front_max_for_depth(10) }>::borrowed(&err);
+    /// assert!(display.to_string().starts_with("request failed"));
+    /// assert_eq!(err.0.to_string(), "request failed");
+    /// ```
+    pub fn borrowed(tree: &E) -> ErrTreeDisplay<&E, FRONT_MAX> {
+        ErrTreeDisplay(tree)
+    }
+
+    /// Borrows the target of a smart pointer (e.g. [`Arc`][`std::sync::Arc`]).
+    ///
+    /// ```rust
+    /// # use std::{io, sync::Arc};
+    /// use bare_err_tree::{front_max_for_depth, ErrTreeDisplay, WrapErr};
+    ///
+    /// #[derive(Debug, thiserror::Error)]
+    /// #[error("request failed")]
+    /// struct Request(#[source] io::Error);
+    ///
+    /// let err = Arc::new(WrapErr(Request(io::ErrorKind::TimedOut.into())));
+    /// let display = ErrTreeDisplay::<_, { front_max_for_depth(10) }>::from_deref(&err);
+    /// assert!(display.to_string().ends_with("╰─▶ timed out"));
+    /// ```
+    pub fn from_deref<D>(tree: &D) -> ErrTreeDisplay<&E, FRONT_MAX>
+    where
+        D: Deref<Target = E> + ?Sized,
+    {
+        ErrTreeDisplay(tree)
+    }
+
+    /// Returns the wrapped error.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+/// Wraps an owned error, e.g. for [`Result::map_err`].
+///
+/// ```rust
+/// # use std::io;
+/// use bare_err_tree::{front_max_for_depth, ErrTreeDisplay, WrapErr};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("request failed")]
+/// struct Request(#[source] io::Error);
+///
+/// let res: Result<(), _> = Err(WrapErr(Request(io::ErrorKind::TimedOut.into())));
+/// let err = res
+///     .map_err(ErrTreeDisplay::<_, { front_max_for_depth(10) }>::from)
+///     .unwrap_err();
+/// assert!(format!("{err:?}").ends_with("╰─▶ timed out"));
+/// ```
+impl<E, const FRONT_MAX: usize> From<E> for ErrTreeDisplay<E, FRONT_MAX> {
+    fn from(tree: E) -> Self {
+        Self(tree)
+    }
+}
+
+impl<E: AsErrTree, const FRONT_MAX: usize> Display for ErrTreeDisplay<E, FRONT_MAX> {
+    #[track_caller]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        print_tree::<FRONT_MAX, _, _>(&self.0, f)
+    }
+}
+
+impl<E: AsErrTree, const FRONT_MAX: usize> Debug for ErrTreeDisplay<E, FRONT_MAX> {
+    #[track_caller]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
@@ -48,6 +48,27 @@ impl<E: Error> WrapErr<E> {
     }
 }
 
+impl<'a, E: Error + ?Sized> WrapErr<&'a E> {
+    /// [`Self::tree`] for a reference to an unsized error, such as the items
+    /// of a `&[&dyn Error]`.
+    ///
+    /// ```rust
+    /// use std::{error::Error, io};
+    /// use bare_err_tree::{AsErrTree, WrapErr};
+    ///
+    /// let err = io::Error::last_os_error();
+    /// let errs: &[&dyn Error] = &[&err];
+    ///
+    /// let trees: Vec<&dyn AsErrTree> = errs.iter().map(WrapErr::tree_dyn).collect();
+    /// ```
+    pub fn tree_dyn<'b>(err: &'b &'a E) -> &'b dyn AsErrTree
+    where
+        'a: 'b,
+    {
+        Self::tree(err)
+    }
+}
+
 impl<E: Error> AsErrTree for WrapErr<E> {
     fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
         match self.0.source() {
@@ -59,3 +80,208 @@ impl<E: Error> AsErrTree for WrapErr<E> {
         }
     }
 }
+
+/// Renders an [`AsErrTree`] with [`ErrTree::collapsed`].
+///
+/// Used by `#[tree_err(collapse)]`. Like [`WrapErr`], this converts `&T`
+/// via the transparent repr guarantees.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{front_max_for_depth, print_tree, Collapse};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::ErrorKind::UnexpectedEof.into());
+/// let err = &err as &dyn Error;
+///
+/// let mut out = String::new();
+/// print_tree::<{ front_max_for_depth(10) }, _, _>(Collapse::tree(&err), &mut out).unwrap();
+/// assert_eq!(out, "outer\n│\n╰─ 1 nested error(s) collapsed");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Collapse<T: ?Sized>(pub T);
+
+impl<T: AsErrTree + ?Sized> From<&T> for &Collapse<T> {
+    fn from(value: &T) -> Self {
+        unsafe { &*(value as *const T as *const Collapse<T>) }
+    }
+}
+
+impl<T: AsErrTree + ?Sized> Collapse<T> {
+    pub fn wrap(tree: &T) -> &Self {
+        tree.into()
+    }
+}
+
+impl<T: AsErrTree> Collapse<T> {
+    pub fn tree(tree: &T) -> &dyn AsErrTree {
+        Self::wrap(tree) as &dyn AsErrTree
+    }
+}
+
+impl<T: AsErrTree + ?Sized> AsErrTree for Collapse<T> {
+    fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+        self.0.as_err_tree(&mut |tree| (func)(tree.collapsed()))
+    }
+}
+
+/// Owns a fixed number of sources computed inside [`AsErrTree::as_err_tree`].
+///
+/// [`ErrTree`] borrows its sources for the duration of the callback, so
+/// sources created on the fly need an owner that outlives the call. Build
+/// this before calling `func`, then pass [`Self::as_tree_sources`] as the
+/// source iterator.
+///
+/// ```rust
+/// # use std::{error::Error, fmt::{self, Display, Formatter}};
+/// use bare_err_tree::{
+///     front_max_for_depth, print_tree, simple_err_tree, AsErrTree, ErrTree, OwnedSources,
+/// };
+///
+/// #[derive(Debug)]
+/// struct Code(u32);
+/// simple_err_tree!(Code);
+///
+/// #[derive(Debug)]
+/// struct Codes([u32; 2]);
+///
+/// impl AsErrTree for Codes {
+///     fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+///         let sources = OwnedSources(self.0.map(Code));
+///         (func)(ErrTree::no_pkg(self, &mut sources.as_tree_sources()))
+///     }
+/// }
+/// # impl Error for Codes {}
+/// # impl Display for Codes {
+/// #     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+/// #         write!(f, "codes")
+/// #     }
+/// # }
+///
+/// let mut out = String::new();
+/// print_tree::<{ front_max_for_depth(10) }, _, _>(Codes([1, 2]), &mut out).unwrap();
+/// assert_eq!(out, "codes\n│\n├─▶ 1\n│\n╰─▶ 2");
+/// ```
+///
+/// Without an owner, the computed sources are dropped while still borrowed:
+///
+/// ```compile_fail
+/// # use std::{error::Error, fmt::{self, Display, Formatter}};
+/// # use bare_err_tree::{simple_err_tree, AsErrTree, ErrTree};
+/// # #[derive(Debug)]
+/// # struct Code(u32);
+/// # simple_err_tree!(Code);
+/// # #[derive(Debug)]
+/// # struct Codes([u32; 2]);
+/// impl AsErrTree for Codes {
+///     fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+///         let mut sources = self.0.iter().map(|c| &Code(*c) as &dyn AsErrTree);
+///         (func)(ErrTree::no_pkg(self, &mut sources))
+///     }
+/// }
+/// # impl Error for Codes {}
+/// # impl Display for Codes {
+/// #     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+/// #         write!(f, "codes")
+/// #     }
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OwnedSources<const N: usize, T: AsErrTree>(pub [T; N]);
+
+impl<const N: usize, T: AsErrTree> OwnedSources<N, T> {
+    /// Borrows each owned source as a tree source.
+    pub fn as_tree_sources(&self) -> OwnedSourcesIter<'_, T> {
+        OwnedSourcesIter(self.0.iter())
+    }
+}
+
+impl<const N: usize, T: AsErrTree> From<[T; N]> for OwnedSources<N, T> {
+    fn from(value: [T; N]) -> Self {
+        Self(value)
+    }
+}
+
+/// Iterator from [`OwnedSources::as_tree_sources`].
+///
+/// A named type, rather than `impl Iterator`, so the borrow checker can see
+/// it has no destructor holding onto the sources.
+#[derive(Debug, Clone)]
+pub struct OwnedSourcesIter<'a, T>(core::slice::Iter<'a, T>);
+
+impl<'a, T: AsErrTree> Iterator for OwnedSourcesIter<'a, T> {
+    type Item = &'a dyn AsErrTree;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|source| source as &dyn AsErrTree)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T: AsErrTree> ExactSizeIterator for OwnedSourcesIter<'_, T> {}
+
+/// Heap backed [`OwnedSources`], for a number of sources only known at
+/// runtime.
+///
+/// ```rust
+/// # use std::{error::Error, fmt::{self, Display, Formatter}};
+/// use bare_err_tree::{
+///     front_max_for_depth, print_tree, simple_err_tree, AsErrTree, ErrTree, OwnedSourcesVec,
+/// };
+///
+/// #[derive(Debug)]
+/// struct Code(u32);
+/// simple_err_tree!(Code);
+///
+/// #[derive(Debug)]
+/// struct Codes(Vec<u32>);
+///
+/// impl AsErrTree for Codes {
+///     fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+///         let sources: OwnedSourcesVec<_> = self.0.iter().copied().map(Code).collect();
+///         (func)(ErrTree::no_pkg(self, &mut sources.as_tree_sources()))
+///     }
+/// }
+/// # impl Error for Codes {}
+/// # impl Display for Codes {
+/// #     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+/// #         write!(f, "codes")
+/// #     }
+/// # }
+///
+/// let mut out = String::new();
+/// print_tree::<{ front_max_for_depth(10) }, _, _>(Codes(vec![1, 2, 3]), &mut out).unwrap();
+/// assert_eq!(out, "codes\n│\n├─▶ 1\n│\n├─▶ 2\n│\n╰─▶ 3");
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OwnedSourcesVec<T: AsErrTree>(pub alloc::vec::Vec<T>);
+
+#[cfg(feature = "alloc")]
+impl<T: AsErrTree> OwnedSourcesVec<T> {
+    /// Borrows each owned source as a tree source.
+    pub fn as_tree_sources(&self) -> OwnedSourcesIter<'_, T> {
+        OwnedSourcesIter(self.0.iter())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: AsErrTree> From<alloc::vec::Vec<T>> for OwnedSourcesVec<T> {
+    fn from(value: alloc::vec::Vec<T>) -> Self {
+        Self(value)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: AsErrTree> FromIterator<T> for OwnedSourcesVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
@@ -5,12 +5,14 @@
  */
 
 use core::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
+    error::Error,
     fmt::{self, Display, Formatter, Write},
+    ops::ControlFlow,
     str::{self, Chars},
 };
 
-use crate::ErrTree;
+use crate::{AsErrTree, ErrTree, FormatOptions, LazyBuf, Progress};
 
 pub(crate) struct ErrTreeFmtWrap<const FRONT_MAX: usize, T>(RefCell<T>);
 
@@ -29,75 +31,649 @@ where
     }
 }
 
-pub(crate) fn fmt_tree<const FRONT_MAX: usize, T, W>(tree: T, f: &mut W) -> fmt::Result
+/// Renders `tree` with the default [`FormatOptions`].
+///
+/// `FRONT_MAX` limits the rendered depth, as in [`print_tree`](crate::print_tree).
+#[inline]
+pub fn fmt_tree<const FRONT_MAX: usize, T, W>(tree: T, f: &mut W) -> fmt::Result
+where
+    T: ErrTreeFormattable,
+    W: fmt::Write + ?Sized,
+{
+    fmt_tree_with::<FRONT_MAX, _, _>(
+        tree,
+        FormatOptions::new(),
+        &mut [],
+        &mut [],
+        &mut [],
+        &RenderProgress::new(None, 0),
+        f,
+    )
+}
+
+/// [`fmt_tree`] with [`FormatOptions`].
+#[inline]
+pub(crate) fn fmt_tree_opts<const FRONT_MAX: usize, T, W>(
+    tree: T,
+    options: FormatOptions,
+    source_lines: &mut [Option<SourceLineCount>],
+    f: &mut W,
+) -> fmt::Result
+where
+    T: ErrTreeFormattable,
+    W: fmt::Write + ?Sized,
+{
+    fmt_tree_progress::<FRONT_MAX, _, _>(tree, options, None, None, source_lines, f)
+}
+
+/// [`fmt_tree_opts`], polling `check` as nodes are rendered and writing
+/// messages through `msg_filter`.
+///
+/// `source_lines` holds the counts from [`with_source_lines`].
+pub(crate) fn fmt_tree_progress<'c, const FRONT_MAX: usize, T, W>(
+    tree: T,
+    options: FormatOptions,
+    check: Option<&'c mut dyn FnMut(Progress) -> ControlFlow<()>>,
+    msg_filter: Option<MsgFilter<'c>>,
+    source_lines: &'c mut [Option<SourceLineCount>],
+    f: &mut W,
+) -> fmt::Result
+where
+    T: ErrTreeFormattable,
+    W: fmt::Write + ?Sized,
+{
+    let mut progress = RenderProgress::new(check, options.progress_every);
+    progress.msg_filter = msg_filter;
+    progress.source_lines = RefCell::new(source_lines);
+    progress.max_nodes = options.max_nodes.max(1);
+
+    if options.numbered {
+        #[cfg(not(feature = "heap_buffer"))]
+        let mut numbers = [0; FRONT_MAX];
+
+        #[cfg(feature = "heap_buffer")]
+        let mut numbers = alloc::vec![0; FRONT_MAX].into_boxed_slice();
+
+        fmt_tree_numbered::<FRONT_MAX, _, _>(tree, options, &mut numbers, &progress, f)
+    } else {
+        fmt_tree_numbered::<FRONT_MAX, _, _>(tree, options, &mut [], &progress, f)
+    }
+}
+
+/// [`fmt_tree_opts`] after allocating the numbering buffer, if needed.
+fn fmt_tree_numbered<const FRONT_MAX: usize, T, W>(
+    tree: T,
+    options: FormatOptions,
+    numbers: &mut [usize],
+    progress: &RenderProgress<'_>,
+    f: &mut W,
+) -> fmt::Result
+where
+    T: ErrTreeFormattable,
+    W: fmt::Write + ?Sized,
+{
+    if options.dedup_by_identity || options.mark_shared {
+        fmt_tree_dedup::<FRONT_MAX, _, _>(tree, options, numbers, progress, f)
+    } else {
+        fmt_tree_with::<FRONT_MAX, _, _>(tree, options, &mut [], &mut [], numbers, progress, f)
+    }
+}
+
+/// [`fmt_tree`] with identity tracking buffers, sized to `FRONT_MAX`.
+fn fmt_tree_dedup<const FRONT_MAX: usize, T, W>(
+    tree: T,
+    options: FormatOptions,
+    numbers: &mut [usize],
+    progress: &RenderProgress<'_>,
+    f: &mut W,
+) -> fmt::Result
 where
     T: ErrTreeFormattable,
     W: fmt::Write + ?Sized,
 {
     #[cfg(not(feature = "heap_buffer"))]
-    let mut front_lines = [0; FRONT_MAX];
+    let (mut seen, mut path) = ([None; FRONT_MAX], [None; FRONT_MAX]);
 
     #[cfg(feature = "heap_buffer")]
-    let mut front_lines = alloc::vec![0; FRONT_MAX].into_boxed_slice();
+    let (mut seen, mut path) = (
+        alloc::vec![None; FRONT_MAX].into_boxed_slice(),
+        alloc::vec![None; FRONT_MAX].into_boxed_slice(),
+    );
+
+    fmt_tree_with::<FRONT_MAX, _, _>(tree, options, &mut seen, &mut path, numbers, progress, f)
+}
 
+fn fmt_tree_with<const FRONT_MAX: usize, T, W>(
+    tree: T,
+    options: FormatOptions,
+    seen: &mut [Option<NodeId>],
+    path: &mut [Option<NodeId>],
+    numbers: &mut [usize],
+    progress: &RenderProgress<'_>,
+    f: &mut W,
+) -> fmt::Result
+where
+    T: ErrTreeFormattable,
+    W: fmt::Write + ?Sized,
+{
+    let mut front_lines = LazyBuf::new();
+
+    // Shared by every node, so trace dedup costs `FRONT_MAX` slots of each
+    // per render rather than per level of depth
     #[cfg(all(not(feature = "heap_buffer"), feature = "tracing"))]
-    let mut found_traces: [_; FRONT_MAX] = core::array::from_fn(|_| None);
+    let (mut found_traces, mut repeated_traces): ([_; FRONT_MAX], _) =
+        (core::array::from_fn(|_| None), [0; FRONT_MAX]);
 
     #[cfg(all(feature = "heap_buffer", feature = "tracing"))]
-    let mut found_traces = core::iter::repeat_with(|| None)
-        .take(FRONT_MAX)
-        .collect::<alloc::vec::Vec<_>>()
-        .into_boxed_slice();
+    let (mut found_traces, mut repeated_traces) = (
+        core::iter::repeat_with(|| None)
+            .take(FRONT_MAX)
+            .collect::<alloc::vec::Vec<_>>()
+            .into_boxed_slice(),
+        alloc::vec![0; FRONT_MAX].into_boxed_slice(),
+    );
+
+    let mut ancestors = LazyBuf::new();
+
+    if let Some(root) = numbers.first_mut() {
+        *root = 1;
+    }
 
-    ErrTreeFmt::<FRONT_MAX, _> {
+    let truncated_at = Cell::new(None);
+    let mut f = TrailingNewline {
+        formatter: f,
+        newline: false,
+        bytes: &progress.bytes,
+    };
+
+    let res = ErrTreeFmt::<FRONT_MAX, _> {
         tree,
         scratch_fill: 0,
         front_lines: &mut front_lines,
 
         #[cfg(feature = "tracing")]
         found_traces: &mut found_traces,
+        #[cfg(feature = "tracing")]
+        repeated_traces: &mut repeated_traces,
+        ancestors: &mut ancestors,
+
+        options,
+        depth: 0,
+        seen,
+        path,
+        numbers,
+        truncated_at: &truncated_at,
+        progress,
+        #[cfg(feature = "timestamp")]
+        parent_time: None,
+    }
+    .fmt(&mut f);
+
+    // Cancelled between nodes, so only the final line is missing
+    if progress.cancelled.get() {
+        if !f.newline {
+            f.write_char('\n')?;
+        }
+        return write!(
+            f,
+            "… rendering cancelled after {} nodes",
+            progress.nodes.get()
+        );
+    }
+    if progress.exhausted.get() {
+        if !f.newline {
+            f.write_char('\n')?;
+        }
+        return write!(f, "… node budget ({}) exhausted", progress.max_nodes);
+    }
+    res?;
+
+    if let Some(depth) = truncated_at.get() {
+        if !f.newline {
+            f.write_char('\n')?;
+        }
+        write!(
+            f,
+            "-- tree truncated at depth {depth}; increase FRONT_MAX (currently supports {} levels) --",
+            crate::depth_for_front_max(FRONT_MAX)
+        )?;
+    }
+    Ok(())
+}
+
+/// Node and byte counts for a render, polling an optional cancellation check.
+pub(crate) struct RenderProgress<'c> {
+    nodes: Cell<usize>,
+    bytes: Cell<usize>,
+    next_check: Cell<usize>,
+    every: usize,
+    check: RefCell<Option<&'c mut dyn FnMut(Progress) -> ControlFlow<()>>>,
+    cancelled: Cell<bool>,
+    /// Errors reached so far, including the root
+    spent: Cell<usize>,
+    /// See [`FormatOptions::max_nodes`]
+    max_nodes: usize,
+    exhausted: Cell<bool>,
+    /// Writes every message in place of the node, when set
+    msg_filter: Option<MsgFilter<'c>>,
+    /// See [`FormatOptions::dedup_source_lines`]
+    source_lines: RefCell<&'c mut [Option<SourceLineCount>]>,
+}
+
+impl<'c> RenderProgress<'c> {
+    pub fn new(
+        check: Option<&'c mut dyn FnMut(Progress) -> ControlFlow<()>>,
+        every: usize,
+    ) -> Self {
+        let every = every.max(1);
+        Self {
+            nodes: Cell::new(0),
+            bytes: Cell::new(0),
+            next_check: Cell::new(every),
+            every,
+            check: RefCell::new(check),
+            cancelled: Cell::new(false),
+            spent: Cell::new(1),
+            max_nodes: usize::MAX,
+            exhausted: Cell::new(false),
+            msg_filter: None,
+            source_lines: RefCell::new(&mut []),
+        }
+    }
+
+    /// Runs the check if due, failing the render when it breaks.
+    fn poll(&self) -> fmt::Result {
+        if let Some(check) = self.check.borrow_mut().as_mut() {
+            let nodes_rendered = self.nodes.get();
+            if nodes_rendered >= self.next_check.get() {
+                self.next_check.set(nodes_rendered + self.every);
+                let progress = Progress {
+                    nodes_rendered,
+                    bytes_written: self.bytes.get(),
+                };
+                if check(progress).is_break() {
+                    self.cancelled.set(true);
+                    return Err(fmt::Error);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts a source against the node budget, failing the render when it
+    /// is used up.
+    fn spend(&self) -> fmt::Result {
+        if self.spent.get() >= self.max_nodes {
+            self.exhausted.set(true);
+            return Err(fmt::Error);
+        }
+        self.spent.set(self.spent.get() + 1);
+        Ok(())
     }
-    .fmt(f)
 }
 
+/// Writes a node's message from its error, e.g. to redact secrets.
+pub(crate) type MsgFilter<'f> = &'f dyn Fn(&mut dyn fmt::Write, &dyn Error) -> fmt::Result;
+
+/// A node's message as an [`Error`], for a [`MsgFilter`] on a node without
+/// an error of its own.
+struct MsgError<'a, T: ?Sized>(&'a T);
+
+impl<T: ErrTreeFormattable + ?Sized> Display for MsgError<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.apply_msg(f)
+    }
+}
+
+impl<T: ErrTreeFormattable + ?Sized> fmt::Debug for MsgError<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl<T: ErrTreeFormattable + ?Sized> Error for MsgError<'_, T> {}
+
+/// Address and size of an error object.
+pub(crate) type NodeId = (usize, usize);
+
+/// Address and length of a source line's file, then its line and column.
+pub(crate) type SourceLineId = (usize, usize, u32, u32);
+
+/// A source line and the number of errors reporting it, zeroed once it has
+/// been rendered.
+pub(crate) type SourceLineCount = (SourceLineId, usize);
+
+/// Pointer to an error object, compared with its vtable so that a newtype
+/// sharing its field's address is not mistaken for the field.
+///
+/// Never dereferenced, so the erased lifetime is not observable. Vtables are
+/// not guaranteed unique, so a cycle may render an extra lap before matching.
+pub(crate) type CycleId = *const (dyn Error + 'static);
+
+/// One span of a node's `tracing` backtrace.
+///
+/// Passed to the callback of [`ErrTreeFormattable::apply_trace`]. Text is
+/// given as character iterators, so a backend can decode it while rendering
+/// instead of building strings first.
+///
+/// ```rust
+/// # #[cfg(feature = "custom_fmt")]
+/// # {
+/// use core::{fmt, str::Chars};
+///
+/// use bare_err_tree::{
+///     custom_fmt::{fmt_tree, ErrTreeFormattable, TraceSpan},
+///     front_max_for_depth,
+/// };
+///
+/// /// A span, as stored by a custom backend.
+/// struct Span {
+///     id: u64,
+///     target: &'static str,
+///     name: &'static str,
+///     fields: &'static str,
+///     line: Option<u32>,
+/// }
+///
+/// struct Report {
+///     msg: &'static str,
+///     spans: Vec<Span>,
+/// }
+///
+/// impl ErrTreeFormattable for Report {
+///     fn apply_msg<W: fmt::Write>(&self, mut f: W) -> fmt::Result {
+///         f.write_str(self.msg)
+///     }
+///
+///     type Source<'a> = &'a mut Report;
+///
+///     fn sources_empty(&mut self) -> bool {
+///         true
+///     }
+///
+///     fn apply_to_leading_sources<F>(&mut self, _func: F) -> fmt::Result {
+///         Ok(())
+///     }
+///
+///     fn apply_to_last_source<F>(&mut self, _func: F) -> fmt::Result {
+///         Ok(())
+///     }
+///
+///     fn trace_empty(&self) -> bool {
+///         self.spans.is_empty()
+///     }
+///
+///     type TraceSpanId = u64;
+///     type TraceSpanIter<'a> = Chars<'a>;
+///
+///     fn apply_trace<F>(&self, mut func: F) -> fmt::Result
+///     where
+///         F: FnMut(TraceSpan<Self::TraceSpanId, Self::TraceSpanIter<'_>>) -> fmt::Result,
+///     {
+///         for span in &self.spans {
+///             let mut trace_span = TraceSpan::new(
+///                 span.id,
+///                 span.target.chars(),
+///                 span.name.chars(),
+///                 span.fields.chars(),
+///             );
+///             if let Some(line) = span.line {
+///                 trace_span = trace_span.with_location("src/main.rs".chars(), line);
+///             }
+///             func(trace_span)?;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let report = Report {
+///     msg: "request failed",
+///     spans: vec![Span {
+///         id: 1,
+///         target: "server",
+///         name: "handle",
+///         fields: "id=7",
+///         line: Some(42),
+///     }],
+/// };
+///
+/// let mut out = String::new();
+/// fmt_tree::<{ front_max_for_depth(10) }, _, _>(report, &mut out).unwrap();
+/// # #[cfg(not(feature = "unix_color"))]
+/// assert_eq!(
+///     out,
+///     "request failed
+/// │
+/// ├─ tracing frame 0 => server::handle with id=7
+/// │        at src/main.rs:42"
+/// );
+/// # }
+/// ```
 #[cfg(feature = "tracing")]
-pub(crate) struct TraceSpan<T: Eq, CharIter> {
+pub struct TraceSpan<T: Eq, CharIter> {
+    /// Deduplicates spans across the tree. Spans with an identifier already
+    /// rendered higher in the tree are listed as duplicates.
     pub identifier: T,
+    /// Target of the span, usually its module path.
     pub target: CharIter,
+    /// Name of the span.
     pub name: CharIter,
+    /// Recorded fields of the span, e.g. `id=7`.
     pub fields: CharIter,
+    /// File and line of the span.
     pub location: Option<(CharIter, u32)>,
 }
 
-pub(crate) trait ErrTreeFormattable {
+#[cfg(all(feature = "tracing", feature = "custom_fmt"))]
+impl<T: Eq, CharIter> TraceSpan<T, CharIter> {
+    /// A span without a location.
+    pub fn new(identifier: T, target: CharIter, name: CharIter, fields: CharIter) -> Self {
+        Self {
+            identifier,
+            target,
+            name,
+            fields,
+            location: None,
+        }
+    }
+
+    /// Sets the file and line of the span.
+    pub fn with_location(mut self, file: CharIter, line: u32) -> Self {
+        self.location = Some((file, line));
+        self
+    }
+}
+
+/// Deduplicates one node's trace against the frames in `found_traces`.
+///
+/// Unseen frames are recorded and passed to `on_new` with their frame index.
+/// Seen frames are stored in `repeated` by their frame index, and the number
+/// of repeats is returned. Frames past the end of either buffer are dropped.
+#[cfg(feature = "tracing")]
+pub(crate) fn dedup_node_traces<T, F>(
+    tree: &T,
+    found_traces: &mut [Option<T::TraceSpanId>],
+    repeated: &mut [usize],
+    mut on_new: F,
+) -> Result<usize, fmt::Error>
+where
+    T: ErrTreeFormattable,
+    F: FnMut(usize, TraceSpan<T::TraceSpanId, T::TraceSpanIter<'_>>) -> fmt::Result,
+{
+    let mut repeated_idx = 0;
+
+    tree.apply_trace(|trace_span| {
+        let pos_dup = found_traces
+            .iter()
+            .take_while(|x| x.is_some())
+            .flatten()
+            .position(|c| *c == trace_span.identifier);
+
+        if let Some(pos_dup) = pos_dup {
+            if let Some(slot) = repeated.get_mut(repeated_idx) {
+                *slot = pos_dup;
+                repeated_idx += 1;
+            }
+            Ok(())
+        } else {
+            let depth = found_traces.partition_point(|x| x.is_some());
+            if let Some(slot) = found_traces.get_mut(depth) {
+                *slot = Some(trace_span.identifier.clone());
+            }
+            (on_new)(depth, trace_span)
+        }
+    })?;
+
+    Ok(repeated_idx)
+}
+
+/// A node that can be rendered as an error tree.
+///
+/// Implemented for [`ErrTree`] and JSON reconstruction, and available to
+/// implement for reports that are not [`Error`]s with the `custom_fmt` feature.
+///
+/// Tracking methods default to reporting no data, so implementors compile
+/// regardless of the tracking features enabled elsewhere in the build. New
+/// provided methods may be added in minor releases, while required methods
+/// and associated types only change in breaking releases.
+pub trait ErrTreeFormattable {
+    /// Writes the error message.
     fn apply_msg<W: fmt::Write>(&self, f: W) -> fmt::Result;
 
+    /// Writes the error message through `filter`, which is given the error
+    /// behind this node.
+    ///
+    /// Defaults to an error displaying [`Self::apply_msg`], with no source.
+    fn apply_msg_filtered<W: fmt::Write>(
+        &self,
+        mut f: W,
+        filter: &dyn Fn(&mut dyn fmt::Write, &dyn Error) -> fmt::Result,
+    ) -> fmt::Result {
+        filter(&mut f, &MsgError(self))
+    }
+
+    /// Writes the error code as `[code] `, if there is one.
+    fn apply_code<W: fmt::Write>(&self, _f: W) -> fmt::Result {
+        Ok(())
+    }
+
+    /// A type name will be written by [`Self::apply_type_name`].
+    #[cfg(feature = "type_name")]
+    fn has_type_name(&self) -> bool {
+        false
+    }
+    /// Writes the name of the error's type, e.g. `my_crate::NotFound`.
+    #[cfg(feature = "type_name")]
+    fn apply_type_name<W: fmt::Write>(&self, _f: W) -> fmt::Result {
+        Ok(())
+    }
+
+    /// Called once per node, just before its message is rendered.
+    fn on_render(&self) {}
+
+    /// Render a count in place of nested errors.
+    fn is_collapsed(&self) -> bool {
+        false
+    }
+
+    /// Render a placeholder for sources that panic while producing their tree.
+    #[cfg(feature = "catch_panics")]
+    fn catch_source_panics(&mut self) {}
+
+    /// Number of sources, if known before rendering them.
+    fn sources_len(&self) -> Option<usize> {
+        None
+    }
+
+    /// Node type of the sources, usually `&'a mut Self` for owned reports.
     type Source<'a>: ErrTreeFormattable<TraceSpanId = Self::TraceSpanId>;
 
+    /// There are no sources to render.
     #[allow(unused)]
     fn sources_empty(&mut self) -> bool;
 
+    /// Calls `func` on every source but the last, in order.
     fn apply_to_leading_sources<F>(&mut self, func: F) -> fmt::Result
     where
         F: FnMut(Self::Source<'_>) -> fmt::Result;
+    /// Calls `func` on the last source, if there is one.
     fn apply_to_last_source<F>(&mut self, func: F) -> fmt::Result
     where
         F: FnMut(Self::Source<'_>) -> fmt::Result;
+
+    /// A source line will be written by [`Self::apply_source_line`].
     #[cfg(feature = "source_line")]
-    fn has_source_line(&self) -> bool;
+    fn has_source_line(&self) -> bool {
+        false
+    }
+    /// Writes the source line, e.g. `<path>:<line>:<col>`.
     #[cfg(feature = "source_line")]
-    fn apply_source_line<W: fmt::Write>(&self, f: W) -> fmt::Result;
+    fn apply_source_line<W: fmt::Write>(&self, _f: W) -> fmt::Result {
+        Ok(())
+    }
+    /// Writes the source line as `<path>:<line>`, for use in a link target.
+    #[cfg(all(feature = "source_line", feature = "hyperlinks"))]
+    fn apply_source_link<W: fmt::Write>(&self, _f: W) -> fmt::Result {
+        Ok(())
+    }
 
+    /// Creation time, in seconds since the UNIX epoch.
+    #[cfg(feature = "timestamp")]
+    fn timestamp(&self) -> Option<i64> {
+        None
+    }
+    /// Creation time, for [`FormatOptions::show_elapsed`].
+    ///
+    /// Defaults to [`Self::timestamp`], which only has whole seconds.
+    #[cfg(feature = "timestamp")]
+    fn creation_time(&self) -> Option<std::time::SystemTime> {
+        self.timestamp().map(crate::from_unix_secs)
+    }
+
+    /// There are no spans for [`Self::apply_trace`].
     #[cfg(feature = "tracing")]
-    fn trace_empty(&self) -> bool;
+    fn trace_empty(&self) -> bool {
+        true
+    }
 
-    type TraceSpanId: Eq;
+    /// Deduplicates `tracing` spans. `()` when there is no trace.
+    type TraceSpanId: Eq + Clone;
+    /// Characters of `tracing` span text, e.g. [`core::iter::Empty<char>`]
+    /// when there is no trace.
     type TraceSpanIter<'a>: IntoIterator<Item = char>;
 
+    /// Calls `func` on each span of the `tracing` backtrace.
     #[cfg(feature = "tracing")]
-    fn apply_trace<F>(&self, func: F) -> fmt::Result
+    fn apply_trace<F>(&self, _func: F) -> fmt::Result
     where
-        F: FnMut(TraceSpan<Self::TraceSpanId, Self::TraceSpanIter<'_>>) -> fmt::Result;
+        F: FnMut(TraceSpan<Self::TraceSpanId, Self::TraceSpanIter<'_>>) -> fmt::Result,
+    {
+        Ok(())
+    }
+
+    /// A captured backtrace provided through [`Error::provide`][`core::error::Error::provide`].
+    #[cfg(nightly_provider)]
+    fn provided_backtrace(&self) -> Option<&std::backtrace::Backtrace> {
+        None
+    }
+
+    /// Identity of the underlying error object, as its address and size, if
+    /// it has one. Repeated identities are rendered once.
+    fn identity(&self) -> Option<NodeId> {
+        None
+    }
+
+    /// Identity of the source line, for
+    /// [`FormatOptions::dedup_source_lines`].
+    #[cfg(feature = "source_line")]
+    fn source_line_id(&self) -> Option<SourceLineId> {
+        None
+    }
+
+    /// Pointer used to detect an error reporting itself as its own source.
+    fn cycle_id(&self) -> Option<CycleId> {
+        None
+    }
 }
 
 impl<T> ErrTreeFormattable for &mut T
@@ -108,6 +684,44 @@ where
         T::apply_msg(self, f)
     }
 
+    fn apply_msg_filtered<W: fmt::Write>(
+        &self,
+        f: W,
+        filter: &dyn Fn(&mut dyn fmt::Write, &dyn Error) -> fmt::Result,
+    ) -> fmt::Result {
+        T::apply_msg_filtered(self, f, filter)
+    }
+
+    fn apply_code<W: fmt::Write>(&self, f: W) -> fmt::Result {
+        T::apply_code(self, f)
+    }
+
+    #[cfg(feature = "type_name")]
+    fn has_type_name(&self) -> bool {
+        T::has_type_name(self)
+    }
+    #[cfg(feature = "type_name")]
+    fn apply_type_name<W: fmt::Write>(&self, f: W) -> fmt::Result {
+        T::apply_type_name(self, f)
+    }
+
+    fn on_render(&self) {
+        T::on_render(self)
+    }
+
+    fn is_collapsed(&self) -> bool {
+        T::is_collapsed(self)
+    }
+
+    #[cfg(feature = "catch_panics")]
+    fn catch_source_panics(&mut self) {
+        T::catch_source_panics(self)
+    }
+
+    fn sources_len(&self) -> Option<usize> {
+        T::sources_len(self)
+    }
+
     type Source<'a> = T::Source<'a>;
     fn sources_empty(&mut self) -> bool {
         T::sources_empty(self)
@@ -133,6 +747,23 @@ where
     fn apply_source_line<W: fmt::Write>(&self, f: W) -> fmt::Result {
         T::apply_source_line(self, f)
     }
+    #[cfg(all(feature = "source_line", feature = "hyperlinks"))]
+    fn apply_source_link<W: fmt::Write>(&self, f: W) -> fmt::Result {
+        T::apply_source_link(self, f)
+    }
+    #[cfg(feature = "source_line")]
+    fn source_line_id(&self) -> Option<SourceLineId> {
+        T::source_line_id(self)
+    }
+
+    #[cfg(feature = "timestamp")]
+    fn timestamp(&self) -> Option<i64> {
+        T::timestamp(self)
+    }
+    #[cfg(feature = "timestamp")]
+    fn creation_time(&self) -> Option<std::time::SystemTime> {
+        T::creation_time(self)
+    }
 
     #[cfg(feature = "tracing")]
     fn trace_empty(&self) -> bool {
@@ -149,11 +780,68 @@ where
     {
         T::apply_trace(self, func)
     }
+
+    #[cfg(nightly_provider)]
+    fn provided_backtrace(&self) -> Option<&std::backtrace::Backtrace> {
+        T::provided_backtrace(self)
+    }
+
+    fn identity(&self) -> Option<NodeId> {
+        T::identity(self)
+    }
+
+    fn cycle_id(&self) -> Option<CycleId> {
+        T::cycle_id(self)
+    }
 }
 
 impl ErrTreeFormattable for ErrTree<'_> {
-    fn apply_msg<W: fmt::Write>(&self, mut f: W) -> fmt::Result {
-        write!(f, "{}", self.inner)
+    fn apply_msg<W: fmt::Write>(&self, f: W) -> fmt::Result {
+        self.write_msg(f)
+    }
+
+    fn apply_msg_filtered<W: fmt::Write>(
+        &self,
+        mut f: W,
+        filter: &dyn Fn(&mut dyn fmt::Write, &dyn Error) -> fmt::Result,
+    ) -> fmt::Result {
+        // The filter sees the override as the message
+        match self.msg_override {
+            Some(_) => filter(&mut f, &MsgError(self)),
+            None => filter(&mut f, self.inner),
+        }
+    }
+
+    fn apply_code<W: fmt::Write>(&self, mut f: W) -> fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "[{code}] "),
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(feature = "type_name")]
+    fn has_type_name(&self) -> bool {
+        self.type_name.is_some()
+    }
+    #[cfg(feature = "type_name")]
+    fn apply_type_name<W: fmt::Write>(&self, mut f: W) -> fmt::Result {
+        match self.type_name {
+            Some(type_name) => f.write_str(type_name),
+            None => Ok(()),
+        }
+    }
+
+    fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    #[cfg(feature = "catch_panics")]
+    fn catch_source_panics(&mut self) {
+        self.catch_panics = true;
+    }
+
+    fn sources_len(&self) -> Option<usize> {
+        self.sources_len
     }
 
     type Source<'a> = ErrTree<'a>;
@@ -168,9 +856,13 @@ impl ErrTreeFormattable for ErrTree<'_> {
             let mut initial_iter = initial_slice.as_slice().iter().cloned();
             if let Some(mut source) = initial_iter.next() {
                 for next_source in initial_iter.chain(self.sources.by_ref()) {
-                    let mut res = Ok(());
-                    source.as_err_tree(&mut |tree| res = (func)(tree));
-                    res?;
+                    apply_source(
+                        source,
+                        self.sources_len.is_some(),
+                        #[cfg(feature = "catch_panics")]
+                        self.catch_panics,
+                        &mut func,
+                    )?;
                     source = next_source
                 }
             }
@@ -183,9 +875,13 @@ impl ErrTreeFormattable for ErrTree<'_> {
     {
         let _ = self.sources.by_ref().last();
         if let Some(source) = self.sources.take_stored() {
-            let mut res = Ok(());
-            source.as_err_tree(&mut |tree| res = (func)(tree));
-            res?;
+            apply_source(
+                source,
+                self.sources_len.is_some(),
+                #[cfg(feature = "catch_panics")]
+                self.catch_panics,
+                &mut func,
+            )?;
         }
         Ok(())
     }
@@ -203,6 +899,31 @@ impl ErrTreeFormattable for ErrTree<'_> {
         Ok(())
     }
 
+    #[cfg(all(feature = "source_line", feature = "hyperlinks"))]
+    fn apply_source_link<W: fmt::Write>(&self, mut f: W) -> fmt::Result {
+        if let Some(loc) = self.location {
+            write!(f, "{}:{}", loc.file(), loc.line())?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "source_line")]
+    fn source_line_id(&self) -> Option<SourceLineId> {
+        self.location.map(|loc| {
+            let file = loc.file();
+            (file.as_ptr() as usize, file.len(), loc.line(), loc.column())
+        })
+    }
+
+    #[cfg(feature = "timestamp")]
+    fn timestamp(&self) -> Option<i64> {
+        self.time.map(crate::unix_secs)
+    }
+    #[cfg(feature = "timestamp")]
+    fn creation_time(&self) -> Option<std::time::SystemTime> {
+        self.time
+    }
+
     #[cfg(feature = "tracing")]
     fn trace_empty(&self) -> bool {
         let mut empty = true;
@@ -247,16 +968,56 @@ impl ErrTreeFormattable for ErrTree<'_> {
             Ok(())
         }
     }
+
+    #[cfg(nightly_provider)]
+    fn provided_backtrace(&self) -> Option<&std::backtrace::Backtrace> {
+        use std::backtrace::{Backtrace, BacktraceStatus};
+
+        core::error::request_ref::<Backtrace>(self.inner)
+            .filter(|backtrace| backtrace.status() == BacktraceStatus::Captured)
+    }
+
+    fn identity(&self) -> Option<NodeId> {
+        // Zero-sized errors can share an address without being the same error
+        let size = core::mem::size_of_val(self.inner);
+        (size != 0).then_some(((self.inner as *const dyn Error).cast::<()>() as usize, size))
+    }
+
+    fn cycle_id(&self) -> Option<CycleId> {
+        // SAFETY: only the trait object lifetime changes, and the pointer is
+        // only ever compared.
+        Some(unsafe { core::mem::transmute::<*const (dyn Error + '_), CycleId>(self.inner) })
+    }
 }
 
-pub(crate) struct ErrTreeFmt<'a, const FRONT_MAX: usize, T: ErrTreeFormattable> {
+pub(crate) struct ErrTreeFmt<'a, 'c, const FRONT_MAX: usize, T: ErrTreeFormattable> {
     pub tree: T,
     pub scratch_fill: usize,
-    /// Most be initialized large enough to fit 6 x (max depth) bytes
-    pub front_lines: &'a mut [u8],
+    /// Glyph bytes of the front lines, up to `scratch_fill`
+    pub front_lines: &'a mut LazyBuf<u8, FRONT_MAX>,
 
     #[cfg(feature = "tracing")]
     pub found_traces: &'a mut [Option<T::TraceSpanId>],
+    /// Scratch for the repeated frames of the current node
+    #[cfg(feature = "tracing")]
+    pub repeated_traces: &'a mut [usize],
+    /// Errors from the root to the current node, indexed by depth
+    pub ancestors: &'a mut LazyBuf<Option<CycleId>, FRONT_MAX>,
+
+    pub options: FormatOptions,
+    pub depth: usize,
+    /// Every rendered identity, in render order
+    pub seen: &'a mut [Option<NodeId>],
+    /// Identities from the root to the current node, indexed by depth
+    pub path: &'a mut [Option<NodeId>],
+    /// Sibling positions from the root to the current node, indexed by depth
+    pub numbers: &'a mut [usize],
+    /// Deepest node with sources cut off by `FRONT_MAX`
+    pub truncated_at: &'a Cell<Option<usize>>,
+    pub progress: &'a RenderProgress<'c>,
+    /// Creation time of the parent, for [`FormatOptions::show_elapsed`]
+    #[cfg(feature = "timestamp")]
+    pub parent_time: Option<std::time::SystemTime>,
 }
 
 /// Workaround for lack of `const` in [`core::cmp::max`].
@@ -269,21 +1030,49 @@ const fn max_const(lhs: usize, rhs: usize) -> usize {
     }
 }
 
+/// Upper bound on [`FormatOptions::inline_fields`].
+#[cfg(feature = "tracing")]
+const INLINE_FIELDS_MAX: usize = 128;
+
 const CONTINUING: &str = "│   ";
 const DANGLING: &str = "    ";
-const MAX_CELL_LEN: usize = max_const(CONTINUING.len(), DANGLING.len());
+pub(crate) const MAX_CELL_LEN: usize = max_const(CONTINUING.len(), DANGLING.len());
+
+/// Glyphs for a line without an arrow, padded to the arrow width when aligned
+const fn connector(aligned: bool, last: bool) -> &'static str {
+    match (aligned, last) {
+        (false, false) => "├─ ",
+        (false, true) => "╰─ ",
+        (true, false) => "├── ",
+        (true, true) => "╰── ",
+    }
+}
+
+/// Lead-in for continuation lines under a connector
+#[cfg(any(feature = "tracing", nightly_provider))]
+const fn continuation(aligned: bool) -> &'static str {
+    if aligned {
+        "│     "
+    } else {
+        "│    "
+    }
+}
 
-impl<const FRONT_MAX: usize, T: ErrTreeFormattable> ErrTreeFmt<'_, FRONT_MAX, T> {
+impl<const FRONT_MAX: usize, T: ErrTreeFormattable> ErrTreeFmt<'_, '_, FRONT_MAX, T> {
     /// The front lines
     #[inline]
-    fn front_lines_str(front_lines: &[u8], scratch_fill: usize) -> &str {
-        str::from_utf8(&front_lines[..scratch_fill])
+    fn front_lines_str(front_lines: &LazyBuf<u8, FRONT_MAX>, scratch_fill: usize) -> &str {
+        str::from_utf8(front_lines.prefix(scratch_fill))
             .expect("All characters are static and guaranteed to be valid UTF-8")
     }
 
     /// Preamble arrow connections
     #[inline]
-    fn write_front_lines<W>(front_lines: &[u8], f: &mut W, scratch_fill: usize) -> fmt::Result
+    fn write_front_lines<W>(
+        front_lines: &LazyBuf<u8, FRONT_MAX>,
+        f: &mut W,
+        scratch_fill: usize,
+    ) -> fmt::Result
     where
         W: fmt::Write + ?Sized,
     {
@@ -293,19 +1082,41 @@ impl<const FRONT_MAX: usize, T: ErrTreeFormattable> ErrTreeFmt<'_, FRONT_MAX, T>
 
     /// Push in the correct fill characters
     #[inline]
-    fn add_front_line(front_lines: &mut [u8], last: bool, scratch_fill: usize) {
+    fn add_front_line(front_lines: &mut LazyBuf<u8, FRONT_MAX>, last: bool, scratch_fill: usize) {
         let chars: &str = if last { DANGLING } else { CONTINUING };
 
-        front_lines[scratch_fill..scratch_fill + chars.len()].copy_from_slice(chars.as_bytes());
+        front_lines.write(scratch_fill, chars.as_bytes());
     }
     #[cfg(feature = "tracing")]
     /// There is tracing after if the trace is nonempty
     fn tracing_after(&self) -> bool {
-        !self.tree.trace_empty()
+        !self.tree.trace_empty() || self.backtrace_after()
     }
 
     #[cfg(not(feature = "tracing"))]
     fn tracing_after(&self) -> bool {
+        self.backtrace_after()
+    }
+
+    #[cfg(all(feature = "source_line", feature = "timestamp"))]
+    /// There is a timestamp after the source line if one was captured
+    fn timestamp_after(&self) -> bool {
+        self.tree.timestamp().is_some()
+    }
+
+    #[cfg(all(feature = "source_line", not(feature = "timestamp")))]
+    fn timestamp_after(&self) -> bool {
+        false
+    }
+
+    #[cfg(nightly_provider)]
+    /// There is a backtrace after if the error provides a captured one
+    fn backtrace_after(&self) -> bool {
+        self.tree.provided_backtrace().is_some()
+    }
+
+    #[cfg(not(nightly_provider))]
+    fn backtrace_after(&self) -> bool {
         false
     }
 
@@ -314,21 +1125,81 @@ impl<const FRONT_MAX: usize, T: ErrTreeFormattable> ErrTreeFmt<'_, FRONT_MAX, T>
     where
         W: fmt::Write + ?Sized,
     {
-        if self.tree.has_source_line() {
+        let seen = self.take_source_line_count();
+        if self.tree.has_source_line() && seen != Some(0) {
             Self::write_front_lines(self.front_lines, f, self.scratch_fill)?;
 
-            if !tracing_after && self.tree.sources_empty() {
-                f.write_str("╰─ ")?;
-            } else {
-                f.write_str("├─ ")?;
+            let last = !tracing_after && self.tree.sources_empty();
+            f.write_str(connector(self.options.aligned, last))?;
+            f.write_str("at ")?;
+
+            #[cfg(feature = "hyperlinks")]
+            {
+                f.write_str("\x1b]8;;file://")?;
+                self.tree.apply_source_link(&mut *f)?;
+                f.write_str("\x1b\\")?;
             }
+
             if cfg!(feature = "unix_color") {
-                f.write_str("at \x1b[3m")?;
+                f.write_str("\x1b[3m")?;
                 self.tree.apply_source_line(&mut *f)?;
                 f.write_str("\x1b[0m")?;
             } else {
-                f.write_str("at ")?;
-                self.tree.apply_source_line(f)?;
+                self.tree.apply_source_line(&mut *f)?;
+            }
+
+            #[cfg(feature = "hyperlinks")]
+            f.write_str("\x1b]8;;\x1b\\")?;
+
+            if let Some(count @ 2..) = seen {
+                write!(f, " (seen {count} times)")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Occurrences of this error's source line in the tree, marking it as
+    /// rendered. `Some(0)` if an earlier error already rendered it.
+    #[cfg(feature = "source_line")]
+    fn take_source_line_count(&self) -> Option<usize> {
+        let id = self.tree.source_line_id()?;
+        let mut source_lines = self.progress.source_lines.borrow_mut();
+        let (_, count) = source_lines
+            .iter_mut()
+            .map_while(Option::as_mut)
+            .find(|(seen, _)| *seen == id)?;
+        Some(core::mem::take(count))
+    }
+
+    #[cfg(feature = "timestamp")]
+    fn timestamp<W>(&mut self, f: &mut W, tracing_after: bool) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        if let Some(secs) = self.tree.timestamp() {
+            let last = !tracing_after && self.tree.sources_empty();
+            let elapsed = self
+                .parent_time
+                .filter(|_| self.options.show_elapsed)
+                .zip(self.tree.creation_time())
+                .map(|(parent, own)| parent.duration_since(own));
+
+            Self::write_front_lines(self.front_lines, f, self.scratch_fill)?;
+            f.write_str(connector(self.options.aligned, last && elapsed.is_none()))?;
+            write!(f, "at {}", crate::Rfc3339(secs))?;
+
+            if let Some(elapsed) = elapsed {
+                Self::write_front_lines(self.front_lines, f, self.scratch_fill)?;
+                f.write_str(connector(self.options.aligned, last))?;
+                match elapsed {
+                    Ok(before) => write!(f, "{} before parent", crate::Elapsed(before))?,
+                    Err(after) => write!(
+                        f,
+                        "0ns before parent (clamped, created {} after it)",
+                        crate::Elapsed(after.duration())
+                    )?,
+                }
             }
         }
 
@@ -339,9 +1210,10 @@ impl<const FRONT_MAX: usize, T: ErrTreeFormattable> ErrTreeFmt<'_, FRONT_MAX, T>
     #[cfg(feature = "tracing")]
     fn tracing_field_fmt<I, W>(
         f: &mut W,
-        front_lines: &[u8],
+        front_lines: &LazyBuf<u8, FRONT_MAX>,
         fields: I,
         scratch_fill: usize,
+        aligned: bool,
     ) -> fmt::Result
     where
         I: IntoIterator<Item = char>,
@@ -356,7 +1228,7 @@ impl<const FRONT_MAX: usize, T: ErrTreeFormattable> ErrTreeFmt<'_, FRONT_MAX, T>
 
         let push_front = |f: &mut W, depth| {
             Self::write_front_lines(front_lines, f, scratch_fill)?;
-            f.write_str("│    ")?;
+            f.write_str(continuation(aligned))?;
             for _ in 0..depth {
                 f.write_str("  ")?;
             }
@@ -425,36 +1297,16 @@ impl<const FRONT_MAX: usize, T: ErrTreeFormattable> ErrTreeFmt<'_, FRONT_MAX, T>
             Self::write_front_lines(self.front_lines, f, self.scratch_fill)?;
             write!(f, "│")?;
 
-            #[cfg(all(not(feature = "heap_buffer"), feature = "tracing"))]
-            let mut repeated: [_; FRONT_MAX] = core::array::from_fn(|_| None);
-
-            #[cfg(all(feature = "heap_buffer", feature = "tracing"))]
-            let mut repeated = core::iter::repeat_with(|| None)
-                .take(FRONT_MAX)
-                .collect::<alloc::vec::Vec<_>>()
-                .into_boxed_slice();
-
-            let mut repeated_idx = 0;
-
-            self.tree.apply_trace(|trace_span| {
-                let pos_dup = self
-                    .found_traces
-                    .iter()
-                    .take_while(|x| x.is_some())
-                    .flatten()
-                    .position(|c| *c == trace_span.identifier);
-
-                if let Some(pos_dup) = pos_dup {
-                    repeated[repeated_idx] = Some(pos_dup);
-                    repeated_idx += 1;
-                } else {
-                    let depth = self.found_traces.partition_point(|x| x.is_some());
-                    if depth < self.found_traces.len() {
-                        self.found_traces[depth] = Some(trace_span.identifier);
-                    }
-
+            let aligned = self.options.aligned;
+            let inline_fields = self.options.inline_fields;
+            let repeated_idx = dedup_node_traces(
+                &self.tree,
+                self.found_traces,
+                self.repeated_traces,
+                |depth, trace_span| {
                     Self::write_front_lines(self.front_lines, f, self.scratch_fill)?;
-                    write!(f, "├─ tracing frame {} => ", depth)?;
+                    f.write_str(connector(aligned, false))?;
+                    write!(f, "tracing frame {} => ", depth)?;
                     //depth, trace_span.target, trace_span.name
                     for c in trace_span.target {
                         f.write_char(c)?
@@ -464,80 +1316,256 @@ impl<const FRONT_MAX: usize, T: ErrTreeFormattable> ErrTreeFmt<'_, FRONT_MAX, T>
                         f.write_char(c)?
                     }
 
-                    let mut fields = trace_span.fields.into_iter().peekable();
-                    if fields.peek().is_some() {
+                    // Read ahead far enough to tell if the fields fit inline
+                    let mut fields = trace_span.fields.into_iter();
+                    let mut head = ['\0'; INLINE_FIELDS_MAX];
+                    let head_max = inline_fields.min(INLINE_FIELDS_MAX);
+                    let mut head_len = 0;
+                    while head_len < head_max {
+                        match fields.next() {
+                            Some(c) => head[head_len] = c,
+                            None => break,
+                        }
+                        head_len += 1;
+                    }
+                    let head = &head[..head_len];
+                    let fits = head_len < head_max && !head.contains(&'\n');
+
+                    let mut fields = head.iter().copied().chain(fields).peekable();
+                    if fits && !head.is_empty() {
+                        f.write_str(" with ")?;
+                        for c in fields {
+                            f.write_char(c)?
+                        }
+                    } else if !fits && fields.peek().is_some() {
                         write!(f, " with")?;
-                        Self::tracing_field_fmt(f, self.front_lines, fields, self.scratch_fill)?;
+                        Self::tracing_field_fmt(
+                            f,
+                            self.front_lines,
+                            fields,
+                            self.scratch_fill,
+                            aligned,
+                        )?;
                     }
 
                     if let Some((file, line)) = trace_span.location {
                         Self::write_front_lines(self.front_lines, f, self.scratch_fill)?;
-                        f.write_str("│        at ")?;
+                        f.write_str(continuation(aligned))?;
+                        f.write_str("    at ")?;
                         for c in file {
                             f.write_char(c)?
                         }
                         f.write_char(':')?;
                         write!(f, "{line}")?;
                     };
-                };
 
-                Ok(())
-            })?;
+                    Ok(())
+                },
+            )?;
 
             if repeated_idx > 0 {
                 Self::write_front_lines(self.front_lines, f, self.scratch_fill)?;
-                if self.tree.sources_empty() {
-                    f.write_str("╰─ ")?;
-                } else {
-                    f.write_str("├─ ")?;
-                }
+                let last = !self.backtrace_after() && self.tree.sources_empty();
+                f.write_str(connector(aligned, last))?;
 
                 write!(f, "{} duplicate tracing frame(s): [", repeated_idx)?;
 
-                for idx in 0..repeated_idx - 1 {
-                    write!(f, "{}, ", repeated[idx].expect("Previously set as Some"))?;
+                let repeated = &self.repeated_traces[..repeated_idx];
+                for idx in &repeated[..repeated_idx - 1] {
+                    write!(f, "{}, ", idx)?;
                 }
 
-                write!(
-                    f,
-                    "{}]",
-                    repeated[repeated_idx - 1].expect("Previously set as Some")
-                )?;
+                write!(f, "{}]", repeated[repeated_idx - 1])?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(nightly_provider)]
+    fn provided_backtrace<W>(&mut self, f: &mut W) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        if let Some(backtrace) = self.tree.provided_backtrace() {
+            Self::write_front_lines(self.front_lines, f, self.scratch_fill)?;
+            f.write_char('│')?;
+            Self::write_front_lines(self.front_lines, f, self.scratch_fill)?;
+            f.write_str(connector(self.options.aligned, false))?;
+            f.write_str("backtrace (provided)")?;
+
+            let backtrace = std::string::ToString::to_string(backtrace);
+            for line in backtrace.lines() {
+                Self::write_front_lines(self.front_lines, f, self.scratch_fill)?;
+                f.write_str(continuation(self.options.aligned))?;
+                f.write_str(line)?;
             }
         }
         Ok(())
     }
 
+    /// This node was already rendered elsewhere, and is not its own ancestor.
+    fn is_shared(&self) -> bool {
+        let identity = self.tree.identity();
+        let ancestors = &self.path[..self.path.len().min(self.depth)];
+        identity.is_some() && self.seen.contains(&identity) && !ancestors.contains(&identity)
+    }
+
+    /// Writes the dotted position of the node at `depth`, e.g. `1.2.1 `
+    fn write_number<W>(numbers: &[usize], depth: usize, f: &mut W) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        if let Some((first, rest)) = numbers.get(..=depth).and_then(<[_]>::split_first) {
+            write!(f, "{first}")?;
+            for number in rest {
+                write!(f, ".{number}")?;
+            }
+            f.write_char(' ')?;
+        }
+        Ok(())
+    }
+
     #[allow(unused_mut)]
     fn fmt<W>(mut self, f: &mut W) -> fmt::Result
     where
         W: fmt::Write + ?Sized,
     {
-        self.tree.apply_msg(LeadingLineFormatter::new(
+        self.tree.on_render();
+        self.progress.nodes.set(self.progress.nodes.get() + 1);
+        let shared = self.options.mark_shared && self.is_shared();
+        if self.options.numbered {
+            Self::write_number(self.numbers, self.depth, f)?;
+        }
+        self.tree.apply_code(&mut *f)?;
+        let mut leading = LeadingLineFormatter::new(
             &mut *f,
             Self::front_lines_str(self.front_lines, self.scratch_fill),
-        ))?;
+        );
+        let sanitized = SanitizeFormatter {
+            formatter: &mut leading,
+            enabled: self.options.sanitize,
+        };
+        match self.progress.msg_filter {
+            Some(filter) => self.tree.apply_msg_filtered(sanitized, filter)?,
+            None => self.tree.apply_msg(sanitized)?,
+        }
+        #[cfg(feature = "type_name")]
+        if self.tree.has_type_name() {
+            if cfg!(feature = "unix_color") {
+                leading.write_str("  \x1b[2m[")?;
+                self.tree.apply_type_name(&mut leading)?;
+                leading.write_str("]\x1b[0m")?;
+            } else {
+                leading.write_str("  [")?;
+                self.tree.apply_type_name(&mut leading)?;
+                leading.write_char(']')?;
+            }
+        }
+        if shared {
+            leading.write_str(" (shared with an earlier occurrence)")?;
+        }
+        leading.finish()?;
 
         #[cfg_attr(
-            not(any(feature = "source_line", feature = "tracing")),
+            not(any(feature = "source_line", feature = "timestamp")),
             expect(unused_variables, reason = "only used to track for a tracing line")
         )]
         let tracing_after = self.tracing_after();
 
         #[cfg(feature = "source_line")]
-        self.source_line(f, tracing_after)?;
+        self.source_line(f, tracing_after || self.timestamp_after())?;
+
+        #[cfg(feature = "timestamp")]
+        self.timestamp(f, tracing_after)?;
 
         #[cfg(feature = "tracing")]
         self.tracing(f)?;
 
+        #[cfg(nightly_provider)]
+        self.provided_backtrace(f)?;
+
+        if self.depth < FRONT_MAX {
+            self.ancestors.write(self.depth, &[self.tree.cycle_id()]);
+        }
+
+        if self.options.dedup_by_identity || self.options.mark_shared {
+            let identity = self.tree.identity();
+            if let Some(slot) = self.path.get_mut(self.depth) {
+                *slot = identity;
+            }
+            if identity.is_some() && !self.seen.contains(&identity) {
+                if let Some(slot) = self.seen.iter_mut().find(|slot| slot.is_none()) {
+                    *slot = identity;
+                }
+            }
+        }
+
+        #[cfg(feature = "catch_panics")]
+        if self.options.catch_source_panics {
+            self.tree.catch_source_panics();
+        }
+
+        if self.tree.is_collapsed() && !self.options.expand_collapsed {
+            let max_depth = crate::depth_for_front_max(FRONT_MAX).saturating_sub(self.depth);
+            let nested = count_nested(&mut self.tree, max_depth)?;
+            if nested > 0 {
+                Self::write_front_lines(self.front_lines, f, self.scratch_fill)?;
+                f.write_char('│')?;
+                Self::write_front_lines(self.front_lines, f, self.scratch_fill)?;
+                f.write_str(connector(self.options.aligned, true))?;
+                write!(f, "{nested} nested error(s) collapsed")?;
+            }
+            return Ok(());
+        }
+
+        let options = self.options;
+        let depth = self.depth;
+        let truncated_at = self.truncated_at;
+        let progress = self.progress;
+        #[cfg(feature = "timestamp")]
+        let parent_time = self.tree.creation_time();
+        // Sources replaced by a single line, and whether it ends the list
+        let (elided, elided_last) = match (options.max_children, self.tree.sources_len()) {
+            (Some(max), Some(len)) if len > max => {
+                let first = max - max / 2;
+                (first..first + (len - max), max / 2 == 0)
+            }
+            _ => (0..0, false),
+        };
+        let mut position = 0;
         let mut source_fmt =
-            |front_lines: &mut [u8],
+            |front_lines: &mut LazyBuf<u8, FRONT_MAX>,
              scratch_fill: usize,
              #[cfg(feature = "tracing")] found_traces: &mut [Option<T::TraceSpanId>],
+             #[cfg(feature = "tracing")] repeated_traces: &mut [usize],
+             ancestors: &mut LazyBuf<Option<CycleId>, FRONT_MAX>,
+             seen: &mut [Option<NodeId>],
+             path: &mut [Option<NodeId>],
+             numbers: &mut [usize],
              source: T::Source<'_>,
              last: bool| {
-                Self::write_front_lines(front_lines, f, scratch_fill)?;
-                f.write_char('│')?;
+                progress.poll()?;
+
+                let index = position;
+                position += 1;
+                if elided.contains(&index) {
+                    if index != elided.start {
+                        return Ok(());
+                    }
+                    if !options.tight {
+                        Self::write_front_lines(front_lines, f, scratch_fill)?;
+                        f.write_char('│')?;
+                    }
+                    Self::write_front_lines(front_lines, f, scratch_fill)?;
+                    f.write_str(connector(options.aligned, elided_last))?;
+                    return write!(f, "… {} similar errors elided", elided.len());
+                }
+                progress.spend()?;
+
+                if !options.tight {
+                    Self::write_front_lines(front_lines, f, scratch_fill)?;
+                    f.write_char('│')?;
+                }
                 Self::write_front_lines(front_lines, f, scratch_fill)?;
 
                 if last {
@@ -546,6 +1574,50 @@ impl<const FRONT_MAX: usize, T: ErrTreeFormattable> ErrTreeFmt<'_, FRONT_MAX, T>
                     f.write_str("├─▶ ")?;
                 }
 
+                if let Some(slot) = numbers.get_mut(depth + 1) {
+                    *slot = position;
+                }
+
+                // Rendering would repeat an ancestor forever
+                let cycle = source.cycle_id().is_some_and(|id| {
+                    ancestors
+                        .prefix(depth + 1)
+                        .iter()
+                        .flatten()
+                        .any(|ancestor| core::ptr::eq(*ancestor, id))
+                });
+                if cycle {
+                    if options.numbered {
+                        Self::write_number(numbers, depth + 1, f)?;
+                    }
+                    return f.write_str("<cycle detected, see above>");
+                }
+
+                // Already rendered, and not an ancestor sharing its address
+                let duplicate = options.dedup_by_identity
+                    && source.identity().is_some_and(|identity| {
+                        let ancestors = &path[..path.len().min(depth + 1)];
+                        !ancestors.contains(&Some(identity)) && seen.contains(&Some(identity))
+                    });
+                if duplicate {
+                    if options.numbered {
+                        Self::write_number(numbers, depth + 1, f)?;
+                    }
+                    f.write_str("(see above: ")?;
+                    let sanitized = SanitizeFormatter {
+                        formatter: FirstLineFormatter {
+                            formatter: &mut *f,
+                            done: false,
+                        },
+                        enabled: options.sanitize,
+                    };
+                    match progress.msg_filter {
+                        Some(filter) => source.apply_msg_filtered(sanitized, filter)?,
+                        None => source.apply_msg(sanitized)?,
+                    }
+                    return f.write_char(')');
+                }
+
                 let additional_scratch = if last {
                     DANGLING.len()
                 } else {
@@ -559,13 +1631,33 @@ impl<const FRONT_MAX: usize, T: ErrTreeFormattable> ErrTreeFmt<'_, FRONT_MAX, T>
 
                     #[cfg(feature = "tracing")]
                     found_traces,
+                    #[cfg(feature = "tracing")]
+                    repeated_traces,
+                    ancestors,
+
+                    options,
+                    depth: depth + 1,
+                    seen,
+                    path,
+                    numbers,
+                    truncated_at,
+                    progress,
+                    #[cfg(feature = "timestamp")]
+                    parent_time,
                 }
                 .fmt(f)
             };
 
-        if self.scratch_fill + MAX_CELL_LEN >= FRONT_MAX {
+        // Compare against whole cells, so a trailing partial cell is never used
+        if self.scratch_fill + MAX_CELL_LEN
+            >= crate::front_max_for_depth(crate::depth_for_front_max(FRONT_MAX))
+        {
             // Stop printing deeper in the stack past this point
             writeln!(f, "{:.<1$}", "", MAX_CELL_LEN)?;
+            if !self.tree.sources_empty() {
+                self.truncated_at
+                    .set(self.truncated_at.get().max(Some(self.depth)));
+            }
         } else {
             // Normal operation
 
@@ -576,6 +1668,12 @@ impl<const FRONT_MAX: usize, T: ErrTreeFormattable> ErrTreeFmt<'_, FRONT_MAX, T>
                     self.scratch_fill,
                     #[cfg(feature = "tracing")]
                     self.found_traces,
+                    #[cfg(feature = "tracing")]
+                    self.repeated_traces,
+                    self.ancestors,
+                    self.seen,
+                    self.path,
+                    self.numbers,
                     source,
                     false,
                 )
@@ -588,6 +1686,12 @@ impl<const FRONT_MAX: usize, T: ErrTreeFormattable> ErrTreeFmt<'_, FRONT_MAX, T>
                     self.scratch_fill,
                     #[cfg(feature = "tracing")]
                     self.found_traces,
+                    #[cfg(feature = "tracing")]
+                    self.repeated_traces,
+                    self.ancestors,
+                    self.seen,
+                    self.path,
+                    self.numbers,
                     source,
                     true,
                 )
@@ -598,38 +1702,318 @@ impl<const FRONT_MAX: usize, T: ErrTreeFormattable> ErrTreeFmt<'_, FRONT_MAX, T>
     }
 }
 
+/// Calls `func` with the tree of `source`.
+///
+/// With `count`, the sources of `source` are counted first, and the count
+/// is passed on to its own sources (see [`FormatOptions::max_children`]).
+///
+/// With `catch_panics`, a panic in [`AsErrTree::as_err_tree`] before it calls
+/// `func` is rendered as [`SourcePanicked`]. Panics from rendering the tree
+/// itself still propagate.
+fn apply_source<F>(
+    source: &dyn AsErrTree,
+    count: bool,
+    #[cfg(feature = "catch_panics")] catch_panics: bool,
+    func: &mut F,
+) -> fmt::Result
+where
+    F: FnMut(ErrTree<'_>) -> fmt::Result,
+{
+    let mut res = Ok(());
+
+    #[cfg(feature = "catch_panics")]
+    if catch_panics {
+        use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+        let mut entered = false;
+        let caught = catch_unwind(AssertUnwindSafe(|| {
+            let sources_len = count.then(|| count_sources(source));
+            source.as_err_tree(&mut |mut tree| {
+                entered = true;
+                tree.sources_len = sources_len;
+                res = (func)(tree)
+            })
+        }));
+        return match caught {
+            Ok(()) => res,
+            Err(payload) if entered => resume_unwind(payload),
+            Err(_) => (func)(ErrTree::no_pkg(&SourcePanicked, &mut core::iter::empty())),
+        };
+    }
+
+    let sources_len = count.then(|| count_sources(source));
+    source.as_err_tree(&mut |mut tree| {
+        tree.sources_len = sources_len;
+        res = (func)(tree)
+    });
+    res
+}
+
+/// Placeholder for a source that panicked while producing its tree.
+#[cfg(feature = "catch_panics")]
+#[derive(Debug)]
+struct SourcePanicked;
+
+#[cfg(feature = "catch_panics")]
+impl Display for SourcePanicked {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("<source formatting panicked>")
+    }
+}
+
+#[cfg(feature = "catch_panics")]
+impl Error for SourcePanicked {}
+
+/// Counts the direct sources of `tree`, with an extra
+/// [`AsErrTree::as_err_tree`] call.
+pub(crate) fn count_sources<E: AsErrTree + ?Sized>(tree: &E) -> usize {
+    let mut count = 0;
+    tree.as_err_tree(&mut |tree| count = tree.sources().count());
+    count
+}
+
+/// Calls `func` with the source lines of `tree` counted, if
+/// [`FormatOptions::dedup_source_lines`] is set.
+///
+/// Buffers are sized to `FRONT_MAX`, matching the other per-render state.
+pub(crate) fn with_source_lines<const FRONT_MAX: usize, E, R>(
+    #[allow(unused)] tree: &E,
+    #[allow(unused)] options: FormatOptions,
+    func: impl FnOnce(&mut [Option<SourceLineCount>]) -> R,
+) -> R
+where
+    E: AsErrTree + ?Sized,
+{
+    #[cfg(feature = "source_line")]
+    if options.dedup_source_lines {
+        #[cfg(not(feature = "heap_buffer"))]
+        let mut source_lines = [None; FRONT_MAX];
+
+        #[cfg(feature = "heap_buffer")]
+        let mut source_lines = alloc::vec![None; FRONT_MAX].into_boxed_slice();
+
+        let max_depth = crate::depth_for_front_max(FRONT_MAX);
+        tree.as_err_tree(&mut |tree| {
+            count_source_lines(tree, options, &mut source_lines, max_depth)
+        });
+        return func(&mut source_lines);
+    }
+
+    func(&mut [])
+}
+
+/// Adds the source lines of `tree` and its sources, up to `max_depth` levels
+/// below it, to `source_lines`.
+#[cfg(feature = "source_line")]
+fn count_source_lines(
+    tree: ErrTree<'_>,
+    options: FormatOptions,
+    source_lines: &mut [Option<SourceLineCount>],
+    max_depth: usize,
+) {
+    if let Some(id) = tree.source_line_id() {
+        let len = source_lines.partition_point(Option::is_some);
+        match source_lines[..len]
+            .iter_mut()
+            .flatten()
+            .find(|(seen, _)| *seen == id)
+        {
+            Some((_, count)) => *count += 1,
+            None => {
+                if let Some(slot) = source_lines.get_mut(len) {
+                    *slot = Some((id, 1));
+                }
+            }
+        }
+    }
+
+    // Sources of collapsed errors are not rendered
+    if max_depth == 0 || (tree.collapsed && !options.expand_collapsed) {
+        return;
+    }
+    for source in tree.sources() {
+        source.as_err_tree(&mut |tree| {
+            count_source_lines(tree, options, source_lines, max_depth - 1)
+        });
+    }
+}
+
+/// Counts nested errors, up to `max_depth` levels below `tree`.
+fn count_nested<T: ErrTreeFormattable>(
+    tree: &mut T,
+    max_depth: usize,
+) -> Result<usize, fmt::Error> {
+    if max_depth == 0 {
+        return Ok(0);
+    }
+
+    let mut count = 0;
+    let mut visit = |mut source: T::Source<'_>| {
+        count += 1 + count_nested(&mut source, max_depth - 1)?;
+        Ok(())
+    };
+    tree.apply_to_leading_sources(&mut visit)?;
+    tree.apply_to_last_source(&mut visit)?;
+    Ok(count)
+}
+
+/// Tracks whether the output currently ends with a newline, and its length
+struct TrailingNewline<'a, F: ?Sized> {
+    formatter: &'a mut F,
+    newline: bool,
+    bytes: &'a Cell<usize>,
+}
+
+impl<F: Write + ?Sized> Write for TrailingNewline<'_, F> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if !s.is_empty() {
+            self.newline = s.ends_with('\n');
+            self.bytes.set(self.bytes.get() + s.len());
+        }
+        self.formatter.write_str(s)
+    }
+}
+
+/// Drops control characters other than newlines, when enabled
+struct SanitizeFormatter<F> {
+    formatter: F,
+    enabled: bool,
+}
+
+impl<F: Write> Write for SanitizeFormatter<F> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if !self.enabled {
+            return self.formatter.write_str(s);
+        }
+        for part in s.split(|c: char| c.is_control() && c != '\n') {
+            self.formatter.write_str(part)?;
+        }
+        Ok(())
+    }
+}
+
+/// Drops everything from the first newline on
+struct FirstLineFormatter<F> {
+    formatter: F,
+    done: bool,
+}
+
+impl<F: Write> Write for FirstLineFormatter<F> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.done {
+            return Ok(());
+        }
+        match s.split_once('\n') {
+            Some((line, _)) => {
+                self.done = true;
+                self.formatter.write_str(line)
+            }
+            None => self.formatter.write_str(s),
+        }
+    }
+}
+
 /// Injects the newline leader
+///
+/// The leader for each line is deferred until that line's content arrives,
+/// so lines that already begin with the exact leader (e.g. a rendered tree
+/// used as a message) are not prefixed a second time. Content matching the
+/// start of the leader is held back until it either completes or diverges,
+/// as messages may be written one character at a time.
 struct LeadingLineFormatter<'a, F> {
     formatter: F,
     leading: &'a str,
+    /// A newline was written without its leader yet.
+    pending: bool,
+    /// Bytes of the leader matched and held back on a pending line.
+    matched: usize,
 }
 
 impl<'a, F> LeadingLineFormatter<'a, F> {
+    const MARKER: &'static str = "│ ";
+
     pub fn new(formatter: F, leading: &'a str) -> Self {
-        Self { formatter, leading }
+        Self {
+            formatter,
+            leading,
+            pending: false,
+            matched: 0,
+        }
+    }
+
+    fn leader_len(&self) -> usize {
+        self.leading.len() + Self::MARKER.len()
+    }
+
+    fn leader_bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.leading.bytes().chain(Self::MARKER.bytes())
     }
 }
 
-impl<F: Write> Write for LeadingLineFormatter<'_, F> {
-    fn write_str(&mut self, s: &str) -> fmt::Result {
-        if s.chars().all(|c| c != '\n') {
-            self.formatter.write_str(s)?
-        } else {
-            for c in s.chars() {
-                self.write_char(c)?;
+impl<F: Write> LeadingLineFormatter<'_, F> {
+    /// Writes the first `len` bytes of the leader.
+    fn write_leader_prefix(&mut self, len: usize) -> fmt::Result {
+        let split = len.min(self.leading.len());
+        self.formatter.write_str(&self.leading[..split])?;
+        self.formatter.write_str(&Self::MARKER[..len - split])
+    }
+
+    fn write_line(&mut self, line: &str) -> fmt::Result {
+        if line.is_empty() {
+            return Ok(());
+        }
+
+        if self.pending {
+            let remaining = self.leader_len() - self.matched;
+            let cmp_len = remaining.min(line.len());
+            let is_leader = self
+                .leader_bytes()
+                .skip(self.matched)
+                .take(cmp_len)
+                .eq(line.bytes().take(cmp_len));
+
+            if is_leader && line.len() < remaining {
+                self.matched += line.len();
+                return Ok(());
+            }
+
+            let held = self.matched;
+            self.pending = false;
+            self.matched = 0;
+            if !is_leader {
+                self.write_leader_prefix(self.leader_len())?;
             }
+            self.write_leader_prefix(held)?;
         }
-        Ok(())
+        self.formatter.write_str(line)
     }
 
-    fn write_char(&mut self, c: char) -> fmt::Result {
-        self.formatter.write_char(c)?;
-
-        if c == '\n' {
-            self.formatter.write_str(self.leading)?;
-            self.formatter.write_str("│ ")?;
+    /// Writes the leader for a trailing newline, along with held content.
+    pub fn finish(&mut self) -> fmt::Result {
+        if self.pending {
+            let held = self.matched;
+            self.pending = false;
+            self.matched = 0;
+            self.write_leader_prefix(self.leader_len())?;
+            self.write_leader_prefix(held)?;
         }
+        Ok(())
+    }
+}
 
+impl<F: Write> Write for LeadingLineFormatter<'_, F> {
+    /// Writes each line in a single call, with the leader between lines.
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut lines = s.split('\n');
+        if let Some(first) = lines.next() {
+            self.write_line(first)?;
+        }
+        for line in lines {
+            self.finish()?;
+            self.formatter.write_char('\n')?;
+            self.pending = true;
+            self.write_line(line)?;
+        }
         Ok(())
     }
 }
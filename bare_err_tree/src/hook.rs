@@ -0,0 +1,66 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Global hook observing every [`ErrTreePkg`][`crate::ErrTreePkg`] capture.
+
+use core::{
+    panic::Location,
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// Signature of a hook registered with [`set_pkg_hook`].
+pub type PkgHook = fn(&'static Location<'static>);
+
+static PKG_HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Registers `hook` to run on every [`ErrTreePkg::new`][`crate::ErrTreePkg::new`].
+///
+/// The hook receives the callsite captured for the new package, and replaces
+/// any previously registered hook. It is safe to register from any thread.
+///
+/// The hook only fires when a tracking feature (`source_line`, `tracing`, or
+/// `timestamp`) is enabled. Without them the package stores nothing, and
+/// construction is left free of side effects.
+///
+/// # Reentrancy
+/// The hook runs inside error construction. It must not construct errors
+/// that capture an [`ErrTreePkg`][`crate::ErrTreePkg`], or it will recurse
+/// without bound. Keep it to cheap work such as incrementing a counter.
+///
+/// ```rust
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use bare_err_tree::{set_pkg_hook, ErrTreePkg};
+///
+/// static CAPTURES: AtomicUsize = AtomicUsize::new(0);
+///
+/// set_pkg_hook(|_| {
+///     CAPTURES.fetch_add(1, Ordering::Relaxed);
+/// });
+///
+/// let _pkg = ErrTreePkg::new();
+/// # #[cfg(any(feature = "source_line", feature = "tracing", feature = "timestamp"))]
+/// assert_eq!(CAPTURES.load(Ordering::Relaxed), 1);
+/// ```
+pub fn set_pkg_hook(hook: PkgHook) {
+    PKG_HOOK.store(hook as *mut (), Ordering::Release);
+}
+
+/// Unregisters the hook set by [`set_pkg_hook`], if any.
+pub fn clear_pkg_hook() {
+    PKG_HOOK.store(ptr::null_mut(), Ordering::Release);
+}
+
+/// Runs the registered hook, if any.
+#[cfg(any(feature = "source_line", feature = "tracing", feature = "timestamp"))]
+pub(crate) fn run_pkg_hook(location: &'static Location<'static>) {
+    let hook = PKG_HOOK.load(Ordering::Acquire);
+    if !hook.is_null() {
+        // SAFETY: Only ever set from a `PkgHook` in `set_pkg_hook`
+        let hook = unsafe { core::mem::transmute::<*mut (), PkgHook>(hook) };
+        (hook)(location);
+    }
+}
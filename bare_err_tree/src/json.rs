@@ -8,17 +8,43 @@
 
 use core::{
     borrow::Borrow,
+    cell::{Cell, RefCell},
     fmt::{self, Write},
     iter::FusedIterator,
+    ops::Range,
     str::Chars,
 };
 
-use crate::{fmt_tree, AsErrTree, ErrTree, ErrTreeFormattable};
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+use crate::{
+    fmt_tree, AsErrTree, ErrTree, ErrTreeFormattable, SinkError, SinkWriter, SliceWriter, TreeSink,
+};
+
+/// Default byte length of the message probe used by [`tree_to_json`].
+pub const DEFAULT_MSG_PROBE: usize = 256;
+
+/// Placeholder message for an error whose [`Display`][`core::fmt::Display`]
+/// implementation returned an error.
+pub const DISPLAY_ERROR_MSG: &str = "<display error>";
+
+/// Version of the JSON format, written as `"v"` on the root error object.
+///
+/// Bumped whenever older output would reconstruct incorrectly. Output without
+/// a `"v"` predates versioning and is version 0.
+pub const JSON_VERSION: u64 = 1;
 
 /// Produces JSON to store [`ErrTree`] formatted output.
 ///
 /// JSON output can be used to display with [`ErrTree`] format with
 /// [`reconstruct_output`], but the [`ErrTree`] itself cannot be reconstructed.
+/// Each `"msg"` holds the exact message as a JSON string, without any of the
+/// rendered tree formatting, and [`json_msg`] reads it back. The root object
+/// starts with the format's [`JSON_VERSION`] as `"v"`.
+///
+/// Equivalent to [`tree_to_json_probed`] with a probe length of
+/// [`DEFAULT_MSG_PROBE`].
 #[track_caller]
 pub fn tree_to_json<E, S, F>(tree: S, formatter: &mut F) -> fmt::Result
 where
@@ -26,19 +52,294 @@ where
     E: AsErrTree + ?Sized,
     F: fmt::Write,
 {
+    tree_to_json_probed::<DEFAULT_MSG_PROBE, E, S, F>(tree, formatter)
+}
+
+/// Produces JSON to store [`ErrTree`] formatted output.
+///
+/// Each error message is buffered through a `PROBE_LEN` byte stack probe
+/// before being written. If an error's [`Display`][`core::fmt::Display`]
+/// returns [`fmt::Error`] within the probe, the message is replaced with
+/// [`DISPLAY_ERROR_MSG`] and the document remains valid JSON. Messages longer
+/// than `PROBE_LEN` stream directly to `formatter`, and a [`fmt::Error`] after
+/// that point is returned with partial output already written.
+///
+/// Panics in [`Display`][`core::fmt::Display`] are not caught, and propagate
+/// out of this function.
+#[track_caller]
+pub fn tree_to_json_probed<const PROBE_LEN: usize, E, S, F>(
+    tree: S,
+    formatter: &mut F,
+) -> fmt::Result
+where
+    S: Borrow<E>,
+    E: AsErrTree + ?Sized,
+    F: fmt::Write,
+{
+    json_budgeted::<PROBE_LEN, E, S, F>(tree, usize::MAX, formatter)
+}
+
+/// [`tree_to_json`] with at most `max_nodes` errors, as in
+/// [`FormatOptions::max_nodes`][`crate::FormatOptions::max_nodes`].
+///
+/// Once the budget is used up, the next source is replaced by one with the
+/// message `… node budget (<max_nodes>) exhausted`, and no further sources
+/// are written. The output stays valid JSON.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::tree_to_json_max_nodes;
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+///
+/// let mut json = String::new();
+/// tree_to_json_max_nodes::<dyn Error, _, _>(&err as &dyn Error, 1, &mut json).unwrap();
+/// assert_eq!(
+///     json,
+///     r#"{"v":1,"msg":"outer","sources":[{"msg":"… node budget (1) exhausted"}]}"#
+/// );
+/// ```
+#[track_caller]
+pub fn tree_to_json_max_nodes<E, S, F>(tree: S, max_nodes: usize, formatter: &mut F) -> fmt::Result
+where
+    S: Borrow<E>,
+    E: AsErrTree + ?Sized,
+    F: fmt::Write,
+{
+    json_budgeted::<DEFAULT_MSG_PROBE, E, S, F>(tree, max_nodes, formatter)
+}
+
+#[track_caller]
+fn json_budgeted<const PROBE_LEN: usize, E, S, F>(
+    tree: S,
+    max_nodes: usize,
+    formatter: &mut F,
+) -> fmt::Result
+where
+    S: Borrow<E>,
+    E: AsErrTree + ?Sized,
+    F: fmt::Write,
+{
+    let budget = NodeBudget {
+        max: max_nodes.max(1),
+        spent: Cell::new(1),
+    };
     let mut res = Ok(());
     tree.borrow().as_err_tree(&mut |tree| {
-        res = json_fmt(tree, formatter);
+        res = json_fmt::<PROBE_LEN, _>(tree, &budget, true, formatter);
     });
     res
 }
 
-/// Custom JSON format outputter
-fn json_fmt<F: fmt::Write>(mut tree: ErrTree<'_>, formatter: &mut F) -> fmt::Result {
-    formatter.write_str("{\"msg\":\"")?;
-    write!(JsonEscapeFormatter { formatter }, "{}", tree.inner)?;
+/// Errors written so far, against [`tree_to_json_max_nodes`]
+struct NodeBudget {
+    max: usize,
+    /// Past `max` once the exhausted placeholder is written
+    spent: Cell<usize>,
+}
+
+/// [`tree_to_json`] into a new [`String`].
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::tree_to_json_string;
+///
+/// let err = io::Error::from(io::ErrorKind::UnexpectedEof);
+///
+/// let json = tree_to_json_string::<dyn Error, _>(&err as &dyn Error).unwrap();
+/// assert_eq!(json, r#"{"v":1,"msg":"unexpected end of file"}"#);
+/// ```
+#[cfg(feature = "alloc")]
+#[track_caller]
+pub fn tree_to_json_string<E, S>(tree: S) -> Result<String, fmt::Error>
+where
+    S: Borrow<E>,
+    E: AsErrTree + ?Sized,
+{
+    let mut out = String::new();
+    tree_to_json::<E, S, _>(tree, &mut out)?;
+    Ok(out)
+}
+
+/// [`tree_to_json`] into `buf`, as UTF-8 bytes.
+///
+/// Returns the number of bytes written. If `buf` is too small, returns the
+/// number of bytes needed instead. `buf` then holds as much of the output as
+/// fits, cut at a `char` boundary, which is not valid JSON.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::render_json_to_slice;
+///
+/// let err = io::Error::from(io::ErrorKind::UnexpectedEof);
+///
+/// let mut buf = [0; 64];
+/// let len = render_json_to_slice(&(&err as &dyn Error), &mut buf).unwrap();
+/// assert_eq!(&buf[..len], br#"{"v":1,"msg":"unexpected end of file"}"#);
+/// ```
+#[track_caller]
+pub fn render_json_to_slice<E>(tree: &E, buf: &mut [u8]) -> Result<usize, usize>
+where
+    E: AsErrTree + ?Sized,
+{
+    let mut writer = SliceWriter::new(buf);
+    let _ = tree_to_json::<E, _, _>(tree, &mut writer);
+    writer.finish()
+}
+
+/// [`tree_to_json`] into a byte-oriented [`TreeSink`].
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{render_json_to_sink, FmtSink};
+///
+/// let err = io::Error::from(io::ErrorKind::UnexpectedEof);
+///
+/// let mut sink = FmtSink::new(String::new());
+/// render_json_to_sink(&(&err as &dyn Error), &mut sink).unwrap();
+/// assert_eq!(sink.finish().unwrap(), r#"{"v":1,"msg":"unexpected end of file"}"#);
+/// ```
+#[track_caller]
+pub fn render_json_to_sink<E, S>(tree: &E, sink: S) -> Result<(), SinkError>
+where
+    E: AsErrTree + ?Sized,
+    S: TreeSink,
+{
+    Ok(tree_to_json::<E, _, _>(tree, &mut SinkWriter(sink))?)
+}
+
+/// Produces a JSON array of [`tree_to_json`] output, one element per tree.
+///
+/// An empty `trees` produces `[]`. Use [`reconstruct_all`] to display the
+/// trees again.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{trees_to_json, AsErrTree};
+///
+/// let first = io::Error::from(io::ErrorKind::UnexpectedEof);
+/// let second = io::Error::from(io::ErrorKind::NotFound);
+///
+/// let mut json = String::new();
+/// trees_to_json(
+///     [&first as &dyn Error, &second as &dyn Error]
+///         .iter()
+///         .map(|err| err as &dyn AsErrTree),
+///     &mut json,
+/// )
+/// .unwrap();
+/// assert_eq!(
+///     json,
+///     r#"[{"v":1,"msg":"unexpected end of file"},{"v":1,"msg":"entity not found"}]"#
+/// );
+/// ```
+#[track_caller]
+pub fn trees_to_json<'a, I, F>(trees: I, formatter: &mut F) -> fmt::Result
+where
+    I: IntoIterator<Item = &'a dyn AsErrTree>,
+    F: fmt::Write,
+{
+    formatter.write_char('[')?;
+    for (idx, tree) in trees.into_iter().enumerate() {
+        if idx > 0 {
+            formatter.write_char(',')?;
+        }
+        tree_to_json::<dyn AsErrTree, _, _>(tree, formatter)?;
+    }
+    formatter.write_char(']')
+}
+
+/// Writes the escaped message, with a placeholder on early [`Display`] error.
+///
+/// [`Display`]: core::fmt::Display
+fn json_msg_fmt<const PROBE_LEN: usize, F: fmt::Write>(
+    tree: &ErrTree<'_>,
+    formatter: &mut F,
+) -> fmt::Result {
+    let mut probe = MsgProbe::<PROBE_LEN, _> {
+        buf: [0; PROBE_LEN],
+        fill: 0,
+        overflowed: false,
+        formatter: JsonEscapeFormatter { formatter },
+    };
+
+    match tree.write_msg(&mut probe) {
+        Ok(()) => probe.flush(),
+        // Nothing has been written to the formatter, so this is guaranteed
+        // to be an error from the Display implementation.
+        Err(_) if !probe.overflowed => probe.formatter.write_str(DISPLAY_ERROR_MSG),
+        Err(e) => Err(e),
+    }
+}
+
+/// Holds up to `PROBE_LEN` bytes before streaming to the formatter.
+struct MsgProbe<'a, const PROBE_LEN: usize, F> {
+    buf: [u8; PROBE_LEN],
+    fill: usize,
+    overflowed: bool,
+    formatter: JsonEscapeFormatter<'a, F>,
+}
+
+impl<const PROBE_LEN: usize, F: Write> MsgProbe<'_, PROBE_LEN, F> {
+    /// Writes out all buffered bytes.
+    fn flush(&mut self) -> fmt::Result {
+        let buffered = core::str::from_utf8(&self.buf[..self.fill])
+            .expect("Only complete strings are copied into the buffer");
+        self.formatter.write_str(buffered)?;
+        self.fill = 0;
+        Ok(())
+    }
+}
+
+impl<const PROBE_LEN: usize, F: Write> Write for MsgProbe<'_, PROBE_LEN, F> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.overflowed {
+            self.formatter.write_str(s)
+        } else if let Some(buf_slice) = self.buf.get_mut(self.fill..self.fill + s.len()) {
+            buf_slice.copy_from_slice(s.as_bytes());
+            self.fill += s.len();
+            Ok(())
+        } else {
+            self.overflowed = true;
+            self.flush()?;
+            self.formatter.write_str(s)
+        }
+    }
+}
+
+/// Custom JSON format outputter, with the version on the `root` object
+fn json_fmt<const PROBE_LEN: usize, F: fmt::Write>(
+    mut tree: ErrTree<'_>,
+    budget: &NodeBudget,
+    root: bool,
+    formatter: &mut F,
+) -> fmt::Result {
+    if root {
+        write!(formatter, "{{\"v\":{JSON_VERSION},")?;
+    } else {
+        formatter.write_char('{')?;
+    }
+    formatter.write_str("\"msg\":\"")?;
+    json_msg_fmt::<PROBE_LEN, _>(&tree, formatter)?;
     formatter.write_char('"')?;
 
+    if let Some(code) = tree.code {
+        formatter.write_str(",\"code\":\"")?;
+        JsonEscapeFormatter { formatter }.write_str(code)?;
+        formatter.write_char('"')?;
+    }
+
+    #[cfg(feature = "type_name")]
+    if let Some(type_name) = tree.type_name {
+        formatter.write_str(",\"type\":\"")?;
+        JsonEscapeFormatter { formatter }.write_str(type_name)?;
+        formatter.write_char('"')?;
+    }
+
     #[cfg(feature = "source_line")]
     if let Some(loc) = tree.location {
         formatter.write_str(",\"location\":\"")?;
@@ -46,6 +347,11 @@ fn json_fmt<F: fmt::Write>(mut tree: ErrTree<'_>, formatter: &mut F) -> fmt::Res
         formatter.write_char('"')?;
     }
 
+    #[cfg(feature = "timestamp")]
+    if let Some(time) = tree.time {
+        write!(formatter, ",\"time\":{}", crate::unix_secs(time))?;
+    }
+
     #[cfg(feature = "tracing")]
     if let Some(trace) = tree.trace {
         formatter.write_str(",\"trace\":[")?;
@@ -60,28 +366,52 @@ fn json_fmt<F: fmt::Write>(mut tree: ErrTree<'_>, formatter: &mut F) -> fmt::Res
         formatter.write_char(']')?;
     }
 
-    if let Some(first_source) = tree.sources.next() {
-        formatter.write_str(",\"sources\":[")?;
-        let mut res = Ok(());
-        first_source.as_err_tree(&mut |subtree| {
-            res = json_fmt(subtree, formatter);
-        });
-        res?;
+    if tree.collapsed {
+        formatter.write_str(",\"collapsed\":true")?;
+    }
 
-        for source in tree.sources {
-            formatter.write_char(',')?;
-            let mut res = Ok(());
-            source.as_err_tree(&mut |subtree| {
-                res = json_fmt(subtree, formatter);
-            });
-            res?
+    if budget.spent.get() <= budget.max {
+        if let Some(first_source) = tree.sources.next() {
+            formatter.write_str(",\"sources\":[")?;
+            json_source_fmt::<PROBE_LEN, _>(first_source, budget, formatter)?;
+
+            for source in tree.sources {
+                if budget.spent.get() > budget.max {
+                    break;
+                }
+                formatter.write_char(',')?;
+                json_source_fmt::<PROBE_LEN, _>(source, budget, formatter)?;
+            }
+            formatter.write_char(']')?;
         }
-        formatter.write_char(']')?;
     }
 
     formatter.write_char('}')
 }
 
+/// Writes `source`, or the exhausted placeholder when the budget is used up
+fn json_source_fmt<const PROBE_LEN: usize, F: fmt::Write>(
+    source: &dyn AsErrTree,
+    budget: &NodeBudget,
+    formatter: &mut F,
+) -> fmt::Result {
+    let spent = budget.spent.get();
+    budget.spent.set(spent + 1);
+    if spent >= budget.max {
+        return write!(
+            formatter,
+            "{{\"msg\":\"… node budget ({}) exhausted\"}}",
+            budget.max
+        );
+    }
+
+    let mut res = Ok(());
+    source.as_err_tree(&mut |subtree| {
+        res = json_fmt::<PROBE_LEN, _>(subtree, budget, false, formatter);
+    });
+    res
+}
+
 /// Escapes strings according to JSON
 struct JsonEscapeFormatter<'a, F> {
     formatter: &'a mut F,
@@ -91,26 +421,50 @@ const BACKSPACE: char = 8 as char;
 const FORM_FEED: char = 12 as char;
 const JSON_ESCAPE: [char; 7] = ['"', '\\', BACKSPACE, FORM_FEED, '\n', '\r', '\t'];
 
+impl<F: Write> JsonEscapeFormatter<'_, F> {
+    /// Returns the escape sequence for `c`, if it needs one.
+    fn escaped(c: char) -> Option<&'static str> {
+        match c {
+            '"' => Some("\\\""),
+            '\\' => Some("\\\\"),
+            BACKSPACE => Some("\\b"),
+            FORM_FEED => Some("\\f"),
+            '\n' => Some("\\n"),
+            '\r' => Some("\\r"),
+            '\t' => Some("\\t"),
+            _ => None,
+        }
+    }
+}
+
 impl<F: Write> Write for JsonEscapeFormatter<'_, F> {
+    /// Writes each run of characters without escapes in a single call.
     fn write_str(&mut self, s: &str) -> fmt::Result {
-        for c in s.chars() {
-            self.write_char(c)?;
+        let mut rest = s;
+        while let Some(idx) = rest.find(JSON_ESCAPE) {
+            let (clean, tail) = rest.split_at(idx);
+            if !clean.is_empty() {
+                self.formatter.write_str(clean)?;
+            }
+
+            let mut tail = tail.chars();
+            if let Some(c) = tail.next() {
+                self.write_char(c)?;
+            }
+            rest = tail.as_str();
         }
-        Ok(())
-    }
 
-    fn write_char(&mut self, c: char) -> fmt::Result {
-        if JSON_ESCAPE.contains(&c) {
-            self.formatter.write_char('\\')?;
+        if rest.is_empty() {
+            Ok(())
+        } else {
+            self.formatter.write_str(rest)
         }
+    }
 
-        match c {
-            BACKSPACE => self.formatter.write_char('b'),
-            FORM_FEED => self.formatter.write_char('f'),
-            '\n' => self.formatter.write_char('n'),
-            '\r' => self.formatter.write_char('r'),
-            '\t' => self.formatter.write_char('t'),
-            x => self.formatter.write_char(x),
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        match Self::escaped(c) {
+            Some(escape) => self.formatter.write_str(escape),
+            None => self.formatter.write_char(c),
         }
     }
 }
@@ -137,9 +491,9 @@ fn json_trace_fmt<F: fmt::Write>(
         .file()
         .and_then(|file| metadata.line().map(|line| (file, line)))
     {
-        formatter.write_str(",\"source_loc\":[\"file\":\"")?;
+        formatter.write_str(",\"source_loc\":{\"file\":\"")?;
         write!(JsonEscapeFormatter { formatter }, "{}", file)?;
-        write!(formatter, "\",\"line\":{line}]")?;
+        write!(formatter, "\",\"line\":{line}}}")?;
     }
     formatter.write_char('}')?;
     Ok(())
@@ -148,11 +502,16 @@ fn json_trace_fmt<F: fmt::Write>(
 /// Reconstructs [`ErrTree`] formatted output from JSON.
 ///
 /// Only the output produced by [`tree_to_json`] is valid for this function.
+/// Output with a newer [`JSON_VERSION`] than this crate's (see
+/// [`json_version`]) returns [`fmt::Error`] without writing anything, instead
+/// of being misread.
 ///
-/// `FRONT_MAX` limits the number of leading bytes. Each deeper error requires 6
-/// bytes to fit "│   ". So for a max depth of 3 errors, `FRONT_MAX` == 18.
+/// `FRONT_MAX` limits the number of leading bytes. Each deeper error requires
+/// [`FRONT_CELL_LEN`][`crate::FRONT_CELL_LEN`] bytes to fit "│   ", so
+/// [`front_max_for_depth`][`crate::front_max_for_depth`] gives the
+/// `FRONT_MAX` for a max depth (e.g. 18 for a max depth of 3 errors).
 /// By default, `FRONT_MAX` bytes are allocated on stack. When `heap_buffer` is
-/// enabled, the bytes are allocated on stack and `FRONT_MAX` only acts as a
+/// enabled, the bytes are allocated on heap and `FRONT_MAX` only acts as a
 /// depth limit. When `tracing` is enabled, at most `FRONT_MAX` stack traces
 /// will be tracked for duplicates.
 pub fn reconstruct_output<const FRONT_MAX: usize, S, F>(json: S, formatter: &mut F) -> fmt::Result
@@ -160,37 +519,375 @@ where
     S: AsRef<str>,
     F: fmt::Write,
 {
-    fmt_tree::<FRONT_MAX, _, _>(JsonReconstruct::new(json.as_ref()), formatter)
+    let json = json.as_ref();
+    check_json_version(json)?;
+    fmt_tree::<FRONT_MAX, _, _>(JsonReconstruct::new(json, None), formatter)
+}
+
+/// Returns the [`JSON_VERSION`] `json` was written with, from the `"v"` of
+/// its root error object.
+///
+/// Output without a `"v"` predates versioning, so it is version 0. Returns
+/// `None` if `"v"` is not a non-negative integer.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{json_version, tree_to_json, JSON_VERSION};
+///
+/// let err = io::Error::from(io::ErrorKind::UnexpectedEof);
+/// let mut json = String::new();
+/// tree_to_json::<dyn Error, _, _>(&err as &dyn Error, &mut json).unwrap();
+///
+/// assert_eq!(json_version(&json), Some(JSON_VERSION));
+/// assert_eq!(json_version(r#"{"msg":"unexpected end of file"}"#), Some(0));
+/// assert_eq!(json_version(r#"{"v":"one","msg":"unexpected end of file"}"#), None);
+/// ```
+pub fn json_version(json: &str) -> Option<u64> {
+    match JsonReconstruct::new(json, None).version {
+        None => Some(0),
+        Some(version) => version.and_then(|version| u64::try_from(version).ok()),
+    }
+}
+
+/// Errors on JSON this crate cannot read.
+fn check_json_version(json: &str) -> fmt::Result {
+    match json_version(json) {
+        Some(version) if version <= JSON_VERSION => Ok(()),
+        _ => Err(fmt::Error),
+    }
+}
+
+/// [`reconstruct_output`] into a new [`String`].
+///
+/// ```rust
+/// use bare_err_tree::{front_max_for_depth, reconstruct_to_string};
+///
+/// let json = r#"{"msg":"outer","sources":[{"msg":"inner"}]}"#;
+///
+/// let out = reconstruct_to_string::<{ front_max_for_depth(10) }, _>(json).unwrap();
+/// assert_eq!(out, "outer\n│\n╰─▶ inner");
+/// ```
+#[cfg(feature = "alloc")]
+pub fn reconstruct_to_string<const FRONT_MAX: usize, S>(json: S) -> Result<String, fmt::Error>
+where
+    S: AsRef<str>,
+{
+    let mut out = String::new();
+    reconstruct_output::<FRONT_MAX, _, _>(json, &mut out)?;
+    Ok(out)
+}
+
+/// [`reconstruct_output`] for each tree in a [`trees_to_json`] array.
+///
+/// Trees are separated by an empty line. Input that is not an array produces
+/// no output. Every tree is version checked before any is written.
+///
+/// ```rust
+/// use bare_err_tree::{front_max_for_depth, reconstruct_all};
+///
+/// let json = r#"[{"msg":"unexpected end of file"},{"msg":"entity not found"}]"#;
+///
+/// let mut out = String::new();
+/// reconstruct_all::<{ front_max_for_depth(10) }, _, _>(json, &mut out).unwrap();
+/// assert_eq!(out, "unexpected end of file\n\nentity not found");
+/// ```
+pub fn reconstruct_all<const FRONT_MAX: usize, S, F>(json: S, formatter: &mut F) -> fmt::Result
+where
+    S: AsRef<str>,
+    F: fmt::Write,
+{
+    let json = json.as_ref();
+    let trees = json_meta_char_idx('[', json)
+        .map(|start| &json[start..])
+        .and_then(|array| {
+            json_char_idx(']', array.char_indices()).map(|end| &array[BRACKET_LEN..end])
+        })
+        .filter(|trees| !trees.trim().is_empty())
+        .unwrap_or(EMPTY_STR);
+
+    SourcesIter::new(trees).try_for_each(check_json_version)?;
+    for (idx, tree) in SourcesIter::new(trees).enumerate() {
+        if idx > 0 {
+            formatter.write_str("\n\n")?;
+        }
+        fmt_tree::<FRONT_MAX, _, _>(JsonReconstruct::new(tree, None), formatter)?;
+    }
+    Ok(())
+}
+
+/// [`reconstruct_output`] for only the error at `path` and its sources.
+///
+/// `path` holds a source index for each level below the root, e.g. `[0, 2]`
+/// is the third source of the root's first source. An empty `path` renders
+/// the whole tree. Returns [`fmt::Error`] without writing anything if `path`
+/// leaves the tree. See [`source_count`] to find valid indexes.
+///
+/// Traces deduplicate against only the rendered subtree.
+///
+/// ```rust
+/// use bare_err_tree::{front_max_for_depth, reconstruct_output_at};
+///
+/// let json = r#"{"msg":"outer","sources":[{"msg":"first"},{"msg":"second","sources":[{"msg":"inner"}]}]}"#;
+///
+/// let mut out = String::new();
+/// reconstruct_output_at::<{ front_max_for_depth(10) }, _, _>(json, &[1], &mut out).unwrap();
+/// assert_eq!(out, "second\n│\n╰─▶ inner");
+///
+/// assert!(reconstruct_output_at::<{ front_max_for_depth(10) }, _, _>(json, &[2], &mut out).is_err());
+/// ```
+pub fn reconstruct_output_at<const FRONT_MAX: usize, S, F>(
+    json: S,
+    path: &[usize],
+    formatter: &mut F,
+) -> fmt::Result
+where
+    S: AsRef<str>,
+    F: fmt::Write,
+{
+    let json = json.as_ref();
+    check_json_version(json)?;
+    let node = json_node_at(json, path).ok_or(fmt::Error)?;
+    fmt_tree::<FRONT_MAX, _, _>(node, formatter)
+}
+
+/// Returns the number of sources of the error at `path`, as in
+/// [`reconstruct_output_at`], or `None` if `path` leaves the tree.
+///
+/// ```rust
+/// use bare_err_tree::source_count;
+///
+/// let json = r#"{"msg":"outer","sources":[{"msg":"first"},{"msg":"second","sources":[{"msg":"inner"}]}]}"#;
+///
+/// assert_eq!(source_count(json, &[]), Some(2));
+/// assert_eq!(source_count(json, &[1]), Some(1));
+/// assert_eq!(source_count(json, &[1, 0]), Some(0));
+/// assert_eq!(source_count(json, &[2]), None);
+/// ```
+pub fn source_count(json: &str, path: &[usize]) -> Option<usize> {
+    json_node_at(json, path).map(|node| SourcesIter::new(node.sources).count())
+}
+
+/// Follows `path` from the root error of `json`.
+fn json_node_at<'f>(json: &'f str, path: &[usize]) -> Option<JsonReconstruct<'f>> {
+    let mut node = JsonReconstruct::new(json, None);
+    for idx in path {
+        let source = SourcesIter::new(node.sources).nth(*idx)?;
+        node = node.source(source);
+    }
+    Some(node)
+}
+
+/// The `"msg"` of a [`tree_to_json`] error object, produced by [`json_msg`].
+///
+/// Unescapes to exactly the bytes the error displayed (or its message
+/// override) when the JSON was written, newlines included. Rendering adds the
+/// `│` leaders for continuation lines, so use this instead of
+/// [`reconstruct_output`] to recover the original text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JsonMsg<'a> {
+    escaped: &'a str,
+}
+
+impl<'a> JsonMsg<'a> {
+    /// The unescaped message characters.
+    pub fn chars(&self) -> impl Iterator<Item = char> + 'a {
+        JsonStrChars::new(self.escaped)
+    }
+
+    /// The message as it is stored in the JSON, still escaped.
+    pub fn as_escaped(&self) -> &'a str {
+        self.escaped
+    }
+}
+
+/// Writes the unescaped message.
+impl fmt::Display for JsonMsg<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        apply_json_str(self.escaped, f)
+    }
+}
+
+/// Returns the message of the root error in `json`, from [`tree_to_json`].
+///
+/// The messages of sources can be read by passing the
+/// [`NodeSpan::byte_range`] of each node instead.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{front_max_for_depth, json_msg, reconstruct_output, tree_to_json};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("first line\nsecond line")]
+/// struct Multiline;
+///
+/// let mut json = String::new();
+/// tree_to_json::<dyn Error, _, _>(&Multiline as &dyn Error, &mut json).unwrap();
+///
+/// assert_eq!(json_msg(&json).to_string(), "first line\nsecond line");
+///
+/// let mut rendered = String::new();
+/// reconstruct_output::<{ front_max_for_depth(10) }, _, _>(&json, &mut rendered).unwrap();
+/// assert_eq!(rendered, "first line\n│ second line");
+/// ```
+pub fn json_msg(json: &str) -> JsonMsg<'_> {
+    JsonMsg {
+        escaped: JsonReconstruct::new(json, None).msg,
+    }
+}
+
+/// Location of a node rendered by [`reconstruct_output_with_spans`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodeSpan {
+    /// Bytes of this node's JSON object within the input.
+    pub byte_range: Range<usize>,
+    /// Distance from the root, which has depth 0.
+    pub depth: usize,
+    /// Output line where this node's message begins, starting at 0.
+    pub first_output_line: usize,
+}
+
+/// [`reconstruct_output`], reporting a [`NodeSpan`] for each rendered node.
+///
+/// `on_node` is called in output order, just before each node's message is
+/// written. Nodes past the `FRONT_MAX` depth limit are not rendered, so they
+/// are not reported.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{front_max_for_depth, reconstruct_output_with_spans, tree_to_json};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+/// let mut json = String::new();
+/// tree_to_json::<dyn Error, _, _>(&err as &dyn Error, &mut json).unwrap();
+///
+/// let mut out = String::new();
+/// let mut spans = Vec::new();
+/// reconstruct_output_with_spans::<{ front_max_for_depth(10) }, _, _>(&json, &mut out, |span| {
+///     spans.push(span)
+/// })
+/// .unwrap();
+///
+/// assert_eq!(spans[1].depth, 1);
+/// assert_eq!(spans[1].first_output_line, 2);
+/// assert!(json[spans[1].byte_range.clone()].starts_with("{\"msg\":\"unexpected end of file\""));
+/// ```
+pub fn reconstruct_output_with_spans<const FRONT_MAX: usize, S, F>(
+    json: S,
+    formatter: &mut F,
+    mut on_node: impl FnMut(NodeSpan),
+) -> fmt::Result
+where
+    S: AsRef<str>,
+    F: fmt::Write,
+{
+    let json = json.as_ref();
+    check_json_version(json)?;
+    let reporter = SpanReporter {
+        lines: Cell::new(0),
+        on_node: RefCell::new(&mut on_node),
+    };
+    let mut counter = LineCountWriter {
+        formatter,
+        lines: &reporter.lines,
+    };
+    fmt_tree::<FRONT_MAX, _, _>(JsonReconstruct::new(json, Some(&reporter)), &mut counter)
+}
+
+/// Shared between the line counting writer and each node.
+struct SpanReporter<'f> {
+    lines: Cell<usize>,
+    on_node: RefCell<&'f mut dyn FnMut(NodeSpan)>,
+}
+
+/// Counts newlines passed through to `formatter`.
+struct LineCountWriter<'a, F> {
+    formatter: &'a mut F,
+    lines: &'a Cell<usize>,
+}
+
+impl<F: fmt::Write> Write for LineCountWriter<'_, F> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.lines.set(self.lines.get() + s.matches('\n').count());
+        self.formatter.write_str(s)
+    }
+}
+
+/// Returns the byte offset of `inner` within `outer`, or 0 if `inner` is not
+/// a subslice (e.g. [`EMPTY_STR`]).
+fn subslice_offset(outer: &str, inner: &str) -> usize {
+    (inner.as_ptr() as usize)
+        .checked_sub(outer.as_ptr() as usize)
+        .filter(|offset| offset + inner.len() <= outer.len())
+        .unwrap_or(0)
 }
 
 const EMPTY_STR: &str = "";
 
 struct JsonReconstruct<'f> {
     msg: &'f str,
+    code: &'f str,
+    #[cfg(feature = "type_name")]
+    type_name: &'f str,
     #[cfg(feature = "source_line")]
     source_line: &'f str,
+    #[cfg(feature = "timestamp")]
+    time: Option<i64>,
     #[cfg(feature = "tracing")]
     trace: &'f str,
     sources: &'f str,
+    collapsed: bool,
+    /// `"v"` of the object, with `None` inside if it is not an integer
+    version: Option<Option<i64>>,
+    /// Bytes of this node within the original document
+    span: Range<usize>,
+    /// Offset of `sources` within the original document
+    sources_base: usize,
+    depth: usize,
+    reporter: Option<&'f SpanReporter<'f>>,
 }
 
 const BRACE_LEN: usize = '{'.len_utf8();
 const BRACKET_LEN: usize = '['.len_utf8();
 
 impl<'f> JsonReconstruct<'f> {
-    pub fn new(json_body: &'f str) -> Self {
+    pub fn new(json_body: &'f str, reporter: Option<&'f SpanReporter<'f>>) -> Self {
+        Self::new_at(json_body, 0, 0, reporter)
+    }
+
+    /// Construct for `json_body` starting at `base` in the original document.
+    fn new_at(
+        json_body: &'f str,
+        base: usize,
+        depth: usize,
+        reporter: Option<&'f SpanReporter<'f>>,
+    ) -> Self {
         const SOURCES_KEY: &str = "\"sources\"";
         const MSG_KEY: &str = "\"msg\"";
+        const CODE_KEY: &str = "\"code\"";
+        const COLLAPSED_KEY: &str = "\"collapsed\"";
+        const VERSION_KEY: &str = "\"v\"";
+        #[cfg(feature = "type_name")]
+        const TYPE_KEY: &str = "\"type\"";
         #[cfg(feature = "source_line")]
         const LOCATION_KEY: &str = "\"location\"";
+        #[cfg(feature = "timestamp")]
+        const TIME_KEY: &str = "\"time\"";
         #[cfg(feature = "tracing")]
         const TRACE_KEY: &str = "\"trace\"";
 
+        let full_body = json_body;
         let first_brace = json_meta_char_idx('{', json_body).unwrap_or(json_body.len());
         let last_brace =
             json_char_idx('{', json_body.char_indices().rev()).unwrap_or(json_body.len());
         let json_body = &json_body[(first_brace + BRACE_LEN)..(last_brace - BRACE_LEN)];
 
+        let span_end = base + full_body.trim_end().len();
+        let span = (base + first_brace).min(span_end)..span_end;
+
         let (before_sources, sources, after_sources) =
             if let Some(sources_colon) = find_json_key(SOURCES_KEY, json_body) {
                 let sources_start_slice = &json_body[sources_colon..];
@@ -213,6 +910,29 @@ impl<'f> JsonReconstruct<'f> {
             .find(|s| !s.is_empty())
             .unwrap_or(EMPTY_STR);
 
+        let code = [before_sources, after_sources]
+            .iter()
+            .map(|sub_body| find_json_str(CODE_KEY, sub_body))
+            .find(|s| !s.is_empty())
+            .unwrap_or(EMPTY_STR);
+
+        #[cfg(feature = "type_name")]
+        let type_name = [before_sources, after_sources]
+            .iter()
+            .map(|sub_body| find_json_str(TYPE_KEY, sub_body))
+            .find(|s| !s.is_empty())
+            .unwrap_or(EMPTY_STR);
+
+        let collapsed = [before_sources, after_sources].iter().any(|sub_body| {
+            find_json_key(COLLAPSED_KEY, sub_body)
+                .is_some_and(|value| sub_body[value..].trim_start().starts_with("true"))
+        });
+
+        let version = [before_sources, after_sources].iter().find_map(|sub_body| {
+            find_json_key(VERSION_KEY, sub_body)?;
+            Some(find_json_int(VERSION_KEY, sub_body))
+        });
+
         #[cfg(feature = "source_line")]
         let source_line = [before_sources, after_sources]
             .iter()
@@ -220,6 +940,11 @@ impl<'f> JsonReconstruct<'f> {
             .find(|s| !s.is_empty())
             .unwrap_or(EMPTY_STR);
 
+        #[cfg(feature = "timestamp")]
+        let time = [before_sources, after_sources]
+            .iter()
+            .find_map(|sub_body| find_json_int(TIME_KEY, sub_body));
+
         #[cfg(feature = "tracing")]
         let trace = [before_sources, after_sources]
             .iter()
@@ -239,13 +964,33 @@ impl<'f> JsonReconstruct<'f> {
 
         Self {
             msg,
+            code,
+            #[cfg(feature = "type_name")]
+            type_name,
             #[cfg(feature = "source_line")]
             source_line,
+            #[cfg(feature = "timestamp")]
+            time,
             #[cfg(feature = "tracing")]
             trace,
             sources,
+            collapsed,
+            version,
+            span,
+            sources_base: base + subslice_offset(full_body, sources),
+            depth,
+            reporter,
         }
     }
+
+    fn source(&self, source: &'f str) -> Self {
+        Self::new_at(
+            source,
+            self.sources_base + subslice_offset(self.sources, source),
+            self.depth + 1,
+            self.reporter,
+        )
+    }
 }
 
 impl<'f> ErrTreeFormattable for JsonReconstruct<'f> {
@@ -253,6 +998,38 @@ impl<'f> ErrTreeFormattable for JsonReconstruct<'f> {
         apply_json_str(self.msg, f)
     }
 
+    fn apply_code<W: fmt::Write>(&self, mut f: W) -> fmt::Result {
+        if self.code.is_empty() {
+            return Ok(());
+        }
+        f.write_char('[')?;
+        apply_json_str(self.code, &mut f)?;
+        f.write_str("] ")
+    }
+
+    #[cfg(feature = "type_name")]
+    fn has_type_name(&self) -> bool {
+        !self.type_name.is_empty()
+    }
+    #[cfg(feature = "type_name")]
+    fn apply_type_name<W: fmt::Write>(&self, f: W) -> fmt::Result {
+        apply_json_str(self.type_name, f)
+    }
+
+    fn on_render(&self) {
+        if let Some(reporter) = self.reporter {
+            (reporter.on_node.borrow_mut())(NodeSpan {
+                byte_range: self.span.clone(),
+                depth: self.depth,
+                first_output_line: reporter.lines.get(),
+            });
+        }
+    }
+
+    fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
     type Source<'a> = JsonReconstruct<'f>;
     fn sources_empty(&mut self) -> bool {
         SourcesIter::new(self.sources).next().is_none()
@@ -266,7 +1043,7 @@ impl<'f> ErrTreeFormattable for JsonReconstruct<'f> {
         if let Some(mut prev_source) = iter.next() {
             // Skips the last source by operating one behind
             for next_source in iter {
-                (func)(Self::new(prev_source))?;
+                (func)(self.source(prev_source))?;
                 prev_source = next_source;
             }
         }
@@ -277,7 +1054,7 @@ impl<'f> ErrTreeFormattable for JsonReconstruct<'f> {
         F: FnMut(Self::Source<'_>) -> fmt::Result,
     {
         if let Some(last_source) = SourcesIter::new(self.sources).next_back() {
-            (func)(Self::new(last_source))?;
+            (func)(self.source(last_source))?;
         }
         Ok(())
     }
@@ -290,6 +1067,20 @@ impl<'f> ErrTreeFormattable for JsonReconstruct<'f> {
     fn apply_source_line<W: fmt::Write>(&self, f: W) -> fmt::Result {
         apply_json_str(self.source_line, f)
     }
+    #[cfg(all(feature = "source_line", feature = "hyperlinks"))]
+    fn apply_source_link<W: fmt::Write>(&self, f: W) -> fmt::Result {
+        // Strip the trailing column from `<path>:<line>:<column>`
+        let link = self
+            .source_line
+            .rsplit_once(':')
+            .map_or(self.source_line, |(link, _)| link);
+        apply_json_str(link, f)
+    }
+
+    #[cfg(feature = "timestamp")]
+    fn timestamp(&self) -> Option<i64> {
+        self.time
+    }
 
     #[cfg(feature = "tracing")]
     fn trace_empty(&self) -> bool {
@@ -323,7 +1114,11 @@ impl<'f> ErrTreeFormattable for JsonReconstruct<'f> {
             let location = find_json_key(LOCATION, trace_line).and_then(|location_start| {
                 let slice_start = &trace_line[location_start..];
 
-                let loc_start_idx = json_meta_char_idx('[', slice_start)? + BRACKET_LEN;
+                // Payloads from older versions wrap the pairs in an array
+                let (loc_open_idx, loc_close) = json_meta_char_idx('{', slice_start)
+                    .map(|idx| (idx, '}'))
+                    .or_else(|| Some((json_meta_char_idx('[', slice_start)?, ']')))?;
+                let loc_start_idx = loc_open_idx + BRACE_LEN;
                 let slice_inner = &slice_start[loc_start_idx..];
 
                 let file = find_json_str(FILE, slice_inner);
@@ -331,7 +1126,8 @@ impl<'f> ErrTreeFormattable for JsonReconstruct<'f> {
                     None
                 } else {
                     let line_start = find_json_key(LINE, slice_inner).unwrap_or(slice_inner.len());
-                    let line_end = json_char_idx(']', slice_start.char_indices())? - loc_start_idx;
+                    let line_end =
+                        json_char_idx(loc_close, slice_start.char_indices())? - loc_start_idx;
 
                     let line = str::parse(&slice_inner[line_start..line_end]).ok()?;
 
@@ -523,6 +1319,18 @@ fn find_json_str<'a>(field: &str, json_body: &'a str) -> &'a str {
     EMPTY_STR
 }
 
+/// Returns `field`'s integer, if it has one.
+///
+/// Field must include its JSON field quotes (e.g. `let field = "\"foo\"";`)
+fn find_json_int(field: &str, json_body: &str) -> Option<i64> {
+    let value = json_body[find_json_key(field, json_body)?..].trim_start();
+    let end = value
+        .char_indices()
+        .find(|(idx, c)| !(c.is_ascii_digit() || (*idx == 0 && *c == '-')))
+        .map_or(value.len(), |(idx, _)| idx);
+    value[..end].parse().ok()
+}
+
 struct JsonStrChars<'a> {
     prev_backslash: bool,
     iter: Chars<'a>,
@@ -21,18 +21,73 @@ Usage of the [`err_tree`] macro incurs a compliation time cost.
 
 # Feature Flags
 * `derive`: Enabled by default, provides [`err_tree`] via proc macro.
+* `derive_alloc`: Enabled by default, allows [`err_tree`] `*_iter_err`
+  annotations on dynamically sized collections (e.g. `Vec`). Without it,
+  only fixed-size arrays and slices are accepted.
+* `alloc`: Enables types that need an allocator, such as
+  [`OwnedSourcesVec`], [`TreeSnapshot`], [`flatten`], [`tree_to_records`], and the
+  [`tree_to_dot`]/[`tree_to_mermaid`] diagrams. Implied
+  by `derive_alloc`, `heap_buffer`, `boxed`, `classify`, `external_pkg`,
+  `markdown`, `report_shim`, and `test_util`.
 * `json`: Allows for storage to/reconstruction from JSON.
-* `heap_buffer`: Uses heap to store so state that `FRONT_MAX` (x3 if tracing
-    is enabled) bytes of the stack aren't statically allocated for this purpose.
+* `heap_buffer`: Uses heap to store so state that the `FRONT_MAX` sized
+  buffers (see [stack usage](#using-aserrtree-implementors-bin)) aren't
+  statically allocated on the stack for this purpose.
 * `boxed`: Boxes the error package. Addresses ballooning from large tracking
-    features. Boxing the error itself is likely more efficient, when available.
+  features. Boxing the error itself is likely more efficient, when available.
+  Packages no larger than a pointer (i.e. without `tracing`) stay inline.
 * `unix_color`: Outputs UNIX console codes for emphasis.
+* `hyperlinks`: Wraps `source_line` locations in OSC 8 hyperlinks to
+  `file://<path>:<line>`, for terminals that support them. The path is the
+  one given by [`Location`](core::panic::Location), which is usually relative
+  to the build directory. Resolving it to an absolute path is the caller's
+  responsibility (e.g. by building with `--remap-path-prefix`).
 * `anyhow`: Adds implementation for [`anyhow::Error`].
 * `eyre`: Adds implementation for [`eyre::Report`].
+* `snafu`: Adds implementations for [`snafu::Whatever`] and
+  [`snafu::WhateverLocal`], [`ErrTreePkg`] as a `#[snafu(implicit)]` field,
+  and the [`snafu_tree`] macro.
 * `adapt`: Provides a [`std::io::Write`] adapter.
+* `nightly-provider`: Renders a [`std::backtrace::Backtrace`] that an error
+  provides through [`Error::provide`]. Uses `std`, and has no effect unless
+  built with a nightly compiler.
+* `hook`: Provides [`set_pkg_hook`] to observe every [`ErrTreePkg`] capture.
+* `pkg_cache`: Provides [`ErrTreePkg::new_cached`], caching `tracing`
+  captures per thread. Uses `std`.
+* `log_emit`: Provides [`log_tree`] to emit each error as a structured
+  [`log`] event.
+* `classify`: Provides [`Classifier`] and [`ClassCounter`] to count trees by
+  the category of their root cause.
+* `catch_panics`: Provides [`FormatOptions::catch_source_panics`], to render
+  a placeholder for sources that panic. Uses `std`.
+* `external_pkg`: Provides `#[err_tree(external_pkg)]` and
+  [`ErrTreePkg::attach`], to store packages outside of the error type.
+  Uses `std`.
+* `custom_fmt`: Provides the [`custom_fmt`] module, to render reports that
+  are not [`Error`]s with the same formatting.
+* `markdown`: Provides [`tree_to_markdown`], for pasting trees into issues
+  and PRs as a bullet list.
+* `test_util`: Provides [`tree_shape`] and the [`assert_tree_shape`] macro,
+  to compare tree structure in tests without matching rendered output.
+* `report_shim`: Provides [`TreeReportShim`], covering the most used parts
+  of `error_stack::Report` for migrating to error trees.
+* `std_panic`: [`tree_unwrap`] panics with a [`TreePanic`] payload, which
+  panic hooks can downcast, and provides [`set_tree_panic_hook`] to print
+  it. Uses `std`.
+* `type_name`: Renders the type of each [`err_tree`] error after its
+  message, dimmed with `unix_color`, and stores it as `"type"` in JSON.
+  Errors without a macro implementation (e.g. a plain `dyn Error`) have no
+  type to show. See [`ErrTree::with_type_name`].
+* `code_registry`: Registers every `#[err_tree(code = "...")]` type in
+  [`ERR_CODES`], and provides [`assert_unique_err_codes`] to check them.
+  Uses [`linkme`](https://docs.rs/linkme), so it is limited to the
+  platforms that `linkme` supports.
 #### Tracking Feature Flags
 * `source_line`: Tracks the source line of tree errors.
 * `tracing`: Produces a `tracing` backtrace with [`tracing_error`].
+* `timestamp`: Tracks the creation time of tree errors, rendered as an
+  RFC 3339 UTC time. [`FormatOptions::show_elapsed`] adds the time between
+  each error and its parent. Uses `std`.
 
 # Adding [`ErrTree`] Support (Library or Bin)
 Both libraries and binaries can add type support for [`ErrTree`] prints.
@@ -51,10 +106,24 @@ Specify desired tracking features by importing `bare_err_tree` in `Cargo.toml`.
 (e.g. `bare_err_tree = { version = "*", features = ["source_line"] }`)
 
 Call [`tree_unwrap`] on the [`Result`] or [`print_tree`] on the [`Error`] with
-`FRONT_MAX` set to `6 * (maximum tree depth)`. Note that unless `heap_buffer`
-is enabled, `FRONT_MAX` (x3 if `tracing` is enabled) bytes will be
-occupied on stack for the duration of a print call. Make sure this falls
-within platform stack size, and single stack frame size, limits.
+`FRONT_MAX` set to [`front_max_for_depth`] of the maximum tree depth. The
+`_default` forms ([`tree_unwrap_default`], [`print_tree_default`],
+[`DefaultTreeDisplay`]) use [`DEFAULT_FRONT_MAX`].
+
+The common imports for both cases are collected in [`prelude`].
+
+Unless `heap_buffer` is enabled, a print call holds its buffers on stack for
+its duration. Each is allocated once per print, not per level of depth, so
+the worst case is `FRONT_MAX` entries of:
+* A byte of leading glyphs and a cycle check pointer (two `usize`), always.
+* A span identifier (two `usize`) and a frame index (`usize`), with `tracing`.
+* Two identities (two `usize` each), with [`FormatOptions::dedup_by_identity`]
+  or [`FormatOptions::mark_shared`].
+* A sibling position (`usize`), with [`FormatOptions::numbered`].
+
+e.g. `FRONT_MAX * 41` bytes with `tracing` on a 64-bit target. Each level of
+depth adds only a fixed size recursion frame. Make sure this falls within
+platform stack size, and single stack frame size, limits.
 
 # Credit
 
@@ -75,11 +144,20 @@ Contributions are welcome at
 #![no_std]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![cfg_attr(coverage, feature(coverage_attribute))]
+#![cfg_attr(nightly_provider, feature(error_generic_member_access))]
 
-#[cfg(feature = "adapt")]
+#[cfg(any(
+    feature = "adapt",
+    feature = "pkg_cache",
+    feature = "timestamp",
+    feature = "catch_panics",
+    feature = "external_pkg",
+    feature = "std_panic",
+    nightly_provider
+))]
 extern crate std;
 
-#[cfg(any(feature = "heap_buffer", feature = "boxed"))]
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
 #[cfg(feature = "source_line")]
@@ -93,11 +171,79 @@ use core::{
 mod pkg;
 pub use pkg::*;
 pub mod flex;
+pub mod prelude;
 pub use flex::*;
 mod fmt_logic;
 use fmt_logic::*;
 mod buffer;
+#[cfg(feature = "custom_fmt")]
+pub mod custom_fmt;
 use buffer::*;
+mod summary;
+pub use summary::*;
+mod options;
+pub use options::*;
+mod slice;
+pub use slice::*;
+mod sink;
+pub use sink::*;
+mod display;
+pub use display::*;
+mod never;
+pub use never::*;
+mod progress;
+pub use progress::*;
+#[cfg(feature = "alloc")]
+mod snapshot;
+#[cfg(feature = "alloc")]
+pub use snapshot::*;
+#[cfg(feature = "alloc")]
+mod records;
+#[cfg(feature = "alloc")]
+pub use records::*;
+#[cfg(feature = "alloc")]
+mod diagram;
+#[cfg(feature = "alloc")]
+pub use diagram::*;
+#[cfg(feature = "markdown")]
+mod markdown;
+#[cfg(feature = "markdown")]
+pub use markdown::*;
+#[cfg(feature = "report_shim")]
+mod report_shim;
+#[cfg(feature = "report_shim")]
+pub use report_shim::*;
+#[cfg(feature = "std_panic")]
+mod tree_panic;
+#[cfg(feature = "std_panic")]
+pub use tree_panic::*;
+#[cfg(feature = "test_util")]
+mod shape;
+#[cfg(feature = "test_util")]
+pub use shape::*;
+#[cfg(feature = "timestamp")]
+mod timestamp;
+#[cfg(feature = "timestamp")]
+use timestamp::*;
+#[cfg(feature = "tracing")]
+mod traces;
+#[cfg(feature = "tracing")]
+pub use traces::*;
+
+#[cfg(feature = "hook")]
+mod hook;
+#[cfg(feature = "hook")]
+pub use hook::*;
+
+#[cfg(feature = "log_emit")]
+mod log_emit;
+#[cfg(feature = "log_emit")]
+pub use log_emit::*;
+
+#[cfg(feature = "classify")]
+mod classify;
+#[cfg(feature = "classify")]
+pub use classify::*;
 
 #[cfg(feature = "json")]
 mod json;
@@ -107,53 +253,252 @@ pub use json::*;
 #[cfg(feature = "derive")]
 pub use bare_err_tree_proc::*;
 
+/// Bytes of `FRONT_MAX` used by each level of tree depth.
+pub const FRONT_CELL_LEN: usize = MAX_CELL_LEN;
+
+/// The `FRONT_MAX` needed to print `depth` levels of errors.
+///
+/// Computed from [`FRONT_CELL_LEN`], so it stays correct if the cell width
+/// changes.
+///
+/// ```rust
+/// use bare_err_tree::{depth_for_front_max, front_max_for_depth, FRONT_CELL_LEN};
+///
+/// assert_eq!(front_max_for_depth(3), 3 * FRONT_CELL_LEN);
+/// assert_eq!(depth_for_front_max(front_max_for_depth(3)), 3);
+/// ```
+///
+/// Usable directly as the `FRONT_MAX` const generic:
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{front_max_for_depth, print_tree};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+///
+/// let mut out = String::new();
+/// print_tree::<{ front_max_for_depth(10) }, _, _>(&err as &dyn Error, &mut out).unwrap();
+/// assert_eq!(out, "outer\n│\n╰─▶ unexpected end of file");
+/// ```
+pub const fn front_max_for_depth(depth: usize) -> usize {
+    depth * FRONT_CELL_LEN
+}
+
+/// `FRONT_MAX` used by the `_default` entry points, fitting 20 levels.
+///
+/// ```rust
+/// use bare_err_tree::{depth_for_front_max, DEFAULT_FRONT_MAX};
+///
+/// assert_eq!(depth_for_front_max(DEFAULT_FRONT_MAX), 20);
+/// ```
+pub const DEFAULT_FRONT_MAX: usize = front_max_for_depth(20);
+
+/// The levels of errors printed with `front_max` bytes.
+///
+/// Rounds down, as a partial cell cannot fit another level.
+pub const fn depth_for_front_max(front_max: usize) -> usize {
+    front_max / FRONT_CELL_LEN
+}
+
 /// Alternative to [`Result::unwrap`] that formats the error as a tree.
 ///
-/// `FRONT_MAX` limits the number of leading bytes. Each deeper error requires 6
-/// bytes to fit "│   ". So for a max depth of 3 errors, `FRONT_MAX` == 18.
+/// `FRONT_MAX` limits the number of leading bytes. Each deeper error requires
+/// [`FRONT_CELL_LEN`] bytes to fit "│   ", so [`front_max_for_depth`] gives the
+/// `FRONT_MAX` for a max depth (e.g. 18 for a max depth of 3 errors).
 /// By default, `FRONT_MAX` bytes are allocated on stack. When `heap_buffer` is
 /// enabled, the bytes are allocated on heap and `FRONT_MAX` only acts as a
 /// depth limit. When `tracing` is enabled, at most `FRONT_MAX` stack traces
-/// will be tracked for duplicates.
+/// will be tracked for duplicates. If `FRONT_MAX` cuts off any errors, the
+/// output ends with a `-- tree truncated at depth N ... --` trailer line.
 ///
 /// Errors must define [`Error::source`] correctly for the tree to display.
 /// The derive macros for [`ErrTree`] track extra information and handle
 /// multiple sources ([`Error::source`] is designed around a single error
 /// source).
+///
+/// The [`Ok`] path is kept small enough to inline, so `tree_unwrap` on a
+/// `Result<T, NeverTree>` compiles to taking the value.
+///
+/// With `std_panic`, the panic payload is a [`TreePanic`] instead of a
+/// message (see [`set_tree_panic_hook`] for the default panic hook).
 #[track_caller]
+#[inline]
 pub fn tree_unwrap<const FRONT_MAX: usize, T, E>(res: Result<T, E>) -> T
 where
     E: AsErrTree,
 {
     match res {
         Ok(x) => x,
-        Err(tree) => {
-            let loc = core::panic::Location::caller();
-            tree.as_err_tree(&mut |tree| {
-                panic!(
-                    "Panic origin at: {:#?}\n{}",
-                    loc,
-                    ErrTreeFmtWrap::<FRONT_MAX, _>::new(tree)
-                )
-            });
-            unreachable!()
+        Err(tree) => tree_unwrap_failed::<FRONT_MAX, _>(&tree, core::panic::Location::caller()),
+    }
+}
+
+/// [`tree_unwrap`] with [`DEFAULT_FRONT_MAX`].
+///
+/// ```rust,should_panic
+/// # use std::io;
+/// use bare_err_tree::{tree_unwrap_default, WrapErr};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("request failed")]
+/// struct Request(#[source] io::Error);
+///
+/// let res: Result<(), _> = Err(WrapErr(Request(io::ErrorKind::TimedOut.into())));
+/// tree_unwrap_default(res);
+/// ```
+#[track_caller]
+#[inline]
+pub fn tree_unwrap_default<T, E>(res: Result<T, E>) -> T
+where
+    E: AsErrTree,
+{
+    tree_unwrap::<DEFAULT_FRONT_MAX, _, _>(res)
+}
+
+/// Panic path of [`tree_unwrap`], kept out of line.
+#[cold]
+#[inline(never)]
+fn tree_unwrap_failed<const FRONT_MAX: usize, E>(
+    tree: &E,
+    loc: &'static core::panic::Location<'static>,
+) -> !
+where
+    E: AsErrTree,
+{
+    #[cfg(feature = "std_panic")]
+    tree.as_err_tree(&mut |tree| {
+        use alloc::string::ToString;
+
+        std::panic::panic_any(TreePanic {
+            rendered: ErrTreeFmtWrap::<FRONT_MAX, _>::new(tree).to_string(),
+            location: loc,
+        })
+    });
+    #[cfg(not(feature = "std_panic"))]
+    tree.as_err_tree(&mut |tree| {
+        panic!(
+            "Panic origin at: {:#?}\n{}",
+            loc,
+            ErrTreeFmtWrap::<FRONT_MAX, _>::new(tree)
+        )
+    });
+    unreachable!()
+}
+
+/// Asserts that a [`Result`] is [`Ok`], returning the value like
+/// [`Result::unwrap`].
+///
+/// On [`Err`], panics with the error rendered as a tree with
+/// [`DEFAULT_FRONT_MAX`], after the asserted expression and the location of
+/// the assertion. Unlike [`tree_unwrap`], the panic is always a message, even
+/// with `std_panic`.
+///
+/// ```rust
+/// # use std::io;
+/// use bare_err_tree::{assert_tree_ok, WrapErr};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("request failed")]
+/// struct Request(#[source] io::Error);
+///
+/// fn fetch(ok: bool) -> Result<u32, WrapErr<Request>> {
+///     if ok {
+///         Ok(200)
+///     } else {
+///         Err(WrapErr(Request(io::ErrorKind::TimedOut.into())))
+///     }
+/// }
+///
+/// assert_eq!(assert_tree_ok!(fetch(true)), 200);
+/// ```
+#[macro_export]
+macro_rules! assert_tree_ok {
+    ($res:expr $(,)?) => {
+        match $res {
+            ::core::result::Result::Ok(value) => value,
+            ::core::result::Result::Err(tree) => $crate::__assert_tree_failed(
+                ::core::stringify!($res),
+                "Ok",
+                ::core::option::Option::Some(&tree),
+            ),
         }
+    };
+}
+
+/// Asserts that a [`Result`] is [`Err`], returning the error like
+/// [`Result::unwrap_err`].
+///
+/// On [`Ok`], panics with the asserted expression and the location of the
+/// assertion. The value is not printed, so it does not need to implement
+/// [`Debug`][`core::fmt::Debug`].
+///
+/// ```rust
+/// # use std::io;
+/// use bare_err_tree::{assert_tree_err, WrapErr};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("request failed")]
+/// struct Request(#[source] io::Error);
+///
+/// let res: Result<(), _> = Err(WrapErr(Request(io::ErrorKind::TimedOut.into())));
+/// let WrapErr(Request(io)) = assert_tree_err!(res);
+/// assert_eq!(io.kind(), io::ErrorKind::TimedOut);
+/// ```
+#[macro_export]
+macro_rules! assert_tree_err {
+    ($res:expr $(,)?) => {
+        match $res {
+            ::core::result::Result::Ok(_) => $crate::__assert_tree_failed::<$crate::NeverTree>(
+                ::core::stringify!($res),
+                "Err",
+                ::core::option::Option::None,
+            ),
+            ::core::result::Result::Err(tree) => tree,
+        }
+    };
+}
+
+/// Panic path of [`assert_tree_ok`] and [`assert_tree_err`].
+#[doc(hidden)]
+#[cold]
+#[inline(never)]
+#[track_caller]
+pub fn __assert_tree_failed<E>(expr: &str, expected: &str, tree: Option<&E>) -> !
+where
+    E: AsErrTree,
+{
+    let loc = core::panic::Location::caller();
+    match tree {
+        Some(tree) => tree.as_err_tree(&mut |tree| {
+            panic!(
+                "assertion failed: `{expr}` is {expected}, at {loc}\n{}",
+                ErrTreeFmtWrap::<DEFAULT_FRONT_MAX, _>::new(tree)
+            )
+        }),
+        None => panic!("assertion failed: `{expr}` is {expected}, at {loc}"),
     }
+    unreachable!()
 }
 
 /// Produces [`ErrTree`] formatted output for an error.
 ///
-/// `FRONT_MAX` limits the number of leading bytes. Each deeper error requires 6
-/// bytes to fit "│   ". So for a max depth of 3 errors, `FRONT_MAX` == 18.
+/// `FRONT_MAX` limits the number of leading bytes. Each deeper error requires
+/// [`FRONT_CELL_LEN`] bytes to fit "│   ", so [`front_max_for_depth`] gives the
+/// `FRONT_MAX` for a max depth (e.g. 18 for a max depth of 3 errors).
 /// By default, `FRONT_MAX` bytes are allocated on stack. When `heap_buffer` is
-/// enabled, the bytes are allocated on stack and `FRONT_MAX` only acts as a
+/// enabled, the bytes are allocated on heap and `FRONT_MAX` only acts as a
 /// depth limit. When `tracing` is enabled, at most `FRONT_MAX` stack traces
-/// will be tracked for duplicates.
+/// will be tracked for duplicates. If `FRONT_MAX` cuts off any errors, the
+/// output ends with a `-- tree truncated at depth N ... --` trailer line.
 ///
 /// Errors must define [`Error::source`] correctly for the tree to display.
 /// The derive macros for [`ErrTree`] track extra information and handle
 /// multiple sources ([`Error::source`] is designed around a single error
-/// source).
+/// source). An error that reports itself as its own transitive source is
+/// rendered once, with `<cycle detected, see above>` in place of the repeat.
 ///
 /// ```rust
 /// # use std::{
@@ -163,9 +508,9 @@ where
 /// #   string::String,
 /// #   io::self,
 /// # };
-/// use bare_err_tree::{AsErrTree, print_tree};
+/// use bare_err_tree::{front_max_for_depth, AsErrTree, print_tree};
 ///
-/// const PRINT_SIZE: usize = 60;
+/// const PRINT_SIZE: usize = front_max_for_depth(10);
 ///
 /// fn sized_print<E, F>(tree: E, formatter: F) -> fmt::Result
 /// where
@@ -192,12 +537,356 @@ where
     res
 }
 
+/// [`print_tree`] with [`DEFAULT_FRONT_MAX`].
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::print_tree_default;
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+///
+/// let mut out = String::new();
+/// print_tree_default(&err as &dyn Error, &mut out).unwrap();
+/// assert_eq!(out, "outer\n│\n╰─▶ unexpected end of file");
+/// ```
+#[track_caller]
+pub fn print_tree_default<E, F>(tree: E, formatter: F) -> fmt::Result
+where
+    E: AsErrTree,
+    F: fmt::Write,
+{
+    print_tree::<DEFAULT_FRONT_MAX, _, _>(tree, formatter)
+}
+
+/// [`print_tree`] with the tree and formatter types erased.
+///
+/// [`print_tree`] is generic over both, so the recursive formatter is
+/// duplicated for every combination used. This form is only duplicated per
+/// `FRONT_MAX`, trading dynamic dispatch on every write for code size.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{front_max_for_depth, print_tree_dyn};
+///
+/// let err = io::Error::from(io::ErrorKind::UnexpectedEof);
+///
+/// let mut out = String::new();
+/// print_tree_dyn::<{ front_max_for_depth(10) }>(&(&err as &dyn Error), &mut out).unwrap();
+/// assert_eq!(out, "unexpected end of file");
+/// ```
+#[track_caller]
+pub fn print_tree_dyn<const FRONT_MAX: usize>(
+    tree: &dyn AsErrTree,
+    formatter: &mut dyn fmt::Write,
+) -> fmt::Result {
+    let mut res = Ok(());
+    tree.as_err_tree(&mut |tree| {
+        res = fmt_tree::<FRONT_MAX, _, _>(tree, formatter);
+    });
+    res
+}
+
+/// Prints each of `trees` as an independent top-level tree, separated by a
+/// blank line.
+///
+/// There is no shared root. Each tree is rendered as if by [`print_tree`], so
+/// per-render state (e.g. `tracing` frame deduplication) starts fresh for
+/// every tree.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{front_max_for_depth, print_forest};
+///
+/// let eof = io::Error::from(io::ErrorKind::UnexpectedEof);
+/// let timed_out = io::Error::from(io::ErrorKind::TimedOut);
+///
+/// let mut out = String::new();
+/// print_forest::<{ front_max_for_depth(10) }, _>(
+///     &[&(&eof as &dyn Error), &(&timed_out as &dyn Error)],
+///     &mut out,
+/// )
+/// .unwrap();
+/// assert_eq!(out, "unexpected end of file\n\ntimed out");
+/// ```
+#[track_caller]
+pub fn print_forest<const FRONT_MAX: usize, F>(
+    trees: &[&dyn AsErrTree],
+    mut formatter: F,
+) -> fmt::Result
+where
+    F: fmt::Write,
+{
+    for (idx, tree) in trees.iter().enumerate() {
+        if idx > 0 {
+            formatter.write_str("\n\n")?;
+        }
+        let mut res = Ok(());
+        tree.as_err_tree(&mut |tree| {
+            res = fmt_tree::<FRONT_MAX, _, _>(tree, &mut formatter);
+        });
+        res?;
+    }
+    Ok(())
+}
+
+/// [`print_tree`] with [`FormatOptions`].
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{front_max_for_depth, print_tree_with, FormatOptions};
+///
+/// let err = io::Error::from(io::ErrorKind::UnexpectedEof);
+///
+/// let mut out = String::new();
+/// let options = FormatOptions::new().dedup_by_identity(true);
+/// print_tree_with::<{ front_max_for_depth(10) }, _, _>(&err as &dyn Error, options, &mut out)
+///     .unwrap();
+/// assert_eq!(out, "unexpected end of file");
+/// ```
+#[track_caller]
+pub fn print_tree_with<const FRONT_MAX: usize, E, F>(
+    tree: E,
+    options: FormatOptions,
+    mut formatter: F,
+) -> fmt::Result
+where
+    E: AsErrTree,
+    F: fmt::Write,
+{
+    let sources_len = options.max_children.map(|_| count_sources(&tree));
+
+    with_source_lines::<FRONT_MAX, _, _>(&tree, options, |source_lines| {
+        let mut res = Ok(());
+        tree.as_err_tree(&mut |mut tree| {
+            tree.sources_len = sources_len;
+            res = fmt_tree_opts::<FRONT_MAX, _, _>(tree, options, source_lines, &mut formatter);
+        });
+        res
+    })
+}
+
+/// [`print_tree`] with [`FormatOptions::sanitize`], for messages that may
+/// hold untrusted input.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{front_max_for_depth, print_tree_sanitized};
+///
+/// let err = io::Error::other("bad name \x1b[2J\nsecond line");
+///
+/// let mut out = String::new();
+/// print_tree_sanitized::<{ front_max_for_depth(10) }, _, _>(&err as &dyn Error, &mut out)
+///     .unwrap();
+/// assert_eq!(out, "bad name [2J\n│ second line");
+/// ```
+#[track_caller]
+pub fn print_tree_sanitized<const FRONT_MAX: usize, E, F>(tree: E, formatter: F) -> fmt::Result
+where
+    E: AsErrTree,
+    F: fmt::Write,
+{
+    print_tree_with::<FRONT_MAX, _, _>(tree, FormatOptions::new().sanitize(true), formatter)
+}
+
+/// [`print_tree`], writing each message with `filter` instead of the error's
+/// [`Display`](core::fmt::Display).
+///
+/// `filter` is called for the root and every source, with the error behind
+/// the node, so it can redact secrets such as tokens or connection strings.
+/// A filter of `|f, err| write!(f, "{err}")` matches [`print_tree`].
+/// Errors with a message override are given as an error displaying the
+/// override.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{front_max_for_depth, print_tree_with_filter};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("failed to connect")]
+/// struct Connect(#[source] io::Error);
+///
+/// let err = Connect(io::Error::other("password=hunter2 rejected"));
+///
+/// let mut out = String::new();
+/// print_tree_with_filter::<{ front_max_for_depth(10) }, _, _>(
+///     &err as &dyn Error,
+///     &mut out,
+///     &|f, err| write!(f, "{}", err.to_string().replace("hunter2", "***")),
+/// )
+/// .unwrap();
+/// assert_eq!(out, "failed to connect\n│\n╰─▶ password=*** rejected");
+/// ```
+#[track_caller]
+pub fn print_tree_with_filter<const FRONT_MAX: usize, E, F>(
+    tree: E,
+    mut formatter: F,
+    filter: &dyn Fn(&mut dyn fmt::Write, &dyn Error) -> fmt::Result,
+) -> fmt::Result
+where
+    E: AsErrTree,
+    F: fmt::Write,
+{
+    let mut res = Ok(());
+    tree.as_err_tree(&mut |tree| {
+        res = fmt_tree_progress::<FRONT_MAX, _, _>(
+            tree,
+            FormatOptions::new(),
+            None,
+            Some(filter),
+            &mut [],
+            &mut formatter,
+        );
+    });
+    res
+}
+
+/// [`print_tree`] with [`FormatOptions::numbered`], so errors can be
+/// referenced by position (e.g. "error 1.2.1").
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{front_max_for_depth, print_tree_numbered};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+///
+/// let mut out = String::new();
+/// print_tree_numbered::<{ front_max_for_depth(10) }, _, _>(&err as &dyn Error, &mut out)
+///     .unwrap();
+/// assert_eq!(out, "1 outer\n│\n╰─▶ 1.1 unexpected end of file");
+/// ```
+#[track_caller]
+pub fn print_tree_numbered<const FRONT_MAX: usize, E, F>(tree: E, formatter: F) -> fmt::Result
+where
+    E: AsErrTree,
+    F: fmt::Write,
+{
+    print_tree_with::<FRONT_MAX, _, _>(tree, FormatOptions::new().numbered(true), formatter)
+}
+
+/// [`print_tree`] under a `title` line, e.g. to separate several trees.
+///
+/// The title is not an error: it has no location or trace, and the tree is
+/// rendered as its only source. The title takes one level of `FRONT_MAX`, so
+/// use [`front_max_for_depth`] of the tree depth plus one.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{front_max_for_depth, print_tree_titled};
+///
+/// let err = io::Error::from(io::ErrorKind::UnexpectedEof);
+///
+/// let mut out = String::new();
+/// print_tree_titled::<{ front_max_for_depth(10) }, _, _>(
+///     "=== Request 42 failed ===",
+///     &err as &dyn Error,
+///     &mut out,
+/// )
+/// .unwrap();
+/// assert_eq!(out, "=== Request 42 failed ===\n│\n╰─▶ unexpected end of file");
+/// ```
+#[track_caller]
+pub fn print_tree_titled<const FRONT_MAX: usize, E, F>(
+    title: &str,
+    tree: E,
+    mut formatter: F,
+) -> fmt::Result
+where
+    E: AsErrTree,
+    F: fmt::Write,
+{
+    let title = Title(title);
+    let mut sources = core::iter::once(&tree as &dyn AsErrTree);
+    fmt_tree::<FRONT_MAX, _, _>(ErrTree::no_pkg(&title, &mut sources), &mut formatter)
+}
+
+/// Header line for [`print_tree_titled`].
+#[derive(Debug)]
+struct Title<'a>(&'a str);
+
+impl Error for Title<'_> {}
+
+impl fmt::Display for Title<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+/// [`print_tree`], falling back to a summary over `max_bytes` of output.
+///
+/// The full output is measured before anything is written. If it would exceed
+/// `max_bytes`, a compact summary is written instead of a truncated tree. The
+/// summary is the root message, [`TreeSummary`] statistics, and the blame path
+/// formed by following the first source of each error down to a leaf, or to
+/// the depth `FRONT_MAX` supports. The summary itself is cut to `max_bytes`.
+///
+/// Errors are visited once for measurement and again for the final output, so
+/// [`Display`][`core::fmt::Display`] implementations should be deterministic.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{front_max_for_depth, print_tree_budgeted};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::ErrorKind::UnexpectedEof.into());
+///
+/// let mut out = String::new();
+/// print_tree_budgeted::<{ front_max_for_depth(10) }, _, _>(&err as &dyn Error, 40, &mut out).unwrap();
+/// assert!(out.starts_with("outer\n├─ summarized to fit 40 bytes"));
+/// assert!(out.len() <= 40);
+/// ```
+#[track_caller]
+pub fn print_tree_budgeted<const FRONT_MAX: usize, E, F>(
+    tree: E,
+    max_bytes: usize,
+    mut formatter: F,
+) -> fmt::Result
+where
+    E: AsErrTree,
+    F: fmt::Write,
+{
+    let mut budget = ByteBudget {
+        written: 0,
+        max: max_bytes,
+    };
+    let mut res = Ok(());
+    tree.as_err_tree(&mut |tree| {
+        res = fmt_tree::<FRONT_MAX, _, _>(tree, &mut budget);
+    });
+
+    if budget.exceeded() {
+        write_summary::<FRONT_MAX, _, _>(tree, max_bytes, &mut formatter)
+    } else {
+        res?;
+        print_tree::<FRONT_MAX, _, _>(tree, formatter)
+    }
+}
+
 #[cfg(feature = "adapt")]
-/// Converts [`std::io::Write`] to [`core::fmt::Write`].
+/// Converts [`std::io::Write`] to [`core::fmt::Write`] and [`TreeSink`].
 ///
 /// Provided for using [`print_tree`] without a [`std::string::String`] buffer.
 /// This adapter does not call [`flush`][`std::io::Write::flush`], only
-/// [`write_all`][`std::io::Write::write_all`].
+/// [`write_all`][`std::io::Write::write_all`]. Each formatted fragment is
+/// written straight through, without an intermediate buffer.
+/// [`std::io::Write::write_fmt`] is not used, as it panics when a
+/// [`Display`][`core::fmt::Display`] implementation fails, where tree
+/// printing returns [`fmt::Error`].
+///
+/// [`fmt::Write`] can only fail with [`fmt::Error`], so the underlying
+/// [`std::io::Error`] is kept for [`Self::take_error`] or [`Self::into_parts`].
+/// Only the most recent failure is kept. The writer is the public `.0` field,
+/// but the adapter is constructed with [`Self::new`] or [`From`].
 ///
 /// ```rust
 /// # use std::{
@@ -206,9 +895,9 @@ where
 /// #   fmt::{self, Write, Display, Formatter},
 /// #   io::{self, stdout},
 /// # };
-/// use bare_err_tree::{AdaptWrite, AsErrTree, print_tree};
+/// use bare_err_tree::{front_max_for_depth, AdaptWrite, AsErrTree, print_tree};
 ///
-/// const PRINT_SIZE: usize = 60;
+/// const PRINT_SIZE: usize = front_max_for_depth(10);
 ///
 /// fn sized_print<E, F>(tree: E, formatter: F) -> fmt::Result
 /// where
@@ -219,18 +908,64 @@ where
 /// }
 ///
 /// fn io_as_tree() {
-///     let mut out = AdaptWrite(stdout());
-///     sized_print(&io::Error::last_os_error() as &dyn Error, &mut out).unwrap();
+///     let mut out = AdaptWrite::new(stdout());
+///     if sized_print(&io::Error::last_os_error() as &dyn Error, &mut out).is_err() {
+///         panic!("{:?}", out.take_error());
+///     }
 ///     out.flush().unwrap();
 /// }
 /// ```
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct AdaptWrite<W>(pub W);
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AdaptWrite<W>(pub W, AdaptError);
+
+/// Last error of an [`AdaptWrite`].
+///
+/// Like [`ErrTreePkg`], this compares as equal and hashes as a no-op, so the
+/// adapter's derives only depend on the writer. Clones start without an error.
+#[cfg(feature = "adapt")]
+#[derive(Debug, Default)]
+struct AdaptError(Option<std::io::Error>);
+
+#[cfg(feature = "adapt")]
+impl Clone for AdaptError {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
+}
+
+#[cfg(feature = "adapt")]
+impl PartialEq for AdaptError {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "adapt")]
+impl Eq for AdaptError {}
+
+#[cfg(feature = "adapt")]
+impl PartialOrd for AdaptError {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(feature = "adapt")]
+impl Ord for AdaptError {
+    fn cmp(&self, _: &Self) -> core::cmp::Ordering {
+        core::cmp::Ordering::Equal
+    }
+}
+
+#[cfg(feature = "adapt")]
+impl core::hash::Hash for AdaptError {
+    fn hash<H: core::hash::Hasher>(&self, _: &mut H) {}
+}
 
 #[cfg(feature = "adapt")]
 impl<W> From<W> for AdaptWrite<W> {
     fn from(value: W) -> Self {
-        Self(value)
+        Self::new(value)
     }
 }
 
@@ -240,7 +975,49 @@ where
     W: std::io::Write,
 {
     fn write_str(&mut self, s: &str) -> fmt::Result {
-        self.0.write_all(s.as_bytes()).map_err(|_| fmt::Error)
+        self.write_bytes(s.as_bytes()).map_err(|_| fmt::Error)
+    }
+}
+
+#[cfg(feature = "adapt")]
+impl<W> TreeSink for AdaptWrite<W>
+where
+    W: std::io::Write,
+{
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), SinkError> {
+        self.0.write_all(bytes).map_err(|e| {
+            self.1 .0 = Some(e);
+            SinkError
+        })
+    }
+}
+
+#[cfg(feature = "adapt")]
+impl<W> AdaptWrite<W> {
+    /// Adapts `writer`, with no error.
+    pub fn new(writer: W) -> Self {
+        Self(writer, AdaptError::default())
+    }
+
+    /// The most recent error from the writer, if any, clearing it.
+    ///
+    /// ```rust
+    /// # use std::{fmt::Write, io};
+    /// use bare_err_tree::AdaptWrite;
+    ///
+    /// let mut buf = [0_u8; 2];
+    /// let mut out = AdaptWrite::new(&mut buf[..]);
+    /// assert!(out.write_str("too long").is_err());
+    /// assert_eq!(out.take_error().unwrap().kind(), io::ErrorKind::WriteZero);
+    /// assert!(out.take_error().is_none());
+    /// ```
+    pub fn take_error(&mut self) -> Option<std::io::Error> {
+        self.1 .0.take()
+    }
+
+    /// The writer and the most recent error from it, if any.
+    pub fn into_parts(self) -> (W, Option<std::io::Error>) {
+        (self.0, self.1 .0)
     }
 }
 
@@ -322,11 +1099,24 @@ where
 /// ```
 pub struct ErrTree<'a> {
     inner: &'a dyn Error,
+    msg_override: Option<&'a str>,
+    code: Option<&'a str>,
+    #[cfg(feature = "type_name")]
+    type_name: Option<&'static str>,
     sources: IterBuffer<&'a mut dyn Iterator<Item = &'a dyn AsErrTree>>,
     #[cfg(feature = "source_line")]
     location: Option<&'a Location<'a>>,
     #[cfg(feature = "tracing")]
     trace: Option<&'a tracing_error::SpanTrace>,
+    #[cfg(feature = "timestamp")]
+    time: Option<std::time::SystemTime>,
+    downcast: Option<&'a (dyn Error + 'static)>,
+    collapsed: bool,
+    /// Number of sources, counted ahead of rendering for
+    /// [`FormatOptions::max_children`]
+    sources_len: Option<usize>,
+    #[cfg(feature = "catch_panics")]
+    catch_panics: bool,
 }
 
 impl<'a> ErrTree<'a> {
@@ -338,11 +1128,45 @@ impl<'a> ErrTree<'a> {
     ) -> Self {
         Self {
             inner,
+            msg_override: None,
+            code: None,
+            #[cfg(feature = "type_name")]
+            type_name: None,
             sources: sources.into(),
             #[cfg(feature = "source_line")]
             location: Some(pkg.location()),
             #[cfg(feature = "tracing")]
             trace: Some(pkg.trace()),
+            #[cfg(feature = "timestamp")]
+            time: Some(pkg.time()),
+            downcast: None,
+            collapsed: false,
+            sources_len: None,
+            #[cfg(feature = "catch_panics")]
+            catch_panics: false,
+        }
+    }
+
+    /// [`Self::with_pkg`], tagged with the name of the error's type (see
+    /// [`Self::with_type_name`]).
+    pub fn with_pkg_named(
+        inner: &'a dyn Error,
+        sources: &'a mut dyn Iterator<Item = &'a dyn AsErrTree>,
+        pkg: &'a ErrTreePkg,
+        type_name: &'static str,
+    ) -> Self {
+        Self::with_pkg(inner, sources, pkg).with_type_name(type_name)
+    }
+
+    /// [`Self::with_pkg`], or [`Self::no_pkg`] without a package.
+    pub fn with_pkg_opt(
+        inner: &'a dyn Error,
+        sources: &'a mut dyn Iterator<Item = &'a dyn AsErrTree>,
+        pkg: Option<&'a ErrTreePkg>,
+    ) -> Self {
+        match pkg {
+            Some(pkg) => Self::with_pkg(inner, sources, pkg),
+            None => Self::no_pkg(inner, sources),
         }
     }
 
@@ -353,18 +1177,114 @@ impl<'a> ErrTree<'a> {
     ) -> Self {
         Self {
             inner,
+            msg_override: None,
+            code: None,
+            #[cfg(feature = "type_name")]
+            type_name: None,
             sources: sources.into(),
             #[cfg(feature = "source_line")]
             location: None,
             #[cfg(feature = "tracing")]
             trace: None,
+            #[cfg(feature = "timestamp")]
+            time: None,
+            downcast: None,
+            collapsed: false,
+            sources_len: None,
+            #[cfg(feature = "catch_panics")]
+            catch_panics: false,
         }
     }
 
+    /// Replaces the [`Display`][`core::fmt::Display`] output of the error
+    /// with `msg` in all tree output.
+    pub fn with_msg_override(mut self, msg: &'a str) -> Self {
+        self.msg_override = Some(msg);
+        self
+    }
+
+    /// Tags the error with a stable code, rendered in brackets before the
+    /// message (e.g. `[E0142] not found`) and stored as `"code"` in JSON.
+    ///
+    /// Set by `#[err_tree(code = "...")]`.
+    pub fn with_code(mut self, code: &'a str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Tags the error with the name of its type, e.g. from
+    /// [`core::any::type_name`]. With the `type_name` feature, it is rendered
+    /// after the message (e.g. `not found  [my_crate::NotFound]`) and stored
+    /// as `"type"` in JSON. Without it, the name is discarded.
+    ///
+    /// Set by [`err_tree`] for every annotated type.
+    #[allow(unused_mut, unused_variables)]
+    pub fn with_type_name(mut self, type_name: &'static str) -> Self {
+        #[cfg(feature = "type_name")]
+        {
+            self.type_name = Some(type_name);
+        }
+        self
+    }
+
+    /// Renders only the message and metadata of this error, followed by a
+    /// count of its nested errors instead of the nested errors themselves.
+    ///
+    /// [`FormatOptions::expand_collapsed`] renders the full tree regardless.
+    pub fn collapsed(mut self) -> Self {
+        self.collapsed = true;
+        self
+    }
+
+    /// Marks the error as `'static`, so it can be downcast by
+    /// [`Self::downcast_ref`].
+    ///
+    /// `inner` should be the same error this tree was constructed with.
+    pub fn downcastable(mut self, inner: &'a (dyn Error + 'static)) -> Self {
+        self.downcast = Some(inner);
+        self
+    }
+
+    /// The error, if it can be downcast.
+    #[cfg(feature = "classify")]
+    pub(crate) fn downcast(&self) -> Option<&'a (dyn Error + 'static)> {
+        self.downcast
+    }
+
+    /// Returns the error as a `T`, if it is one.
+    ///
+    /// Only errors known to be `'static` can be downcast: those reached as
+    /// `dyn Error` (e.g. through [`Error::source`]), [`err_tree`] types
+    /// without lifetime or type parameters, or marked with
+    /// [`Self::downcastable`]. Others, including errors held directly by an
+    /// [`err_tree`] field, always return `None`.
+    ///
+    /// ```rust
+    /// # use std::{error::Error, io};
+    /// use bare_err_tree::AsErrTree;
+    ///
+    /// let err: Box<dyn Error> = io::Error::from(io::ErrorKind::NotFound).into();
+    /// err.as_err_tree(&mut |tree| {
+    ///     let io_err = tree.downcast_ref::<io::Error>().unwrap();
+    ///     assert_eq!(io_err.kind(), io::ErrorKind::NotFound);
+    /// });
+    /// ```
+    pub fn downcast_ref<T: Error + 'static>(&self) -> Option<&'a T> {
+        self.downcast?.downcast_ref()
+    }
+
     /// Consumes this tree to return its sources
     pub fn sources(self) -> impl Iterator<Item = &'a dyn AsErrTree> {
         self.sources
     }
+
+    /// Writes the message override, or the error's display if unset.
+    pub(crate) fn write_msg<W: fmt::Write>(&self, mut f: W) -> fmt::Result {
+        match self.msg_override {
+            Some(msg) => f.write_str(msg),
+            None => write!(f, "{}", self.inner),
+        }
+    }
 }
 
 /// Defines an [`Error`]'s temporary view as an [`ErrTree`] for printing.
@@ -374,6 +1294,23 @@ impl<'a> ErrTree<'a> {
 /// any more information than standard library errors or track multiple sources.
 ///
 /// Implementors must call `func` with a properly constructed [`ErrTree`].
+///
+/// # Thread Safety
+/// Rendering only takes `&self`, so one error (e.g. in an `Arc`) may be
+/// rendered from several threads at once. The print functions keep all of
+/// their state in per-call buffers, and the crate's shared state (the
+/// [`set_pkg_hook`] hook, the `pkg_cache` cache, and the `external_pkg`
+/// table) is atomic, thread-local, or locked. Renders of one error are then
+/// byte-identical as long as its [`Display`](core::fmt::Display) and
+/// `as_err_tree` are. Implementors with interior mutability (e.g. a summary
+/// computed into a `OnceLock`) must produce the same output whichever
+/// thread initializes it, and must not expose a partially computed value.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not an error tree",
+    label = "`{Self}` does not implement `AsErrTree`",
+    note = "mark sources that only implement `Error` with `#[dyn_err]` or \
+        `#[dyn_iter_err]`, or wrap them in `WrapErr`"
+)]
 pub trait AsErrTree {
     /// Constructs the [`ErrTree`] internally and calls `func` on it.
     fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>));
@@ -385,13 +1322,9 @@ pub trait AsErrTree {
 /// sources.
 impl AsErrTree for dyn Error {
     fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
-        match self.source() {
-            Some(e) => (func)(ErrTree::no_pkg(
-                self,
-                &mut core::iter::once(&e as &dyn AsErrTree),
-            )),
-            None => (func)(ErrTree::no_pkg(self, &mut core::iter::empty())),
-        }
+        let source = self.source();
+        let mut sources = source.as_ref().map(|e| e as &dyn AsErrTree).into_iter();
+        (func)(ErrTree::no_pkg(self, &mut sources).downcastable(self))
     }
 }
 
@@ -411,12 +1344,46 @@ impl AsErrTree for eyre::Report {
     }
 }
 
+#[cfg(feature = "snafu")]
+impl AsErrTree for snafu::Whatever {
+    fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+        let this: &dyn Error = self;
+        this.as_err_tree(func)
+    }
+}
+
+#[cfg(feature = "snafu")]
+impl AsErrTree for snafu::WhateverLocal {
+    fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+        let this: &dyn Error = self;
+        this.as_err_tree(func)
+    }
+}
+
 impl<T: ?Sized + AsErrTree> AsErrTree for &T {
     fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
         T::as_err_tree(self, func)
     }
 }
 
+/// Renders as the shared `T`, so every holder has the same identity for
+/// [`FormatOptions::dedup_by_identity`] and [`FormatOptions::mark_shared`].
+#[cfg(feature = "alloc")]
+impl<T: ?Sized + AsErrTree> AsErrTree for alloc::sync::Arc<T> {
+    fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+        T::as_err_tree(self, func)
+    }
+}
+
+/// Renders as the shared `T`, so every holder has the same identity for
+/// [`FormatOptions::dedup_by_identity`] and [`FormatOptions::mark_shared`].
+#[cfg(feature = "alloc")]
+impl<T: ?Sized + AsErrTree> AsErrTree for alloc::rc::Rc<T> {
+    fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+        T::as_err_tree(self, func)
+    }
+}
+
 /// Boilerplate reducer for manual [`ErrTree`].
 ///
 /// Expands out to [`ErrTree::with_pkg`] with `$x` as source(s).
@@ -486,3 +1453,261 @@ macro_rules! tree {
         ))
     };
 }
+
+/// Boilerplate reducer for [`AsErrTree`] on `snafu` errors.
+///
+/// `snafu` errors only expose a single [`Error::source`], so the `dyn`
+/// rendering loses any additional sources. This matches on `$err` and builds
+/// an [`ErrTree`] from the sources listed for each arm. Prefix each source
+/// with `tree` for an [`AsErrTree`] reference, or `dyn` for an [`Error`]
+/// reference rendered through [`WrapErr`].
+///
+/// Add an [`ErrTreePkg`] to a variant with `#[snafu(implicit)]` to capture
+/// the context selector callsite, and pass it with `with PKG,`. The
+/// `snafu::Location` type cannot be converted, as tree locations are only
+/// produced by `#[track_caller]`.
+///
+/// Blanket implementation over `snafu::ErrorCompat` is not possible, as it
+/// would conflict with the existing implementations of [`AsErrTree`].
+///
+/// ```
+/// # use std::io;
+/// use bare_err_tree::{front_max_for_depth, print_tree, snafu_tree, AsErrTree, ErrTree, ErrTreePkg};
+/// use snafu::Snafu;
+///
+/// #[derive(Debug, Snafu)]
+/// enum ConfigError {
+///     #[snafu(display("failed to read {path}"))]
+///     Read {
+///         path: String,
+///         source: io::Error,
+///         #[snafu(implicit)]
+///         pkg: ErrTreePkg,
+///     },
+///     #[snafu(display("no usable config"))]
+///     Fallbacks {
+///         primary: Box<ConfigError>,
+///         secondary: Box<ConfigError>,
+///     },
+/// }
+///
+/// impl AsErrTree for ConfigError {
+///     fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+///         snafu_tree!(func, self, {
+///             ConfigError::Read { source, pkg, .. } => with pkg, [dyn source],
+///             ConfigError::Fallbacks { primary, secondary } => [tree &**primary, tree &**secondary],
+///         })
+///     }
+/// }
+///
+/// let read = |path: &str| -> ConfigError {
+///     snafu::IntoError::into_error(
+///         ReadSnafu { path },
+///         io::Error::from(io::ErrorKind::NotFound),
+///     )
+/// };
+/// let err = ConfigError::Fallbacks {
+///     primary: Box::new(read("app.toml")),
+///     secondary: Box::new(read("/etc/app.toml")),
+/// };
+///
+/// let mut out = String::new();
+/// print_tree::<{ front_max_for_depth(10) }, _, _>(&err, &mut out).unwrap();
+/// assert!(out.contains("failed to read app.toml"));
+/// assert!(out.contains("failed to read /etc/app.toml"));
+/// ```
+#[cfg(feature = "snafu")]
+#[macro_export]
+macro_rules! snafu_tree {
+    ($func:expr, $err:expr, {
+        $( $pat:pat => $(with $pkg:expr,)? [ $( $kind:tt $src:expr ),* $(,)? ] ),+ $(,)?
+    }) => {
+        match $err {
+            $(
+                err @ $pat => $crate::snafu_tree!(
+                    @tree $func, err, [$($pkg)?],
+                    &mut ::core::iter::empty()$( .chain(::core::iter::once(
+                        $crate::snafu_tree!(@src $kind $src)
+                    )) )*
+                ),
+            )+
+        }
+    };
+    (@tree $func:expr, $err:expr, [], $sources:expr) => {
+        ($func)($crate::ErrTree::no_pkg($err, $sources))
+    };
+    (@tree $func:expr, $err:expr, [$pkg:expr], $sources:expr) => {
+        ($func)($crate::ErrTree::with_pkg($err, $sources, $pkg))
+    };
+    (@src dyn $src:expr) => {
+        $crate::WrapErr::tree($src)
+    };
+    (@src tree $src:expr) => {
+        $src as &dyn $crate::AsErrTree
+    };
+}
+
+/// Implements a newtype over a displayable value as a leaf error.
+///
+/// Generates [`Error`], [`Display`][`core::fmt::Display`] forwarding to the
+/// inner value, and a source-less [`AsErrTree`]. The type must implement
+/// [`Debug`][`core::fmt::Debug`]. Multiple types can be passed at once.
+///
+/// ```
+/// use bare_err_tree::{front_max_for_depth, print_tree, simple_err_tree};
+///
+/// #[derive(Debug)]
+/// struct MyError(String);
+///
+/// simple_err_tree!(MyError);
+///
+/// let mut out = String::new();
+/// print_tree::<{ front_max_for_depth(10) }, _, _>(MyError("invalid input".to_string()), &mut out).unwrap();
+/// assert_eq!(out, "invalid input");
+/// ```
+#[macro_export]
+macro_rules! simple_err_tree {
+    ($( $ty:ty ),+ $(,)?) => {
+        $(
+            impl ::core::error::Error for $ty {}
+
+            impl ::core::fmt::Display for $ty {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    ::core::fmt::Display::fmt(&self.0, f)
+                }
+            }
+
+            impl $crate::AsErrTree for $ty {
+                fn as_err_tree(&self, func: &mut dyn FnMut($crate::ErrTree<'_>)) {
+                    (func)($crate::ErrTree::no_pkg(self, &mut ::core::iter::empty()))
+                }
+            }
+        )+
+    };
+}
+
+/// Asserts at compile time that a type fits in `max` bytes.
+///
+/// [`ErrTreePkg`] grows with the enabled metadata features, which can make
+/// error types in hot `Result` paths noticeably larger. Placing this assertion
+/// next to an error type surfaces that growth as a build failure instead of a
+/// profiling surprise. Enabling `boxed` caps the overhead at one pointer.
+///
+/// ```
+/// # #[cfg(feature = "derive")]
+/// # mod derived {
+/// # use std::{error::Error, fmt::{self, Display, Formatter}};
+/// use bare_err_tree::{assert_err_tree_size, err_tree};
+///
+/// #[err_tree]
+/// #[derive(Debug)]
+/// struct MyError {
+///     code: u32,
+/// }
+/// # impl Error for MyError {}
+/// # impl Display for MyError {
+/// #     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+/// #         write!(f, "{}", self.code)
+/// #     }
+/// # }
+///
+/// assert_err_tree_size!(MyError, max = 80);
+/// # }
+/// ```
+///
+/// ```compile_fail
+/// use bare_err_tree::assert_err_tree_size;
+///
+/// assert_err_tree_size!([u8; 33], max = 32);
+/// ```
+#[macro_export]
+macro_rules! assert_err_tree_size {
+    ($ty:ty, max = $max:expr $(,)?) => {
+        const _: () = ::core::assert!(
+            ::core::mem::size_of::<$ty>() <= $max,
+            ::core::concat!(
+                "`",
+                ::core::stringify!($ty),
+                "` is larger than ",
+                ::core::stringify!($max),
+                " bytes"
+            ),
+        );
+    };
+}
+
+/// Every `#[err_tree(code = "...")]` type linked into the binary, as its
+/// path and code. Filled in by [`err_tree`] with `code_registry`.
+#[cfg(feature = "code_registry")]
+#[linkme::distributed_slice]
+pub static ERR_CODES: [(&'static str, &'static str)];
+
+#[cfg(feature = "code_registry")]
+#[doc(hidden)]
+pub use linkme as __linkme;
+
+/// Panics if two types in [`ERR_CODES`] share an `ERR_CODE`, naming both.
+///
+/// `#[err_tree(code = "...")]` gives a type an associated `ERR_CODE`, and
+/// registers it with `code_registry`. Calling this from a test checks every
+/// coded type linked into the test binary, including those of dependencies.
+///
+/// ```
+/// # #[cfg(feature = "code_registry")]
+/// # mod coded {
+/// # use std::{error::Error, fmt::{self, Display, Formatter}};
+/// use bare_err_tree::{assert_unique_err_codes, err_tree};
+///
+/// #[err_tree(code = "E0001")]
+/// #[derive(Debug)]
+/// struct NotFound(u32);
+///
+/// #[err_tree(code = "E0002")]
+/// #[derive(Debug)]
+/// struct Denied(u32);
+/// # impl Error for NotFound {}
+/// # impl Display for NotFound {
+/// #     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+/// #         write!(f, "{} not found", self.0)
+/// #     }
+/// # }
+/// # impl Error for Denied {}
+/// # impl Display for Denied {
+/// #     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+/// #         write!(f, "{} denied", self.0)
+/// #     }
+/// # }
+///
+/// # pub fn check() {
+/// assert_unique_err_codes!();
+/// # }
+/// # }
+/// # #[cfg(feature = "code_registry")]
+/// # coded::check();
+/// ```
+#[cfg(feature = "code_registry")]
+#[macro_export]
+macro_rules! assert_unique_err_codes {
+    () => {{
+        let codes: &[(&str, &str)] = &$crate::ERR_CODES;
+        for (idx, (name, code)) in codes.iter().enumerate() {
+            if let ::core::option::Option::Some((first, _)) = codes[..idx]
+                .iter()
+                .find(|(_, first_code)| first_code == code)
+            {
+                // Registry order is unspecified, so name the pair in order
+                let (first, second) = if first <= name {
+                    (first, name)
+                } else {
+                    (name, first)
+                };
+                ::core::panic!(
+                    "error code `{}` is used by both `{}` and `{}`",
+                    code,
+                    first,
+                    second
+                );
+            }
+        }
+    }};
+}
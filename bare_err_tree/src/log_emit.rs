@@ -0,0 +1,100 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Structured [`log`] events per tree node.
+
+use core::fmt::{self, Display, Formatter};
+
+use crate::{AsErrTree, ErrTree};
+
+/// Emits one [`log::error!`] event per error in the tree, depth-first.
+///
+/// Each event's message is the error message, with the key-values:
+/// * `msg`: The error message.
+/// * `depth`: Distance from the root, which has depth 0.
+/// * `path`: Dot-separated source indices from the root, e.g. `0.1.0` is
+///   the first source of the second source of the root. The root is `0`.
+/// * `location`: The tracked source location, if any. Only with `source_line`.
+///
+/// Events use the default target of this module. `tracing` subscribers can
+/// receive them through a `log` bridge such as `tracing-log`.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::log_tree;
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+///
+/// // Emits "outer" with path 0, then "unexpected end of file" with path 0.0
+/// log_tree(&err as &dyn Error);
+/// ```
+pub fn log_tree<E: AsErrTree>(tree: E) {
+    tree.as_err_tree(&mut |tree| {
+        log_node(
+            tree,
+            NodePath {
+                idx: 0,
+                parent: None,
+            },
+            0,
+        )
+    });
+}
+
+fn log_node(tree: ErrTree<'_>, path: NodePath<'_>, depth: usize) {
+    let msg = NodeMsg(&tree);
+
+    #[cfg(feature = "source_line")]
+    log::error!(
+        msg:% = msg, depth, path:% = path, location = tree.location.map(log::kv::Value::from_display);
+        "{msg}"
+    );
+
+    #[cfg(not(feature = "source_line"))]
+    log::error!(msg:% = msg, depth, path:% = path; "{msg}");
+
+    for (idx, source) in tree.sources().enumerate() {
+        source.as_err_tree(&mut |tree| {
+            log_node(
+                tree,
+                NodePath {
+                    idx,
+                    parent: Some(&path),
+                },
+                depth + 1,
+            )
+        });
+    }
+}
+
+/// Displays the error message without allocating.
+struct NodeMsg<'a, 'b>(&'a ErrTree<'b>);
+
+impl Display for NodeMsg<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.write_msg(f)
+    }
+}
+
+/// Position in the tree, linked back to the root through the call stack.
+#[derive(Clone, Copy)]
+struct NodePath<'a> {
+    idx: usize,
+    parent: Option<&'a NodePath<'a>>,
+}
+
+impl Display for NodePath<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(parent) = self.parent {
+            write!(f, "{parent}.")?;
+        }
+        write!(f, "{}", self.idx)
+    }
+}
@@ -0,0 +1,83 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Markdown bullet lists of an error tree.
+
+use core::fmt::{self, Write};
+
+use crate::{snapshot::snapshot, AsErrTree, TreeSnapshot};
+
+/// Renders the tree as a nested markdown bullet list.
+///
+/// Each error is a `- ` bullet, indented by two spaces per depth, so the
+/// output pastes into issues and PRs without a code fence. Tracked locations
+/// (`source_line`) follow the message in italics, as `*at file:line:column*`.
+/// Later lines of a multi-line message continue the same bullet.
+///
+/// `*`, `_`, `` ` ``, and `\` are escaped with a backslash.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::tree_to_markdown;
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("read *config*")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+///
+/// let mut out = String::new();
+/// tree_to_markdown(&(&err as &dyn Error), &mut out).unwrap();
+/// assert_eq!(out, "- read \\*config\\*\n  - unexpected end of file");
+/// ```
+pub fn tree_to_markdown<E, W>(tree: &E, mut formatter: W) -> fmt::Result
+where
+    E: AsErrTree + ?Sized,
+    W: Write,
+{
+    write_node(&mut formatter, &snapshot(tree), 0)
+}
+
+/// Writes `node` and its sources, each on a new line after the first.
+fn write_node<W: Write>(formatter: &mut W, node: &TreeSnapshot, depth: usize) -> fmt::Result {
+    let indent = depth * 2;
+    write!(formatter, "{:indent$}- ", "")?;
+
+    let mut lines = node.msg.lines();
+    MarkdownEscape(&mut *formatter).write_str(lines.next().unwrap_or_default())?;
+    for line in lines {
+        // Aligned with the bullet text, to continue the same item
+        write!(formatter, "\n{:indent$}", "", indent = indent + 2)?;
+        MarkdownEscape(&mut *formatter).write_str(line)?;
+    }
+    #[cfg(feature = "source_line")]
+    if let Some(location) = &node.location {
+        formatter.write_str(" *at ")?;
+        MarkdownEscape(&mut *formatter).write_str(location)?;
+        formatter.write_char('*')?;
+    }
+
+    node.children.iter().try_for_each(|child| {
+        formatter.write_char('\n')?;
+        write_node(formatter, child, depth + 1)
+    })
+}
+
+/// Escapes markdown emphasis and code characters with a backslash.
+struct MarkdownEscape<W>(W);
+
+impl<W: Write> Write for MarkdownEscape<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut rest = s;
+        while let Some(idx) = rest.find(['*', '_', '`', '\\']) {
+            self.0.write_str(&rest[..idx])?;
+            self.0.write_char('\\')?;
+            self.0.write_str(&rest[idx..=idx])?;
+            rest = &rest[idx + 1..];
+        }
+        self.0.write_str(rest)
+    }
+}
@@ -0,0 +1,88 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Uninhabited error for pipelines that cannot fail.
+
+use core::{
+    convert::Infallible,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    mem::size_of,
+};
+
+use crate::{AsErrTree, ErrTree};
+
+/// An error that can never be constructed.
+///
+/// Generic reporting code bounded on [`AsErrTree`] can be instantiated with
+/// `Result<T, NeverTree>` in specializations that always succeed. The only
+/// field is a private [`Infallible`], so no value exists at runtime and
+/// [`tree_unwrap`][`crate::tree_unwrap`] reduces to taking the [`Ok`] value.
+///
+/// `NeverTree` is zero-sized, and `Result<T, NeverTree>` has the layout of
+/// `T`.
+///
+/// ```rust
+/// use bare_err_tree::{front_max_for_depth, tree_unwrap, AsErrTree, NeverTree};
+///
+/// fn report<T, E: AsErrTree>(res: Result<T, E>) -> T {
+///     tree_unwrap::<{ front_max_for_depth(10) }, _, _>(res)
+/// }
+///
+/// let res: Result<u32, NeverTree> = Ok(4);
+/// assert_eq!(report(res), 4);
+/// ```
+///
+/// The field is private, so `NeverTree` cannot be built safely:
+/// ```rust,compile_fail
+/// use core::convert::Infallible;
+/// use bare_err_tree::NeverTree;
+///
+/// fn make(inner: Infallible) -> NeverTree {
+///     NeverTree(inner)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NeverTree(Infallible);
+
+const _: () = assert!(size_of::<NeverTree>() == 0);
+
+impl NeverTree {
+    /// Converts to any type, as this value cannot exist.
+    #[inline(always)]
+    pub const fn into_any<T>(self) -> T {
+        match self.0 {}
+    }
+}
+
+impl From<Infallible> for NeverTree {
+    #[inline(always)]
+    fn from(value: Infallible) -> Self {
+        match value {}
+    }
+}
+
+impl Error for NeverTree {}
+impl Display for NeverTree {
+    #[inline(always)]
+    fn fmt(&self, _f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {}
+    }
+}
+
+impl AsErrTree for NeverTree {
+    #[inline(always)]
+    fn as_err_tree(&self, _func: &mut dyn FnMut(ErrTree<'_>)) {
+        match self.0 {}
+    }
+}
+
+impl AsErrTree for Infallible {
+    #[inline(always)]
+    fn as_err_tree(&self, _func: &mut dyn FnMut(ErrTree<'_>)) {
+        match *self {}
+    }
+}
@@ -0,0 +1,258 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Rendering options for [`print_tree_with`][`crate::print_tree_with`].
+
+/// Opt-in rendering behavior for
+/// [`print_tree_with`][`crate::print_tree_with`].
+///
+/// The default matches [`print_tree`][`crate::print_tree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FormatOptions {
+    pub(crate) dedup_by_identity: bool,
+    pub(crate) mark_shared: bool,
+    pub(crate) expand_collapsed: bool,
+    pub(crate) sanitize: bool,
+    pub(crate) numbered: bool,
+    pub(crate) tight: bool,
+    pub(crate) aligned: bool,
+    pub(crate) progress_every: usize,
+    pub(crate) max_children: Option<usize>,
+    pub(crate) max_nodes: usize,
+    #[cfg(feature = "catch_panics")]
+    pub(crate) catch_source_panics: bool,
+    #[cfg(feature = "timestamp")]
+    pub(crate) show_elapsed: bool,
+    #[cfg(feature = "tracing")]
+    pub(crate) inline_fields: usize,
+    #[cfg(feature = "source_line")]
+    pub(crate) dedup_source_lines: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FormatOptions {
+    /// Options matching [`print_tree`][`crate::print_tree`].
+    pub const fn new() -> Self {
+        Self {
+            dedup_by_identity: false,
+            mark_shared: false,
+            expand_collapsed: false,
+            sanitize: false,
+            numbered: false,
+            tight: false,
+            aligned: false,
+            progress_every: 256,
+            max_children: None,
+            max_nodes: usize::MAX,
+            #[cfg(feature = "catch_panics")]
+            catch_source_panics: false,
+            #[cfg(feature = "timestamp")]
+            show_elapsed: false,
+            #[cfg(feature = "tracing")]
+            inline_fields: 60,
+            #[cfg(feature = "source_line")]
+            dedup_source_lines: false,
+        }
+    }
+
+    /// Render each error object at most once.
+    ///
+    /// An error can be reached through more than one branch, e.g. when a
+    /// wrapper's [`Error::source`][`core::error::Error::source`] returns an
+    /// error that is also annotated as a separate field. With this enabled,
+    /// later occurrences render as `(see above: <first line>)` instead of
+    /// repeating the subtree.
+    ///
+    /// Errors are identified by address and size, so zero-sized errors are
+    /// never deduplicated. A node sharing an address with one of its own
+    /// ancestors (e.g. a newtype wrapper's only field) is not a duplicate.
+    /// Up to `FRONT_MAX` errors are tracked; past that, errors render in full.
+    pub const fn dedup_by_identity(mut self, enable: bool) -> Self {
+        self.dedup_by_identity = enable;
+        self
+    }
+
+    /// Mark errors reached through more than one parent, e.g. a child held
+    /// in an `Arc` by two errors.
+    ///
+    /// Unlike [`Self::dedup_by_identity`], later occurrences still render
+    /// their subtree, with `(shared with an earlier occurrence)` after the
+    /// message. Identity and tracking limits are the same as for
+    /// [`Self::dedup_by_identity`], which takes precedence when both are set.
+    pub const fn mark_shared(mut self, enable: bool) -> Self {
+        self.mark_shared = enable;
+        self
+    }
+
+    /// Render the nested errors of [`collapsed`][`crate::ErrTree::collapsed`]
+    /// errors, instead of only their count.
+    pub const fn expand_collapsed(mut self, enable: bool) -> Self {
+        self.expand_collapsed = enable;
+        self
+    }
+
+    /// Strip control characters from error messages, other than the `\n`
+    /// that splits lines.
+    ///
+    /// Removes the C0 and C1 control characters (e.g. `\x1b` starting a
+    /// terminal escape sequence), so messages built from untrusted input
+    /// cannot rewrite the terminal. Only messages are filtered, not source
+    /// lines or `tracing` fields.
+    pub const fn sanitize(mut self, enable: bool) -> Self {
+        self.sanitize = enable;
+        self
+    }
+
+    /// Prefix each error message with its dotted position in the tree.
+    ///
+    /// The root is `1`, its sources are `1.1`, `1.2`, and so on. Source
+    /// lines, traces, and continuation lines are not numbered. Errors cut off
+    /// by `FRONT_MAX` are not rendered, so they take no number.
+    pub const fn numbered(mut self, enable: bool) -> Self {
+        self.numbered = enable;
+        self
+    }
+
+    /// Omit the blank `│` line above each source, so sibling errors sit
+    /// directly under one another.
+    ///
+    /// ```rust
+    /// # use std::{error::Error, io};
+    /// use bare_err_tree::{front_max_for_depth, print_tree_with, FormatOptions};
+    ///
+    /// #[derive(Debug, thiserror::Error)]
+    /// #[error("outer")]
+    /// struct Outer(#[source] io::Error);
+    ///
+    /// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+    ///
+    /// let mut out = String::new();
+    /// print_tree_with::<{ front_max_for_depth(10) }, _, _>(
+    ///     &err as &dyn Error,
+    ///     FormatOptions::new().tight(true),
+    ///     &mut out,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(out, "outer\n╰─▶ unexpected end of file");
+    /// ```
+    pub const fn tight(mut self, enable: bool) -> Self {
+        self.tight = enable;
+        self
+    }
+
+    /// Pad connectors without an arrow (e.g. `├─ at ...`) to the width of
+    /// the source arrows, so the text under an error starts in one column.
+    ///
+    /// Source lines, traces, and other metadata then start where the
+    /// messages of sources do, and their continuation lines move right by one
+    /// column to match. The front lines keep their width, so `FRONT_MAX`
+    /// limits the same depth either way.
+    pub const fn aligned(mut self, enable: bool) -> Self {
+        self.aligned = enable;
+        self
+    }
+
+    /// Number of errors rendered between progress checks, for
+    /// [`print_tree_cancellable_with`][`crate::print_tree_cancellable_with`].
+    ///
+    /// Defaults to 256. Zero is treated as one.
+    pub const fn progress_every(mut self, nodes: usize) -> Self {
+        self.progress_every = nodes;
+        self
+    }
+
+    /// Render at most `max` sources of each error, replacing the middle of a
+    /// longer list with `… <count> similar errors elided`.
+    ///
+    /// The first `max - max / 2` and last `max / 2` sources are kept, e.g.
+    /// the first and last two for a `max` of 4. Numbering (see
+    /// [`numbered`][`Self::numbered`]) keeps the position of each source in
+    /// the full list.
+    ///
+    /// Sources are iterated once while rendering, so they are counted up
+    /// front with a second [`AsErrTree::as_err_tree`][`crate::AsErrTree::as_err_tree`]
+    /// call on each error. JSON output is unaffected.
+    pub const fn max_children(mut self, max: Option<usize>) -> Self {
+        self.max_children = max;
+        self
+    }
+
+    /// Render at most `max` errors in total, ending the output with
+    /// `… node budget (<max>) exhausted` once they are used up.
+    ///
+    /// Unlike `FRONT_MAX` and [`max_children`][`Self::max_children`], this
+    /// bounds the work of a render regardless of the shape of the tree, e.g.
+    /// for sources that multiply at every level. Every source reached counts,
+    /// including `(see above: ...)` and cycle lines, but not elided sources.
+    /// The root always renders, so zero is treated as one. Unlimited by
+    /// default.
+    ///
+    /// [`tree_to_json_max_nodes`][`crate::tree_to_json_max_nodes`] applies the
+    /// same budget to JSON.
+    pub const fn max_nodes(mut self, max: usize) -> Self {
+        self.max_nodes = max;
+        self
+    }
+
+    /// Render `<source formatting panicked>` in place of a source whose
+    /// [`AsErrTree::as_err_tree`][`crate::AsErrTree::as_err_tree`] panics,
+    /// instead of propagating the panic.
+    ///
+    /// Keeps a buggy source implementation from hiding the original error,
+    /// e.g. in production logging. The panic hook still runs, and panics from
+    /// rendering the source's own tree (e.g. its `Display`) still propagate.
+    #[cfg(feature = "catch_panics")]
+    pub const fn catch_source_panics(mut self, enable: bool) -> Self {
+        self.catch_source_panics = enable;
+        self
+    }
+
+    /// Render how long before its parent each error was created, e.g.
+    /// `├─ 12ms before parent`, under its timestamp.
+    ///
+    /// Errors are built from the inside out, so this is the time spent
+    /// between a failure and each layer wrapping it. Only errors with a
+    /// timestamp under a parent with a timestamp show the line. The system
+    /// clock can step backwards, so a source created after its parent
+    /// renders as `0ns`, noting how much later it was. JSON reconstruction
+    /// only has whole seconds to compare.
+    #[cfg(feature = "timestamp")]
+    pub const fn show_elapsed(mut self, enable: bool) -> Self {
+        self.show_elapsed = enable;
+        self
+    }
+
+    /// Render `tracing` fields shorter than `len` characters on the
+    /// `tracing frame` line, e.g. `... with retries=3`.
+    ///
+    /// Longer fields, and fields containing a newline, are split across
+    /// lines by their structure. Fields are read ahead into a stack buffer,
+    /// so `len` is capped at 128. Defaults to 60, and zero always splits.
+    #[cfg(feature = "tracing")]
+    pub const fn inline_fields(mut self, len: usize) -> Self {
+        self.inline_fields = len;
+        self
+    }
+
+    /// Render each repeated source line once, as `├─ at <loc> (seen N times)`
+    /// at its first occurrence, omitting it from later errors.
+    ///
+    /// Errors constructed in one helper all report that helper's location,
+    /// which this collapses like repeated `tracing` frames. Locations are
+    /// counted up front with a second walk of the tree, and compared by
+    /// file, line, and column. Up to `FRONT_MAX` distinct locations are
+    /// tracked; past that, source lines render on every error.
+    #[cfg(feature = "source_line")]
+    pub const fn dedup_source_lines(mut self, enable: bool) -> Self {
+        self.dedup_source_lines = enable;
+        self
+    }
+}
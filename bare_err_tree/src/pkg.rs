@@ -12,9 +12,12 @@ use core::panic::Location;
 #[cfg(feature = "tracing")]
 use tracing_error::SpanTrace;
 
-#[cfg(feature = "boxed")]
+#[cfg(all(feature = "boxed", any(feature = "tracing", feature = "timestamp")))]
 use alloc::boxed::Box;
 
+#[cfg(feature = "timestamp")]
+use std::time::SystemTime;
+
 /// Captures extra information for [`ErrTree`][`crate::ErrTree`]
 /// automatically.
 ///
@@ -25,37 +28,137 @@ use alloc::boxed::Box;
 /// combinations via feature flags without changing the API. The `boxed`
 /// feature can be enabled to store this in heap.
 ///
+/// # Boxing
+/// `boxed` only allocates when the metadata is larger than the [`Box`]
+/// pointer, which is when `tracing` or `timestamp` is enabled. Otherwise, the
+/// metadata is at most one pointer and is stored inline, so [`Self::new`]
+/// never allocates. Error types that are built speculatively and usually
+/// discarded (e.g. parser lookahead errors) only pay for an allocation when
+/// the allocation makes them smaller. The `pkg_new` bench measures this
+/// pattern.
+///
+/// [`Box`]: alloc::boxed::Box
+///
 /// All instances of this are considered equal, to avoid infecting sort order
 /// or comparisons between the parent error types. Hashing is a no-op.
+///
+/// # Size
+/// Each error type carrying this grows by its size. On 64-bit targets:
+///
+/// | Features              | Size                            |
+/// |-----------------------|---------------------------------|
+/// | none                  | 0 bytes                         |
+/// | `source_line`         | +8 bytes (`&'static Location`)  |
+/// | `tracing`             | +40 bytes (`SpanTrace`)         |
+/// | `timestamp`           | +16 bytes (`SystemTime`, Unix)  |
+/// | `boxed`, `tracing`    | 8 bytes total, metadata on heap |
+/// | `boxed`, `timestamp`  | 8 bytes total, metadata on heap |
+/// | `boxed`, otherwise    | unchanged, stored inline        |
+///
+/// Pointer-sized entries halve on 32-bit targets, and [`SystemTime`] varies
+/// by platform. These values are checked by the crate's test suite; use
+/// [`assert_err_tree_size`][`crate::assert_err_tree_size`] to guard the size
+/// of your own error types.
+///
+/// [`SystemTime`]: https://doc.rust-lang.org/std/time/struct.SystemTime.html
 #[derive(Clone)]
 pub struct ErrTreePkg {
-    #[cfg(not(feature = "boxed"))]
-    #[allow(dead_code)]
-    inner: InnerErrTreePkg,
-    #[cfg(feature = "boxed")]
     #[allow(dead_code)]
-    inner: Box<InnerErrTreePkg>,
+    inner: PkgStorage,
 }
 
+/// Hidden field type of a stubbed `#[err_tree(stub_when(...))]` struct.
+///
+/// Zero-sized, so a stubbed error is the size of its own fields. See
+/// [`err_tree`][`crate::err_tree`] for the generated code.
+pub type UnitPkg = ();
+
+/// Metadata boxed only when `boxed` is enabled and it is pointer sized or
+/// larger.
+#[cfg(all(feature = "boxed", any(feature = "tracing", feature = "timestamp")))]
+type PkgStorage = Box<InnerErrTreePkg>;
+
+#[cfg(not(all(feature = "boxed", any(feature = "tracing", feature = "timestamp"))))]
+type PkgStorage = InnerErrTreePkg;
+
+// Any new tracking feature that grows the inline metadata must be boxed
+#[cfg(feature = "boxed")]
+const _: () = assert!(core::mem::size_of::<PkgStorage>() <= core::mem::size_of::<usize>());
+
 #[derive(Clone)]
 pub struct InnerErrTreePkg {
     #[cfg(feature = "source_line")]
     location: &'static Location<'static>,
     #[cfg(feature = "tracing")]
     trace: SpanTrace,
+    #[cfg(feature = "timestamp")]
+    time: SystemTime,
 }
 
 impl ErrTreePkg {
     #[track_caller]
     pub fn new() -> Self {
+        #[cfg(all(
+            feature = "hook",
+            any(feature = "source_line", feature = "tracing", feature = "timestamp")
+        ))]
+        crate::run_pkg_hook(core::panic::Location::caller());
+
         let inner = InnerErrTreePkg {
             #[cfg(feature = "source_line")]
             location: Location::caller(),
             #[cfg(feature = "tracing")]
             trace: SpanTrace::capture(),
+            #[cfg(feature = "timestamp")]
+            time: SystemTime::now(),
+        };
+
+        #[cfg(all(feature = "boxed", any(feature = "tracing", feature = "timestamp")))]
+        let inner = Box::new(inner);
+
+        Self { inner }
+    }
+
+    /// [`Self::new`], reusing the `tracing` capture of an earlier call from
+    /// the same callsite in the same span on this thread.
+    ///
+    /// Captures are cached per thread in a fixed number of slots, each keyed
+    /// by callsite and current span ID. A cached capture holds its span open
+    /// until evicted or the thread exits. Calls outside of any span capture
+    /// normally. Without `tracing`, this is identical to [`Self::new`].
+    ///
+    /// # Performance
+    /// [`SpanTrace::capture`] only clones a handle to the current span, which
+    /// a cache hit must also do, so this is not faster with
+    /// `tracing_subscriber`'s `Registry`. The `pkg_cache` bench constructs
+    /// packages in a loop inside three nested spans (x86_64, release):
+    ///
+    /// | Constructor   | Time per call |
+    /// |---------------|---------------|
+    /// | [`Self::new`] | ~125 ns       |
+    /// | `new_cached`  | ~145 ns       |
+    ///
+    /// Prefer [`Self::new`], unless a subscriber with costly span lookups
+    /// measures faster with this.
+    #[cfg(feature = "pkg_cache")]
+    #[track_caller]
+    pub fn new_cached() -> Self {
+        #[cfg(all(
+            feature = "hook",
+            any(feature = "source_line", feature = "tracing", feature = "timestamp")
+        ))]
+        crate::run_pkg_hook(core::panic::Location::caller());
+
+        let inner = InnerErrTreePkg {
+            #[cfg(feature = "source_line")]
+            location: Location::caller(),
+            #[cfg(feature = "tracing")]
+            trace: trace_cache::capture(core::panic::Location::caller()),
+            #[cfg(feature = "timestamp")]
+            time: SystemTime::now(),
         };
 
-        #[cfg(feature = "boxed")]
+        #[cfg(all(feature = "boxed", any(feature = "tracing", feature = "timestamp")))]
         let inner = Box::new(inner);
 
         Self { inner }
@@ -70,6 +173,121 @@ impl ErrTreePkg {
     pub(crate) fn trace(&self) -> &SpanTrace {
         &self.inner.trace
     }
+
+    #[cfg(feature = "timestamp")]
+    pub(crate) fn time(&self) -> SystemTime {
+        self.inner.time
+    }
+}
+
+/// Per-thread [`SpanTrace`] cache for [`ErrTreePkg::new_cached`].
+#[cfg(all(feature = "pkg_cache", feature = "tracing"))]
+mod trace_cache {
+    use core::{cell::RefCell, panic::Location};
+
+    use tracing_error::SpanTrace;
+
+    const SLOTS: usize = 64;
+
+    /// Callsite address and span ID
+    type Key = (usize, u64);
+
+    std::thread_local! {
+        static CACHE: RefCell<[Option<(Key, SpanTrace)>; SLOTS]> =
+            const { RefCell::new([const { None }; SLOTS]) };
+    }
+
+    pub(super) fn capture(location: &'static Location<'static>) -> SpanTrace {
+        let span = tracing_core::dispatcher::get_default(|dispatch| {
+            dispatch.current_span().id().map(|id| id.into_u64())
+        });
+        let Some(span) = span else {
+            return SpanTrace::capture();
+        };
+
+        let key = (location as *const _ as usize, span);
+        let slot = (key.0 ^ (span as usize).rotate_left(16)) % SLOTS;
+
+        let cached = CACHE.with_borrow(|cache| match &cache[slot] {
+            Some((cached_key, trace)) if *cached_key == key => Some(trace.clone()),
+            _ => None,
+        });
+
+        cached.unwrap_or_else(|| {
+            // Captured outside of the borrow, in case the subscriber reenters
+            let trace = SpanTrace::capture();
+            CACHE.with_borrow_mut(|cache| cache[slot] = Some((key, trace.clone())));
+            trace
+        })
+    }
+}
+
+/// Packages stored outside of their errors, for `#[err_tree(external_pkg)]`.
+///
+/// An error's address is its identity while it stays in place. As a struct
+/// and its first field share an address, keys also hold the type name.
+#[cfg(feature = "external_pkg")]
+impl ErrTreePkg {
+    /// Stores this package for `error`, replacing any package already stored
+    /// for the same address and type.
+    ///
+    /// `error` must be at its final address, e.g. already boxed or in its
+    /// static. A moved error is not found by [`Self::attached`] and renders
+    /// without metadata, while its old entry remains until another error of
+    /// the same type is attached at that address. Zero-sized errors share
+    /// addresses, so must not be attached.
+    ///
+    /// ```rust
+    /// use bare_err_tree::ErrTreePkg;
+    ///
+    /// let err = Box::new(std::io::Error::other("closed"));
+    /// ErrTreePkg::new().attach(&*err);
+    /// assert!(ErrTreePkg::attached(&*err).is_some());
+    ///
+    /// ErrTreePkg::detach(&*err);
+    /// assert!(ErrTreePkg::attached(&*err).is_none());
+    /// ```
+    pub fn attach<E: ?Sized>(self, error: &E) {
+        external::table().insert(external::key(error), self);
+    }
+
+    /// A copy of the package attached to `error`, if any.
+    pub fn attached<E: ?Sized>(error: &E) -> Option<Self> {
+        external::table().get(&external::key(error)).cloned()
+    }
+
+    /// Removes the package attached to `error`, if any.
+    ///
+    /// `#[err_tree(external_pkg)]` types call this when dropped.
+    pub fn detach<E: ?Sized>(error: &E) {
+        external::table().remove(&external::key(error));
+    }
+}
+
+/// Side table for [`ErrTreePkg::attach`].
+#[cfg(feature = "external_pkg")]
+mod external {
+    use alloc::collections::BTreeMap;
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+
+    use super::ErrTreePkg;
+
+    /// Address and type name
+    pub(super) type Key = (usize, &'static str);
+
+    static TABLE: Mutex<BTreeMap<Key, ErrTreePkg>> = Mutex::new(BTreeMap::new());
+
+    pub(super) fn key<E: ?Sized>(error: &E) -> Key {
+        (
+            (error as *const E).cast::<()>() as usize,
+            core::any::type_name::<E>(),
+        )
+    }
+
+    /// Locks the table, ignoring poison as every operation leaves it valid.
+    pub(super) fn table() -> MutexGuard<'static, BTreeMap<Key, ErrTreePkg>> {
+        TABLE.lock().unwrap_or_else(PoisonError::into_inner)
+    }
 }
 
 impl Default for ErrTreePkg {
@@ -80,6 +298,16 @@ impl Default for ErrTreePkg {
     }
 }
 
+/// Captures the package at the context selector call, for
+/// `#[snafu(implicit)]` fields.
+#[cfg(feature = "snafu")]
+impl snafu::GenerateImplicitData for ErrTreePkg {
+    #[track_caller]
+    fn generate() -> Self {
+        Self::new()
+    }
+}
+
 impl Debug for ErrTreePkg {
     #[cfg_attr(coverage, coverage(off))]
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
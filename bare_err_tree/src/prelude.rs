@@ -0,0 +1,36 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+/*!
+The commonly used subset of `bare_err_tree`, for a single glob import.
+
+```rust
+use bare_err_tree::prelude::*;
+```
+
+Includes:
+* [`err_tree`], with `derive`.
+* [`AsErrTree`], [`ErrTree`], and [`ErrTreePkg`] for implementing trees.
+* [`WrapErr`] to use any [`Error`](core::error::Error) as a tree.
+* [`print_tree`], [`print_tree_default`], [`print_tree_with`],
+  [`tree_unwrap`], and [`tree_unwrap_default`] to print trees.
+* [`FormatOptions`], [`front_max_for_depth`], and [`DEFAULT_FRONT_MAX`] to
+  configure those prints.
+
+Everything else stays at the crate root, including feature specific items.
+
+Adding an item to this module is a minor release, as local names shadow glob
+imports. Removing one is a major release. When an item collides with a local
+name, import it from the crate root under another name to use both (e.g.
+`use bare_err_tree::ErrTree as BareErrTree;`).
+*/
+
+#[cfg(feature = "derive")]
+pub use crate::err_tree;
+pub use crate::{
+    front_max_for_depth, print_tree, print_tree_default, print_tree_with, tree_unwrap,
+    tree_unwrap_default, AsErrTree, ErrTree, ErrTreePkg, FormatOptions, WrapErr, DEFAULT_FRONT_MAX,
+};
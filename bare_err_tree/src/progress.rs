@@ -0,0 +1,113 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Progress reporting and cancellation for large trees.
+
+use core::{fmt, ops::ControlFlow};
+
+use crate::{count_sources, fmt_tree_progress, with_source_lines, AsErrTree, FormatOptions};
+
+/// Rendering progress, reported by [`print_tree_cancellable`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Progress {
+    /// Errors rendered so far, including the root.
+    pub nodes_rendered: usize,
+    /// Bytes written to the formatter so far.
+    pub bytes_written: usize,
+}
+
+/// [`print_tree`][`crate::print_tree`], reporting [`Progress`] to `check`
+/// every 256 errors.
+///
+/// Returning [`ControlFlow::Break`] from `check` stops rendering and ends the
+/// output with a `… rendering cancelled after <N> nodes` line. Checks only run
+/// between errors, so every line before the cancellation line is complete.
+///
+/// ```rust
+/// # use std::{error::Error, io, ops::ControlFlow};
+/// use bare_err_tree::{front_max_for_depth, print_tree_cancellable};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+///
+/// let mut out = String::new();
+/// print_tree_cancellable::<{ front_max_for_depth(10) }, _, _>(
+///     &err as &dyn Error,
+///     &mut out,
+///     &mut |_| ControlFlow::Continue(()),
+/// )
+/// .unwrap();
+/// assert_eq!(out, "outer\n│\n╰─▶ unexpected end of file");
+/// ```
+#[track_caller]
+pub fn print_tree_cancellable<const FRONT_MAX: usize, E, F>(
+    tree: E,
+    formatter: F,
+    check: &mut dyn FnMut(Progress) -> ControlFlow<()>,
+) -> fmt::Result
+where
+    E: AsErrTree,
+    F: fmt::Write,
+{
+    print_tree_cancellable_with::<FRONT_MAX, _, _>(tree, FormatOptions::new(), formatter, check)
+}
+
+/// [`print_tree_cancellable`] with [`FormatOptions`].
+///
+/// `check` runs every [`FormatOptions::progress_every`] errors.
+///
+/// ```rust
+/// # use std::{error::Error, io, ops::ControlFlow};
+/// use bare_err_tree::{front_max_for_depth, print_tree_cancellable_with, FormatOptions};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+///
+/// let mut out = String::new();
+/// print_tree_cancellable_with::<{ front_max_for_depth(10) }, _, _>(
+///     &err as &dyn Error,
+///     FormatOptions::new().progress_every(1),
+///     &mut out,
+///     &mut |_| ControlFlow::Break(()),
+/// )
+/// .unwrap();
+/// assert_eq!(out, "outer\n… rendering cancelled after 1 nodes");
+/// ```
+#[track_caller]
+pub fn print_tree_cancellable_with<const FRONT_MAX: usize, E, F>(
+    tree: E,
+    options: FormatOptions,
+    mut formatter: F,
+    check: &mut dyn FnMut(Progress) -> ControlFlow<()>,
+) -> fmt::Result
+where
+    E: AsErrTree,
+    F: fmt::Write,
+{
+    let sources_len = options.max_children.map(|_| count_sources(&tree));
+
+    with_source_lines::<FRONT_MAX, _, _>(&tree, options, |source_lines| {
+        let mut res = Ok(());
+        tree.as_err_tree(&mut |mut tree| {
+            tree.sources_len = sources_len;
+            res = fmt_tree_progress::<FRONT_MAX, _, _>(
+                tree,
+                options,
+                Some(&mut *check),
+                None,
+                source_lines,
+                &mut formatter,
+            );
+        });
+        res
+    })
+}
@@ -0,0 +1,150 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Single-line record output for line-oriented log sinks.
+
+use core::fmt::{self, Debug, Display, Formatter, Write};
+
+use alloc::string::String;
+
+use crate::{print_tree, AsErrTree};
+
+/// One line of tree output, produced by [`tree_to_records`].
+#[derive(Clone, Copy)]
+pub struct RecordLine<'a> {
+    /// Position of this line in the output, starting at 0.
+    pub seq: u32,
+    /// Expected number of lines, if known.
+    pub total_hint: Option<u32>,
+    /// Identifier shared by every line of the same tree.
+    pub correlation: &'a dyn Display,
+    /// The line, with its tree glyph prefix and without the newline.
+    pub text: &'a str,
+}
+
+impl Debug for RecordLine<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordLine")
+            .field("seq", &self.seq)
+            .field("total_hint", &self.total_hint)
+            .field("correlation", &format_args!("{}", self.correlation))
+            .field("text", &self.text)
+            .finish()
+    }
+}
+
+/// Renders the [`print_tree`] output as one [`RecordLine`] per line.
+///
+/// Log sinks such as syslog and journald split multi-line messages into
+/// separate records that can be interleaved under load. Each line keeps its
+/// tree glyphs, so a partial set of records still reads sensibly, and joining
+/// all `text` with `\n` in `seq` order reproduces the [`print_tree`] output.
+///
+/// `total_hint` is measured with a first rendering pass, so it is only exact
+/// when [`Display`] implementations are deterministic.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{front_max_for_depth, tree_to_records};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+///
+/// let mut records = Vec::new();
+/// tree_to_records::<{ front_max_for_depth(10) }, _>(&(&err as &dyn Error), &"req-42", |line| {
+///     records.push(format!(
+///         "[{}] {}/{}: {}",
+///         line.correlation,
+///         line.seq,
+///         line.total_hint.unwrap(),
+///         line.text
+///     ));
+/// })
+/// .unwrap();
+///
+/// assert_eq!(
+///     records,
+///     [
+///         "[req-42] 0/3: outer",
+///         "[req-42] 1/3: │",
+///         "[req-42] 2/3: ╰─▶ unexpected end of file",
+///     ]
+/// );
+/// ```
+pub fn tree_to_records<const FRONT_MAX: usize, E>(
+    tree: &E,
+    correlation: &dyn Display,
+    emit: impl FnMut(RecordLine<'_>),
+) -> fmt::Result
+where
+    E: AsErrTree + ?Sized,
+{
+    let mut counter = LineCounter { newlines: 0 };
+    print_tree::<FRONT_MAX, _, _>(tree, &mut counter)?;
+    let total_hint = u32::try_from(counter.newlines + 1).ok();
+
+    let mut records = RecordSplitter {
+        line: String::new(),
+        seq: 0,
+        total_hint,
+        correlation,
+        emit,
+    };
+    print_tree::<FRONT_MAX, _, _>(tree, &mut records)?;
+    records.flush();
+    Ok(())
+}
+
+/// Counts newlines without storing output.
+struct LineCounter {
+    newlines: usize,
+}
+
+impl Write for LineCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.newlines += s.matches('\n').count();
+        Ok(())
+    }
+}
+
+/// Buffers a line at a time, emitting each completed line.
+struct RecordSplitter<'a, F> {
+    line: String,
+    seq: u32,
+    total_hint: Option<u32>,
+    correlation: &'a dyn Display,
+    emit: F,
+}
+
+impl<F: FnMut(RecordLine<'_>)> RecordSplitter<'_, F> {
+    fn flush(&mut self) {
+        (self.emit)(RecordLine {
+            seq: self.seq,
+            total_hint: self.total_hint,
+            correlation: self.correlation,
+            text: &self.line,
+        });
+        self.seq = self.seq.saturating_add(1);
+        self.line.clear();
+    }
+}
+
+impl<F: FnMut(RecordLine<'_>)> Write for RecordSplitter<'_, F> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut lines = s.split('\n');
+        if let Some(first) = lines.next() {
+            self.line.push_str(first);
+        }
+        for line in lines {
+            self.flush();
+            self.line.push_str(line);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! A subset of `error_stack::Report`, for migrating to error trees.
+
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+    marker::PhantomData,
+};
+
+use crate::{AsErrTree, ErrTree, ErrTreePkg};
+
+/// Stand-in for `error_stack::Report`, covering
+/// [`change_context`][`Self::change_context`] and
+/// [`attach_printable`][`Self::attach_printable`].
+///
+/// Each context is a node of the tree, above the context it changed from.
+/// Attachments render as extra lines of their context's message. Contexts
+/// capture an [`ErrTreePkg`] where they are added, so `source_line` points
+/// at the [`new`][`Self::new`] or [`change_context`][`Self::change_context`]
+/// call. The first context's own [`Error::source`] chain renders below it.
+///
+/// This is deliberately a subset: attachments are stored as strings, and
+/// there are no typed attachments, hooks, or frame iteration.
+///
+/// ```rust
+/// # use std::io;
+/// use bare_err_tree::{front_max_for_depth, print_tree, TreeReportShim};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("could not load config")]
+/// struct ConfigError;
+///
+/// let report = TreeReportShim::new(io::Error::from(io::ErrorKind::NotFound))
+///     .attach_printable("path: config.toml")
+///     .change_context(ConfigError);
+///
+/// let mut out = String::new();
+/// print_tree::<{ front_max_for_depth(10) }, _, _>(&report, &mut out).unwrap();
+/// # #[cfg(not(any(feature = "source_line", feature = "tracing", feature = "timestamp")))]
+/// assert_eq!(
+///     out,
+///     "could not load config\n│\n╰─▶ entity not found\n    │ path: config.toml"
+/// );
+/// ```
+pub struct TreeReportShim<C> {
+    root: Box<ReportNode>,
+    _context: PhantomData<fn() -> C>,
+}
+
+/// One context of a [`TreeReportShim`], owning the context it changed from.
+struct ReportNode {
+    pkg: ErrTreePkg,
+    context: Box<dyn Error + Send + Sync>,
+    notes: Vec<String>,
+    child: Option<Box<ReportNode>>,
+}
+
+impl<C: Error + Send + Sync + 'static> TreeReportShim<C> {
+    /// Starts a report with `context` as its only node.
+    #[track_caller]
+    pub fn new(context: C) -> Self {
+        Self {
+            root: Box::new(ReportNode {
+                pkg: ErrTreePkg::new(),
+                context: Box::new(context),
+                notes: Vec::new(),
+                child: None,
+            }),
+            _context: PhantomData,
+        }
+    }
+
+    /// Adds `context` as the new root, above the current tree.
+    #[track_caller]
+    pub fn change_context<T: Error + Send + Sync + 'static>(self, context: T) -> TreeReportShim<T> {
+        TreeReportShim {
+            root: Box::new(ReportNode {
+                pkg: ErrTreePkg::new(),
+                context: Box::new(context),
+                notes: Vec::new(),
+                child: Some(self.root),
+            }),
+            _context: PhantomData,
+        }
+    }
+
+    /// Adds `attachment` as a line under the current context's message.
+    pub fn attach_printable<A: Display>(mut self, attachment: A) -> Self {
+        self.root.notes.push(attachment.to_string());
+        self
+    }
+
+    /// The context at the root.
+    pub fn current_context(&self) -> &C {
+        self.root
+            .context
+            .downcast_ref()
+            .expect("The root context is always a `C`")
+    }
+}
+
+impl<C: Error + Send + Sync + 'static> From<C> for TreeReportShim<C> {
+    #[track_caller]
+    fn from(context: C) -> Self {
+        Self::new(context)
+    }
+}
+
+impl<C> Debug for TreeReportShim<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.root, f)
+    }
+}
+
+impl<C> AsErrTree for TreeReportShim<C> {
+    fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+        self.root.as_err_tree(func)
+    }
+}
+
+impl Debug for ReportNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReportNode")
+            .field("context", &self.context)
+            .field("notes", &self.notes)
+            .field("child", &self.child)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Display for ReportNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.context, f)?;
+        for note in &self.notes {
+            write!(f, "\n{note}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ReportNode {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.child {
+            Some(child) => Some(&**child),
+            None => self.context.source(),
+        }
+    }
+}
+
+impl AsErrTree for ReportNode {
+    fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+        let own_source = self.context.source();
+        let own_source = own_source.as_ref().map(|x| x as &dyn AsErrTree);
+        let child = self.child.as_deref().map(|x| x as &dyn AsErrTree);
+        (func)(ErrTree::with_pkg(
+            self,
+            &mut own_source.into_iter().chain(child),
+            &self.pkg,
+        ))
+    }
+}
@@ -0,0 +1,109 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Structural comparison of error trees, for test suites.
+
+use core::fmt::{self, Display, Formatter};
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{snapshot::snapshot, AsErrTree, TreeSnapshot};
+
+/// Comparable shape of an error tree, produced by [`tree_shape`].
+///
+/// Usually built with [`assert_tree_shape`](crate::assert_tree_shape) rather
+/// than by hand. [`Display`] lists one message per line, indented by depth, to
+/// keep assertion failures readable.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Shape {
+    /// The error message, after any message override.
+    pub msg: String,
+    /// The tracked source location, or `None` when masked or untracked.
+    pub location: Option<String>,
+    /// Shapes of each source, in order.
+    pub children: Vec<Shape>,
+}
+
+impl Shape {
+    /// A shape with no location.
+    pub fn new<M, C>(msg: M, children: C) -> Self
+    where
+        M: Into<String>,
+        C: IntoIterator<Item = Shape>,
+    {
+        Self {
+            msg: msg.into(),
+            location: None,
+            children: children.into_iter().collect(),
+        }
+    }
+
+    /// Clears the location of this shape and all of its children.
+    pub fn mask_locations(mut self) -> Self {
+        self.location = None;
+        self.children = self
+            .children
+            .into_iter()
+            .map(Self::mask_locations)
+            .collect();
+        self
+    }
+
+    fn fmt_depth(&self, f: &mut Formatter<'_>, depth: usize) -> fmt::Result {
+        write!(f, "{:indent$}{:?}", "", self.msg, indent = depth * 2)?;
+        if let Some(location) = &self.location {
+            write!(f, " at {location}")?;
+        }
+        self.children.iter().try_for_each(|child| {
+            writeln!(f)?;
+            child.fmt_depth(f, depth + 1)
+        })
+    }
+}
+
+impl Display for Shape {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_depth(f, 0)
+    }
+}
+
+/// Keeps the tracked location, if any.
+impl From<TreeSnapshot> for Shape {
+    fn from(snapshot: TreeSnapshot) -> Self {
+        Self {
+            msg: snapshot.msg,
+            #[cfg(feature = "source_line")]
+            location: snapshot.location,
+            #[cfg(not(feature = "source_line"))]
+            location: None,
+            children: snapshot.children.into_iter().map(Self::from).collect(),
+        }
+    }
+}
+
+/// The [`Shape`] of `tree`, with locations masked.
+///
+/// Masking keeps comparisons stable as code moves. To compare locations too,
+/// convert a [`TreeSnapshot`] with [`Shape::from`].
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{tree_shape, Shape};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+///
+/// assert_eq!(
+///     tree_shape(&(&err as &dyn Error)),
+///     Shape::new("outer", [Shape::new("unexpected end of file", [])])
+/// );
+/// ```
+pub fn tree_shape<E: AsErrTree + ?Sized>(tree: &E) -> Shape {
+    Shape::from(snapshot(tree)).mask_locations()
+}
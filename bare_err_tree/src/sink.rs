@@ -0,0 +1,181 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Byte-oriented output, for sinks that do not implement [`fmt::Write`].
+
+use core::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    str,
+};
+
+use crate::{print_tree, AsErrTree};
+
+/// Failure reported by a [`TreeSink`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SinkError;
+
+impl Display for SinkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("tree sink write failed")
+    }
+}
+
+impl Error for SinkError {}
+
+impl From<fmt::Error> for SinkError {
+    fn from(_: fmt::Error) -> Self {
+        Self
+    }
+}
+
+/// Destination for UTF-8 tree output, written as raw bytes.
+///
+/// Tree output is always valid UTF-8, and is written to the sink without
+/// any further validation. A sink may still receive a multibyte `char`
+/// split across calls.
+///
+/// [`fmt::Write`] destinations are used through [`FmtSink`], and
+/// [`std::io::Write`] destinations through `AdaptWrite` with `adapt`.
+pub trait TreeSink {
+    /// Writes all of `bytes`, or fails.
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), SinkError>;
+}
+
+impl<S: TreeSink + ?Sized> TreeSink for &mut S {
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), SinkError> {
+        S::write_bytes(self, bytes)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl TreeSink for alloc::vec::Vec<u8> {
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), SinkError> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// Converts [`fmt::Write`] to [`TreeSink`], validating bytes as UTF-8.
+///
+/// A `char` split across [`TreeSink::write_bytes`] calls is held until it
+/// completes. Invalid UTF-8 fails with [`SinkError`].
+///
+/// ```rust
+/// use bare_err_tree::{FmtSink, TreeSink};
+///
+/// let mut sink = FmtSink::new(String::new());
+/// let glyph = "╰─▶".as_bytes();
+/// sink.write_bytes(&glyph[..4]).unwrap();
+/// sink.write_bytes(&glyph[4..]).unwrap();
+/// assert_eq!(sink.finish().unwrap(), "╰─▶");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FmtSink<W> {
+    inner: W,
+    pending: [u8; 4],
+    pending_len: usize,
+}
+
+impl<W: fmt::Write> FmtSink<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            pending: [0; 4],
+            pending_len: 0,
+        }
+    }
+
+    /// Returns the inner writer, failing if a `char` was left incomplete.
+    pub fn finish(self) -> Result<W, SinkError> {
+        if self.pending_len == 0 {
+            Ok(self.inner)
+        } else {
+            Err(SinkError)
+        }
+    }
+}
+
+/// Length of the UTF-8 sequence starting with `lead`.
+fn utf8_len(lead: u8) -> usize {
+    match lead {
+        0xF0.. => 4,
+        0xE0.. => 3,
+        _ => 2,
+    }
+}
+
+impl<W: fmt::Write> TreeSink for FmtSink<W> {
+    fn write_bytes(&mut self, mut bytes: &[u8]) -> Result<(), SinkError> {
+        // Finish a char split by the previous call
+        if self.pending_len > 0 {
+            let char_len = utf8_len(self.pending[0]);
+            let take = (char_len - self.pending_len).min(bytes.len());
+            self.pending[self.pending_len..self.pending_len + take].copy_from_slice(&bytes[..take]);
+            self.pending_len += take;
+            bytes = &bytes[take..];
+
+            if self.pending_len < char_len {
+                return Ok(());
+            }
+            let c = str::from_utf8(&self.pending[..char_len]).map_err(|_| SinkError)?;
+            self.inner.write_str(c)?;
+            self.pending_len = 0;
+        }
+
+        match str::from_utf8(bytes) {
+            Ok(s) => Ok(self.inner.write_str(s)?),
+            Err(e) => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                // SAFETY: validated by `from_utf8` up to this point
+                self.inner
+                    .write_str(unsafe { str::from_utf8_unchecked(valid) })?;
+
+                // Only a truncated char at the very end is held for later
+                if e.error_len().is_some() {
+                    return Err(SinkError);
+                }
+                self.pending[..rest.len()].copy_from_slice(rest);
+                self.pending_len = rest.len();
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Converts [`TreeSink`] to [`fmt::Write`], without copying or validating.
+pub(crate) struct SinkWriter<S>(pub S);
+
+impl<S: TreeSink> fmt::Write for SinkWriter<S> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write_bytes(s.as_bytes()).map_err(|_| fmt::Error)
+    }
+}
+
+/// [`print_tree`] into a byte-oriented [`TreeSink`].
+///
+/// An error's [`Display`][`core::fmt::Display`] returning an error is also
+/// reported as [`SinkError`].
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{front_max_for_depth, render_tree_to_sink, FmtSink};
+///
+/// let err = io::Error::from(io::ErrorKind::UnexpectedEof);
+///
+/// let mut sink = FmtSink::new(String::new());
+/// render_tree_to_sink::<{ front_max_for_depth(10) }, _, _>(&(&err as &dyn Error), &mut sink)
+///     .unwrap();
+/// assert_eq!(sink.finish().unwrap(), "unexpected end of file");
+/// ```
+#[track_caller]
+pub fn render_tree_to_sink<const FRONT_MAX: usize, E, S>(tree: &E, sink: S) -> Result<(), SinkError>
+where
+    E: AsErrTree + ?Sized,
+    S: TreeSink,
+{
+    Ok(print_tree::<FRONT_MAX, _, _>(tree, SinkWriter(sink))?)
+}
@@ -0,0 +1,93 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Output into caller-provided byte buffers, e.g. across FFI.
+
+use core::fmt;
+
+use crate::{print_tree, AsErrTree};
+
+/// [`print_tree`] into `buf`, as UTF-8 bytes.
+///
+/// Returns the number of bytes written. If `buf` is too small, returns the
+/// number of bytes needed instead. `buf` then holds as much of the output as
+/// fits, cut at a `char` boundary so it remains valid UTF-8.
+///
+/// An error's [`Display`][`core::fmt::Display`] returning an error ends the
+/// output early, and the output up to that point is reported as above.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{front_max_for_depth, render_tree_to_slice};
+///
+/// let err = io::Error::from(io::ErrorKind::UnexpectedEof);
+/// let err = &err as &dyn Error;
+///
+/// let mut buf = [0; 64];
+/// let len = render_tree_to_slice::<{ front_max_for_depth(10) }, _>(&err, &mut buf).unwrap();
+/// assert_eq!(&buf[..len], b"unexpected end of file");
+///
+/// let mut small = [0; 10];
+/// assert_eq!(
+///     render_tree_to_slice::<{ front_max_for_depth(10) }, _>(&err, &mut small),
+///     Err(len)
+/// );
+/// assert_eq!(&small, b"unexpected");
+/// ```
+#[track_caller]
+pub fn render_tree_to_slice<const FRONT_MAX: usize, E>(
+    tree: &E,
+    buf: &mut [u8],
+) -> Result<usize, usize>
+where
+    E: AsErrTree + ?Sized,
+{
+    let mut writer = SliceWriter::new(buf);
+    let _ = print_tree::<FRONT_MAX, _, _>(tree, &mut writer);
+    writer.finish()
+}
+
+/// Writes into a byte slice, counting the bytes needed past its end.
+pub(crate) struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    written: usize,
+    needed: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self {
+            buf,
+            written: 0,
+            needed: 0,
+        }
+    }
+
+    /// Bytes written, or bytes needed if the output did not fit.
+    pub fn finish(self) -> Result<usize, usize> {
+        if self.needed > self.buf.len() {
+            Err(self.needed)
+        } else {
+            Ok(self.written)
+        }
+    }
+}
+
+impl fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // Only write while everything so far has fit, to keep a prefix
+        if self.needed == self.written {
+            let mut len = s.len().min(self.buf.len() - self.written);
+            while !s.is_char_boundary(len) {
+                len -= 1;
+            }
+            self.buf[self.written..self.written + len].copy_from_slice(&s.as_bytes()[..len]);
+            self.written += len;
+        }
+        self.needed += s.len();
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Structural snapshots of an error tree.
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{AsErrTree, ErrTree};
+
+/// Owned structure of an error tree, produced by [`ErrTree::to_snapshot`].
+///
+/// Compares trees independently of the rendered output, so tests are not tied
+/// to glyphs or whitespace.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TreeSnapshot {
+    /// The error message, after any message override.
+    pub msg: String,
+    /// The tracked source location, formatted as `file:line:column`.
+    #[cfg(feature = "source_line")]
+    pub location: Option<String>,
+    /// Snapshots of each source, in order.
+    pub children: Vec<TreeSnapshot>,
+}
+
+impl ErrTree<'_> {
+    /// Walks this tree into an owned [`TreeSnapshot`].
+    ///
+    /// ```rust
+    /// # use std::{error::Error, io};
+    /// use bare_err_tree::{AsErrTree, TreeSnapshot};
+    ///
+    /// #[derive(Debug, thiserror::Error)]
+    /// #[error("outer")]
+    /// struct Outer(#[source] io::Error);
+    ///
+    /// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+    ///
+    /// let mut snapshot = TreeSnapshot::default();
+    /// (&err as &dyn Error).as_err_tree(&mut |tree| snapshot = tree.to_snapshot());
+    ///
+    /// assert_eq!(snapshot.msg, "outer");
+    /// assert_eq!(snapshot.children.len(), 1);
+    /// assert_eq!(snapshot.children[0].msg, "unexpected end of file");
+    /// ```
+    pub fn to_snapshot(self) -> TreeSnapshot {
+        let mut msg = String::new();
+        // Writing into a String only fails if the error's Display does
+        let _ = self.write_msg(&mut msg);
+
+        #[cfg(feature = "source_line")]
+        let location = self.location.map(|loc| alloc::format!("{loc}"));
+
+        let children = self
+            .sources()
+            .map(|source| {
+                let mut snapshot = TreeSnapshot::default();
+                source.as_err_tree(&mut |tree| snapshot = tree.to_snapshot());
+                snapshot
+            })
+            .collect();
+
+        TreeSnapshot {
+            msg,
+            #[cfg(feature = "source_line")]
+            location,
+            children,
+        }
+    }
+}
+
+/// One error of a flattened tree, produced by [`flatten`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FlatErr {
+    /// The error message, after any message override.
+    pub msg: String,
+    /// The tracked source location, formatted as `file:line:column`.
+    #[cfg(feature = "source_line")]
+    pub location: Option<String>,
+}
+
+/// Flattens the tree into its errors and their depths, in pre-order.
+///
+/// The root has depth 0, and sources follow their parent in order. This is an
+/// indexable alternative to [`TreeSnapshot`], e.g. for table views.
+///
+/// Errors are owned copies, as sources only lend out their [`ErrTree`] for the
+/// duration of [`AsErrTree::as_err_tree`].
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{flatten, AsErrTree};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+///
+/// let mut flat = Vec::new();
+/// (&err as &dyn Error).as_err_tree(&mut |tree| flat = flatten(tree));
+///
+/// let flat: Vec<_> = flat.iter().map(|(depth, err)| (*depth, err.msg.as_str())).collect();
+/// assert_eq!(flat, [(0, "outer"), (1, "unexpected end of file")]);
+/// ```
+pub fn flatten(tree: ErrTree<'_>) -> Vec<(usize, FlatErr)> {
+    let mut flat = Vec::new();
+    flatten_into(tree, 0, &mut flat);
+    flat
+}
+
+fn flatten_into(tree: ErrTree<'_>, depth: usize, flat: &mut Vec<(usize, FlatErr)>) {
+    let mut msg = String::new();
+    // Writing into a String only fails if the error's Display does
+    let _ = tree.write_msg(&mut msg);
+
+    flat.push((
+        depth,
+        FlatErr {
+            msg,
+            #[cfg(feature = "source_line")]
+            location: tree.location.map(|loc| alloc::format!("{loc}")),
+        },
+    ));
+
+    for source in tree.sources() {
+        source.as_err_tree(&mut |tree| flatten_into(tree, depth + 1, flat));
+    }
+}
+
+/// Snapshots the tree of any [`AsErrTree`].
+pub(crate) fn snapshot<E: AsErrTree + ?Sized>(tree: &E) -> TreeSnapshot {
+    let mut snapshot = TreeSnapshot::default();
+    tree.as_err_tree(&mut |tree| snapshot = tree.to_snapshot());
+    snapshot
+}
@@ -0,0 +1,365 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Compact statistics over an error tree.
+
+use core::fmt::{self, Write};
+
+use crate::{AsErrTree, ErrTree};
+
+/// Statistics for an error tree, produced by [`tree_summary`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TreeSummary {
+    /// Total number of errors, including the root.
+    pub nodes: usize,
+    /// Number of errors on the longest root to leaf path.
+    pub max_depth: usize,
+    /// Number of errors without sources.
+    pub leaves: usize,
+}
+
+/// Walks every error in the tree to collect [`TreeSummary`] statistics.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::{tree_summary, TreeSummary};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+/// let summary = tree_summary(&err as &dyn Error);
+///
+/// assert_eq!(
+///     summary,
+///     TreeSummary {
+///         nodes: 2,
+///         max_depth: 2,
+///         leaves: 1,
+///     }
+/// );
+/// ```
+pub fn tree_summary<E: AsErrTree>(tree: E) -> TreeSummary {
+    let mut summary = TreeSummary::default();
+    tree.as_err_tree(&mut |tree| summarize(tree, 1, &mut summary));
+    summary
+}
+
+fn summarize(tree: ErrTree<'_>, depth: usize, summary: &mut TreeSummary) {
+    summary.nodes += 1;
+    summary.max_depth = summary.max_depth.max(depth);
+
+    let mut has_sources = false;
+    for source in tree.sources() {
+        has_sources = true;
+        source.as_err_tree(&mut |tree| summarize(tree, depth + 1, summary));
+    }
+
+    if !has_sources {
+        summary.leaves += 1;
+    }
+}
+
+/// Errors written by [`compact_summary`], past which it stops.
+pub const COMPACT_SUMMARY_NODES: usize = 10;
+
+/// Bytes of each message written by [`compact_summary`], past which it is cut
+/// short with `...`.
+pub const COMPACT_SUMMARY_MSG_LEN: usize = 80;
+
+/// Displays the first line of each error, indented two spaces per depth.
+///
+/// Intended for quick inspection (e.g. in a debugger or `eprintln!`) without
+/// the full [`print_tree`][`crate::print_tree`] output. At most
+/// [`COMPACT_SUMMARY_NODES`] errors are written, with a final `...` line if
+/// more remain, and each message is cut to [`COMPACT_SUMMARY_MSG_LEN`] bytes.
+/// No allocation or `FRONT_MAX` buffer is needed.
+///
+/// `#[err_tree(debug_summary)]` generates a `tree_summary` method calling this.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::compact_summary;
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer\nwith details")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+/// let summary = compact_summary(&err as &dyn Error).to_string();
+///
+/// assert_eq!(summary, "outer\n  unexpected end of file");
+/// ```
+pub fn compact_summary<E: AsErrTree>(tree: E) -> CompactSummary<E> {
+    CompactSummary(tree)
+}
+
+/// [`Display`][`fmt::Display`] output of [`compact_summary`].
+#[derive(Debug, Clone, Copy)]
+pub struct CompactSummary<E>(E);
+
+impl<E: AsErrTree> fmt::Display for CompactSummary<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut written = 0;
+        let mut res = Ok(());
+        self.0
+            .as_err_tree(&mut |tree| res = write_compact(tree, 0, &mut written, f));
+        res
+    }
+}
+
+fn write_compact(
+    tree: ErrTree<'_>,
+    depth: usize,
+    written: &mut usize,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    if *written > 0 {
+        f.write_char('\n')?;
+    }
+    if *written == COMPACT_SUMMARY_NODES {
+        *written += 1;
+        return f.write_str("...");
+    }
+    *written += 1;
+
+    for _ in 0..depth {
+        f.write_str("  ")?;
+    }
+    let mut capped = CappedLine {
+        formatter: &mut *f,
+        remaining: COMPACT_SUMMARY_MSG_LEN,
+        res: Ok(()),
+        cut: false,
+    };
+    // Errors from stopping early are not display errors
+    let _ = tree.write_msg(&mut capped);
+    capped.res?;
+    if capped.cut {
+        f.write_str("...")?;
+    }
+
+    for source in tree.sources() {
+        if *written > COMPACT_SUMMARY_NODES {
+            break;
+        }
+        let mut res = Ok(());
+        source.as_err_tree(&mut |tree| res = write_compact(tree, depth + 1, written, f));
+        res?;
+    }
+    Ok(())
+}
+
+/// Writes up to the first newline, within `remaining` bytes.
+///
+/// Fails to stop the writer early, with the real result in `res`.
+struct CappedLine<F> {
+    formatter: F,
+    remaining: usize,
+    res: fmt::Result,
+    cut: bool,
+}
+
+impl<F: Write> Write for CappedLine<F> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let (line, ended) = match s.split_once('\n') {
+            Some((line, _)) => (line, true),
+            None => (s, false),
+        };
+
+        let mut end = line.len().min(self.remaining);
+        while !line.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.cut = end < line.len();
+        self.remaining -= end;
+        self.res = self.formatter.write_str(&line[..end]);
+
+        if self.res.is_err() || ended || self.cut {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Writes the root message, [`TreeSummary`], and blame path, cut to
+/// `max_bytes`.
+///
+/// The blame path follows the first source of each error down to a leaf,
+/// mirroring a chain of [`Error::source`][`core::error::Error::source`]. Like
+/// [`print_tree`][`crate::print_tree`], it stops past the depth `FRONT_MAX`
+/// supports with a `-- tree truncated at depth N ... --` trailer line.
+pub(crate) fn write_summary<const FRONT_MAX: usize, E, F>(
+    tree: E,
+    max_bytes: usize,
+    formatter: &mut F,
+) -> fmt::Result
+where
+    E: AsErrTree,
+    F: fmt::Write + ?Sized,
+{
+    let summary = tree_summary(&tree);
+    let mut capped = CappedBytes {
+        formatter,
+        remaining: max_bytes,
+        res: Ok(()),
+        full: false,
+    };
+    let formatter = &mut capped;
+
+    let mut res = Ok(());
+    tree.as_err_tree(&mut |tree| {
+        res = (|| {
+            tree.write_msg(IndentFormatter::new(&mut *formatter, 0))?;
+            write!(
+                formatter,
+                "\n├─ summarized to fit {max_bytes} bytes: {} error(s), depth {}, {} root cause(s)",
+                summary.nodes, summary.max_depth, summary.leaves
+            )?;
+            if let Some(first) = tree.sources().next() {
+                formatter.write_str("\n│")?;
+                let mut truncated_at = None;
+                write_blame::<FRONT_MAX, _>(first, 0, &mut truncated_at, formatter)?;
+                if let Some(depth) = truncated_at {
+                    write!(
+                        formatter,
+                        "\n-- tree truncated at depth {depth}; increase FRONT_MAX (currently supports {} levels) --",
+                        crate::depth_for_front_max(FRONT_MAX)
+                    )?;
+                }
+            }
+            Ok(())
+        })();
+    });
+
+    // Errors from stopping at `max_bytes` are not display errors
+    if capped.full {
+        capped.res
+    } else {
+        res
+    }
+}
+
+fn write_blame<const FRONT_MAX: usize, F>(
+    source: &dyn AsErrTree,
+    depth: usize,
+    truncated_at: &mut Option<usize>,
+    formatter: &mut F,
+) -> fmt::Result
+where
+    F: fmt::Write + ?Sized,
+{
+    // The root is depth 0, so this source is one deeper
+    if depth + 1 >= crate::depth_for_front_max(FRONT_MAX) {
+        *truncated_at = Some(depth);
+        return Ok(());
+    }
+
+    let mut res = Ok(());
+    source.as_err_tree(&mut |tree| {
+        res = (|| {
+            formatter.write_char('\n')?;
+            for _ in 0..depth {
+                formatter.write_str("    ")?;
+            }
+            formatter.write_str("╰─▶ ")?;
+            tree.write_msg(IndentFormatter::new(&mut *formatter, depth + 1))?;
+
+            if let Some(next) = tree.sources().next() {
+                write_blame::<FRONT_MAX, _>(next, depth + 1, truncated_at, formatter)?;
+            }
+            Ok(())
+        })();
+    });
+    res
+}
+
+/// Writes up to `remaining` bytes, cut at a char boundary.
+///
+/// Fails to stop the writer early once full, with the real result in `res`.
+struct CappedBytes<F> {
+    formatter: F,
+    remaining: usize,
+    res: fmt::Result,
+    full: bool,
+}
+
+impl<F: Write> Write for CappedBytes<F> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut end = s.len().min(self.remaining);
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.full = end < s.len();
+        self.remaining -= end;
+        self.res = self.formatter.write_str(&s[..end]);
+
+        if self.res.is_err() || self.full {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Injects `depth` dangling indents and a continuation marker after newlines.
+struct IndentFormatter<F> {
+    formatter: F,
+    depth: usize,
+}
+
+impl<F> IndentFormatter<F> {
+    fn new(formatter: F, depth: usize) -> Self {
+        Self { formatter, depth }
+    }
+}
+
+impl<F: Write> Write for IndentFormatter<F> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            self.write_char(c)?;
+        }
+        Ok(())
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.formatter.write_char(c)?;
+
+        if c == '\n' {
+            for _ in 0..self.depth {
+                self.formatter.write_str("    ")?;
+            }
+            self.formatter.write_str("│ ")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Counts written bytes, failing once over `max`.
+pub(crate) struct ByteBudget {
+    pub written: usize,
+    pub max: usize,
+}
+
+impl ByteBudget {
+    pub fn exceeded(&self) -> bool {
+        self.written > self.max
+    }
+}
+
+impl Write for ByteBudget {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.written += s.len();
+        if self.exceeded() {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,91 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Creation time rendering for the `timestamp` feature.
+
+use core::fmt::{self, Display, Formatter};
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Whole seconds since the UNIX epoch, rounded down.
+pub(crate) fn unix_secs(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => i64::try_from(after.as_secs()).unwrap_or(i64::MAX),
+        Err(before) => {
+            let before = before.duration();
+            let secs = i64::try_from(before.as_secs()).unwrap_or(i64::MAX);
+            -secs - i64::from(before.subsec_nanos() > 0)
+        }
+    }
+}
+
+/// Inverse of [`unix_secs`], saturating at the bounds of [`SystemTime`].
+pub(crate) fn from_unix_secs(secs: i64) -> SystemTime {
+    let offset = Duration::from_secs(secs.unsigned_abs());
+    let time = if secs < 0 {
+        UNIX_EPOCH.checked_sub(offset)
+    } else {
+        UNIX_EPOCH.checked_add(offset)
+    };
+    time.unwrap_or(UNIX_EPOCH)
+}
+
+/// Formats a duration in its largest whole unit, e.g. `12ms` or `1.250s`.
+pub(crate) struct Elapsed(pub Duration);
+
+impl Display for Elapsed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let nanos = self.0.subsec_nanos();
+        match self.0.as_secs() {
+            0 if nanos < 1_000 => write!(f, "{nanos}ns"),
+            0 if nanos < 1_000_000 => write!(f, "{}µs", nanos / 1_000),
+            0 => write!(f, "{}ms", nanos / 1_000_000),
+            secs if nanos < 1_000_000 => write!(f, "{secs}s"),
+            secs => write!(f, "{secs}.{:03}s", nanos / 1_000_000),
+        }
+    }
+}
+
+/// Formats seconds since the UNIX epoch as an RFC 3339 UTC time, e.g.
+/// `2024-05-02T10:31:04Z`.
+pub(crate) struct Rfc3339(pub i64);
+
+impl Display for Rfc3339 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let days = self.0.div_euclid(86_400);
+        let day_secs = self.0.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+            day_secs / 3_600,
+            day_secs / 60 % 60,
+            day_secs % 60
+        )
+    }
+}
+
+/// Proleptic Gregorian `(year, month, day)` for days since 1970-01-01.
+///
+/// Howard Hinnant's `civil_from_days`, over 400 year eras.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days.saturating_add(719_468);
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // Months starting from March, so the leap day is last
+    let march_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * march_month + 2) / 5 + 1;
+    let month = if march_month < 10 {
+        march_month + 3
+    } else {
+        march_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,157 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Tracing frame deduplication across an error tree.
+
+use core::fmt;
+
+use crate::{dedup_node_traces, AsErrTree, ErrTree, ErrTreeFormattable};
+
+/// A tracing frame seen for the first time in [`dedup_traces`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceFrame<'a> {
+    /// Index assigned to this frame, referenced by later repeats.
+    pub frame: usize,
+    /// Span target, typically the module path.
+    pub target: &'a str,
+    /// Span name, typically the instrumented function.
+    pub name: &'a str,
+    /// Formatted span fields.
+    pub fields: &'a str,
+    /// File and line of the span definition, if known.
+    pub location: Option<(&'a str, u32)>,
+}
+
+/// Deduplicated tracing output for a single error, produced by
+/// [`dedup_traces`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DedupTrace<'a> {
+    /// A frame not seen earlier in the tree.
+    New(TraceFrame<'a>),
+    /// Frame indices of every repeated frame on this error, in trace order.
+    ///
+    /// Emitted once per error after all [`DedupTrace::New`] frames, and only
+    /// if there are repeats.
+    Repeated(&'a [usize]),
+}
+
+/// Walks the tree depth-first, deduplicating tracing frames as
+/// [`print_tree`][`crate::print_tree`] does.
+///
+/// `func` receives the pre-order index of each error alongside its
+/// [`DedupTrace`] entries. At most `MAX_FRAMES` unique frames are tracked;
+/// frames past that limit are always reported as new with index `MAX_FRAMES`.
+/// Likewise, at most `MAX_FRAMES` repeats are reported per error.
+///
+/// ```rust
+/// # use std::error::Error;
+/// use bare_err_tree::{dedup_traces, err_tree, DedupTrace};
+/// # use tracing_subscriber::layer::SubscriberExt;
+///
+/// #[err_tree]
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer {
+///     #[tree_err]
+///     #[source]
+///     inner: Inner,
+/// }
+///
+/// #[err_tree]
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("inner")]
+/// struct Inner {}
+///
+/// #[tracing::instrument]
+/// fn fail() -> Outer {
+///     Outer::_tree(Inner::_tree())
+/// }
+///
+/// # let subscriber = tracing_subscriber::Registry::default().with(tracing_error::ErrorLayer::default());
+/// # tracing::subscriber::with_default(subscriber, || {
+/// let err = fail();
+///
+/// let (mut new, mut repeated) = (0, 0);
+/// dedup_traces::<10, _, _>(&err, |_, trace| {
+///     match trace {
+///         DedupTrace::New(_) => new += 1,
+///         DedupTrace::Repeated(idx) => repeated += idx.len(),
+///     }
+///     Ok(())
+/// })
+/// .unwrap();
+///
+/// // Both errors were created in the same span
+/// assert_eq!((new, repeated), (1, 1));
+/// # });
+/// ```
+pub fn dedup_traces<const MAX_FRAMES: usize, E, F>(tree: E, mut func: F) -> fmt::Result
+where
+    E: AsErrTree,
+    F: FnMut(usize, DedupTrace<'_>) -> fmt::Result,
+{
+    #[cfg(not(feature = "heap_buffer"))]
+    let mut found_traces: [_; MAX_FRAMES] = core::array::from_fn(|_| None);
+
+    #[cfg(feature = "heap_buffer")]
+    let mut found_traces = core::iter::repeat_with(|| None)
+        .take(MAX_FRAMES)
+        .collect::<alloc::vec::Vec<_>>()
+        .into_boxed_slice();
+
+    #[cfg(not(feature = "heap_buffer"))]
+    let mut repeated = [0; MAX_FRAMES];
+
+    #[cfg(feature = "heap_buffer")]
+    let mut repeated = alloc::vec![0; MAX_FRAMES].into_boxed_slice();
+
+    let mut node = 0;
+    let mut res = Ok(());
+    tree.as_err_tree(&mut |tree| {
+        res = dedup_tree(tree, &mut found_traces, &mut repeated, &mut node, &mut func);
+    });
+    res
+}
+
+fn dedup_tree<F>(
+    tree: ErrTree<'_>,
+    found_traces: &mut [Option<<ErrTree<'_> as ErrTreeFormattable>::TraceSpanId>],
+    repeated: &mut [usize],
+    node: &mut usize,
+    func: &mut F,
+) -> fmt::Result
+where
+    F: FnMut(usize, DedupTrace<'_>) -> fmt::Result,
+{
+    let this_node = *node;
+    *node += 1;
+
+    let repeated_count = dedup_node_traces(&tree, found_traces, repeated, |frame, span| {
+        (func)(
+            this_node,
+            DedupTrace::New(TraceFrame {
+                frame,
+                target: span.target.as_str(),
+                name: span.name.as_str(),
+                fields: span.fields.as_str(),
+                location: span.location.map(|(file, line)| (file.as_str(), line)),
+            }),
+        )
+    })?;
+
+    if repeated_count > 0 {
+        (func)(this_node, DedupTrace::Repeated(&repeated[..repeated_count]))?;
+    }
+
+    for source in tree.sources() {
+        let mut res = Ok(());
+        source.as_err_tree(&mut |tree| {
+            res = dedup_tree(tree, found_traces, repeated, node, func);
+        });
+        res?;
+    }
+    Ok(())
+}
@@ -0,0 +1,82 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Typed panic payload for [`tree_unwrap`][`crate::tree_unwrap`].
+
+use alloc::{boxed::Box, string::String};
+use core::{
+    fmt::{self, Display, Formatter},
+    panic::Location,
+};
+use std::{panic, thread};
+
+/// Payload of a [`tree_unwrap`][`crate::tree_unwrap`] panic, with `std_panic`.
+///
+/// A panic hook can downcast the payload to tell tree panics apart from other
+/// panics, and read the rendered tree without parsing the message. The
+/// [`Display`] output is the message `tree_unwrap` panics with otherwise.
+///
+/// The default panic hook only prints [`str`] and [`String`] payloads, so it
+/// prints `Box<dyn Any>` for this one. Install [`set_tree_panic_hook`], or a
+/// hook that downcasts it, to keep the tree in the panic output.
+///
+/// ```rust,no_run
+/// use std::panic;
+/// use bare_err_tree::TreePanic;
+///
+/// let default_hook = panic::take_hook();
+/// panic::set_hook(Box::new(move |info| match info.payload().downcast_ref::<TreePanic>() {
+///     Some(tree_panic) => eprintln!("{tree_panic}"),
+///     None => default_hook(info),
+/// }));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TreePanic {
+    /// The error tree, as rendered by [`print_tree`][`crate::print_tree`].
+    pub rendered: String,
+    /// Callsite of the `tree_unwrap` that panicked.
+    pub location: &'static Location<'static>,
+}
+
+impl Display for TreePanic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Panic origin at: {:#?}\n{}",
+            self.location, self.rendered
+        )
+    }
+}
+
+/// Wraps the current panic hook to print [`TreePanic`] payloads.
+///
+/// Tree panics are printed like the default hook prints a message, with the
+/// panicking thread, the `tree_unwrap` callsite, and the [`Display`] output
+/// of the payload. All other panics
+/// are passed on to the hook that was set before.
+///
+/// ```rust,no_run
+/// use bare_err_tree::{set_tree_panic_hook, tree_unwrap_default, WrapErr};
+///
+/// set_tree_panic_hook();
+///
+/// let res: Result<(), _> = Err(WrapErr(std::fmt::Error));
+/// tree_unwrap_default(res);
+/// ```
+pub fn set_tree_panic_hook() {
+    let prev = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        match info.payload().downcast_ref::<TreePanic>() {
+            Some(tree_panic) => {
+                let thread = thread::current();
+                let name = thread.name().unwrap_or("<unnamed>");
+                let location = tree_panic.location;
+                std::eprintln!("thread '{name}' panicked at {location}:\n{tree_panic}");
+            }
+            None => prev(info),
+        }
+    }));
+}
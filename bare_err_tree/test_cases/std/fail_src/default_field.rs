@@ -0,0 +1,32 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+};
+
+use bare_err_tree::err_tree;
+
+fn main() {}
+
+#[derive(Debug)]
+struct NoDefault;
+
+#[allow(dead_code)]
+#[err_tree(default)]
+#[derive(Debug)]
+struct ErrStruct {
+    code: u32,
+    inner: NoDefault,
+}
+
+impl Error for ErrStruct {}
+impl Display for ErrStruct {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code)
+    }
+}
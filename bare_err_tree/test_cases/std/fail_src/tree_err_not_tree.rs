@@ -0,0 +1,58 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+};
+
+use bare_err_tree::err_tree;
+
+fn main() {}
+
+#[derive(Debug)]
+struct Plain;
+
+impl Error for Plain {}
+impl Display for Plain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "plain")
+    }
+}
+
+#[allow(dead_code)]
+#[err_tree]
+#[derive(Debug)]
+struct ErrStruct {
+    #[dyn_err]
+    io: std::io::Error,
+    #[tree_err]
+    plain: Plain,
+}
+
+impl Error for ErrStruct {}
+impl Display for ErrStruct {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.plain, f)
+    }
+}
+
+#[allow(dead_code)]
+#[err_tree(ErrEnumWrap)]
+#[derive(Debug)]
+enum ErrEnum {
+    #[tree_err]
+    Plain(Plain),
+    #[tree_iter_err]
+    Many([Plain; 2]),
+}
+
+impl Error for ErrEnum {}
+impl Display for ErrEnum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "enum")
+    }
+}
@@ -0,0 +1,42 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use bare_err_tree::err_tree;
+
+mod outer {
+    pub mod inner {
+        use std::{
+            error::Error,
+            fmt::{self, Display, Formatter},
+        };
+
+        use super::super::err_tree;
+
+        #[err_tree(InnerWrap, vis = "pub(super)")]
+        #[derive(Debug)]
+        pub enum Inner {
+            Only,
+        }
+
+        impl Error for Inner {}
+        impl Display for Inner {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                write!(f, "inner")
+            }
+        }
+    }
+
+    // Visible to the parent module
+    pub fn wrap() -> usize {
+        core::mem::size_of::<inner::InnerWrap>()
+    }
+}
+
+fn main() {
+    let _ = outer::inner::Inner::Only;
+    let _ = outer::wrap();
+    let _: Option<outer::inner::InnerWrap> = None;
+}
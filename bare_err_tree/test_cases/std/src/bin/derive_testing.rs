@@ -48,6 +48,10 @@ struct ErrStruct<
     err_slice_static: &'a [std::io::Error; 3],
     #[tree_iter_err]
     err2_slice_static: [InnerErrWrap; THREE],
+    #[dyn_iter_err]
+    err_opt_vec: Option<Vec<std::io::Error>>,
+    #[tree_iter_err]
+    err2_opt_array: Option<[InnerErrWrap; THREE]>,
     _phantom: PhantomData<&'a ()>,
     _phantom_2: PhantomData<&'b ()>,
 }
@@ -76,6 +80,8 @@ enum ErrEnum {
     IoGroup([std::io::Error; 7]),
     #[dyn_iter_err]
     IoVec(Vec<std::io::Error>),
+    #[dyn_iter_err]
+    IoOptVec(Option<Vec<std::io::Error>>),
 }
 
 impl Error for ErrEnum {
@@ -84,6 +90,7 @@ impl Error for ErrEnum {
             Self::Local(x) => x.source(),
             Self::IoGroup(x) => Some(&x[0]),
             Self::IoVec(x) => x.first().map(|x| x as &dyn Error),
+            Self::IoOptVec(x) => x.iter().flatten().next().map(|x| x as &dyn Error),
         }
     }
 }
@@ -93,3 +100,44 @@ impl Display for ErrEnum {
         write!(f, "")
     }
 }
+
+#[err_tree]
+#[derive(Debug)]
+struct RawIdents {
+    #[dyn_err]
+    r#type: std::io::Error,
+    #[tree_err]
+    r#match: InnerErrWrap,
+    #[dyn_iter_err]
+    r#loop: Vec<std::io::Error>,
+    #[tree_iter_err]
+    r#for: [InnerErrWrap; 2],
+}
+
+impl Error for RawIdents {}
+impl Display for RawIdents {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.r#type, f)
+    }
+}
+
+#[err_tree(r#Wrapper)]
+#[allow(dead_code, non_camel_case_types)]
+#[derive(Debug)]
+enum r#type {
+    #[dyn_err]
+    r#try(std::io::Error),
+    #[tree_err]
+    r#match(InnerErrWrap),
+    #[dyn_iter_err]
+    r#loop(Vec<std::io::Error>),
+    #[tree_iter_err]
+    r#for([InnerErrWrap; 2]),
+}
+
+impl Error for r#type {}
+impl Display for r#type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "")
+    }
+}
@@ -6,7 +6,7 @@
 
 use std::fmt::{self, Display, Formatter};
 
-use bare_err_tree::{err_tree, print_tree};
+use bare_err_tree::prelude::*;
 use thiserror::Error;
 
 #[allow(dead_code)]
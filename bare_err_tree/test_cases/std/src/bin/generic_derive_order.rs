@@ -0,0 +1,98 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+};
+
+use bare_err_tree::err_tree;
+
+fn main() {
+    let named = Named::<'_, Leaf>::new(Leaf, &[1, 2]);
+    let named_clone = named.clone();
+    assert_eq!(named_clone.data, &[1, 2]);
+
+    let tuple = Tuple::<u8>::new(3, [4; 2]);
+    let tuple_clone = tuple.clone();
+    assert_eq!(tuple_clone, tuple);
+    assert_eq!((tuple_clone.0, tuple_clone.1), (3, [4; 2]));
+}
+
+#[derive(Debug, Clone)]
+struct Leaf;
+
+impl Error for Leaf {}
+impl Display for Leaf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "leaf")
+    }
+}
+
+#[err_tree]
+#[derive(Debug, Clone)]
+struct Named<'a, E, const N: usize = 2, T: Copy + Debug = u32>
+where
+    E: Error + Clone + 'static,
+    T: Default,
+{
+    #[dyn_err]
+    err: E,
+    data: &'a [T; N],
+}
+
+impl<'a, E, const N: usize, T: Copy + Debug> Named<'a, E, N, T>
+where
+    E: Error + Clone + 'static,
+    T: Default,
+{
+    #[track_caller]
+    fn new(err: E, data: &'a [T; N]) -> Self {
+        Self::_tree(err, data)
+    }
+}
+
+impl<E, const N: usize, T: Copy + Debug> Error for Named<'_, E, N, T>
+where
+    E: Error + Clone + 'static,
+    T: Default,
+{
+}
+impl<E, const N: usize, T: Copy + Debug> Display for Named<'_, E, N, T>
+where
+    E: Error + Clone + 'static,
+    T: Default,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.err, f)
+    }
+}
+
+#[err_tree]
+#[derive(Debug, Clone, PartialEq)]
+struct Tuple<T, const N: usize = 2>(T, [T; N])
+where
+    T: Debug + Clone + PartialEq;
+
+impl<T, const N: usize> Tuple<T, N>
+where
+    T: Debug + Clone + PartialEq,
+{
+    #[track_caller]
+    fn new(val: T, arr: [T; N]) -> Self {
+        Self::_tree(val, arr)
+    }
+}
+
+impl<T, const N: usize> Error for Tuple<T, N> where T: Debug + Clone + PartialEq {}
+impl<T, const N: usize> Display for Tuple<T, N>
+where
+    T: Debug + Clone + PartialEq,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
@@ -0,0 +1,51 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+};
+
+use bare_err_tree::err_tree;
+
+#[allow(dead_code)]
+fn main() {}
+
+#[allow(dead_code)]
+#[err_tree]
+#[derive(Debug)]
+struct FixedStruct<'a> {
+    #[dyn_iter_err]
+    array: [std::io::Error; 3],
+    #[dyn_iter_err]
+    slice: &'a [std::io::Error],
+    #[dyn_iter_err]
+    opt_array: Option<[std::io::Error; 2]>,
+}
+
+impl Error for FixedStruct<'_> {}
+impl Display for FixedStruct<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "")
+    }
+}
+
+#[err_tree(FixedEnumWrap)]
+#[allow(dead_code)]
+#[derive(Debug)]
+enum FixedEnum<'a> {
+    #[dyn_iter_err]
+    Array([std::io::Error; 3]),
+    #[dyn_iter_err]
+    Slice(&'a [std::io::Error]),
+}
+
+impl Error for FixedEnum<'_> {}
+impl Display for FixedEnum<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "")
+    }
+}
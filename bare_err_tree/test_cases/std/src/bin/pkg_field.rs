@@ -0,0 +1,113 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+    io,
+};
+
+use bare_err_tree::err_tree;
+
+fn main() {
+    let generated = Generated::_tree(7, io::ErrorKind::UnexpectedEof.into());
+    assert_eq!(generated._err_tree_pkg, 7);
+    assert_eq!(
+        format!("{generated:?}"),
+        "Generated { _err_tree_pkg: 7, io: Kind(UnexpectedEof) }"
+    );
+
+    let built = Built::_tree_builder()._err_tree_pkg("user").build();
+    assert_eq!(built._err_tree_pkg, "user");
+
+    let tuple = Tuple::_tree(3_u8);
+    assert_eq!(format!("{tuple:?}"), "Tuple(3)");
+    assert_eq!(tuple, Tuple::_tree(3));
+
+    assert_eq!(format!("{:?}", Unit::_tree()), "Unit");
+
+    let auto = Auto::_tree(4);
+    assert_eq!(auto.clone()._err_tree_pkg, 4);
+    assert_eq!(Quoted::_tree(5)._err_tree_pkg, 5);
+}
+
+/// Mirrors generated code that already uses the default hidden field name.
+#[err_tree(pkg_field = meta, pkg_debug_skip)]
+#[derive(Debug)]
+struct Generated {
+    _err_tree_pkg: u32,
+    #[dyn_err]
+    io: io::Error,
+}
+
+#[err_tree(builder, pkg_field = meta)]
+#[derive(Debug)]
+struct Built {
+    _err_tree_pkg: &'static str,
+}
+
+#[err_tree(pkg_debug_skip)]
+#[derive(Debug, PartialEq)]
+struct Tuple<T: Debug>(T);
+
+#[err_tree(pkg_field = meta, pkg_debug_skip)]
+#[derive(Debug)]
+struct Unit;
+
+/// Picks a free hidden field name by itself.
+#[err_tree]
+#[derive(Debug, Clone)]
+struct Auto {
+    _err_tree_pkg: u32,
+}
+
+#[err_tree(pkg_field = "__my_pkg")]
+#[derive(Debug)]
+struct Quoted {
+    _err_tree_pkg: u32,
+}
+
+impl Error for Generated {}
+impl Display for Generated {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "generated {}", self._err_tree_pkg)
+    }
+}
+
+impl Error for Auto {}
+impl Display for Auto {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "auto {}", self._err_tree_pkg)
+    }
+}
+
+impl Error for Quoted {}
+impl Display for Quoted {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "quoted {}", self._err_tree_pkg)
+    }
+}
+
+impl Error for Built {}
+impl Display for Built {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "built {}", self._err_tree_pkg)
+    }
+}
+
+impl<T: Debug> Error for Tuple<T> {}
+impl<T: Debug> Display for Tuple<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "tuple {:?}", self.0)
+    }
+}
+
+impl Error for Unit {}
+impl Display for Unit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "unit")
+    }
+}
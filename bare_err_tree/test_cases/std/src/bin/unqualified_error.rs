@@ -0,0 +1,65 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Wrapper generation without `Error` imported, and with common names shadowed.
+
+#![allow(dead_code)]
+
+use bare_err_tree::err_tree;
+
+/// Shadows the crate, so only `::core` paths resolve.
+mod core {}
+
+struct Error;
+struct Option;
+struct Result;
+struct Formatter;
+struct Ordering;
+
+fn main() {
+    let wrapped: FooWrap = Foo::Io(::std::sync::Arc::new(::std::io::Error::other("inner"))).into();
+    ::std::assert!(::std::error::Error::source(&wrapped).is_some());
+    ::std::assert_eq!(::std::format!("{wrapped}"), "foo");
+
+    let _: Foo = ::std::clone::Clone::clone(&wrapped).into();
+
+    let _ = <BarWrap as ::std::default::Default>::default();
+}
+
+#[err_tree(FooWrap)]
+#[derive(Debug, Clone)]
+enum Foo {
+    #[dyn_err]
+    Io(::std::sync::Arc<::std::io::Error>),
+    Leaf,
+}
+
+impl ::std::error::Error for Foo {
+    fn source(&self) -> ::std::option::Option<&(dyn ::std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => ::std::option::Option::Some(&**err),
+            Self::Leaf => ::std::option::Option::None,
+        }
+    }
+}
+
+impl ::std::fmt::Display for Foo {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        f.write_str("foo")
+    }
+}
+
+#[err_tree(BarWrap)]
+#[derive(Debug, Default, Hash)]
+struct Bar;
+
+impl ::std::error::Error for Bar {}
+
+impl ::std::fmt::Display for Bar {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        f.write_str("bar")
+    }
+}
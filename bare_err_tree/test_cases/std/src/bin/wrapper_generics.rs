@@ -0,0 +1,194 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+    io,
+    marker::PhantomData,
+};
+
+use bare_err_tree::{err_tree, print_tree, AsErrTree};
+
+fn main() {
+    let errs = [
+        io::Error::from(io::ErrorKind::NotFound),
+        io::Error::from(io::ErrorKind::NotFound),
+        io::Error::from(io::ErrorKind::NotFound),
+    ];
+    let wrapped: ErrStructWrap<'_, '_, InnerErrWrap> = ErrStruct {
+        err: io::Error::from(io::ErrorKind::UnexpectedEof),
+        err2: InnerErrStruct.into(),
+        err_slice: vec![io::Error::from(io::ErrorKind::TimedOut)],
+        err2_slice: vec![],
+        err_slice_static: &errs,
+        err2_slice_static: core::array::from_fn(|_| InnerErrStruct.into()),
+        err_opt_vec: None,
+        err2_opt_array: None,
+        _phantom: PhantomData,
+        _phantom_2: PhantomData,
+    }
+    .into();
+    let mut out = String::new();
+    print_tree::<60, _, _>(&wrapped, &mut out).unwrap();
+    assert!(out.starts_with("unexpected end of file"));
+
+    let enum_wrapped = ErrEnumWrap::from(ErrEnum::Leaf(InnerErrStruct));
+    let _: ErrEnum<InnerErrStruct> = enum_wrapped.clone().into();
+
+    let const_wrapped: ConstEnumWrap<2> =
+        ConstEnum::Many([io::Error::other("a"), io::Error::other("b")]).into();
+    let mut out = String::new();
+    print_tree::<60, _, _>(&const_wrapped, &mut out).unwrap();
+    assert!(out.ends_with("╰─▶ b"));
+    assert!(const_enum_result().is_err());
+
+    let tuple_wrapped = TupleErrWrap::from(TupleErr(io::Error::other("inner"), 1u8));
+    assert_eq!(tuple_wrapped.1, 1);
+    let mut out = String::new();
+    print_tree::<60, _, _>(&tuple_wrapped, &mut out).unwrap();
+    assert!(out.ends_with("╰─▶ inner"));
+
+    let direct = TupleDirect::_tree(2, io::Error::other("direct"));
+    let mut out = String::new();
+    print_tree::<60, _, _>(&direct, &mut out).unwrap();
+    assert!(out.ends_with("╰─▶ direct"));
+}
+
+#[err_tree(InnerErrWrap)]
+#[derive(Default, Debug, Clone, Copy)]
+struct InnerErrStruct;
+
+impl Error for InnerErrStruct {}
+impl Display for InnerErrStruct {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "")
+    }
+}
+
+#[allow(dead_code)]
+#[err_tree(ErrStructWrap)]
+#[derive(Debug)]
+struct ErrStruct<
+    'a,
+    'b: 'a,
+    Tree: AsErrTree + Debug,
+    const C: usize = 5,
+    Err: Error + 'static = std::io::Error,
+> {
+    #[dyn_err]
+    err: Err,
+    #[tree_err]
+    err2: Tree,
+    #[dyn_iter_err]
+    err_slice: Vec<std::io::Error>,
+    #[tree_iter_err]
+    err2_slice: Vec<InnerErrWrap>,
+    #[dyn_iter_err]
+    err_slice_static: &'a [std::io::Error; 3],
+    #[tree_iter_err]
+    err2_slice_static: [InnerErrWrap; THREE],
+    #[dyn_iter_err]
+    err_opt_vec: Option<Vec<std::io::Error>>,
+    #[tree_iter_err]
+    err2_opt_array: Option<[InnerErrWrap; THREE]>,
+    _phantom: PhantomData<&'a ()>,
+    _phantom_2: PhantomData<&'b ()>,
+}
+
+const THREE: usize = 3;
+
+impl<const C: usize, Tree: AsErrTree + Debug, Err: Error> Error
+    for ErrStruct<'_, '_, Tree, C, Err>
+{
+}
+impl<const C: usize, Tree: AsErrTree + Debug, Err: Error> Display
+    for ErrStruct<'_, '_, Tree, C, Err>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.err, f)
+    }
+}
+
+#[err_tree(ErrEnumWrap)]
+#[derive(Debug, Clone)]
+enum ErrEnum<T = InnerErrStruct>
+where
+    T: Error + Clone + 'static,
+{
+    #[dyn_err]
+    Leaf(T),
+}
+
+impl<T> Error for ErrEnum<T> where T: Error + Clone + 'static {}
+impl<T> Display for ErrEnum<T>
+where
+    T: Error + Clone + 'static,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Leaf(x) => Display::fmt(x, f),
+        }
+    }
+}
+
+#[err_tree(ConstEnumWrap)]
+#[derive(Debug)]
+enum ConstEnum<const N: usize, T = io::Error>
+where
+    T: Error + 'static,
+{
+    #[dyn_iter_err]
+    Many([T; N]),
+}
+
+impl<const N: usize, T> Error for ConstEnum<N, T> where T: Error + 'static {}
+impl<const N: usize, T> Display for ConstEnum<N, T>
+where
+    T: Error + 'static,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{N} errors")
+    }
+}
+
+fn const_enum_result() -> Result<(), ConstEnumWrap<1>> {
+    Err(ConstEnum::Many([io::Error::other("only")]))?
+}
+
+#[err_tree(TupleErrWrap)]
+#[derive(Debug)]
+struct TupleErr<T, N>(#[dyn_err] T, N)
+where
+    T: Error + 'static,
+    N: Debug;
+
+impl<T, N> Error for TupleErr<T, N>
+where
+    T: Error + 'static,
+    N: Debug,
+{
+}
+impl<T, N> Display for TupleErr<T, N>
+where
+    T: Error + 'static,
+    N: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+#[err_tree]
+#[derive(Debug)]
+struct TupleDirect(u8, #[dyn_err] io::Error);
+
+impl Error for TupleDirect {}
+impl Display for TupleDirect {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
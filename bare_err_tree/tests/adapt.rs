@@ -0,0 +1,118 @@
+#![cfg(feature = "adapt")]
+
+use core::{
+    error::Error,
+    fmt::{self, Display, Formatter, Write as _},
+};
+use std::io;
+
+use bare_err_tree::{front_max_for_depth, print_tree, render_tree_to_sink, AdaptWrite, SinkError};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("outer é")]
+struct Outer(#[source] Inner);
+
+#[derive(Debug, Error)]
+#[error("inner 🦀")]
+struct Inner;
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+fn tree() -> Outer {
+    Outer(Inner)
+}
+
+/// Accepts `limit` bytes, then fails with [`io::ErrorKind::BrokenPipe`].
+#[derive(Clone, PartialEq)]
+struct Failing {
+    written: Vec<u8>,
+    limit: usize,
+}
+
+impl io::Write for Failing {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.limit - self.written.len());
+        if len == 0 {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        self.written.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn matches_string() {
+    let mut expected = String::new();
+    print_tree::<FRONT_MAX, _, _>(&tree() as &dyn Error, &mut expected).unwrap();
+
+    let mut out = AdaptWrite::new(Vec::new());
+    print_tree::<FRONT_MAX, _, _>(&tree() as &dyn Error, &mut out).unwrap();
+    let (written, error) = out.into_parts();
+    assert_eq!(written, expected.as_bytes());
+    assert!(error.is_none());
+
+    let mut sink = AdaptWrite::from(Vec::new());
+    render_tree_to_sink::<FRONT_MAX, _, _>(&(&tree() as &dyn Error), &mut sink).unwrap();
+    assert_eq!(sink.0, expected.as_bytes());
+}
+
+#[test]
+fn recovers_io_error() {
+    let mut out = AdaptWrite::new(Failing {
+        written: Vec::new(),
+        limit: 4,
+    });
+    assert_eq!(
+        print_tree::<FRONT_MAX, _, _>(&tree() as &dyn Error, &mut out),
+        Err(fmt::Error)
+    );
+    assert_eq!(out.0.written, b"oute");
+    assert_eq!(out.take_error().unwrap().kind(), io::ErrorKind::BrokenPipe);
+    assert!(out.take_error().is_none());
+
+    let mut sink = AdaptWrite::new(Failing {
+        written: Vec::new(),
+        limit: 0,
+    });
+    assert_eq!(
+        render_tree_to_sink::<FRONT_MAX, _, _>(&(&tree() as &dyn Error), &mut sink),
+        Err(SinkError)
+    );
+    let (_, error) = sink.into_parts();
+    assert_eq!(error.unwrap().kind(), io::ErrorKind::BrokenPipe);
+}
+
+/// Fails to display, while the writer is fine.
+struct BadDisplay;
+
+impl Display for BadDisplay {
+    fn fmt(&self, _: &mut Formatter<'_>) -> fmt::Result {
+        Err(fmt::Error)
+    }
+}
+
+#[test]
+fn display_error_passthrough() {
+    let mut out = AdaptWrite::new(Vec::new());
+    assert_eq!(write!(out, "{BadDisplay}"), Err(fmt::Error));
+    assert!(out.take_error().is_none());
+}
+
+#[test]
+fn clone_drops_error() {
+    let mut out = AdaptWrite::new(Failing {
+        written: Vec::new(),
+        limit: 0,
+    });
+    assert!(out.write_str("x").is_err());
+
+    let mut clone = out.clone();
+    assert!(out == clone);
+    assert!(clone.take_error().is_none());
+    assert!(out.take_error().is_some());
+}
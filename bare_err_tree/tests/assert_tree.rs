@@ -0,0 +1,67 @@
+#![cfg(feature = "derive")]
+
+use std::{io, panic};
+
+use bare_err_tree::{assert_tree_err, assert_tree_ok, err_tree};
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("request failed")]
+struct Request {
+    #[dyn_err]
+    io: io::Error,
+}
+
+fn fetch(ok: bool) -> Result<u32, Request> {
+    if ok {
+        Ok(200)
+    } else {
+        Err(Request::_tree(io::ErrorKind::TimedOut.into()))
+    }
+}
+
+/// The panic message of `assert`.
+fn panic_msg(assert: impl FnOnce()) -> String {
+    let payload = panic::catch_unwind(panic::AssertUnwindSafe(assert)).unwrap_err();
+    *payload.downcast::<String>().unwrap()
+}
+
+#[test]
+fn ok_passes() {
+    assert_eq!(assert_tree_ok!(fetch(true)), 200);
+}
+
+#[test]
+fn err_passes() {
+    let err = assert_tree_err!(fetch(false));
+    assert_eq!(err.io.kind(), io::ErrorKind::TimedOut);
+}
+
+#[test]
+fn ok_fails_with_tree() {
+    let msg = panic_msg(|| {
+        assert_tree_ok!(fetch(false));
+    });
+
+    assert!(
+        msg.starts_with("assertion failed: `fetch(false)` is Ok, at "),
+        "{msg}"
+    );
+    assert!(msg.contains("tests/assert_tree.rs:"), "{msg}");
+    assert!(msg.contains("request failed"), "{msg}");
+    assert!(msg.contains("╰─▶ timed out"), "{msg}");
+}
+
+#[test]
+fn err_fails_without_tree() {
+    let msg = panic_msg(|| {
+        assert_tree_err!(fetch(true));
+    });
+
+    assert!(
+        msg.starts_with("assertion failed: `fetch(true)` is Err, at "),
+        "{msg}"
+    );
+    assert!(msg.contains("tests/assert_tree.rs:"), "{msg}");
+    assert!(!msg.contains('\n'), "{msg}");
+}
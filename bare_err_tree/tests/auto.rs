@@ -0,0 +1,114 @@
+#![cfg(all(
+    feature = "derive",
+    not(feature = "unix_color"),
+    not(feature = "type_name")
+))]
+
+use std::io;
+
+use bare_err_tree::{err_tree, front_max_for_depth, print_tree, AsErrTree};
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("failed to parse")]
+struct Leaf {
+    #[source]
+    #[dyn_err]
+    io: io::Error,
+}
+
+#[err_tree(auto)]
+#[derive(Debug, thiserror::Error)]
+#[error("failed to load")]
+struct Auto {
+    #[source]
+    leaf: Leaf,
+}
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("failed to load")]
+struct Annotated {
+    #[source]
+    #[tree_err]
+    leaf: Leaf,
+}
+
+#[err_tree(AutoWrap, auto)]
+#[derive(Debug, thiserror::Error)]
+#[error("failed to load")]
+struct Wrapped {
+    #[source]
+    leaf: Leaf,
+}
+
+#[err_tree(AutoEnumWrap, auto)]
+#[derive(Debug, thiserror::Error)]
+enum AutoEnum {
+    #[error("failed to load")]
+    Leaf(#[source] Leaf),
+    #[error("no config")]
+    Missing,
+}
+
+fn leaf() -> Leaf {
+    Leaf::_tree(io::ErrorKind::NotFound.into())
+}
+
+fn render(err: impl AsErrTree) -> String {
+    let mut out = String::new();
+    print_tree::<FRONT_MAX, _, _>(err, &mut out).unwrap();
+    out
+}
+
+#[cfg(not(any(feature = "source_line", feature = "tracing", feature = "timestamp")))]
+#[test]
+fn matches_annotated() {
+    let annotated = render(Annotated::_tree(leaf()));
+    assert_eq!(
+        annotated,
+        "failed to load
+│
+╰─▶ failed to parse
+    │
+    ╰─▶ entity not found"
+    );
+
+    assert_eq!(render(Auto::_tree(leaf())), annotated);
+    assert_eq!(render(AutoWrap::from(Wrapped { leaf: leaf() })), annotated);
+    assert_eq!(
+        render(AutoEnumWrap::from(AutoEnum::Leaf(leaf()))),
+        annotated
+    );
+    assert_eq!(render(AutoEnumWrap::from(AutoEnum::Missing)), "no config");
+}
+
+#[cfg(feature = "source_line")]
+#[test]
+fn root_location_only() {
+    let locations = |out: &str| out.matches("tests/auto.rs:").count();
+
+    let annotated = render(Annotated::_tree(leaf()));
+    assert_eq!(locations(&annotated), 2, "{annotated}");
+
+    // The source renders through its `Error` impl, without its location
+    let auto = render(Auto::_tree(leaf()));
+    assert_eq!(locations(&auto), 1, "{auto}");
+    let root = auto
+        .lines()
+        .take_while(|line| !line.contains("failed to parse"));
+    assert_eq!(
+        root.filter(|line| line.contains("tests/auto.rs:")).count(),
+        1,
+        "{auto}"
+    );
+
+    let wrapped = render(AutoWrap::from(Wrapped { leaf: leaf() }));
+    assert_eq!(locations(&wrapped), 1, "{wrapped}");
+    let wrapped_enum = render(AutoEnumWrap::from(AutoEnum::Leaf(leaf())));
+    assert_eq!(locations(&wrapped_enum), 1, "{wrapped_enum}");
+    let missing = render(AutoEnumWrap::from(AutoEnum::Missing));
+    assert_eq!(locations(&missing), 1, "{missing}");
+}
@@ -0,0 +1,141 @@
+#![cfg(feature = "catch_panics")]
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+use bare_err_tree::{
+    front_max_for_depth, print_tree, print_tree_with, AsErrTree, ErrTree, FormatOptions, WrapErr,
+};
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+/// Source with a buggy tree implementation.
+#[derive(Debug)]
+struct Buggy {
+    /// Panic from `Display` instead, after the tree is produced.
+    in_display: bool,
+}
+
+impl AsErrTree for Buggy {
+    fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+        if !self.in_display {
+            panic!("buggy as_err_tree");
+        }
+        (func)(ErrTree::no_pkg(self, &mut core::iter::empty()))
+    }
+}
+
+impl Error for Buggy {}
+impl Display for Buggy {
+    fn fmt(&self, _: &mut Formatter<'_>) -> fmt::Result {
+        panic!("buggy display")
+    }
+}
+
+#[derive(Debug)]
+struct Root {
+    buggy: Buggy,
+    io: io::Error,
+}
+
+impl Root {
+    fn new(in_display: bool) -> Self {
+        Self {
+            buggy: Buggy { in_display },
+            io: io::Error::from(io::ErrorKind::UnexpectedEof),
+        }
+    }
+}
+
+impl AsErrTree for Root {
+    fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+        let sources = [&self.buggy as &dyn AsErrTree, WrapErr::tree(&self.io)];
+        (func)(ErrTree::no_pkg(self, &mut sources.iter().copied()))
+    }
+}
+
+impl Error for Root {}
+impl Display for Root {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "root")
+    }
+}
+
+fn render(tree: &Root, options: FormatOptions) -> String {
+    let mut out = String::new();
+    print_tree_with::<FRONT_MAX, _, _>(tree, options, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn placeholder() {
+    let out = render(
+        &Root::new(false),
+        FormatOptions::new().catch_source_panics(true),
+    );
+    assert_eq!(
+        out,
+        "root
+│
+├─▶ <source formatting panicked>
+│
+╰─▶ unexpected end of file"
+    );
+}
+
+#[test]
+fn nested_placeholder() {
+    #[derive(Debug)]
+    struct Outer(Root);
+
+    impl AsErrTree for Outer {
+        fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+            let sources = &mut core::iter::once(&self.0 as &dyn AsErrTree);
+            (func)(ErrTree::no_pkg(self, sources))
+        }
+    }
+
+    impl Error for Outer {}
+    impl Display for Outer {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "outer")
+        }
+    }
+
+    let mut out = String::new();
+    print_tree_with::<FRONT_MAX, _, _>(
+        &Outer(Root::new(false)),
+        FormatOptions::new().catch_source_panics(true),
+        &mut out,
+    )
+    .unwrap();
+    assert!(
+        out.contains("    ├─▶ <source formatting panicked>"),
+        "{out}"
+    );
+    assert!(out.ends_with("╰─▶ unexpected end of file"), "{out}");
+}
+
+#[test]
+fn disabled_propagates() {
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        let mut out = String::new();
+        print_tree::<FRONT_MAX, _, _>(&Root::new(false), &mut out)
+    }));
+    assert!(res.is_err());
+}
+
+#[test]
+fn display_propagates() {
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        render(
+            &Root::new(true),
+            FormatOptions::new().catch_source_panics(true),
+        )
+    }));
+    assert!(res.is_err());
+}
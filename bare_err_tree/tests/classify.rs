@@ -0,0 +1,128 @@
+#![cfg(all(feature = "classify", feature = "derive"))]
+
+use std::{error::Error, io};
+
+use bare_err_tree::{classify_tree, err_tree, AsErrTree, ClassCounter, Classifier, UNCLASSIFIED};
+
+#[derive(Debug, thiserror::Error)]
+#[error("request failed")]
+struct Request(#[source] io::Error);
+
+#[derive(Debug, thiserror::Error)]
+#[error("missing key {0}")]
+struct Config(&'static str);
+
+#[derive(Debug, thiserror::Error)]
+#[error("loading settings")]
+struct Load(#[source] Config);
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("job failed")]
+struct Job {
+    #[dyn_err]
+    request: Request,
+    #[dyn_err]
+    cleanup: io::Error,
+}
+
+fn classifier() -> Classifier {
+    Classifier::new()
+        .rule("network", |e| {
+            e.downcast_ref::<io::Error>().is_some_and(|e| {
+                matches!(
+                    e.kind(),
+                    io::ErrorKind::TimedOut | io::ErrorKind::ConnectionRefused
+                )
+            })
+        })
+        .rule("config", |e| e.is::<Config>())
+        // Never reached for config errors, as rules apply in order
+        .rule("shadowed", |e| e.is::<Config>())
+}
+
+#[test]
+fn mixed() {
+    let classifier = classifier();
+
+    let timeout = Request(io::ErrorKind::TimedOut.into());
+    let refused = Request(io::ErrorKind::ConnectionRefused.into());
+    let not_found = Request(io::ErrorKind::NotFound.into());
+    let config = Load(Config("port"));
+    let bare_config = Config("host");
+    let job = Job::_tree(
+        Request(io::ErrorKind::TimedOut.into()),
+        io::ErrorKind::Other.into(),
+    );
+
+    let trees: [&dyn AsErrTree; 6] = [
+        &(&timeout as &dyn Error),
+        &(&refused as &dyn Error),
+        &(&not_found as &dyn Error),
+        &(&config as &dyn Error),
+        &(&bare_config as &dyn Error),
+        &job,
+    ];
+
+    let mut counter = ClassCounter::new();
+    let classes: Vec<_> = trees
+        .iter()
+        .map(|tree| counter.add_tree(*tree, &classifier))
+        .collect();
+
+    assert_eq!(
+        classes,
+        [
+            "network",
+            "network",
+            UNCLASSIFIED,
+            "config",
+            "config",
+            "network"
+        ]
+    );
+    assert_eq!(counter.count("network"), 3);
+    assert_eq!(counter.count("config"), 2);
+    assert_eq!(counter.count(UNCLASSIFIED), 1);
+    assert_eq!(counter.count("shadowed"), 0);
+    assert_eq!(counter.total(), 6);
+    assert_eq!(
+        counter.summary().to_string(),
+        "3 network errors, 2 config errors, 1 other"
+    );
+}
+
+#[test]
+fn derived_root_cause() {
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("direct")]
+    struct Direct {
+        #[dyn_err]
+        io: io::Error,
+    }
+
+    // Held directly by a derived field, so it cannot be downcast
+    let direct = Direct::_tree(io::ErrorKind::TimedOut.into());
+    assert_eq!(classify_tree(&direct, &classifier()), UNCLASSIFIED);
+}
+
+#[test]
+fn summary() {
+    assert_eq!(ClassCounter::new().summary().to_string(), "no errors");
+
+    let mut counter = ClassCounter::new();
+    counter.add(UNCLASSIFIED);
+    counter.add(UNCLASSIFIED);
+    counter.add("config");
+    assert_eq!(counter.summary().to_string(), "1 config error, 2 other");
+
+    // Ties keep the order they were first counted in
+    counter.add("network");
+    counter.add("network");
+    counter.add("config");
+    assert_eq!(
+        counter.summary().to_string(),
+        "2 config errors, 2 network errors, 2 other"
+    );
+}
@@ -0,0 +1,177 @@
+#![cfg(feature = "derive_alloc")]
+
+use core::fmt::{self, Display, Formatter};
+use std::{sync::OnceLock, thread};
+
+use bare_err_tree::{
+    err_tree, front_max_for_depth, print_tree, print_tree_with, AsErrTree, FormatOptions,
+};
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+const THREADS: usize = 8;
+const RENDERS: usize = 200;
+
+/// Lazily computes its message on first display, from any thread.
+#[err_tree]
+#[derive(Debug)]
+struct Cached {
+    name: &'static str,
+    summary: OnceLock<String>,
+    #[tree_iter_err]
+    children: Vec<Cached>,
+}
+
+impl core::error::Error for Cached {}
+impl Display for Cached {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let summary = self
+            .summary
+            .get_or_init(|| format!("{} ({} direct causes)", self.name, self.children.len()));
+        f.write_str(summary)
+    }
+}
+
+fn cached(name: &'static str, children: Vec<Cached>) -> Cached {
+    Cached::_tree(name, OnceLock::new(), children)
+}
+
+fn tree() -> Cached {
+    cached(
+        "sync failed",
+        (0..4)
+            .map(|_| cached("upload failed", vec![cached("timed out", vec![])]))
+            .collect(),
+    )
+}
+
+/// Renders `err` from every thread, returning every render.
+fn hammer<E: AsErrTree + Sync>(err: &E, render: fn(&E) -> String) -> Vec<String> {
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..THREADS)
+            .map(|_| scope.spawn(|| (0..RENDERS).map(|_| render(err)).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+fn assert_identical(renders: &[String]) {
+    assert_eq!(renders.len(), THREADS * RENDERS);
+    assert!(
+        renders.iter().all(|render| render == &renders[0]),
+        "{:?}",
+        renders.iter().find(|render| *render != &renders[0])
+    );
+}
+
+#[test]
+fn shared_render() {
+    // Uninitialized summaries race to be filled in by the first renders
+    let err = tree();
+    let renders = hammer(&err, |err| {
+        let mut out = String::new();
+        print_tree::<FRONT_MAX, _, _>(err, &mut out).unwrap();
+        out
+    });
+    assert_identical(&renders);
+    assert_eq!(renders[0].matches("upload failed").count(), 4);
+}
+
+#[test]
+fn shared_render_with_options() {
+    let err = tree();
+    let renders = hammer(&err, |err| {
+        let options = FormatOptions::new()
+            .dedup_by_identity(true)
+            .numbered(true)
+            .max_children(Some(2));
+        let mut out = String::new();
+        print_tree_with::<FRONT_MAX, _, _>(err, options, &mut out).unwrap();
+        out
+    });
+    assert_identical(&renders);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn shared_json() {
+    let err = tree();
+    let renders = hammer(&err, |err| {
+        bare_err_tree::tree_to_json_string::<Cached, _>(err).unwrap()
+    });
+    assert_identical(&renders);
+}
+
+#[cfg(all(feature = "hook", any(feature = "source_line", feature = "tracing")))]
+mod hook {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use bare_err_tree::{clear_pkg_hook, set_pkg_hook};
+
+    use super::*;
+
+    static CAPTURES: AtomicUsize = AtomicUsize::new(0);
+
+    // The only test in this binary registering a hook
+    #[test]
+    fn exact_count() {
+        set_pkg_hook(|_| {
+            CAPTURES.fetch_add(1, Ordering::Relaxed);
+        });
+        thread::scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|| {
+                    for _ in 0..RENDERS {
+                        drop(cached("leaf", vec![]));
+                    }
+                });
+            }
+        });
+        clear_pkg_hook();
+
+        assert_eq!(CAPTURES.load(Ordering::Relaxed), THREADS * RENDERS);
+    }
+}
+
+#[cfg(feature = "external_pkg")]
+mod external_pkg {
+    use bare_err_tree::ErrTreePkg;
+
+    use super::*;
+
+    #[err_tree(external_pkg)]
+    #[derive(Debug, thiserror::Error)]
+    #[error("external {0}")]
+    struct External(usize);
+
+    #[test]
+    fn attach_detach() {
+        thread::scope(|scope| {
+            for thread in 0..THREADS {
+                scope.spawn(move || {
+                    for render in 0..RENDERS {
+                        let err = Box::new(External::_tree(thread * RENDERS + render));
+                        ErrTreePkg::new().attach(&*err);
+                        assert!(ErrTreePkg::attached(&*err).is_some());
+                        drop(err);
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn shared_render() {
+        let err = Box::new(External::_tree(0));
+        ErrTreePkg::new().attach(&*err);
+
+        let renders = hammer(&*err, |err| {
+            let mut out = String::new();
+            print_tree::<FRONT_MAX, _, _>(err, &mut out).unwrap();
+            out
+        });
+        assert_identical(&renders);
+    }
+}
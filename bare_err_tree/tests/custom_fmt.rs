@@ -0,0 +1,181 @@
+#![cfg(all(feature = "custom_fmt", not(feature = "unix_color")))]
+
+use core::{fmt, iter::Empty};
+
+use bare_err_tree::{
+    custom_fmt::{fmt_tree, ErrTreeFormattable},
+    front_max_for_depth,
+};
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+/// The `custom_fmt` module example.
+struct Report {
+    msg: String,
+    sources: Vec<Report>,
+}
+
+impl Report {
+    fn new(msg: &str, sources: Vec<Report>) -> Self {
+        Self {
+            msg: msg.to_string(),
+            sources,
+        }
+    }
+}
+
+impl ErrTreeFormattable for Report {
+    fn apply_msg<W: fmt::Write>(&self, mut f: W) -> fmt::Result {
+        f.write_str(&self.msg)
+    }
+
+    type Source<'a> = &'a mut Report;
+
+    fn sources_empty(&mut self) -> bool {
+        self.sources.is_empty()
+    }
+
+    fn apply_to_leading_sources<F>(&mut self, func: F) -> fmt::Result
+    where
+        F: FnMut(Self::Source<'_>) -> fmt::Result,
+    {
+        let leading = self.sources.len().saturating_sub(1);
+        self.sources[..leading].iter_mut().try_for_each(func)
+    }
+
+    fn apply_to_last_source<F>(&mut self, func: F) -> fmt::Result
+    where
+        F: FnMut(Self::Source<'_>) -> fmt::Result,
+    {
+        self.sources.last_mut().map_or(Ok(()), func)
+    }
+
+    type TraceSpanId = ();
+    type TraceSpanIter<'a> = Empty<char>;
+}
+
+#[test]
+fn owned_report() {
+    let report = Report::new(
+        "worker failed",
+        vec![
+            Report::new("disk full", vec![]),
+            Report::new(
+                "retry failed",
+                vec![Report::new("connection reset", vec![])],
+            ),
+        ],
+    );
+
+    let mut out = String::new();
+    fmt_tree::<FRONT_MAX, _, _>(report, &mut out).unwrap();
+    assert_eq!(
+        out,
+        "worker failed
+│
+├─▶ disk full
+│
+╰─▶ retry failed
+    │
+    ╰─▶ connection reset"
+    );
+}
+
+#[test]
+fn leaf() {
+    let mut out = String::new();
+    fmt_tree::<FRONT_MAX, _, _>(Report::new("worker failed", vec![]), &mut out).unwrap();
+    assert_eq!(out, "worker failed");
+}
+
+#[cfg(feature = "tracing")]
+mod trace_spans {
+    use core::{fmt, str::Chars};
+
+    use bare_err_tree::custom_fmt::{fmt_tree, ErrTreeFormattable, TraceSpan};
+
+    use super::FRONT_MAX;
+
+    /// A report with `(id, name)` spans, all in the `app` target.
+    struct Traced {
+        msg: &'static str,
+        spans: Vec<(u64, &'static str)>,
+        sources: Vec<Traced>,
+    }
+
+    impl ErrTreeFormattable for Traced {
+        fn apply_msg<W: fmt::Write>(&self, mut f: W) -> fmt::Result {
+            f.write_str(self.msg)
+        }
+
+        type Source<'a> = &'a mut Traced;
+
+        fn sources_empty(&mut self) -> bool {
+            self.sources.is_empty()
+        }
+
+        fn apply_to_leading_sources<F>(&mut self, func: F) -> fmt::Result
+        where
+            F: FnMut(Self::Source<'_>) -> fmt::Result,
+        {
+            let leading = self.sources.len().saturating_sub(1);
+            self.sources[..leading].iter_mut().try_for_each(func)
+        }
+
+        fn apply_to_last_source<F>(&mut self, func: F) -> fmt::Result
+        where
+            F: FnMut(Self::Source<'_>) -> fmt::Result,
+        {
+            self.sources.last_mut().map_or(Ok(()), func)
+        }
+
+        fn trace_empty(&self) -> bool {
+            self.spans.is_empty()
+        }
+
+        type TraceSpanId = u64;
+        type TraceSpanIter<'a> = Chars<'a>;
+
+        fn apply_trace<F>(&self, mut func: F) -> fmt::Result
+        where
+            F: FnMut(TraceSpan<Self::TraceSpanId, Self::TraceSpanIter<'_>>) -> fmt::Result,
+        {
+            for (id, name) in &self.spans {
+                func(
+                    TraceSpan::new(*id, "app".chars(), name.chars(), "".chars())
+                        .with_location("src/app.rs".chars(), 10 + *id as u32),
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn built_spans() {
+        let report = Traced {
+            msg: "request failed",
+            spans: vec![(1, "handle")],
+            sources: vec![Traced {
+                msg: "query failed",
+                spans: vec![(2, "query"), (1, "handle")],
+                sources: vec![],
+            }],
+        };
+
+        let mut out = String::new();
+        fmt_tree::<FRONT_MAX, _, _>(report, &mut out).unwrap();
+        assert_eq!(
+            out,
+            "request failed
+│
+├─ tracing frame 0 => app::handle
+│        at src/app.rs:11
+│
+╰─▶ query failed
+    │
+    ├─ tracing frame 1 => app::query
+    │        at src/app.rs:12
+    ╰─ 1 duplicate tracing frame(s): [0]"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "derive_alloc")]
+
+use std::panic;
+
+use bare_err_tree::{
+    err_tree, print_tree, print_tree_default, tree_unwrap, tree_unwrap_default, DefaultTreeDisplay,
+    ErrTreeDisplay, DEFAULT_FRONT_MAX,
+};
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("{msg}")]
+struct Node {
+    msg: &'static str,
+    #[tree_iter_err]
+    children: Vec<Node>,
+}
+
+fn tree() -> Node {
+    Node::_tree(
+        "failed to load config",
+        vec![Node::_tree(
+            "failed to parse",
+            vec![Node::_tree("unexpected end of file", vec![])],
+        )],
+    )
+}
+
+/// The panic message of `unwrap`, past the panic origin.
+fn panic_tree(unwrap: impl FnOnce()) -> String {
+    let payload = panic::catch_unwind(panic::AssertUnwindSafe(unwrap)).unwrap_err();
+    #[cfg(feature = "std_panic")]
+    let msg = payload
+        .downcast::<bare_err_tree::TreePanic>()
+        .unwrap()
+        .to_string();
+    #[cfg(not(feature = "std_panic"))]
+    let msg = payload.downcast::<String>().unwrap();
+    msg.split_once("\n}\n").unwrap().1.to_string()
+}
+
+#[test]
+fn print_tree_matches() {
+    let tree = tree();
+
+    let mut explicit = String::new();
+    print_tree::<DEFAULT_FRONT_MAX, _, _>(&tree, &mut explicit).unwrap();
+    let mut default = String::new();
+    print_tree_default(&tree, &mut default).unwrap();
+
+    assert_eq!(default, explicit);
+}
+
+#[test]
+fn tree_unwrap_matches() {
+    let tree = tree();
+    let res = || Err::<(), _>(&tree);
+
+    let explicit = panic_tree(|| tree_unwrap::<DEFAULT_FRONT_MAX, _, _>(res()));
+    let default = panic_tree(|| tree_unwrap_default(res()));
+
+    assert!(default.starts_with("failed to load config"), "{default}");
+    assert_eq!(default, explicit);
+}
+
+#[test]
+fn display_matches() {
+    let tree = tree();
+    assert_eq!(
+        DefaultTreeDisplay::borrowed(&tree).to_string(),
+        ErrTreeDisplay::<_, DEFAULT_FRONT_MAX>::borrowed(&tree).to_string()
+    );
+}
@@ -2,17 +2,62 @@
 
 use trybuild::TestCases;
 
+#[cfg(feature = "derive_alloc")]
 #[test]
 fn derive_example() {
     TestCases::new().pass("test_cases/std/src/bin/derive_testing.rs");
 }
 
-#[cfg(not(any(feature = "anyhow", feature = "eyre")))]
+#[test]
+fn no_alloc_example() {
+    TestCases::new().pass("test_cases/std/src/bin/no_alloc.rs");
+}
+
+#[test]
+fn generic_derive_order() {
+    TestCases::new().pass("test_cases/std/src/bin/generic_derive_order.rs");
+}
+
+#[test]
+fn pkg_field() {
+    TestCases::new().pass("test_cases/std/src/bin/pkg_field.rs");
+}
+
+#[test]
+fn unqualified_error() {
+    TestCases::new().pass("test_cases/std/src/bin/unqualified_error.rs");
+}
+
+#[cfg(feature = "derive_alloc")]
+#[test]
+fn wrapper_generics() {
+    TestCases::new().pass("test_cases/std/src/bin/wrapper_generics.rs");
+}
+
+#[cfg(not(feature = "derive_alloc"))]
+#[test]
+fn no_alloc_dynamic() {
+    TestCases::new().compile_fail("test_cases/std/fail_src/no_alloc_dynamic.rs");
+}
+
+#[cfg(not(any(feature = "anyhow", feature = "eyre", feature = "report_shim")))]
 #[test]
 fn false_tree_defs() {
     TestCases::new().compile_fail("test_cases/std/fail_src/false_tree*.rs");
 }
 
+#[cfg(not(any(feature = "anyhow", feature = "eyre", feature = "report_shim")))]
+#[test]
+fn tree_err_not_tree() {
+    TestCases::new().compile_fail("test_cases/std/fail_src/tree_err_not_tree.rs");
+}
+
+#[test]
+fn default_field() {
+    TestCases::new().compile_fail("test_cases/std/fail_src/default_field.rs");
+}
+
+#[cfg(feature = "derive_alloc")]
 #[test]
 fn container_as_err() {
     TestCases::new().compile_fail("test_cases/std/fail_src/container.rs");
@@ -37,3 +82,8 @@ fn direct_enum() {
 fn direct_unit() {
     TestCases::new().compile_fail("test_cases/std/fail_src/direct_union.rs");
 }
+
+#[test]
+fn wrapper_vis() {
+    TestCases::new().compile_fail("test_cases/std/fail_src/wrapper_vis.rs");
+}
@@ -0,0 +1,160 @@
+#![cfg(feature = "derive_alloc")]
+
+#[cfg(not(feature = "source_line"))]
+mod example {
+    use bare_err_tree::{tree_to_dot, tree_to_mermaid};
+
+    include!("../test_cases/std/src/bin/example.rs");
+
+    fn fatal() -> MissedClassTree {
+        MissedClass::Overslept(Overslept::new(BedTime::new(
+            2,
+            vec![
+                ClassProject::new("proving 1 == 2".to_string()).into(),
+                BedTimeReasons::ExamStressed,
+                BedTimeReasons::PlayingGames,
+            ],
+        )))
+        .into()
+    }
+
+    #[test]
+    fn readme_dot() {
+        let mut out = String::new();
+        tree_to_dot(&fatal(), &mut out).unwrap();
+        assert_eq!(
+            out,
+            r#"digraph {
+    n0 [label="missed class"];
+    n1 [label="stayed in bed too long"];
+    n0 -> n1;
+    n2 [label="bed is comfortable"];
+    n1 -> n2;
+    n3 [label="went to sleep at 2 A.M."];
+    n1 -> n3;
+    n4 [label="finishing a project"];
+    n3 -> n4;
+    n5 [label="proving 1 == 2"];
+    n4 -> n5;
+    n6 [label="stressed about exams"];
+    n3 -> n6;
+    n7 [label="playing video games"];
+    n3 -> n7;
+}"#
+        );
+    }
+
+    #[test]
+    fn readme_mermaid() {
+        let mut out = String::new();
+        tree_to_mermaid(&fatal(), &mut out).unwrap();
+        assert_eq!(
+            out,
+            r#"graph TD
+    n0["missed class"]
+    n1["stayed in bed too long"]
+    n0 --> n1
+    n2["bed is comfortable"]
+    n1 --> n2
+    n3["went to sleep at 2 A.M."]
+    n1 --> n3
+    n4["finishing a project"]
+    n3 --> n4
+    n5["proving 1 == 2"]
+    n4 --> n5
+    n6["stressed about exams"]
+    n3 --> n6
+    n7["playing video games"]
+    n3 --> n7"#
+        );
+    }
+}
+
+mod escaping {
+    #[cfg(not(feature = "source_line"))]
+    use bare_err_tree::{tree_to_dot, tree_to_dot_with, tree_to_mermaid_with};
+
+    use bare_err_tree::err_tree;
+    use thiserror::Error;
+
+    #[err_tree]
+    #[derive(Debug, Error)]
+    #[error("{msg}")]
+    struct Msg {
+        msg: &'static str,
+        #[tree_iter_err]
+        children: Vec<Msg>,
+    }
+
+    fn tree() -> Msg {
+        Msg::_tree(
+            r#"read "a\b" [#1] <cfg>"#,
+            vec![Msg::_tree("first line\nsecond \"line\"", vec![])],
+        )
+    }
+
+    /// Node labels, in output order.
+    #[cfg(not(feature = "source_line"))]
+    fn labels<'a>(out: &'a str, start: &str, end: &str) -> Vec<&'a str> {
+        out.lines()
+            .filter_map(|line| line.split_once(start))
+            .filter_map(|(_, rest)| rest.rsplit_once(end))
+            .map(|(label, _)| label)
+            .collect()
+    }
+
+    #[cfg(not(feature = "source_line"))]
+    #[test]
+    fn dot() {
+        let mut out = String::new();
+        tree_to_dot(&tree(), &mut out).unwrap();
+        assert_eq!(
+            labels(&out, "[label=\"", "\""),
+            [r#"read \"a\\b\" [#1] <cfg>"#, "first line"]
+        );
+    }
+
+    #[cfg(not(feature = "source_line"))]
+    #[test]
+    fn mermaid() {
+        let mut out = String::new();
+        tree_to_mermaid_with(&tree(), 80, &mut out).unwrap();
+        assert_eq!(
+            labels(&out, "[\"", "\"]"),
+            [
+                "read #quot;a\\b#quot; #91;#35;1#93; #lt;cfg#gt;",
+                "first line"
+            ]
+        );
+    }
+
+    #[cfg(not(feature = "source_line"))]
+    #[test]
+    fn label_max() {
+        let mut out = String::new();
+        tree_to_dot_with(&Msg::_tree("ünïcödé message", vec![]), 6, &mut out).unwrap();
+        assert_eq!(labels(&out, "[label=\"", "\""), ["ünïcö…"]);
+
+        let mut out = String::new();
+        tree_to_mermaid_with(&Msg::_tree("exactly", vec![]), 7, &mut out).unwrap();
+        assert_eq!(labels(&out, "[\"", "\"]"), ["exactly"]);
+
+        // Escapes apply after cutting, so they never split
+        let mut out = String::new();
+        tree_to_dot_with(&Msg::_tree("ab\"cd", vec![]), 4, &mut out).unwrap();
+        assert_eq!(labels(&out, "[label=\"", "\""), ["ab\\\"…"]);
+    }
+
+    #[cfg(feature = "source_line")]
+    #[test]
+    fn location() {
+        let mut out = String::new();
+        bare_err_tree::tree_to_dot(&tree(), &mut out).unwrap();
+        assert_eq!(out.matches(", tooltip=\"").count(), 2, "{out}");
+        assert!(out.contains("tests/diagram.rs:"), "{out}");
+
+        let mut out = String::new();
+        bare_err_tree::tree_to_mermaid(&tree(), &mut out).unwrap();
+        assert_eq!(out.matches("<br/>at ").count(), 2, "{out}");
+    }
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "derive")]
+
+use std::{error::Error, io};
+
+use bare_err_tree::{err_tree, AsErrTree, ErrTree};
+
+#[derive(Debug, thiserror::Error)]
+#[error("request failed")]
+struct Request(#[source] io::Error);
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("sync failed")]
+struct Sync {
+    #[dyn_err]
+    request: Request,
+}
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("job failed")]
+struct Job {
+    #[tree_err]
+    sync: Sync,
+}
+
+#[err_tree(StageWrap)]
+#[derive(Debug, thiserror::Error)]
+enum Stage {
+    #[error("stage failed")]
+    #[tree_err]
+    Run(Job),
+}
+
+fn job(kind: io::ErrorKind) -> Job {
+    Job::_tree(Sync::_tree(Request(kind.into())))
+}
+
+/// Reads the first error in `tree`, depth first, that downcasts to `T`.
+fn find<T: Error + 'static, R>(tree: ErrTree<'_>, read: &impl Fn(&T) -> R) -> Option<R> {
+    if let Some(found) = tree.downcast_ref::<T>() {
+        return Some(read(found));
+    }
+    let mut found = None;
+    for source in tree.sources() {
+        source.as_err_tree(&mut |tree| found = find(tree, read));
+        if found.is_some() {
+            break;
+        }
+    }
+    found
+}
+
+#[test]
+fn nested_io_error() {
+    let mut kind = None;
+    job(io::ErrorKind::TimedOut).as_err_tree(&mut |tree| kind = find(tree, &io::Error::kind));
+    assert_eq!(kind, Some(io::ErrorKind::TimedOut));
+}
+
+#[test]
+fn wrong_type() {
+    job(io::ErrorKind::TimedOut).as_err_tree(&mut |tree| {
+        assert!(tree.downcast_ref::<io::Error>().is_none());
+        assert!(find(tree, &|_: &std::fmt::Error| ()).is_none());
+    });
+}
+
+#[test]
+fn err_tree_types() {
+    let stage: StageWrap = Stage::Run(job(io::ErrorKind::TimedOut)).into();
+    stage.as_err_tree(&mut |tree| {
+        assert!(matches!(tree.downcast_ref::<Stage>(), Some(Stage::Run(_))));
+    });
+    stage.as_err_tree(&mut |tree| assert!(find(tree, &|_: &Job| ()).is_some()));
+
+    let mut kind = None;
+    stage.as_err_tree(&mut |tree| kind = find(tree, &|sync: &Sync| sync.request.0.kind()));
+    assert_eq!(kind, Some(io::ErrorKind::TimedOut));
+}
+
+#[test]
+fn downcastable_manual() {
+    #[derive(Debug, thiserror::Error)]
+    #[error("leaf")]
+    struct Leaf(u8);
+
+    impl AsErrTree for Leaf {
+        fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+            (func)(ErrTree::no_pkg(self, &mut core::iter::empty()).downcastable(self))
+        }
+    }
+
+    Leaf(7).as_err_tree(&mut |tree| {
+        assert_eq!(tree.downcast_ref::<Leaf>().map(|leaf| leaf.0), Some(7));
+    });
+}
@@ -0,0 +1,91 @@
+#![cfg(feature = "derive")]
+
+use core::fmt::{self, Display, Formatter};
+use std::io;
+
+use bare_err_tree::{err_tree, front_max_for_depth, print_tree};
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+#[err_tree(code = "E0142")]
+#[derive(Debug)]
+struct NotFound {
+    #[dyn_err]
+    io: io::Error,
+}
+
+impl core::error::Error for NotFound {}
+impl Display for NotFound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "config not found")
+    }
+}
+
+#[err_tree(LoadWrap, code = "E0200")]
+#[derive(Debug, thiserror::Error)]
+enum Load {
+    #[error("failed to load")]
+    #[tree_err]
+    Missing(NotFound),
+}
+
+fn load() -> LoadWrap {
+    Load::Missing(NotFound::_tree(io::ErrorKind::NotFound.into())).into()
+}
+
+#[test]
+fn associated_const() {
+    assert_eq!(NotFound::ERR_CODE, "E0142");
+    assert_eq!(Load::ERR_CODE, "E0200");
+}
+
+#[cfg(not(any(
+    feature = "source_line",
+    feature = "tracing",
+    feature = "timestamp",
+    feature = "type_name"
+)))]
+#[test]
+fn rendered_before_msg() {
+    let mut out = String::new();
+    print_tree::<FRONT_MAX, _, _>(load(), &mut out).unwrap();
+    assert_eq!(
+        out,
+        "[E0200] failed to load
+│
+╰─▶ [E0142] config not found
+    │
+    ╰─▶ entity not found"
+    );
+
+    let mut numbered = String::new();
+    bare_err_tree::print_tree_numbered::<FRONT_MAX, _, _>(load(), &mut numbered).unwrap();
+    assert!(
+        numbered.starts_with("1 [E0200] failed to load"),
+        "{numbered}"
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_round_trip() {
+    let json = bare_err_tree::tree_to_json_string::<LoadWrap, _>(load()).unwrap();
+    assert!(
+        json.starts_with(r#"{"v":1,"msg":"failed to load","code":"E0200""#),
+        "{json}"
+    );
+    assert_eq!(bare_err_tree::json_msg(&json).to_string(), "failed to load");
+
+    let mut rendered = String::new();
+    print_tree::<FRONT_MAX, _, _>(load(), &mut rendered).unwrap();
+    assert_eq!(
+        bare_err_tree::reconstruct_to_string::<FRONT_MAX, _>(&json).unwrap(),
+        rendered
+    );
+}
+
+#[cfg(feature = "code_registry")]
+#[test]
+fn unique_codes() {
+    bare_err_tree::assert_unique_err_codes!();
+}
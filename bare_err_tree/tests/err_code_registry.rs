@@ -0,0 +1,52 @@
+#![cfg(feature = "code_registry")]
+
+use core::fmt::{self, Display, Formatter};
+
+use bare_err_tree::{assert_unique_err_codes, err_tree, ERR_CODES};
+
+#[err_tree(code = "E0142")]
+#[derive(Debug)]
+struct NotFound(u8);
+
+impl core::error::Error for NotFound {}
+impl Display for NotFound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} not found", self.0)
+    }
+}
+
+mod planted {
+    use super::*;
+
+    #[err_tree(code = "E0142")]
+    #[derive(Debug)]
+    pub struct Planted(u8);
+
+    impl core::error::Error for Planted {}
+    impl Display for Planted {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "planted {}", self.0)
+        }
+    }
+}
+
+#[test]
+fn registered() {
+    let mut codes: Vec<_> = ERR_CODES.iter().copied().collect();
+    codes.sort();
+    assert_eq!(
+        codes,
+        [
+            ("err_code_registry::NotFound", "E0142"),
+            ("err_code_registry::planted::Planted", "E0142"),
+        ]
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "error code `E0142` is used by both `err_code_registry::NotFound` and `err_code_registry::planted::Planted`"
+)]
+fn duplicate_code() {
+    assert_unique_err_codes!();
+}
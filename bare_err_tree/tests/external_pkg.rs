@@ -0,0 +1,126 @@
+#![cfg(all(feature = "external_pkg", feature = "derive"))]
+
+use core::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    mem::{offset_of, size_of, ManuallyDrop},
+};
+
+use bare_err_tree::{err_tree, front_max_for_depth, print_tree, ErrTreePkg};
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+#[derive(Debug)]
+struct Leaf;
+
+impl Error for Leaf {}
+impl Display for Leaf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "leaf")
+    }
+}
+
+#[err_tree(external_pkg)]
+#[derive(Debug)]
+#[repr(C)]
+struct Named {
+    code: u8,
+    value: u64,
+    #[dyn_err]
+    leaf: Leaf,
+}
+
+/// [`Named`] as C sees it.
+#[repr(C)]
+struct NamedC {
+    code: u8,
+    value: u64,
+    leaf: Leaf,
+}
+
+#[err_tree(external_pkg)]
+#[derive(Debug)]
+struct Tuple(u32, #[tree_err] Named);
+
+impl Error for Named {}
+impl Display for Named {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "named {}", self.code)
+    }
+}
+
+impl Error for Tuple {}
+impl Display for Tuple {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "tuple {}", self.0)
+    }
+}
+
+fn render(err: &impl bare_err_tree::AsErrTree) -> String {
+    let mut out = String::new();
+    print_tree::<FRONT_MAX, _, _>(err, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn layout_untouched() {
+    assert_eq!(size_of::<Named>(), size_of::<NamedC>());
+    assert_eq!(offset_of!(Named, code), offset_of!(NamedC, code));
+    assert_eq!(offset_of!(Named, value), offset_of!(NamedC, value));
+    assert_eq!(offset_of!(Named, leaf), offset_of!(NamedC, leaf));
+    assert_eq!(size_of::<Tuple>(), size_of::<(u32, Named)>());
+}
+
+#[test]
+fn attached() {
+    let err = Box::new(Tuple::_tree(1, Named::_tree(2, 3, Leaf)));
+    ErrTreePkg::new().attach(&*err);
+    let line = line!() - 1;
+    ErrTreePkg::new().attach(&err.1);
+
+    assert!(ErrTreePkg::attached(&*err).is_some());
+    // The field shares an address with its parent, but not a type
+    assert!(ErrTreePkg::attached(&err.1).is_some());
+
+    let out = render(&*err);
+    assert!(out.starts_with("tuple 1"), "{out}");
+    assert!(out.contains("named 2"), "{out}");
+    assert!(out.contains("leaf"), "{out}");
+    #[cfg(feature = "source_line")]
+    assert!(
+        out.contains(&format!("tests/external_pkg.rs:{line}:")),
+        "{out}"
+    );
+    #[cfg(not(feature = "source_line"))]
+    let _ = line;
+}
+
+#[cfg(not(any(
+    feature = "source_line",
+    feature = "tracing",
+    feature = "timestamp",
+    feature = "type_name"
+)))]
+#[test]
+fn unattached() {
+    let err = Tuple::_tree(1, Named::_tree(2, 3, Leaf));
+    assert_eq!(
+        render(&err),
+        "tuple 1
+│
+╰─▶ named 2
+    │
+    ╰─▶ leaf"
+    );
+}
+
+#[test]
+fn detached_on_drop() {
+    let mut err = ManuallyDrop::new(Named::_tree(2, 3, Leaf));
+    ErrTreePkg::new().attach(&*err);
+    assert!(ErrTreePkg::attached(&*err).is_some());
+
+    // SAFETY: `Named` holds no resources, so it is still readable after drop
+    unsafe { ManuallyDrop::drop(&mut err) };
+    assert!(ErrTreePkg::attached(&*err).is_none());
+}
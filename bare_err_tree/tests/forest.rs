@@ -0,0 +1,73 @@
+#![cfg(all(
+    feature = "derive",
+    not(feature = "unix_color"),
+    not(feature = "type_name")
+))]
+
+use std::io;
+
+use bare_err_tree::{err_tree, front_max_for_depth, print_forest, print_tree, AsErrTree};
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("{msg}")]
+struct Leaf {
+    msg: &'static str,
+    #[dyn_err]
+    io: io::Error,
+}
+
+fn leaf(msg: &'static str) -> Leaf {
+    Leaf::_tree(msg, io::ErrorKind::UnexpectedEof.into())
+}
+
+fn render(tree: &impl AsErrTree) -> String {
+    let mut out = String::new();
+    print_tree::<FRONT_MAX, _, _>(tree, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn separated_roots() {
+    let (first, second) = (leaf("first"), leaf("second"));
+
+    let mut out = String::new();
+    print_forest::<FRONT_MAX, _>(&[&first, &second], &mut out).unwrap();
+    assert_eq!(out, format!("{}\n\n{}", render(&first), render(&second)));
+    assert!(out.starts_with("first\n"), "{out}");
+}
+
+#[test]
+fn empty_and_single() {
+    let mut out = String::new();
+    print_forest::<FRONT_MAX, _>(&[], &mut out).unwrap();
+    assert_eq!(out, "");
+
+    let only = leaf("only");
+    print_forest::<FRONT_MAX, _>(&[&only], &mut out).unwrap();
+    assert_eq!(out, render(&only));
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn traces_reset_between_roots() {
+    use tracing_error::ErrorLayer;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let subscriber = tracing_subscriber::registry().with(ErrorLayer::default());
+    let (first, second) = tracing::subscriber::with_default(subscriber, || {
+        let _span = tracing::info_span!("load").entered();
+        (leaf("first"), leaf("second"))
+    });
+
+    let mut out = String::new();
+    print_forest::<FRONT_MAX, _>(&[&first, &second], &mut out).unwrap();
+    assert_eq!(
+        out.matches("tracing frame 0 => forest::load").count(),
+        2,
+        "{out}"
+    );
+    assert!(!out.contains("duplicate tracing frame"), "{out}");
+}
@@ -0,0 +1,37 @@
+#![cfg(all(
+    feature = "hook",
+    feature = "derive_alloc",
+    any(feature = "source_line", feature = "tracing", feature = "timestamp")
+))]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bare_err_tree::{clear_pkg_hook, set_pkg_hook};
+
+mod example {
+    include!("../test_cases/std/src/bin/example.rs");
+
+    pub fn build() {
+        let _ = gen_print();
+    }
+}
+
+static CAPTURES: AtomicUsize = AtomicUsize::new(0);
+
+fn count_capture(location: &'static core::panic::Location<'static>) {
+    assert!(location.file().ends_with("example.rs"));
+    CAPTURES.fetch_add(1, Ordering::Relaxed);
+}
+
+#[test]
+fn counts_captures() {
+    set_pkg_hook(count_capture);
+    example::build();
+    clear_pkg_hook();
+
+    // BedTime, Overslept, and the MissedClass wrapper
+    assert_eq!(CAPTURES.load(Ordering::Relaxed), 3);
+
+    example::build();
+    assert_eq!(CAPTURES.load(Ordering::Relaxed), 3);
+}
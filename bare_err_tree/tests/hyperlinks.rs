@@ -0,0 +1,20 @@
+#![cfg(all(
+    not(feature = "tracing"),
+    feature = "derive",
+    feature = "source_line",
+    feature = "hyperlinks",
+    not(feature = "unix_color")
+))]
+
+mod near_empty {
+    include!("../test_cases/std/src/bin/near-empty.rs");
+
+    #[test]
+    fn near_empty() {
+        let expected_lines = "EMPTY
+╰─ at \x1b]8;;file://bare_err_tree/tests/../test_cases/std/src/bin/near-empty.rs:17\x1b\\\
+bare_err_tree/tests/../test_cases/std/src/bin/near-empty.rs:17:17\x1b]8;;\x1b\\";
+
+        assert_eq!(gen_print(), expected_lines);
+    }
+}
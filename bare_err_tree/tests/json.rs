@@ -1,9 +1,11 @@
 #![cfg(all(
     feature = "tracing",
-    feature = "derive",
+    feature = "derive_alloc",
     feature = "source_line",
     feature = "json",
-    not(feature = "unix_color")
+    not(feature = "unix_color"),
+    not(feature = "hyperlinks"),
+    not(feature = "timestamp")
 ))]
 
 mod example {
@@ -11,9 +13,14 @@ mod example {
 
     #[test]
     fn readme_example() {
-        let expected_json = "{\"msg\":\"missed class\",\"location\":\"bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs:51:6\",\"trace\":[{\"target\":\"json::example\",\"name\":\"gen_print_inner\",\"fields\":\"\",\"source_loc\":[\"file\":\"bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs\",\"line\":38]}],\"sources\":[{\"msg\":\"stayed in bed too long\",\"location\":\"bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs:40:57\",\"trace\":[{\"target\":\"json::example\",\"name\":\"new\",\"fields\":\"bed_time=BedTime { hour: 2, reasons: [FinishingProject(ClassProject { desc: \\\"proving 1 == 2\\\" }), ExamStressed, PlayingGames] } _garbage=5\",\"source_loc\":[\"file\":\"bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs\",\"line\":130]},{\"target\":\"json::example\",\"name\":\"gen_print_inner\",\"fields\":\"\",\"source_loc\":[\"file\":\"bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs\",\"line\":38]}],\"sources\":[{\"msg\":\"bed is comfortable\"},{\"msg\":\"went to sleep at 2 A.M.\",\"location\":\"bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs:41:9\",\"trace\":[{\"target\":\"json::example\",\"name\":\"gen_print_inner\",\"fields\":\"\",\"source_loc\":[\"file\":\"bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs\",\"line\":38]}],\"sources\":[{\"msg\":\"finishing a project\",\"sources\":[{\"msg\":\"proving 1 == 2\"}]},{\"msg\":\"stressed about exams\"},{\"msg\":\"playing video games\"}]}]}]}";
+        let expected_json = "{\"v\":1,\"msg\":\"missed class\",\"location\":\"bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs:51:6\",\"trace\":[{\"target\":\"json::example\",\"name\":\"gen_print_inner\",\"fields\":\"\",\"source_loc\":{\"file\":\"bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs\",\"line\":38}}],\"sources\":[{\"msg\":\"stayed in bed too long\",\"location\":\"bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs:40:57\",\"trace\":[{\"target\":\"json::example\",\"name\":\"new\",\"fields\":\"bed_time=BedTime { hour: 2, reasons: [FinishingProject(ClassProject { desc: \\\"proving 1 == 2\\\" }), ExamStressed, PlayingGames] } _garbage=5\",\"source_loc\":{\"file\":\"bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs\",\"line\":130}},{\"target\":\"json::example\",\"name\":\"gen_print_inner\",\"fields\":\"\",\"source_loc\":{\"file\":\"bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs\",\"line\":38}}],\"sources\":[{\"msg\":\"bed is comfortable\"},{\"msg\":\"went to sleep at 2 A.M.\",\"location\":\"bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs:41:9\",\"trace\":[{\"target\":\"json::example\",\"name\":\"gen_print_inner\",\"fields\":\"\",\"source_loc\":{\"file\":\"bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs\",\"line\":38}}],\"sources\":[{\"msg\":\"finishing a project\",\"sources\":[{\"msg\":\"proving 1 == 2\"}]},{\"msg\":\"stressed about exams\"},{\"msg\":\"playing video games\"}]}]}]}";
 
-        let expected_lines = r#"missed class
+        assert_eq!(gen_print(), expected_json);
+
+        assert_eq!(reconstruct(&gen_print()), EXPECTED_LINES);
+    }
+
+    const EXPECTED_LINES: &str = r#"missed class
 ├─ at bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs:51:6
 │
 ├─ tracing frame 0 => json::example::gen_print_inner
@@ -54,16 +61,174 @@ mod example {
         │
         ╰─▶ playing video games"#;
 
-        assert_eq!(gen_print(), expected_json);
+    #[test]
+    fn readme_subtree() {
+        use bare_err_tree::{reconstruct_output_at, source_count};
+
+        let json = gen_print();
+        assert_eq!(source_count(&json, &[]), Some(1));
+        assert_eq!(source_count(&json, &[0]), Some(2));
+        assert_eq!(source_count(&json, &[0, 1]), Some(3));
+        assert_eq!(source_count(&json, &[0, 1, 0, 0]), Some(0));
+        assert_eq!(source_count(&json, &[0, 2]), None);
+
+        let mut out = String::new();
+        reconstruct_output_at::<60, _, _>(&json, &[0, 1], &mut out).unwrap();
+
+        // "went to sleep" onwards, up two levels. Its frame is no longer a
+        // duplicate of the root's.
+        let below = EXPECTED_LINES
+            .lines()
+            .skip_while(|line| !line.ends_with("went to sleep at 2 A.M."))
+            .skip(1);
+        let fragment: Vec<_> = ["went to sleep at 2 A.M."]
+            .into_iter()
+            .chain(below.map(|line| &line[8..]))
+            .collect();
+        let fragment = fragment.join("\n").replace(
+            "├─ 1 duplicate tracing frame(s): [0]",
+            "├─ tracing frame 0 => json::example::gen_print_inner
+│        at bare_err_tree/tests/../test_cases/json/src/bin/reconstruct.rs:38",
+        );
+        assert_eq!(out, fragment);
+
+        let mut out = String::new();
+        assert!(reconstruct_output_at::<60, _, _>(&json, &[0, 2], &mut out).is_err());
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn standard_json() {
+        let json = gen_print();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        let trace = &parsed["sources"][0]["trace"][0];
+        assert_eq!(trace["name"], "new");
+        assert_eq!(trace["source_loc"]["line"], 130);
+        assert!(trace["source_loc"]["file"]
+            .as_str()
+            .unwrap()
+            .ends_with("reconstruct.rs"));
+    }
+
+    #[test]
+    fn legacy_source_loc() {
+        let json = gen_print();
+        // Older versions wrote the location pairs inside an array
+        let legacy = json
+            .replace("\"source_loc\":{", "\"source_loc\":[")
+            .replace("}}", "]}");
+        assert_ne!(legacy, json);
+        assert!(serde_json::from_str::<serde_json::Value>(&legacy).is_err());
+
+        assert_eq!(reconstruct(&legacy), reconstruct(&json));
+    }
+
+    #[test]
+    fn readme_spans() {
+        use bare_err_tree::reconstruct_output_with_spans;
+
+        let json = gen_print();
+        let mut out = String::new();
+        let mut spans = Vec::new();
+        reconstruct_output_with_spans::<60, _, _>(&json, &mut out, |span| spans.push(span))
+            .unwrap();
 
-        assert_eq!(reconstruct(&gen_print()), expected_lines);
+        let lines: Vec<_> = out.lines().collect();
+        let msgs: Vec<_> = spans
+            .iter()
+            .map(|span| {
+                let node = &json[span.byte_range.clone()];
+                assert!(node.ends_with('}'));
+                // Only the root is versioned
+                let node = node.replacen("{\"v\":1,", "{", (span.depth == 0).into());
+
+                // The message follows the glyphs, except on the root line
+                let line = lines[span.first_output_line];
+                let msg = line.split_once("▶ ").map_or(line, |(_, msg)| msg);
+                assert!(
+                    node.starts_with(&format!("{{\"msg\":\"{msg}\"")),
+                    "{node} does not start with {msg}"
+                );
+                (msg, span.depth)
+            })
+            .collect();
+
+        assert_eq!(
+            msgs,
+            [
+                ("missed class", 0),
+                ("stayed in bed too long", 1),
+                ("bed is comfortable", 2),
+                ("went to sleep at 2 A.M.", 2),
+                ("finishing a project", 3),
+                ("proving 1 == 2", 4),
+                ("stressed about exams", 3),
+                ("playing video games", 3),
+            ]
+        );
+    }
+}
+
+mod to_string {
+    use core::error::Error;
+
+    use bare_err_tree::{
+        front_max_for_depth, reconstruct_output, reconstruct_to_string, tree_to_json,
+        tree_to_json_string,
+    };
+    use thiserror::Error;
+
+    #[derive(Debug, Error)]
+    #[error("outer")]
+    struct Outer(#[source] std::io::Error);
+
+    const FRONT_MAX: usize = front_max_for_depth(10);
+
+    #[test]
+    fn matches_buffered() {
+        let err = Outer(std::io::ErrorKind::UnexpectedEof.into());
+        let err = &err as &dyn Error;
+
+        let mut json = String::new();
+        tree_to_json::<&dyn Error, _, _>(err, &mut json).unwrap();
+        assert_eq!(tree_to_json_string::<&dyn Error, _>(err).unwrap(), json);
+
+        let mut reconstructed = String::new();
+        reconstruct_output::<FRONT_MAX, _, _>(&json, &mut reconstructed).unwrap();
+        assert_eq!(
+            reconstruct_to_string::<FRONT_MAX, _>(&json).unwrap(),
+            reconstructed
+        );
+    }
+
+    #[derive(Debug, Error)]
+    #[error("{0}")]
+    struct Rendered(String);
+
+    #[test]
+    fn rendered_message_round_trip() {
+        let err = Outer(std::io::ErrorKind::UnexpectedEof.into());
+        let mut rendered = String::new();
+        bare_err_tree::print_tree::<FRONT_MAX, _, _>(&err as &dyn Error, &mut rendered).unwrap();
+
+        let mut wrapped = Rendered(rendered);
+        for _ in 0..3 {
+            let json = tree_to_json_string::<&dyn Error, _>(&wrapped as &dyn Error).unwrap();
+            let reconstructed = reconstruct_to_string::<FRONT_MAX, _>(&json).unwrap();
+            assert!(!reconstructed.contains("│ │ │"), "{reconstructed}");
+            wrapped = Rendered(reconstructed);
+        }
+        assert_eq!(wrapped.0, "outer\n│ │\n│ ╰─▶ unexpected end of file");
     }
 }
 
 mod json_escapes {
     use core::error::Error;
 
-    use bare_err_tree::{reconstruct_output, tree_to_json};
+    use bare_err_tree::{
+        json_msg, reconstruct_output, reconstruct_output_with_spans, tree_to_json,
+    };
     use thiserror::Error;
 
     #[derive(Debug, Error)]
@@ -78,7 +243,7 @@ bar"
         let mut out = String::new();
         tree_to_json::<&dyn Error, _, _>((&WeirdError) as &dyn Error, &mut out).unwrap();
 
-        let expected_json = r#"{"msg":"foo\n \\ \\n \t/\nbar"}"#;
+        let expected_json = r#"{"v":1,"msg":"foo\n \\ \\n \t/\nbar"}"#;
         let expected_reconstruct = "foo\n│  \\ \\n \t/\n│ bar";
 
         assert_eq!(out, expected_json);
@@ -87,4 +252,299 @@ bar"
         reconstruct_output::<60, _, _>(out, &mut reconstructed).unwrap();
         assert_eq!(reconstructed, expected_reconstruct);
     }
+
+    #[derive(Debug, Error)]
+    #[error("outer\r\n  \"quoted\"\n")]
+    struct Outer(#[source] WeirdError);
+
+    #[test]
+    fn raw_msg_exact() {
+        let err = Outer(WeirdError);
+        let mut json = String::new();
+        tree_to_json::<&dyn Error, _, _>(&err as &dyn Error, &mut json).unwrap();
+
+        assert_eq!(json_msg(&json).to_string(), err.to_string());
+        assert!(json_msg(&json).chars().eq(err.to_string().chars()));
+
+        let mut spans = Vec::new();
+        reconstruct_output_with_spans::<60, _, _>(&json, &mut String::new(), |span| {
+            spans.push(span)
+        })
+        .unwrap();
+        let source_msg = json_msg(&json[spans[1].byte_range.clone()]);
+        assert_eq!(source_msg.to_string(), WeirdError.to_string());
+    }
+
+    #[derive(Debug, Error)]
+    #[error("{0}")]
+    struct Owned(String);
+
+    /// Counts calls into the underlying writer.
+    struct CountingWriter {
+        out: String,
+        calls: usize,
+    }
+
+    impl core::fmt::Write for CountingWriter {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            self.calls += 1;
+            self.out.push_str(s);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn long_runs_batched() {
+        let run = "x".repeat(4096);
+        let msg = format!("{run}\"{run}\\\t\n{run}\u{8}\u{c}\r{run}");
+
+        let mut out = CountingWriter {
+            out: String::new(),
+            calls: 0,
+        };
+        tree_to_json::<&dyn Error, _, _>((&Owned(msg.clone())) as &dyn Error, &mut out).unwrap();
+
+        let expected_json = format!(
+            "{{\"v\":1,\"msg\":{}}}",
+            serde_json::to_string(&msg).unwrap()
+        );
+        assert_eq!(out.out, expected_json);
+        assert!(out.calls < 32, "{} write calls", out.calls);
+    }
+}
+
+mod display_errors {
+    use core::{
+        error::Error,
+        fmt::{self, Display, Formatter},
+    };
+
+    use bare_err_tree::{reconstruct_output, tree_to_json, tree_to_json_probed};
+
+    #[derive(Debug)]
+    struct FailingDisplay;
+
+    impl Error for FailingDisplay {}
+    impl Display for FailingDisplay {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.write_str("partial \"message\"")?;
+            Err(fmt::Error)
+        }
+    }
+
+    #[derive(Debug)]
+    struct Parent(FailingDisplay);
+
+    impl Error for Parent {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+    impl Display for Parent {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "parent")
+        }
+    }
+
+    #[test]
+    fn root_display_error() {
+        let mut out = String::new();
+        tree_to_json::<&dyn Error, _, _>((&FailingDisplay) as &dyn Error, &mut out).unwrap();
+
+        assert_eq!(out, r#"{"v":1,"msg":"<display error>"}"#);
+
+        let mut reconstructed = String::new();
+        reconstruct_output::<60, _, _>(out, &mut reconstructed).unwrap();
+        assert_eq!(reconstructed, "<display error>");
+    }
+
+    #[test]
+    fn nested_display_error() {
+        let mut out = String::new();
+        tree_to_json::<&dyn Error, _, _>((&Parent(FailingDisplay)) as &dyn Error, &mut out)
+            .unwrap();
+
+        assert_eq!(
+            out,
+            r#"{"v":1,"msg":"parent","sources":[{"msg":"<display error>"}]}"#
+        );
+
+        let mut reconstructed = String::new();
+        reconstruct_output::<60, _, _>(out, &mut reconstructed).unwrap();
+        assert_eq!(reconstructed, "parent\n│\n╰─▶ <display error>");
+    }
+
+    #[test]
+    fn overflowed_probe_errors() {
+        let mut out = String::new();
+        assert!(tree_to_json_probed::<4, &dyn Error, _, _>(
+            (&FailingDisplay) as &dyn Error,
+            &mut out
+        )
+        .is_err());
+    }
+}
+
+mod collapsed {
+    use core::error::Error;
+
+    use bare_err_tree::{
+        front_max_for_depth, print_tree, reconstruct_output, tree_to_json, AsErrTree, Collapse,
+    };
+    use thiserror::Error;
+
+    #[derive(Debug, Error)]
+    #[error("outer")]
+    struct Outer(#[source] Inner);
+
+    #[derive(Debug, Error)]
+    #[error("inner")]
+    struct Inner(#[source] std::io::Error);
+
+    #[test]
+    fn round_trip() {
+        let err = Outer(Inner(std::io::ErrorKind::UnexpectedEof.into()));
+        let err = &err as &dyn Error;
+        let collapsed = Collapse::tree(&err);
+
+        let mut out = String::new();
+        tree_to_json::<&dyn AsErrTree, _, _>(collapsed, &mut out).unwrap();
+        assert_eq!(
+            out,
+            r#"{"v":1,"msg":"outer","collapsed":true,"sources":[{"msg":"inner","sources":[{"msg":"unexpected end of file"}]}]}"#
+        );
+
+        let mut printed = String::new();
+        print_tree::<{ front_max_for_depth(10) }, _, _>(collapsed, &mut printed).unwrap();
+        assert_eq!(printed, "outer\n│\n╰─ 2 nested error(s) collapsed");
+
+        let mut reconstructed = String::new();
+        reconstruct_output::<{ front_max_for_depth(10) }, _, _>(out, &mut reconstructed).unwrap();
+        assert_eq!(reconstructed, printed);
+    }
+}
+
+mod multi_root {
+    use core::error::Error;
+
+    use bare_err_tree::{
+        front_max_for_depth, print_tree, reconstruct_all, trees_to_json, AsErrTree,
+    };
+    use thiserror::Error;
+
+    #[derive(Debug, Error)]
+    #[error("batch item {0}")]
+    struct Item(usize, #[source] std::io::Error);
+
+    const FRONT_MAX: usize = front_max_for_depth(10);
+
+    #[test]
+    fn round_trip() {
+        let items: Vec<_> = (0..3)
+            .map(|idx| Item(idx, std::io::ErrorKind::UnexpectedEof.into()))
+            .collect();
+        let trees: Vec<_> = items.iter().map(|item| item as &dyn Error).collect();
+
+        let mut json = String::new();
+        trees_to_json(trees.iter().map(|tree| tree as &dyn AsErrTree), &mut json).unwrap();
+        assert!(json.starts_with("[{\"v\":1,\"msg\":\"batch item 0\""));
+        assert!(json.ends_with("}]"));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json)
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .len(),
+            3
+        );
+
+        let expected = trees
+            .iter()
+            .map(|tree| {
+                let mut out = String::new();
+                print_tree::<FRONT_MAX, _, _>(tree, &mut out).unwrap();
+                out
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        let mut reconstructed = String::new();
+        reconstruct_all::<FRONT_MAX, _, _>(&json, &mut reconstructed).unwrap();
+        assert_eq!(reconstructed, expected);
+    }
+
+    #[test]
+    fn empty() {
+        let mut json = String::new();
+        trees_to_json(core::iter::empty(), &mut json).unwrap();
+        assert_eq!(json, "[]");
+
+        let mut reconstructed = String::new();
+        reconstruct_all::<FRONT_MAX, _, _>(&json, &mut reconstructed).unwrap();
+        assert_eq!(reconstructed, "");
+
+        reconstruct_all::<FRONT_MAX, _, _>(" [ ] ", &mut reconstructed).unwrap();
+        assert_eq!(reconstructed, "");
+    }
+}
+
+mod version {
+    use core::error::Error;
+
+    use bare_err_tree::{
+        json_version, reconstruct_all, reconstruct_output, reconstruct_output_with_spans,
+        tree_to_json_string, JSON_VERSION,
+    };
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("outer")]
+    struct Outer(#[source] std::io::Error);
+
+    fn json() -> String {
+        let err = Outer(std::io::ErrorKind::UnexpectedEof.into());
+        tree_to_json_string::<dyn Error, _>(&err as &dyn Error).unwrap()
+    }
+
+    fn reconstruct(json: &str) -> Result<String, core::fmt::Error> {
+        let mut out = String::new();
+        reconstruct_output::<60, _, _>(json, &mut out).map(|()| out)
+    }
+
+    #[test]
+    fn root_only() {
+        let json = json();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["v"], JSON_VERSION);
+        assert!(parsed["sources"][0].get("v").is_none(), "{json}");
+        assert_eq!(json_version(&json), Some(JSON_VERSION));
+    }
+
+    #[test]
+    fn unversioned_is_v0() {
+        let json = json();
+        let v0 = json.replacen("\"v\":1,", "", 1);
+        assert_eq!(json_version(&v0), Some(0));
+        assert_eq!(reconstruct(&v0).unwrap(), reconstruct(&json).unwrap());
+    }
+
+    #[test]
+    fn rejects_unknown() {
+        let newer = json().replacen("\"v\":1,", "\"v\":2,", 1);
+        assert_eq!(json_version(&newer), Some(2));
+        assert!(reconstruct(&newer).is_err());
+
+        let malformed = json().replacen("\"v\":1,", "\"v\":\"1\",", 1);
+        assert_eq!(json_version(&malformed), None);
+        assert!(reconstruct(&malformed).is_err());
+
+        let mut out = String::new();
+        assert!(reconstruct_output_with_spans::<60, _, _>(&newer, &mut out, |_| ()).is_err());
+        assert!(out.is_empty());
+
+        // Nothing is written when any tree is unreadable
+        let all = format!("[{},{newer}]", json());
+        let mut out = String::new();
+        assert!(reconstruct_all::<60, _, _>(&all, &mut out).is_err());
+        assert!(out.is_empty());
+    }
 }
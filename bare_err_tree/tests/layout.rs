@@ -0,0 +1,92 @@
+#![cfg(feature = "derive")]
+
+use core::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    mem::{offset_of, size_of},
+};
+
+use bare_err_tree::{err_tree, ErrTreePkg};
+
+#[derive(Debug)]
+struct Leaf;
+
+impl Error for Leaf {}
+impl Display for Leaf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "leaf")
+    }
+}
+
+#[err_tree(pkg_attrs(cfg_attr(all(), doc = "Not visible to C")))]
+#[derive(Debug)]
+#[repr(C)]
+struct Named {
+    code: u8,
+    value: u64,
+    #[dyn_err]
+    leaf: Leaf,
+    flags: u16,
+}
+
+/// [`Named`] as C sees it.
+#[repr(C)]
+struct NamedC {
+    code: u8,
+    value: u64,
+    leaf: Leaf,
+    flags: u16,
+}
+
+#[err_tree(pkg_attrs(allow(unused)))]
+#[derive(Debug)]
+#[repr(C)]
+struct Tuple(u8, u32, #[dyn_err] Leaf);
+
+/// [`Tuple`] as C sees it.
+#[repr(C)]
+struct TupleC(u8, u32, Leaf);
+
+impl Error for Named {}
+impl Display for Named {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "named")
+    }
+}
+
+impl Error for Tuple {}
+impl Display for Tuple {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "tuple")
+    }
+}
+
+#[test]
+fn named_pkg_last() {
+    assert_eq!(offset_of!(Named, code), offset_of!(NamedC, code));
+    assert_eq!(offset_of!(Named, value), offset_of!(NamedC, value));
+    assert_eq!(offset_of!(Named, leaf), offset_of!(NamedC, leaf));
+    assert_eq!(offset_of!(Named, flags), offset_of!(NamedC, flags));
+
+    assert!(offset_of!(Named, _err_tree_pkg) >= offset_of!(NamedC, flags) + size_of::<u16>());
+    assert!(size_of::<Named>() >= size_of::<NamedC>() + size_of::<ErrTreePkg>());
+}
+
+#[test]
+fn tuple_pkg_last() {
+    assert_eq!(offset_of!(Tuple, 0), offset_of!(TupleC, 0));
+    assert_eq!(offset_of!(Tuple, 1), offset_of!(TupleC, 1));
+    assert_eq!(offset_of!(Tuple, 2), offset_of!(TupleC, 2));
+
+    assert!(offset_of!(Tuple, 3) >= offset_of!(TupleC, 1) + size_of::<u32>());
+    assert!(size_of::<Tuple>() >= size_of::<TupleC>() + size_of::<ErrTreePkg>());
+}
+
+#[test]
+fn constructors() {
+    let named = Named::_tree(1, 2, Leaf, 3);
+    assert_eq!((named.code, named.value, named.flags), (1, 2, 3));
+
+    let tuple = Tuple::_tree(4, 5, Leaf);
+    assert_eq!((tuple.0, tuple.1), (4, 5));
+}
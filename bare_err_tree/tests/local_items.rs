@@ -0,0 +1,63 @@
+#![cfg(all(feature = "derive", not(feature = "unix_color")))]
+#![deny(warnings)]
+
+use bare_err_tree::{err_tree, front_max_for_depth, print_tree, AsErrTree};
+
+fn render(err: impl AsErrTree) -> String {
+    let mut out = String::new();
+    print_tree::<{ front_max_for_depth(10) }, _, _>(err, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn struct_in_fn() {
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("local")]
+    struct Local {
+        #[dyn_err]
+        io: std::io::Error,
+    }
+
+    let err = Local::_tree(std::io::ErrorKind::UnexpectedEof.into());
+    assert!(render(&err).contains("unexpected end of file"));
+}
+
+#[test]
+fn unit_struct_in_fn() {
+    // A local free function does not conflict with generated items
+    fn new() -> Unit {
+        Unit::_tree()
+    }
+
+    // Generates neither `new` nor `Default` without the opt-in
+    #[err_tree]
+    #[derive(Debug, Default, thiserror::Error)]
+    #[error("unit")]
+    struct Unit;
+
+    #[err_tree(default)]
+    #[derive(Debug, thiserror::Error)]
+    #[error("defaulted unit")]
+    struct DefaultUnit;
+
+    assert!(render(new()).starts_with("unit"));
+    assert!(render(DefaultUnit::default()).starts_with("defaulted unit"));
+}
+
+#[test]
+fn enum_wrapper_in_fn() {
+    #[err_tree(LocalWrap)]
+    #[derive(Debug, thiserror::Error)]
+    enum Local {
+        #[error("leaf")]
+        Leaf,
+        #[error("io")]
+        #[dyn_err]
+        Io(std::io::Error),
+    }
+
+    let err: LocalWrap = Local::Io(std::io::ErrorKind::UnexpectedEof.into()).into();
+    assert!(render(&err).contains("unexpected end of file"));
+    assert!(render(LocalWrap::from(Local::Leaf)).starts_with("leaf"));
+}
@@ -0,0 +1,122 @@
+#![cfg(all(feature = "log_emit", feature = "derive"))]
+
+use std::sync::Mutex;
+
+use bare_err_tree::{err_tree, log_tree};
+use log::{
+    kv::{Key, Value, VisitSource},
+    Level, Log, Metadata, Record,
+};
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("root")]
+struct Root {
+    #[dyn_err]
+    first: std::io::Error,
+    #[tree_err]
+    second: Branch,
+}
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("branch")]
+struct Branch {
+    #[dyn_iter_err]
+    leaves: [std::io::Error; 2],
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Event {
+    msg: String,
+    depth: String,
+    path: String,
+    location: String,
+}
+
+struct Capture(Mutex<Vec<(Level, String, Event)>>);
+
+impl Log for Capture {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        struct Fields<'a>(&'a mut Event);
+        impl<'kvs> VisitSource<'kvs> for Fields<'_> {
+            fn visit_pair(
+                &mut self,
+                key: Key<'kvs>,
+                value: Value<'kvs>,
+            ) -> Result<(), log::kv::Error> {
+                let value = value.to_string();
+                match key.as_str() {
+                    "msg" => self.0.msg = value,
+                    "depth" => self.0.depth = value,
+                    "path" => self.0.path = value,
+                    "location" => self.0.location = value,
+                    _ => (),
+                }
+                Ok(())
+            }
+        }
+
+        let mut event = Event::default();
+        record.key_values().visit(&mut Fields(&mut event)).unwrap();
+        self.0
+            .lock()
+            .unwrap()
+            .push((record.level(), record.args().to_string(), event));
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+
+#[test]
+fn node_events() {
+    log::set_logger(&CAPTURE).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let err = Root::_tree(
+        std::io::ErrorKind::UnexpectedEof.into(),
+        Branch::_tree([
+            std::io::ErrorKind::NotFound.into(),
+            std::io::ErrorKind::TimedOut.into(),
+        ]),
+    );
+    log_tree(&err);
+
+    let events = CAPTURE.0.lock().unwrap();
+    let summary: Vec<_> = events
+        .iter()
+        .map(|(level, args, event)| {
+            assert_eq!(*level, Level::Error);
+            assert_eq!(*args, event.msg);
+            (
+                event.msg.as_str(),
+                event.depth.as_str(),
+                event.path.as_str(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        summary,
+        [
+            ("root", "0", "0"),
+            ("unexpected end of file", "1", "0.0"),
+            ("branch", "1", "0.1"),
+            ("entity not found", "2", "0.1.0"),
+            ("timed out", "2", "0.1.1"),
+        ]
+    );
+
+    #[cfg(feature = "source_line")]
+    {
+        assert!(events[0].2.location.contains("tests/log_emit.rs"));
+        assert!(events[2].2.location.contains("tests/log_emit.rs"));
+        assert_eq!(events[1].2.location, "None");
+    }
+}
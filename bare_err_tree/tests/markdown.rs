@@ -0,0 +1,97 @@
+#![cfg(all(feature = "markdown", feature = "derive_alloc"))]
+
+#[cfg(not(feature = "source_line"))]
+mod example {
+    use bare_err_tree::tree_to_markdown;
+
+    include!("../test_cases/std/src/bin/example.rs");
+
+    fn fatal() -> MissedClassTree {
+        MissedClass::Overslept(Overslept::new(BedTime::new(
+            2,
+            vec![
+                ClassProject::new("proving 1 == 2".to_string()).into(),
+                BedTimeReasons::ExamStressed,
+                BedTimeReasons::PlayingGames,
+            ],
+        )))
+        .into()
+    }
+
+    #[test]
+    fn readme_markdown() {
+        let mut out = String::new();
+        tree_to_markdown(&fatal(), &mut out).unwrap();
+        assert_eq!(
+            out,
+            "- missed class
+  - stayed in bed too long
+    - bed is comfortable
+    - went to sleep at 2 A.M.
+      - finishing a project
+        - proving 1 == 2
+      - stressed about exams
+      - playing video games"
+        );
+    }
+}
+
+mod escaping {
+    use bare_err_tree::{err_tree, tree_to_markdown};
+    use thiserror::Error;
+
+    #[err_tree]
+    #[derive(Debug, Error)]
+    #[error("{msg}")]
+    struct Msg {
+        msg: &'static str,
+        #[tree_iter_err]
+        children: Vec<Msg>,
+    }
+
+    fn render(tree: &Msg) -> String {
+        let mut out = String::new();
+        tree_to_markdown(tree, &mut out).unwrap();
+        out
+    }
+
+    #[cfg(not(feature = "source_line"))]
+    #[test]
+    fn special_chars() {
+        let tree = Msg::_tree(
+            r"*bold* _em_ `code` a\b",
+            vec![Msg::_tree("snake_case", vec![])],
+        );
+        assert_eq!(
+            render(&tree),
+            r"- \*bold\* \_em\_ \`code\` a\\b
+  - snake\_case"
+        );
+    }
+
+    #[cfg(not(feature = "source_line"))]
+    #[test]
+    fn multiline() {
+        let tree = Msg::_tree("outer", vec![Msg::_tree("first line\nsecond line", vec![])]);
+        assert_eq!(
+            render(&tree),
+            "- outer
+  - first line
+    second line"
+        );
+    }
+
+    #[cfg(feature = "source_line")]
+    #[test]
+    fn location() {
+        let out = render(&Msg::_tree("outer", vec![Msg::_tree("inner", vec![])]));
+        let mut lines = out.lines();
+
+        let root = lines.next().unwrap();
+        assert!(root.starts_with("- outer *at "), "{out}");
+        assert!(root.contains("tests/markdown.rs:"), "{out}");
+        assert!(root.ends_with('*'), "{out}");
+        assert!(lines.next().unwrap().starts_with("  - inner *at "), "{out}");
+        assert_eq!(lines.next(), None);
+    }
+}
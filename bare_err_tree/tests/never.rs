@@ -0,0 +1,55 @@
+use core::{convert::Infallible, error::Error, mem::size_of};
+
+use bare_err_tree::{front_max_for_depth, print_tree, tree_unwrap, AsErrTree, NeverTree};
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+/// Reporting code written once for any tree error.
+fn report<T, E: AsErrTree>(res: Result<T, E>, out: &mut String) -> Option<T> {
+    match res {
+        Ok(val) => Some(val),
+        Err(err) => {
+            print_tree::<FRONT_MAX, _, _>(&err, out).unwrap();
+            None
+        }
+    }
+}
+
+fn assert_error<E: Error + AsErrTree>() {}
+
+#[test]
+fn generic_report() {
+    assert_error::<NeverTree>();
+
+    let mut out = String::new();
+    assert_eq!(report(Ok::<_, NeverTree>(1), &mut out), Some(1));
+    assert_eq!(report(Ok::<_, Infallible>(2), &mut out), Some(2));
+    assert!(out.is_empty());
+
+    let err = std::io::Error::from(std::io::ErrorKind::UnexpectedEof);
+    assert_eq!(report(Err::<u8, _>(&err as &dyn Error), &mut out), None);
+    assert_eq!(out, "unexpected end of file");
+}
+
+#[test]
+fn unwrap() {
+    assert_eq!(tree_unwrap::<FRONT_MAX, _, _>(Ok::<_, NeverTree>(3)), 3);
+    assert_eq!(
+        tree_unwrap::<FRONT_MAX, _, _>(Ok::<_, NeverTree>("ok")).len(),
+        2
+    );
+}
+
+#[test]
+fn layout() {
+    assert_eq!(size_of::<NeverTree>(), 0);
+    assert_eq!(size_of::<Result<u64, NeverTree>>(), size_of::<u64>());
+    assert_eq!(size_of::<Option<NeverTree>>(), 0);
+}
+
+#[test]
+fn from_infallible() {
+    let res: Result<u8, Infallible> = Ok(4);
+    let res: Result<u8, NeverTree> = res.map_err(NeverTree::from);
+    assert_eq!(tree_unwrap::<FRONT_MAX, _, _>(res), 4);
+}
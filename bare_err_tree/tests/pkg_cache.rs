@@ -0,0 +1,54 @@
+#![cfg(all(feature = "pkg_cache", feature = "tracing"))]
+
+use core::fmt::{self, Display, Formatter};
+use std::error::Error;
+
+use bare_err_tree::{front_max_for_depth, print_tree, AsErrTree, ErrTree, ErrTreePkg};
+use tracing::info_span;
+use tracing_error::ErrorLayer;
+use tracing_subscriber::{layer::SubscriberExt, Registry};
+
+#[derive(Debug)]
+struct Traced(ErrTreePkg);
+
+impl Error for Traced {}
+impl Display for Traced {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "traced")
+    }
+}
+
+impl AsErrTree for Traced {
+    fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+        (func)(ErrTree::with_pkg(self, &mut core::iter::empty(), &self.0))
+    }
+}
+
+fn cached() -> Traced {
+    Traced(ErrTreePkg::new_cached())
+}
+
+fn print(err: &Traced) -> String {
+    let mut out = String::new();
+    print_tree::<{ front_max_for_depth(10) }, _, _>(err, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn keyed_by_span() {
+    let subscriber = Registry::default().with(ErrorLayer::default());
+    tracing::subscriber::with_default(subscriber, || {
+        let first = info_span!("first").in_scope(|| [cached(), cached()]);
+        let second = info_span!("second").in_scope(cached);
+        let outside = cached();
+
+        assert!(print(&first[0]).contains("pkg_cache::first"));
+        assert_eq!(print(&first[0]), print(&first[1]));
+
+        let second = print(&second);
+        assert!(second.contains("pkg_cache::second"));
+        assert!(!second.contains("pkg_cache::first"));
+
+        assert!(!print(&outside).contains("tracing frame"));
+    });
+}
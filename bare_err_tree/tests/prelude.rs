@@ -0,0 +1,76 @@
+#![cfg(feature = "derive_alloc")]
+
+// The fixture imports nothing from `bare_err_tree` except the prelude
+mod example {
+    include!("../test_cases/std/src/bin/example.rs");
+
+    #[test]
+    fn readme_example() {
+        let out = gen_print();
+        assert!(out.starts_with("missed class"), "{out}");
+        for msg in [
+            "stayed in bed too long",
+            "bed is comfortable",
+            "went to sleep at 2 A.M.",
+            "proving 1 == 2",
+            "playing video games",
+        ] {
+            assert!(out.contains(msg), "{out}");
+        }
+    }
+}
+
+mod manual {
+    use bare_err_tree::prelude::*;
+    use std::{
+        fmt::{self, Display, Formatter},
+        io,
+    };
+
+    #[derive(Debug)]
+    struct Manual {
+        pkg: ErrTreePkg,
+        io: io::Error,
+    }
+
+    impl std::error::Error for Manual {}
+    impl Display for Manual {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.write_str("manual")
+        }
+    }
+
+    impl AsErrTree for Manual {
+        fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+            let io = WrapErr::tree(&self.io);
+            (func)(ErrTree::with_pkg(
+                self,
+                &mut core::iter::once(io),
+                &self.pkg,
+            ))
+        }
+    }
+
+    #[test]
+    fn print_entry_points() {
+        let err = Manual {
+            pkg: ErrTreePkg::new(),
+            io: io::ErrorKind::NotFound.into(),
+        };
+
+        let mut out = String::new();
+        print_tree::<{ front_max_for_depth(2) }, _, _>(&err, &mut out).unwrap();
+        let mut default_out = String::new();
+        print_tree_default(&err, &mut default_out).unwrap();
+        assert_eq!(out, default_out);
+
+        let mut with_out = String::new();
+        print_tree_with::<DEFAULT_FRONT_MAX, _, _>(&err, FormatOptions::new(), &mut with_out)
+            .unwrap();
+        assert_eq!(out, with_out);
+        assert!(out.contains("─▶ entity not found"), "{out}");
+
+        let ok: Result<u8, Manual> = Ok(1);
+        assert_eq!(tree_unwrap_default(ok), 1);
+    }
+}
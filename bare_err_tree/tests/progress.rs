@@ -0,0 +1,142 @@
+#![cfg(feature = "derive_alloc")]
+
+use core::ops::ControlFlow;
+
+use bare_err_tree::{
+    err_tree, front_max_for_depth, print_tree, print_tree_cancellable, print_tree_cancellable_with,
+    print_tree_with, FormatOptions, Progress,
+};
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("{msg}")]
+struct Node {
+    msg: String,
+    #[tree_iter_err]
+    children: Vec<Node>,
+}
+
+/// `width` children per node, `depth` levels below the root.
+fn wide(depth: usize, width: usize, name: String) -> Node {
+    let children = if depth == 0 {
+        Vec::new()
+    } else {
+        (0..width)
+            .map(|idx| wide(depth - 1, width, format!("{name}.{idx}")))
+            .collect()
+    };
+    Node::_tree(format!("{name}\nsecond line"), children)
+}
+
+fn full(tree: &Node) -> String {
+    let mut out = String::new();
+    print_tree::<FRONT_MAX, _, _>(tree, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn completes_without_break() {
+    let tree = wide(3, 10, "root".to_string());
+
+    let mut reports = Vec::new();
+    let mut out = String::new();
+    print_tree_cancellable::<FRONT_MAX, _, _>(&tree, &mut out, &mut |progress| {
+        reports.push(progress);
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+
+    assert_eq!(out, full(&tree));
+    // 1111 errors, checked every 256
+    assert_eq!(
+        reports.iter().map(|p| p.nodes_rendered).collect::<Vec<_>>(),
+        [256, 512, 768, 1024]
+    );
+    assert!(reports
+        .windows(2)
+        .all(|pair| pair[0].bytes_written < pair[1].bytes_written));
+}
+
+#[test]
+fn cancel_after_count() {
+    let tree = wide(3, 10, "root".to_string());
+    let expected = full(&tree);
+
+    let mut last = Progress::default();
+    let mut out = String::new();
+    print_tree_cancellable_with::<FRONT_MAX, _, _>(
+        &tree,
+        FormatOptions::new().progress_every(10),
+        &mut out,
+        &mut |progress| {
+            last = progress;
+            if progress.nodes_rendered >= 50 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        },
+    )
+    .unwrap();
+
+    assert_eq!(last.nodes_rendered, 50);
+    let (rendered, cancelled) = out.rsplit_once('\n').unwrap();
+    assert_eq!(cancelled, "… rendering cancelled after 50 nodes");
+    assert_eq!(rendered.len(), last.bytes_written);
+
+    // Every line before the cancellation is a complete line of the full tree
+    let rendered_lines: Vec<_> = rendered.lines().collect();
+    let expected_lines: Vec<_> = expected.lines().collect();
+    assert_eq!(
+        rendered_lines,
+        expected_lines[..rendered_lines.len()],
+        "{out}"
+    );
+    assert_eq!(
+        rendered_lines
+            .iter()
+            .filter(|line| line.contains("second line"))
+            .count(),
+        50
+    );
+}
+
+#[test]
+fn cancel_immediately() {
+    let tree = wide(1, 3, "root".to_string());
+
+    let mut out = String::new();
+    print_tree_cancellable_with::<FRONT_MAX, _, _>(
+        &tree,
+        FormatOptions::new().progress_every(0),
+        &mut out,
+        &mut |_| ControlFlow::Break(()),
+    )
+    .unwrap();
+
+    let mut lines = out.lines();
+    assert_eq!(lines.next(), full(&tree).lines().next());
+    assert_eq!(
+        out.lines().last(),
+        Some("… rendering cancelled after 1 nodes")
+    );
+}
+
+#[test]
+fn max_children() {
+    let tree = wide(2, 6, "root".to_string());
+    let options = FormatOptions::new().max_children(Some(2));
+
+    let mut expected = String::new();
+    print_tree_with::<FRONT_MAX, _, _>(&tree, options, &mut expected).unwrap();
+    assert!(expected.contains("… 4 similar errors elided"));
+
+    let mut out = String::new();
+    print_tree_cancellable_with::<FRONT_MAX, _, _>(&tree, options, &mut out, &mut |_| {
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert_eq!(out, expected);
+}
@@ -0,0 +1,64 @@
+#![cfg(all(nightly_provider, not(feature = "unix_color")))]
+#![cfg_attr(nightly_provider, feature(error_generic_member_access))]
+
+use std::{
+    backtrace::Backtrace,
+    error::{Error, Request},
+    fmt::{self, Display, Formatter},
+};
+
+use bare_err_tree::{front_max_for_depth, print_tree};
+
+#[derive(Debug)]
+struct Provided {
+    backtrace: Backtrace,
+}
+
+impl Error for Provided {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide_ref::<Backtrace>(&self.backtrace);
+    }
+}
+impl Display for Provided {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "provided")
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("outer")]
+struct Outer(#[source] Provided);
+
+fn render(err: &(dyn Error + 'static)) -> String {
+    let mut out = String::new();
+    print_tree::<{ front_max_for_depth(10) }, _, _>(err, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn renders_provided() {
+    let err = Outer(Provided {
+        backtrace: Backtrace::force_capture(),
+    });
+    let out = render(&err);
+
+    let mut lines = out.lines();
+    assert_eq!(lines.next(), Some("outer"));
+    assert_eq!(lines.next(), Some("│"));
+    assert_eq!(lines.next(), Some("╰─▶ provided"));
+    assert_eq!(lines.next(), Some("    │"));
+    assert_eq!(lines.next(), Some("    ├─ backtrace (provided)"));
+
+    let frames: Vec<_> = lines.collect();
+    assert!(!frames.is_empty());
+    assert!(frames.iter().all(|line| line.starts_with("    │    ")));
+    assert!(frames.iter().any(|line| line.contains("renders_provided")));
+}
+
+#[test]
+fn skips_disabled() {
+    let err = Outer(Provided {
+        backtrace: Backtrace::disabled(),
+    });
+    assert_eq!(render(&err), "outer\n│\n╰─▶ provided");
+}
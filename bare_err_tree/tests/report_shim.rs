@@ -0,0 +1,120 @@
+#![cfg(all(feature = "report_shim", feature = "derive_alloc"))]
+
+use std::io;
+
+use bare_err_tree::{front_max_for_depth, print_tree, AsErrTree, TreeReportShim};
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+#[derive(Debug, thiserror::Error)]
+#[error("failed to parse config")]
+struct ParseError;
+
+#[derive(Debug, thiserror::Error)]
+#[error("failed to load settings")]
+struct SettingsError;
+
+#[derive(Debug, thiserror::Error)]
+#[error("failed to start")]
+struct StartError;
+
+fn report() -> TreeReportShim<StartError> {
+    TreeReportShim::new(io::Error::from(io::ErrorKind::UnexpectedEof))
+        .attach_printable("path: config.toml")
+        .change_context(ParseError)
+        .attach_printable("line 3")
+        .attach_printable(format_args!("column {}", 7))
+        .change_context(SettingsError)
+        .change_context(StartError)
+        .attach_printable("port: 8080")
+}
+
+fn render(tree: impl AsErrTree) -> String {
+    let mut out = String::new();
+    print_tree::<FRONT_MAX, _, _>(tree, &mut out).unwrap();
+    out
+}
+
+#[cfg(not(any(feature = "source_line", feature = "tracing", feature = "timestamp")))]
+#[test]
+fn matches_hand_built() {
+    use bare_err_tree::err_tree;
+
+    // The tree `report` stands in for
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("{msg}")]
+    struct Node {
+        msg: &'static str,
+        #[tree_iter_err]
+        children: Vec<Node>,
+    }
+
+    fn hand_built() -> Node {
+        let node = |msg, children| Node::_tree(msg, children);
+        node(
+            "failed to start\nport: 8080",
+            vec![node(
+                "failed to load settings",
+                vec![node(
+                    "failed to parse config\nline 3\ncolumn 7",
+                    vec![node("unexpected end of file\npath: config.toml", vec![])],
+                )],
+            )],
+        )
+    }
+
+    let expected_lines = "failed to start
+│ port: 8080
+│
+╰─▶ failed to load settings
+    │
+    ╰─▶ failed to parse config
+        │ line 3
+        │ column 7
+        │
+        ╰─▶ unexpected end of file
+            │ path: config.toml";
+
+    assert_eq!(render(report()), expected_lines);
+    assert_eq!(render(hand_built()), expected_lines);
+}
+
+#[test]
+fn context_and_sources() {
+    let report = report();
+    let _: &StartError = report.current_context();
+
+    let out = render(&report);
+    assert!(out.starts_with("failed to start"), "{out}");
+    assert_eq!(out.matches("─▶").count(), 3, "{out}");
+
+    // The first context keeps its own sources
+    #[derive(Debug, thiserror::Error)]
+    #[error("outer")]
+    struct Outer(#[source] io::Error);
+
+    let report =
+        TreeReportShim::from(Outer(io::ErrorKind::NotFound.into())).change_context(StartError);
+    let out = render(&report);
+    assert!(out.contains("─▶ outer"), "{out}");
+    assert!(out.contains("─▶ entity not found"), "{out}");
+}
+
+#[cfg(all(feature = "source_line", not(feature = "hyperlinks")))]
+#[test]
+fn context_locations() {
+    let report = TreeReportShim::new(ParseError);
+    let first = line!() - 1;
+    let report = report.attach_printable("attachment");
+    let report = report.change_context(StartError);
+    let second = line!() - 1;
+
+    let out = render(&report);
+    let locations: Vec<_> = out
+        .lines()
+        .filter_map(|line| line.split_once("tests/report_shim.rs:"))
+        .map(|(_, at)| at.split_once(':').unwrap().0.parse::<u32>().unwrap())
+        .collect();
+    assert_eq!(locations, [second, first], "{out}");
+}
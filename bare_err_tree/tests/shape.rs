@@ -0,0 +1,83 @@
+#![cfg(all(feature = "test_util", feature = "derive_alloc"))]
+
+mod example {
+    use bare_err_tree::{assert_tree_shape, tree_shape, Shape};
+
+    include!("../test_cases/std/src/bin/example.rs");
+
+    fn fatal() -> MissedClassTree {
+        MissedClass::Overslept(Overslept::new(BedTime::new(
+            2,
+            vec![
+                ClassProject::new("proving 1 == 2".to_string()).into(),
+                BedTimeReasons::ExamStressed,
+                BedTimeReasons::PlayingGames,
+            ],
+        )))
+        .into()
+    }
+
+    #[test]
+    fn readme_shape() {
+        assert_tree_shape!(fatal(), {
+            "missed class" => [
+                "stayed in bed too long" => [
+                    "bed is comfortable",
+                    "went to sleep at 2 A.M." => [
+                        "finishing a project" => ["proving 1 == 2"],
+                        "stressed about exams",
+                        "playing video games",
+                    ],
+                ],
+            ]
+        });
+    }
+
+    #[test]
+    fn locations_masked() {
+        let shape = tree_shape(&fatal());
+        assert_eq!(shape, shape.clone().mask_locations());
+
+        let mut snapshot = bare_err_tree::TreeSnapshot::default();
+        bare_err_tree::AsErrTree::as_err_tree(&fatal(), &mut |tree| snapshot = tree.to_snapshot());
+        let located = Shape::from(snapshot);
+        assert_eq!(located.clone().mask_locations(), shape);
+
+        #[cfg(feature = "source_line")]
+        assert!(
+            located
+                .location
+                .as_deref()
+                .is_some_and(|loc| loc.contains("tests/shape.rs")),
+            "{located}"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "error tree shape mismatch")]
+    fn missing_source() {
+        assert_tree_shape!(fatal(), {
+            "missed class" => [
+                "stayed in bed too long" => ["bed is comfortable"],
+            ]
+        });
+    }
+
+    #[test]
+    fn display() {
+        let shape = Shape::new(
+            "outer",
+            [
+                Shape::new("inner", [Shape::new("leaf", [])]),
+                Shape::new("last", []),
+            ],
+        );
+        assert_eq!(
+            shape.to_string(),
+            "\"outer\"
+  \"inner\"
+    \"leaf\"
+  \"last\""
+        );
+    }
+}
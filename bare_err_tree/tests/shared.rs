@@ -1,4 +1,4 @@
-#![cfg(not(feature = "unix_color"))]
+#![cfg(not(any(feature = "unix_color", feature = "type_name")))]
 
 #[cfg(feature = "derive")]
 mod empty {
@@ -11,7 +11,12 @@ mod empty {
     }
 }
 
-#[cfg(all(feature = "derive", feature = "source_line"))]
+#[cfg(all(
+    feature = "derive",
+    feature = "source_line",
+    not(feature = "hyperlinks"),
+    not(feature = "timestamp")
+))]
 mod near_empty {
     include!("../test_cases/std/src/bin/near-empty.rs");
 
@@ -53,4 +58,2387 @@ mod multiline {
 
         assert_eq!(out, expected_lines);
     }
+
+    #[derive(Debug, Error)]
+    #[error("{0}")]
+    struct Owned(String);
+
+    /// Counts calls into the underlying writer.
+    struct CountingWriter {
+        out: String,
+        calls: usize,
+    }
+
+    impl core::fmt::Write for CountingWriter {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            self.calls += 1;
+            self.out.push_str(s);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn long_lines_batched() {
+        let line = "x".repeat(4096);
+        let msg = format!("{line}\n\n{line}\n{line}");
+        let expected_lines = format!("{line}\n│ \n│ {line}\n│ {line}");
+
+        let mut out = CountingWriter {
+            out: String::new(),
+            calls: 0,
+        };
+        print_tree::<60, _, _>(&Owned(msg) as &dyn Error, &mut out).unwrap();
+
+        assert_eq!(out.out, expected_lines);
+        assert!(out.calls < 32, "{} write calls", out.calls);
+    }
+
+    #[derive(Debug, Error)]
+    #[error("{0}")]
+    struct OwnedWrap(String, #[source] Owned);
+
+    fn render(err: &(dyn Error + 'static)) -> String {
+        let mut out = String::new();
+        print_tree::<60, _, _>(err, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn preset_leader_kept() {
+        let msg = "first\n│ second\nthird";
+        assert_eq!(render(&Owned(msg.into())), "first\n│ second\n│ third");
+
+        let nested = OwnedWrap("outer".into(), Owned(msg.into()));
+        assert_eq!(
+            render(&nested),
+            "outer\n│\n╰─▶ first\n    │ │ second\n    │ third"
+        );
+    }
+
+    #[test]
+    fn round_trip_stable() {
+        let first = render(&MultilineErr(InnerMultiline));
+        let second = render(&Owned(first.clone()));
+        assert_eq!(
+            second,
+            "This error spans
+│ multiple
+│ lines
+│ │
+│ ╰─▶ And is
+│     │ nested"
+        );
+
+        // Every line already carries the root leader, so further cycles are
+        // unchanged.
+        let third = render(&Owned(second.clone()));
+        assert_eq!(third, second);
+        assert_eq!(render(&Owned(third)), second);
+    }
+
+    #[test]
+    fn round_trip_nested() {
+        let first = render(&MultilineErr(InnerMultiline));
+        let wrapped = render(&OwnedWrap("outer".into(), Owned(first.clone())));
+
+        // One additional leader per line for the one additional nesting. The
+        // last line already matches the child leader, so it is kept as is.
+        assert_eq!(
+            wrapped,
+            "outer
+│
+╰─▶ This error spans
+    │ │ multiple
+    │ │ lines
+    │ │
+    │ ╰─▶ And is
+    │ nested"
+        );
+
+        let rewrapped = render(&OwnedWrap("outer".into(), Owned(wrapped.clone())));
+        assert_eq!(rewrapped.lines().count(), wrapped.lines().count() + 2);
+        assert!(rewrapped.lines().all(|line| !line.contains("│ │ │")));
+    }
+}
+
+#[cfg(feature = "derive_alloc")]
+mod optional_iter {
+    use bare_err_tree::{err_tree, AsErrTree};
+    use thiserror::Error;
+
+    #[err_tree]
+    #[derive(Debug, Error)]
+    #[error("optional collections")]
+    struct OptionalIter {
+        #[dyn_iter_err]
+        vec_errs: Option<Vec<std::io::Error>>,
+        #[dyn_iter_err]
+        array_errs: Option<[std::io::Error; 2]>,
+    }
+
+    fn source_count(err: &OptionalIter) -> usize {
+        let mut count = 0;
+        err.as_err_tree(&mut |tree| count = tree.sources().count());
+        count
+    }
+
+    #[test]
+    fn flattens_options() {
+        let eof_gen = || std::io::Error::from(std::io::ErrorKind::UnexpectedEof);
+
+        let none = OptionalIter::_tree(None, None);
+        assert_eq!(source_count(&none), 0);
+
+        let some = OptionalIter::_tree(
+            Some(vec![eof_gen(), eof_gen(), eof_gen()]),
+            Some([eof_gen(), eof_gen()]),
+        );
+        assert_eq!(source_count(&some), 5);
+    }
+}
+
+#[cfg(feature = "derive_alloc")]
+mod iter_with {
+    use std::io;
+
+    use bare_err_tree::{err_tree, AsErrTree};
+    use thiserror::Error;
+
+    #[err_tree]
+    #[derive(Debug, Error)]
+    #[error("leaf")]
+    struct Leaf;
+
+    /// Errors keyed by retry attempt, with no `.iter()` of its own.
+    #[derive(Debug)]
+    struct Attempts {
+        attempts: Vec<(u32, Leaf)>,
+    }
+
+    impl Attempts {
+        fn errors(&self) -> impl Iterator<Item = &Leaf> {
+            self.attempts.iter().map(|(_, err)| err)
+        }
+    }
+
+    fn some_errs(errs: &Option<Vec<io::Error>>) -> impl Iterator<Item = &io::Error> {
+        errs.iter().flatten()
+    }
+
+    #[err_tree]
+    #[derive(Debug, Error)]
+    #[error("custom iteration")]
+    struct Custom {
+        #[tree_iter_err(with = Attempts::errors)]
+        attempts: Attempts,
+        #[dyn_iter_err(with = some_errs)]
+        io_errs: Option<Vec<io::Error>>,
+    }
+
+    #[err_tree(CustomEnumWrap)]
+    #[derive(Debug, Error)]
+    enum CustomEnum {
+        #[error("attempts")]
+        #[tree_iter_err(with = Attempts::errors)]
+        Attempts(Attempts),
+        #[error("io")]
+        #[dyn_iter_err(with = some_errs)]
+        Io(Option<Vec<io::Error>>),
+        #[error("collapsed")]
+        #[tree_iter_err(collapse, with = Attempts::errors)]
+        Collapsed(Attempts),
+    }
+
+    fn source_count<E: AsErrTree>(err: &E) -> usize {
+        let mut count = 0;
+        err.as_err_tree(&mut |tree| count = tree.sources().count());
+        count
+    }
+
+    fn attempts(count: u32) -> Attempts {
+        Attempts {
+            attempts: (0..count).map(|idx| (idx, Leaf::_tree())).collect(),
+        }
+    }
+
+    fn io_errs(count: usize) -> Vec<io::Error> {
+        (0..count)
+            .map(|_| io::Error::from(io::ErrorKind::UnexpectedEof))
+            .collect()
+    }
+
+    #[test]
+    fn struct_fields() {
+        assert_eq!(source_count(&Custom::_tree(attempts(0), None)), 0);
+        assert_eq!(source_count(&Custom::_tree(attempts(3), None)), 3);
+        assert_eq!(
+            source_count(&Custom::_tree(attempts(2), Some(io_errs(4)))),
+            6
+        );
+    }
+
+    #[test]
+    fn enum_variants() {
+        let wrap = CustomEnumWrap::from(CustomEnum::Attempts(attempts(3)));
+        assert_eq!(source_count(&wrap), 3);
+
+        let wrap = CustomEnumWrap::from(CustomEnum::Io(Some(io_errs(2))));
+        assert_eq!(source_count(&wrap), 2);
+
+        let wrap = CustomEnumWrap::from(CustomEnum::Io(None));
+        assert_eq!(source_count(&wrap), 0);
+
+        let wrap = CustomEnumWrap::from(CustomEnum::Collapsed(attempts(4)));
+        assert_eq!(source_count(&wrap), 4);
+    }
+}
+
+#[cfg(feature = "derive_alloc")]
+mod budget_summary {
+    use core::error::Error;
+
+    use bare_err_tree::{
+        err_tree, front_max_for_depth, print_tree, print_tree_budgeted, tree_summary, TreeSummary,
+    };
+    use thiserror::Error;
+
+    #[derive(Debug, Error)]
+    #[error("leaf {0}")]
+    struct Leaf(usize);
+
+    #[derive(Debug, Error)]
+    #[error("branch\nwith two lines")]
+    struct Branch(#[source] Leaf);
+
+    #[err_tree]
+    #[derive(Debug, Error)]
+    #[error("wide root")]
+    struct Wide {
+        #[dyn_iter_err]
+        branches: Vec<Branch>,
+    }
+
+    fn wide() -> Wide {
+        Wide::_tree((0..20).map(|idx| Branch(Leaf(idx))).collect())
+    }
+
+    #[test]
+    fn summary_stats() {
+        assert_eq!(
+            tree_summary(wide()),
+            TreeSummary {
+                nodes: 41,
+                max_depth: 3,
+                leaves: 20,
+            }
+        );
+    }
+
+    #[test]
+    fn summary_fallback() {
+        let expected_lines = "wide root
+├─ summarized to fit 200 bytes: 41 error(s), depth 3, 20 root cause(s)
+│
+╰─▶ branch
+    │ with two lines
+    ╰─▶ leaf 0";
+
+        let mut out = String::new();
+        print_tree_budgeted::<60, _, _>(wide(), 200, &mut out).unwrap();
+        assert_eq!(out, expected_lines);
+    }
+
+    #[test]
+    fn summary_cut_to_budget() {
+        let mut out = String::new();
+        print_tree_budgeted::<60, _, _>(wide(), 100, &mut out).unwrap();
+        assert_eq!(
+            out,
+            "wide root
+├─ summarized to fit 100 bytes: 41 error(s), depth 3, 20 root cause(s)
+│
+╰─▶ b"
+        );
+        assert!(out.len() <= 100);
+    }
+
+    #[test]
+    fn summary_blame_depth() {
+        let expected_lines = "wide root
+├─ summarized to fit 300 bytes: 41 error(s), depth 3, 20 root cause(s)
+│
+╰─▶ branch
+    │ with two lines
+-- tree truncated at depth 1; increase FRONT_MAX (currently supports 2 levels) --";
+
+        let mut out = String::new();
+        print_tree_budgeted::<{ front_max_for_depth(2) }, _, _>(wide(), 300, &mut out).unwrap();
+        assert_eq!(out, expected_lines);
+    }
+
+    #[test]
+    fn within_budget() {
+        let err = Branch(Leaf(0));
+
+        let mut full = String::new();
+        print_tree::<60, _, _>(&err as &dyn Error, &mut full).unwrap();
+
+        let mut out = String::new();
+        print_tree_budgeted::<60, _, _>(&err as &dyn Error, full.len(), &mut out).unwrap();
+        assert_eq!(out, full);
+    }
+}
+
+#[cfg(feature = "derive_alloc")]
+mod raw_idents {
+    use bare_err_tree::{err_tree, tree_summary, TreeSummary};
+    use thiserror::Error;
+
+    #[derive(Debug, Error)]
+    #[error("leaf")]
+    struct Leaf;
+
+    #[err_tree]
+    #[derive(Debug, Error)]
+    #[error("inner")]
+    struct r#Inner {
+        #[dyn_err]
+        r#type: Leaf,
+    }
+
+    #[err_tree]
+    #[derive(Debug, Error)]
+    #[error("raw")]
+    struct Raw {
+        #[dyn_err]
+        r#type: Leaf,
+        #[tree_err]
+        r#match: r#Inner,
+        #[dyn_iter_err]
+        r#loop: Vec<Leaf>,
+        #[tree_iter_err]
+        r#for: [r#Inner; 2],
+    }
+
+    #[err_tree(r#RawEnumWrap)]
+    #[allow(non_camel_case_types)]
+    #[derive(Debug, Error)]
+    enum r#enum {
+        #[dyn_err]
+        #[error("try")]
+        r#try(Leaf),
+        #[tree_err]
+        #[error("match")]
+        r#match(r#Inner),
+        #[dyn_iter_err]
+        #[error("loop")]
+        r#loop(Vec<Leaf>),
+        #[tree_iter_err]
+        #[error("for")]
+        r#for([r#Inner; 2]),
+    }
+
+    fn inner() -> r#Inner {
+        r#Inner::_tree(Leaf)
+    }
+
+    #[test]
+    fn raw_struct() {
+        let err = Raw::_tree(Leaf, inner(), vec![Leaf, Leaf], [inner(), inner()]);
+        assert_eq!(
+            tree_summary(err),
+            TreeSummary {
+                nodes: 10,
+                max_depth: 3,
+                leaves: 6,
+            }
+        );
+    }
+
+    #[test]
+    fn raw_enum() {
+        let summary = |err: r#enum| tree_summary(r#RawEnumWrap::from(err));
+
+        assert_eq!(summary(r#enum::r#try(Leaf)).nodes, 2);
+        assert_eq!(summary(r#enum::r#match(inner())).nodes, 3);
+        assert_eq!(summary(r#enum::r#loop(vec![Leaf, Leaf])).nodes, 3);
+        assert_eq!(summary(r#enum::r#for([inner(), inner()])).nodes, 5);
+    }
+
+    #[cfg(feature = "test_util")]
+    #[test]
+    fn raw_shape() {
+        let err = r#RawEnumWrap::from(r#enum::r#for([inner(), inner()]));
+        bare_err_tree::assert_tree_shape!(err, {
+            "for" => [
+                "inner" => ["leaf"],
+                "inner" => ["leaf"],
+            ]
+        });
+    }
+}
+
+#[cfg(feature = "derive_alloc")]
+mod namespaced {
+    use bare_err_tree::{err_tree, tree_summary, TreeSummary};
+    use thiserror::Error;
+
+    #[derive(Debug, Error)]
+    #[error("leaf")]
+    struct Leaf;
+
+    #[err_tree]
+    #[derive(Debug, Error)]
+    #[error("bare")]
+    struct Bare {
+        #[dyn_err]
+        leaf: Leaf,
+        #[tree_iter_err]
+        children: Vec<Bare>,
+    }
+
+    #[err_tree(namespaced_only)]
+    #[derive(Debug, Error)]
+    #[error("namespaced")]
+    struct Namespaced {
+        #[err_tree(dyn_err)]
+        leaf: Leaf,
+        #[err_tree(tree_iter_err)]
+        children: Vec<Namespaced>,
+    }
+
+    #[err_tree(NamespacedEnumWrap, namespaced_only)]
+    #[derive(Debug, Error)]
+    enum NamespacedEnum {
+        #[err_tree(dyn_iter_err)]
+        #[error("leaves")]
+        Leaves(Vec<Leaf>),
+    }
+
+    #[test]
+    fn matches_bare() {
+        let bare = Bare::_tree(Leaf, vec![Bare::_tree(Leaf, vec![])]);
+        let namespaced = Namespaced::_tree(Leaf, vec![Namespaced::_tree(Leaf, vec![])]);
+        let expected = TreeSummary {
+            nodes: 4,
+            max_depth: 3,
+            leaves: 2,
+        };
+        assert_eq!(tree_summary(bare), expected);
+        assert_eq!(tree_summary(namespaced), expected);
+    }
+
+    #[test]
+    fn namespaced_enum() {
+        let err = NamespacedEnumWrap::from(NamespacedEnum::Leaves(vec![Leaf, Leaf]));
+        assert_eq!(tree_summary(err).nodes, 3);
+    }
+}
+
+#[cfg(feature = "derive")]
+mod via {
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree, tree_summary};
+    use thiserror::Error;
+
+    #[derive(Debug, Error)]
+    #[error("leaf")]
+    struct Leaf;
+
+    #[err_tree]
+    #[derive(Debug, Error)]
+    #[error("inner")]
+    struct Inner {
+        #[dyn_err]
+        leaf: Leaf,
+    }
+
+    mod state {
+        #[derive(Debug)]
+        pub struct State<E> {
+            last_error: E,
+        }
+
+        impl<E> State<E> {
+            pub fn new(last_error: E) -> Self {
+                Self { last_error }
+            }
+
+            pub fn last_error(&self) -> &E {
+                &self.last_error
+            }
+        }
+    }
+
+    #[err_tree]
+    #[derive(Debug, Error)]
+    #[error("outer")]
+    struct Outer {
+        #[tree_err(via = inner)]
+        inner_state: state::State<Inner>,
+        #[dyn_err(via = leaf)]
+        leaf_state: state::State<Leaf>,
+    }
+
+    impl Outer {
+        fn inner(&self) -> &Inner {
+            self.inner_state.last_error()
+        }
+
+        fn leaf(&self) -> &Leaf {
+            self.leaf_state.last_error()
+        }
+    }
+
+    #[test]
+    fn getter_sources() {
+        let err = Outer::_tree(
+            state::State::new(Inner::_tree(Leaf)),
+            state::State::new(Leaf),
+        );
+        assert_eq!(tree_summary(&err).nodes, 4);
+
+        let mut out = String::new();
+        print_tree::<{ front_max_for_depth(10) }, _, _>(&err, &mut out).unwrap();
+        let messages: Vec<_> = out
+            .lines()
+            .filter_map(|line| line.split_once("─▶ ").map(|(_, msg)| msg))
+            .collect();
+        assert_eq!(messages, ["inner", "leaf", "leaf"], "{out}");
+    }
+}
+
+mod simple {
+    use bare_err_tree::{print_tree, simple_err_tree};
+
+    #[derive(Debug)]
+    struct StringErr(String);
+
+    #[derive(Debug)]
+    struct StaticErr(&'static str);
+
+    simple_err_tree!(StringErr, StaticErr);
+
+    #[test]
+    fn simple_leaf() {
+        let mut out = String::new();
+        print_tree::<60, _, _>(StringErr("owned\nmessage".to_string()), &mut out).unwrap();
+        assert_eq!(out, "owned\n│ message");
+
+        let mut out = String::new();
+        print_tree::<60, _, _>(StaticErr("static message"), &mut out).unwrap();
+        assert_eq!(out, "static message");
+
+        assert!(core::error::Error::source(&StaticErr("")).is_none());
+    }
+}
+
+#[cfg(feature = "derive")]
+mod msg_field {
+    use core::fmt::{self, Display, Formatter};
+
+    use bare_err_tree::err_tree;
+
+    #[err_tree(msg_field = context)]
+    #[derive(Debug)]
+    struct Named {
+        context: &'static str,
+    }
+
+    impl core::error::Error for Named {}
+    impl Display for Named {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "generic failure")
+        }
+    }
+
+    #[err_tree(TupleWrap, msg_field = 0)]
+    #[derive(Debug)]
+    struct Tuple(String);
+
+    impl core::error::Error for Tuple {}
+    impl Display for Tuple {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "generic failure")
+        }
+    }
+
+    #[test]
+    fn display_unchanged() {
+        assert_eq!(Named::_tree("ctx").to_string(), "generic failure");
+        assert_eq!(
+            TupleWrap::from(Tuple("ctx".to_string())).to_string(),
+            "generic failure"
+        );
+    }
+
+    #[cfg(not(any(feature = "source_line", feature = "tracing", feature = "timestamp")))]
+    #[test]
+    fn tree_uses_field() {
+        use bare_err_tree::print_tree;
+
+        let mut out = String::new();
+        print_tree::<60, _, _>(Named::_tree("reading config.toml"), &mut out).unwrap();
+        assert_eq!(out, "reading config.toml");
+
+        let mut out = String::new();
+        let wrapped = TupleWrap::from(Tuple("multi\nline".to_string()));
+        print_tree::<60, _, _>(wrapped, &mut out).unwrap();
+        assert_eq!(out, "multi\n│ line");
+    }
+
+    #[cfg(all(
+        feature = "json",
+        not(any(feature = "source_line", feature = "tracing", feature = "timestamp"))
+    ))]
+    #[test]
+    fn json_uses_field() {
+        let mut out = String::new();
+        bare_err_tree::tree_to_json::<_, _, _>(Named::_tree("reading config.toml"), &mut out)
+            .unwrap();
+        assert_eq!(out, r#"{"v":1,"msg":"reading config.toml"}"#);
+    }
+}
+
+#[cfg(feature = "derive_alloc")]
+mod snapshot {
+    use core::fmt::{self, Display, Formatter};
+
+    use bare_err_tree::{err_tree, flatten, AsErrTree, TreeSnapshot};
+
+    #[err_tree]
+    #[derive(Debug)]
+    struct Parent {
+        #[dyn_iter_err]
+        children: Vec<std::io::Error>,
+    }
+
+    impl core::error::Error for Parent {}
+    impl Display for Parent {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "parent")
+        }
+    }
+
+    fn snapshot(err: &impl AsErrTree) -> TreeSnapshot {
+        let mut snapshot = TreeSnapshot::default();
+        err.as_err_tree(&mut |tree| snapshot = tree.to_snapshot());
+        snapshot
+    }
+
+    #[test]
+    fn structure() {
+        let err = Parent::_tree(vec![
+            std::io::ErrorKind::UnexpectedEof.into(),
+            std::io::ErrorKind::NotFound.into(),
+        ]);
+        let snapshot = snapshot(&err);
+
+        let leaf = |msg: &str| TreeSnapshot {
+            msg: msg.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            snapshot.children,
+            [leaf("unexpected end of file"), leaf("entity not found")]
+        );
+        assert_eq!(snapshot.msg, "parent");
+
+        #[cfg(feature = "source_line")]
+        assert!(snapshot
+            .location
+            .is_some_and(|loc| loc.contains("tests/shared.rs")));
+    }
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("{msg}")]
+    struct Node {
+        msg: &'static str,
+        #[tree_iter_err]
+        children: Vec<Node>,
+    }
+
+    #[test]
+    fn flattened() {
+        let node = |msg, children| Node::_tree(msg, children);
+        let err = node(
+            "root",
+            vec![
+                node("a", vec![node("a1", vec![]), node("a2", vec![])]),
+                node("b", vec![]),
+                node("c", vec![node("c1", vec![node("c1x", vec![])])]),
+            ],
+        );
+
+        let mut flat = Vec::new();
+        err.as_err_tree(&mut |tree| flat = flatten(tree));
+
+        let flat: Vec<_> = flat
+            .iter()
+            .map(|(depth, err)| (*depth, err.msg.as_str()))
+            .collect();
+        assert_eq!(
+            flat,
+            [
+                (0, "root"),
+                (1, "a"),
+                (2, "a1"),
+                (2, "a2"),
+                (1, "b"),
+                (1, "c"),
+                (2, "c1"),
+                (3, "c1x"),
+            ]
+        );
+    }
+}
+
+#[cfg(feature = "derive_alloc")]
+mod forgotten_variant {
+    use std::io;
+
+    use bare_err_tree::{err_tree, AsErrTree, TreeSnapshot};
+    use thiserror::Error;
+
+    #[err_tree(ReadWrap)]
+    #[derive(Debug, Error)]
+    enum Read {
+        #[dyn_err]
+        #[error("annotated")]
+        Annotated(#[source] io::Error),
+        // Missing `#[dyn_err]`
+        #[error("forgotten")]
+        Forgotten(#[source] io::Error),
+        #[error("no source")]
+        Bare,
+    }
+
+    fn snapshot(err: Read) -> TreeSnapshot {
+        let mut snapshot = TreeSnapshot::default();
+        ReadWrap::from(err).as_err_tree(&mut |tree| snapshot = tree.to_snapshot());
+        snapshot
+    }
+
+    fn cause() -> io::Error {
+        io::ErrorKind::UnexpectedEof.into()
+    }
+
+    #[test]
+    fn both_render_causes() {
+        let annotated = snapshot(Read::Annotated(cause()));
+        let forgotten = snapshot(Read::Forgotten(cause()));
+
+        assert_eq!(annotated.msg, "annotated");
+        assert_eq!(forgotten.msg, "forgotten");
+        assert_eq!(annotated.children, forgotten.children);
+
+        let [child] = &forgotten.children[..] else {
+            panic!("{forgotten:?}");
+        };
+        assert_eq!(child.msg, "unexpected end of file");
+        assert!(child.children.is_empty());
+    }
+
+    #[test]
+    fn sourceless_stays_bare() {
+        let bare = snapshot(Read::Bare);
+        assert_eq!(bare.msg, "no source");
+        assert!(bare.children.is_empty());
+    }
+}
+
+mod front_max {
+    use core::fmt::{self, Display, Formatter};
+
+    use bare_err_tree::{
+        depth_for_front_max, front_max_for_depth, print_tree, print_tree_with, FormatOptions,
+        FRONT_CELL_LEN,
+    };
+
+    #[test]
+    fn helpers() {
+        assert_eq!(FRONT_CELL_LEN, "│   ".len());
+        assert_eq!(front_max_for_depth(0), 0);
+        assert_eq!(front_max_for_depth(10), 10 * FRONT_CELL_LEN);
+
+        for depth in 0..20 {
+            assert_eq!(depth_for_front_max(front_max_for_depth(depth)), depth);
+            assert_eq!(
+                depth_for_front_max(front_max_for_depth(depth + 1) - 1),
+                depth
+            );
+        }
+    }
+
+    /// Linear chain of errors `depth` long.
+    #[derive(Debug)]
+    struct Chain(usize);
+
+    impl core::error::Error for Chain {
+        fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+            // Leak to get a 'static reference; test only
+            (self.0 > 0).then(|| &*Box::leak(Box::new(Chain(self.0 - 1))) as _)
+        }
+    }
+    impl Display for Chain {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    fn print<const FRONT_MAX: usize>(depth: usize) -> String {
+        let mut out = String::new();
+        print_tree::<FRONT_MAX, _, _>(&Chain(depth) as &dyn core::error::Error, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn partial_cell_unused() {
+        const FULL: usize = front_max_for_depth(3);
+
+        let expected = print::<FULL>(10);
+        assert_eq!(print::<{ FULL + 1 }>(10), expected);
+        assert_eq!(print::<{ FULL + FRONT_CELL_LEN - 1 }>(10), expected);
+        assert_ne!(print::<{ FULL + FRONT_CELL_LEN }>(10), expected);
+    }
+
+    #[test]
+    fn truncation_trailer() {
+        let out = print::<{ front_max_for_depth(2) }>(5);
+        assert!(out.ends_with(
+            "......\n-- tree truncated at depth 2; increase FRONT_MAX (currently supports 2 levels) --"
+        ));
+        assert_eq!(out.matches("-- tree truncated").count(), 1);
+
+        let mut with_options = String::new();
+        let options = FormatOptions::new().dedup_by_identity(true);
+        print_tree_with::<{ front_max_for_depth(2) }, _, _>(
+            &Chain(5) as &dyn core::error::Error,
+            options,
+            &mut with_options,
+        )
+        .unwrap();
+        assert_eq!(with_options, out);
+    }
+
+    #[test]
+    fn no_trailer() {
+        let out = print::<{ front_max_for_depth(10) }>(2);
+        assert!(!out.contains("truncated"));
+        assert!(out.ends_with("╰─▶ 0"));
+    }
+}
+
+#[cfg(feature = "derive_alloc")]
+mod records {
+    use std::fmt::{self, Display, Formatter};
+
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree, tree_to_records};
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("outer\nwith a second line")]
+    struct Outer {
+        #[dyn_iter_err]
+        sources: Vec<std::io::Error>,
+    }
+
+    struct Correlation(u64);
+
+    impl Display for Correlation {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "corr-{:x}", self.0)
+        }
+    }
+
+    #[test]
+    fn rejoin_matches_render() {
+        let err = Outer::_tree(vec![
+            std::io::ErrorKind::UnexpectedEof.into(),
+            std::io::ErrorKind::NotFound.into(),
+        ]);
+
+        let mut rendered = String::new();
+        print_tree::<{ front_max_for_depth(10) }, _, _>(&err, &mut rendered).unwrap();
+
+        let mut lines = Vec::new();
+        tree_to_records::<{ front_max_for_depth(10) }, _>(&err, &Correlation(0xbeef), |line| {
+            lines.push((
+                line.seq,
+                line.total_hint,
+                line.correlation.to_string(),
+                line.text.to_string(),
+            ))
+        })
+        .unwrap();
+
+        let total = rendered.lines().count();
+        assert_eq!(lines.len(), total);
+        for (idx, (seq, total_hint, correlation, text)) in lines.iter().enumerate() {
+            assert_eq!(*seq as usize, idx);
+            assert_eq!(*total_hint, Some(total as u32));
+            assert_eq!(correlation, "corr-beef");
+            assert!(!text.contains('\n'));
+        }
+        assert_eq!(lines[1].3, "│ with a second line");
+
+        let rejoined: Vec<_> = lines.into_iter().map(|(.., text)| text).collect();
+        assert_eq!(rejoined.join("\n"), rendered);
+    }
+}
+
+#[cfg(feature = "derive_alloc")]
+mod dyn_print {
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree, print_tree_dyn, AsErrTree};
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("outer")]
+    struct Outer {
+        #[tree_err]
+        inner: Inner,
+        #[dyn_iter_err]
+        extra: Vec<std::io::Error>,
+    }
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("inner")]
+    struct Inner {
+        #[dyn_err]
+        io: std::io::Error,
+    }
+
+    fn compare<const FRONT_MAX: usize>(err: &Outer) {
+        let mut generic = String::new();
+        print_tree::<FRONT_MAX, _, _>(err, &mut generic).unwrap();
+
+        let mut erased = String::new();
+        print_tree_dyn::<FRONT_MAX>(err as &dyn AsErrTree, &mut erased).unwrap();
+
+        assert_eq!(erased, generic);
+    }
+
+    #[test]
+    fn matches_generic() {
+        let err = Outer::_tree(
+            Inner::_tree(std::io::ErrorKind::UnexpectedEof.into()),
+            vec![std::io::ErrorKind::NotFound.into()],
+        );
+
+        compare::<{ front_max_for_depth(10) }>(&err);
+        // Truncated below the full depth
+        compare::<{ front_max_for_depth(2) }>(&err);
+        compare::<0>(&err);
+    }
+}
+
+#[cfg(feature = "derive")]
+mod dedup_identity {
+    use std::{error::Error, io};
+
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree_with, AsErrTree, FormatOptions};
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("outer")]
+    struct Outer {
+        #[dyn_err]
+        wrapper: Wrapper,
+        #[tree_err]
+        leaf: &'static Leaf,
+    }
+
+    /// Reports the same [`Leaf`] that [`Outer`] also holds directly.
+    #[derive(Debug, thiserror::Error)]
+    #[error("wrapper")]
+    struct Wrapper(#[source] &'static Leaf);
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("leaf\nsecond line")]
+    struct Leaf {
+        #[dyn_err]
+        #[source]
+        io: io::Error,
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("newtype")]
+    struct Newtype(#[source] io::Error);
+
+    fn render<E: AsErrTree>(err: E, dedup: bool) -> String {
+        let mut out = String::new();
+        let options = FormatOptions::new().dedup_by_identity(dedup);
+        print_tree_with::<{ front_max_for_depth(10) }, _, _>(err, options, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn double_annotation() {
+        let leaf: &'static Leaf =
+            Box::leak(Box::new(Leaf::_tree(io::ErrorKind::UnexpectedEof.into())));
+        let err = Outer::_tree(Wrapper(leaf), leaf);
+
+        let full = render(&err, false);
+        assert_eq!(full.matches("unexpected end of file").count(), 2);
+        assert!(!full.contains("see above"));
+
+        let deduped = render(&err, true);
+        assert_eq!(deduped.matches("unexpected end of file").count(), 1);
+        assert_eq!(deduped.matches("second line").count(), 1);
+        assert_eq!(deduped.matches("╰─▶ (see above: leaf)").count(), 1);
+        assert!(deduped.ends_with("╰─▶ (see above: leaf)"));
+    }
+
+    #[test]
+    fn shared_address_ancestor() {
+        let err = Newtype(io::ErrorKind::UnexpectedEof.into());
+        let err = &err as &dyn Error;
+
+        assert_eq!(render(err, true), render(err, false));
+        assert!(!render(err, true).contains("see above"));
+    }
+}
+
+#[cfg(all(feature = "derive", feature = "alloc"))]
+mod mark_shared {
+    use std::{io, sync::Arc};
+
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree_with, AsErrTree, FormatOptions};
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("both failed")]
+    struct Both {
+        #[tree_err]
+        first: Parent,
+        #[tree_err]
+        second: Parent,
+    }
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("parent {id}")]
+    struct Parent {
+        id: u8,
+        #[tree_err]
+        leaf: Arc<Leaf>,
+    }
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("leaf")]
+    struct Leaf {
+        #[dyn_err]
+        io: io::Error,
+    }
+
+    fn render<E: AsErrTree>(err: E, options: FormatOptions) -> String {
+        let mut out = String::new();
+        print_tree_with::<{ front_max_for_depth(10) }, _, _>(err, options, &mut out).unwrap();
+        out
+    }
+
+    fn both() -> Both {
+        let leaf = Arc::new(Leaf::_tree(io::ErrorKind::UnexpectedEof.into()));
+        Both::_tree(Parent::_tree(1, leaf.clone()), Parent::_tree(2, leaf))
+    }
+
+    #[test]
+    fn arc_under_two_parents() {
+        let err = both();
+
+        let unmarked = render(&err, FormatOptions::new());
+        assert!(!unmarked.contains("(shared with"), "{unmarked}");
+
+        let marked = render(&err, FormatOptions::new().mark_shared(true));
+        assert_eq!(marked.matches("╰─▶ leaf\n").count(), 1, "{marked}");
+        assert_eq!(
+            marked
+                .matches("╰─▶ leaf (shared with an earlier occurrence)\n")
+                .count(),
+            1,
+            "{marked}"
+        );
+        // The shared subtree still renders under both parents
+        assert_eq!(marked.matches("unexpected end of file").count(), 2);
+        assert!(marked.find("parent 2").unwrap() < marked.find("(shared").unwrap());
+    }
+
+    #[test]
+    fn distinct_leaves() {
+        let leaf = || Arc::new(Leaf::_tree(io::ErrorKind::UnexpectedEof.into()));
+        let err = Both::_tree(Parent::_tree(1, leaf()), Parent::_tree(2, leaf()));
+
+        let marked = render(&err, FormatOptions::new().mark_shared(true));
+        assert!(!marked.contains("(shared with"), "{marked}");
+    }
+
+    #[test]
+    fn dedup_takes_precedence() {
+        let options = FormatOptions::new()
+            .mark_shared(true)
+            .dedup_by_identity(true);
+        let out = render(both(), options);
+        assert!(!out.contains("(shared with"), "{out}");
+        assert_eq!(out.matches("(see above: leaf)").count(), 1, "{out}");
+    }
+}
+
+mod sanitize {
+    use std::{error::Error, io};
+
+    use bare_err_tree::{front_max_for_depth, print_tree, print_tree_sanitized};
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("{0}")]
+    struct Outer(String, #[source] io::Error);
+
+    fn outer(msg: &str, leaf: &str) -> Outer {
+        Outer(msg.to_string(), io::Error::other(leaf.to_string()))
+    }
+
+    #[test]
+    fn strips_controls() {
+        let hostile = outer(
+            "open \x1b]0;title\x07file\u{9b}2J",
+            "first\x1b[31m line\r\nsecond\tline\nthird",
+        );
+        let clean = outer("open ]0;titlefile2J", "first[31m line\nsecondline\nthird");
+
+        let mut sanitized = String::new();
+        print_tree_sanitized::<{ front_max_for_depth(10) }, _, _>(
+            &hostile as &dyn Error,
+            &mut sanitized,
+        )
+        .unwrap();
+
+        let mut expected = String::new();
+        print_tree::<{ front_max_for_depth(10) }, _, _>(&clean as &dyn Error, &mut expected)
+            .unwrap();
+
+        assert_eq!(sanitized, expected);
+        assert!(!sanitized.chars().any(|c| c.is_control() && c != '\n'));
+        assert!(sanitized.ends_with("╰─▶ first[31m line\n    │ secondline\n    │ third"));
+    }
+
+    #[test]
+    fn off_by_default() {
+        let hostile = outer("bell\x07", "leaf");
+
+        let mut out = String::new();
+        print_tree::<{ front_max_for_depth(10) }, _, _>(&hostile as &dyn Error, &mut out).unwrap();
+        assert!(out.starts_with("bell\x07"));
+    }
+}
+
+mod cycle {
+    use core::fmt::{self, Display, Formatter};
+    use std::{
+        error::Error,
+        sync::{Arc, OnceLock},
+    };
+
+    use bare_err_tree::{front_max_for_depth, print_tree};
+
+    /// Error that reports `next` as its source, which may loop back.
+    #[derive(Debug)]
+    struct Node {
+        name: &'static str,
+        next: OnceLock<Arc<Node>>,
+    }
+
+    impl Error for Node {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            self.next.get().map(|next| &**next as _)
+        }
+    }
+    impl Display for Node {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.write_str(self.name)
+        }
+    }
+
+    fn node(name: &'static str) -> Arc<Node> {
+        Arc::new(Node {
+            name,
+            next: OnceLock::new(),
+        })
+    }
+
+    #[test]
+    fn arc_cycle() {
+        let (first, second) = (node("first"), node("second"));
+        first.next.set(second.clone()).unwrap();
+        second.next.set(first.clone()).unwrap();
+
+        let mut out = String::new();
+        print_tree::<{ front_max_for_depth(100) }, _, _>(&*first as &dyn Error, &mut out).unwrap();
+
+        assert_eq!(out.matches("<cycle detected, see above>").count(), 1);
+        assert!(out.ends_with("╰─▶ <cycle detected, see above>"));
+        assert_eq!(out.matches("second").count(), 1);
+        assert!(!out.contains("truncated"));
+
+        // Break the cycle so both nodes are freed
+        drop(Arc::into_inner(first).map(|node| node.next));
+    }
+
+    #[test]
+    fn self_source() {
+        let only = node("only");
+        only.next.set(only.clone()).unwrap();
+
+        let mut out = String::new();
+        print_tree::<{ front_max_for_depth(100) }, _, _>(&*only as &dyn Error, &mut out).unwrap();
+        assert_eq!(out.matches("<cycle detected, see above>").count(), 1);
+        assert!(out.ends_with("╰─▶ <cycle detected, see above>"));
+    }
+}
+
+#[cfg(feature = "derive")]
+mod builder {
+    use core::fmt::{self, Debug, Display, Formatter};
+
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree};
+
+    #[err_tree(builder)]
+    #[derive(Debug)]
+    struct Request<T: Debug> {
+        method: &'static str,
+        path: String,
+        status: u16,
+        body: T,
+        #[dyn_err]
+        io: std::io::Error,
+        #[err_tree(default)]
+        retries: u32,
+    }
+
+    impl<T: Debug> core::error::Error for Request<T> {}
+    impl<T: Debug> Display for Request<T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "{} {} returned {} with {:?} after {} retries",
+                self.method, self.path, self.status, self.body, self.retries
+            )
+        }
+    }
+
+    #[test]
+    fn six_fields() {
+        #[cfg_attr(not(feature = "source_line"), expect(unused_variables))]
+        let line = line!() + 8;
+        let err = Request::_tree_builder()
+            .status(503)
+            .path("/upload".to_string())
+            .method("PUT")
+            .io(std::io::ErrorKind::UnexpectedEof.into())
+            .body([1, 2])
+            // Location is captured here
+            .build();
+
+        let mut out = String::new();
+        print_tree::<{ front_max_for_depth(10) }, _, _>(&err, &mut out).unwrap();
+        assert!(out.starts_with("PUT /upload returned 503 with [1, 2] after 0 retries"));
+        assert!(out.contains("unexpected end of file"));
+
+        #[cfg(feature = "source_line")]
+        assert!(out.contains(&format!("tests/shared.rs:{line}:")), "{out}");
+    }
+
+    #[test]
+    #[should_panic(expected = "missing field `io`")]
+    fn missing_field() {
+        let _ = Request::_tree_builder()
+            .method("GET")
+            .path("/".to_string())
+            .status(404)
+            .body(())
+            .build();
+    }
+}
+
+#[cfg(feature = "derive")]
+mod field_defaults {
+    use core::fmt::{self, Display, Formatter};
+
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree};
+
+    #[err_tree(default)]
+    #[derive(Debug)]
+    struct Named {
+        path: String,
+        attempts: u32,
+    }
+
+    #[err_tree(default)]
+    #[derive(Debug)]
+    struct Tuple(u8, Option<&'static str>);
+
+    /// Without `default`, a user [`Default`] does not conflict.
+    #[err_tree]
+    #[derive(Debug)]
+    struct Manual {
+        attempts: u32,
+    }
+
+    impl Default for Manual {
+        #[track_caller]
+        fn default() -> Self {
+            Self::_tree(3)
+        }
+    }
+
+    impl core::error::Error for Named {}
+    impl core::error::Error for Tuple {}
+    impl core::error::Error for Manual {}
+    impl Display for Named {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "read {:?} {} times", self.path, self.attempts)
+        }
+    }
+    impl Display for Tuple {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "tuple {} {:?}", self.0, self.1)
+        }
+    }
+    impl Display for Manual {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "manual {}", self.attempts)
+        }
+    }
+
+    #[test]
+    fn generated() {
+        #[cfg_attr(not(feature = "source_line"), expect(unused_variables))]
+        let line = line!() + 1;
+        let err = Named::default();
+        assert_eq!(err.to_string(), r#"read "" 0 times"#);
+        assert_eq!(Tuple::default().to_string(), "tuple 0 None");
+
+        let mut out = String::new();
+        print_tree::<{ front_max_for_depth(2) }, _, _>(&err, &mut out).unwrap();
+        #[cfg(feature = "source_line")]
+        assert!(out.contains(&format!("tests/shared.rs:{line}:")), "{out}");
+    }
+
+    #[test]
+    fn user_impl() {
+        assert_eq!(Manual::default().to_string(), "manual 3");
+    }
+}
+
+#[cfg(feature = "derive")]
+mod collapse {
+    use std::io;
+
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree_with, AsErrTree, FormatOptions};
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("outer")]
+    struct Outer {
+        #[dyn_err(collapse)]
+        #[source]
+        middle: Middle,
+        #[tree_err(collapse)]
+        leaf: Leaf,
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("middle")]
+    struct Middle(#[source] Inner);
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("inner")]
+    struct Inner(#[source] io::Error);
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("leaf")]
+    struct Leaf {
+        #[dyn_err]
+        io: io::Error,
+    }
+
+    fn render<E: AsErrTree>(err: E, expand: bool) -> String {
+        let mut out = String::new();
+        let options = FormatOptions::new().expand_collapsed(expand);
+        print_tree_with::<{ front_max_for_depth(10) }, _, _>(err, options, &mut out).unwrap();
+        out
+    }
+
+    fn error() -> Outer {
+        Outer::_tree(
+            Middle(Inner(io::ErrorKind::UnexpectedEof.into())),
+            Leaf::_tree(io::ErrorKind::NotFound.into()),
+        )
+    }
+
+    #[test]
+    fn count() {
+        let out = render(error(), false);
+        assert!(out.contains("middle\n│   │\n│   ╰─ 2 nested error(s) collapsed\n"));
+        assert!(out.ends_with("    │\n    ╰─ 1 nested error(s) collapsed"));
+        assert!(!out.contains("inner"));
+        assert!(!out.contains("entity not found"));
+    }
+
+    #[test]
+    fn expanded() {
+        let out = render(error(), true);
+        assert!(out.contains("inner"));
+        assert!(out.contains("unexpected end of file"));
+        assert!(out.contains("entity not found"));
+        assert!(!out.contains("collapsed"));
+    }
+}
+
+#[cfg(feature = "derive")]
+mod declaration_order {
+    use std::io;
+
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree};
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("outer")]
+    struct Outer {
+        #[dyn_err]
+        first: io::Error,
+        #[tree_err]
+        second: Leaf,
+        #[dyn_iter_err]
+        third: [io::Error; 1],
+        #[dyn_err]
+        fourth: io::Error,
+        #[tree_iter_err]
+        fifth: [Leaf; 1],
+    }
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("{0}")]
+    struct Leaf(&'static str);
+
+    #[test]
+    fn interleaved() {
+        let err = Outer::_tree(
+            io::Error::other("first"),
+            Leaf::_tree("second"),
+            [io::Error::other("third")],
+            io::Error::other("fourth"),
+            [Leaf::_tree("fifth")],
+        );
+
+        let mut out = String::new();
+        print_tree::<{ front_max_for_depth(10) }, _, _>(&err, &mut out).unwrap();
+
+        let order: Vec<_> = out
+            .lines()
+            .filter_map(|line| line.split_once("▶ ").map(|(_, msg)| msg))
+            .collect();
+        assert_eq!(order, ["first", "second", "third", "fourth", "fifth"]);
+    }
+}
+
+#[cfg(feature = "derive")]
+mod debug_summary {
+    use std::io;
+
+    use bare_err_tree::{err_tree, COMPACT_SUMMARY_MSG_LEN, COMPACT_SUMMARY_NODES};
+
+    #[err_tree(debug_summary)]
+    #[derive(Debug, thiserror::Error)]
+    #[error("{msg}")]
+    struct Node {
+        msg: String,
+        #[dyn_iter_err]
+        leaves: [io::Error; 3],
+    }
+
+    #[err_tree(NestedWrap, debug_summary)]
+    #[derive(Debug, thiserror::Error)]
+    enum Nested {
+        #[error("nested")]
+        #[tree_err]
+        Node(Node),
+    }
+
+    fn node(msg: &str) -> Node {
+        Node::_tree(
+            msg.to_string(),
+            core::array::from_fn(|idx| io::Error::other(format!("leaf {idx}"))),
+        )
+    }
+
+    #[test]
+    fn summary() {
+        assert_eq!(
+            node("root\nsecond line").tree_summary().to_string(),
+            "root\n  leaf 0\n  leaf 1\n  leaf 2"
+        );
+
+        let wrapped: NestedWrap = Nested::Node(node("root")).into();
+        assert_eq!(
+            wrapped.tree_summary().to_string(),
+            "nested\n  root\n    leaf 0\n    leaf 1\n    leaf 2"
+        );
+    }
+
+    #[test]
+    fn node_cap() {
+        #[err_tree(debug_summary)]
+        #[derive(Debug, thiserror::Error)]
+        #[error("wide")]
+        struct Wide {
+            #[tree_iter_err]
+            nodes: [Node; 4],
+        }
+
+        let err = Wide::_tree(core::array::from_fn(|idx| node(&format!("node {idx}"))));
+        let summary = err.tree_summary().to_string();
+        let lines: Vec<_> = summary.lines().collect();
+
+        assert_eq!(lines.len(), COMPACT_SUMMARY_NODES + 1);
+        assert_eq!(lines.last(), Some(&"..."));
+        assert_eq!(lines[COMPACT_SUMMARY_NODES - 1], "  node 2");
+    }
+
+    #[test]
+    fn msg_truncation() {
+        let long = "é".repeat(COMPACT_SUMMARY_MSG_LEN);
+        let summary = node(&long).tree_summary().to_string();
+        let first = summary.lines().next().unwrap();
+
+        let cut = first.strip_suffix("...").unwrap();
+        assert!(cut.len() <= COMPACT_SUMMARY_MSG_LEN);
+        assert_eq!(cut, "é".repeat(COMPACT_SUMMARY_MSG_LEN / 2));
+
+        let exact = "a".repeat(COMPACT_SUMMARY_MSG_LEN);
+        let summary = node(&exact).tree_summary().to_string();
+        assert_eq!(summary.lines().next(), Some(exact.as_str()));
+    }
+}
+
+#[cfg(feature = "derive")]
+mod display_from_doc {
+    use std::{error::Error, io};
+
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree};
+
+    /// Failed to read {path} after {attempts} attempts
+    ///
+    /// Only the first line is used.
+    #[err_tree(display_from_doc)]
+    #[derive(Debug)]
+    struct Read {
+        path: &'static str,
+        attempts: u32,
+        #[dyn_err]
+        io: io::Error,
+    }
+    impl Error for Read {}
+
+    /// Code {0} ({{raw}})
+    #[err_tree(display_from_doc)]
+    #[derive(Debug)]
+    struct Code(u8, &'static str);
+    impl Error for Code {}
+
+    /// Unit failure
+    #[err_tree(display_from_doc)]
+    #[derive(Debug)]
+    struct Unit;
+    impl Error for Unit {}
+
+    #[err_tree(KindWrap, display_from_doc)]
+    #[derive(Debug)]
+    enum Kind {
+        /// Timed out after {0}s
+        Timeout(u32),
+        /// Refused by {host}
+        Refused { host: &'static str },
+        /// Unknown
+        #[dyn_err]
+        Other(io::Error),
+    }
+    impl Error for Kind {}
+
+    #[test]
+    fn structs() {
+        let err = Read::_tree("a.toml", 3, io::ErrorKind::NotFound.into());
+        assert_eq!(err.to_string(), "Failed to read a.toml after 3 attempts");
+
+        let mut out = String::new();
+        print_tree::<{ front_max_for_depth(10) }, _, _>(&err, &mut out).unwrap();
+        assert!(out.starts_with("Failed to read a.toml after 3 attempts"));
+        assert!(out.contains("╰─▶ entity not found"));
+
+        assert_eq!(Code::_tree(7, "unused").to_string(), "Code 7 ({raw})");
+        assert_eq!(Unit::_tree().to_string(), "Unit failure");
+    }
+
+    #[test]
+    fn enum_variants() {
+        assert_eq!(Kind::Timeout(5).to_string(), "Timed out after 5s");
+        assert_eq!(
+            Kind::Refused { host: "localhost" }.to_string(),
+            "Refused by localhost"
+        );
+
+        let wrapped: KindWrap = Kind::Other(io::ErrorKind::NotFound.into()).into();
+        assert_eq!(wrapped.to_string(), "Unknown");
+    }
+}
+
+#[cfg(feature = "derive")]
+mod infer_sources {
+    use std::io;
+
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree};
+
+    #[err_tree(infer_sources)]
+    #[derive(Debug, thiserror::Error)]
+    #[error("inferred")]
+    struct Inferred {
+        #[source]
+        io: io::Error,
+    }
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("not inferred")]
+    struct NotInferred {
+        #[source]
+        io: io::Error,
+    }
+
+    #[err_tree(infer_sources)]
+    #[derive(Debug, thiserror::Error)]
+    #[error("explicit")]
+    struct Explicit {
+        #[source]
+        #[tree_err]
+        inner: Inferred,
+    }
+
+    #[err_tree(KindWrap, infer_sources)]
+    #[derive(Debug, thiserror::Error)]
+    enum Kind {
+        #[error("io")]
+        Io(#[from] io::Error),
+        #[error("timeout")]
+        Timeout(u32),
+    }
+
+    fn render(tree: &impl bare_err_tree::AsErrTree) -> String {
+        let mut out = String::new();
+        print_tree::<{ front_max_for_depth(10) }, _, _>(tree, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn inferred() {
+        let out = render(&Inferred::_tree(io::ErrorKind::NotFound.into()));
+        assert!(out.starts_with("inferred"));
+        assert!(out.ends_with("╰─▶ entity not found"));
+    }
+
+    #[test]
+    fn requires_flag() {
+        let out = render(&NotInferred::_tree(io::ErrorKind::NotFound.into()));
+        assert!(!out.contains("─▶"));
+    }
+
+    #[test]
+    fn explicit_precedence() {
+        let inner = Inferred::_tree(io::ErrorKind::NotFound.into());
+        let out = render(&Explicit::_tree(inner));
+        assert_eq!(out.matches("─▶").count(), 2);
+        assert!(out.contains("─▶ inferred"));
+        assert!(out.ends_with("╰─▶ entity not found"));
+    }
+
+    #[test]
+    fn enum_variants() {
+        let wrapped: KindWrap = Kind::from(io::Error::from(io::ErrorKind::NotFound)).into();
+        assert!(render(&wrapped).ends_with("╰─▶ entity not found"));
+
+        let wrapped: KindWrap = Kind::Timeout(5).into();
+        let out = render(&wrapped);
+        assert!(out.starts_with("timeout"));
+        assert!(!out.contains("─▶"));
+    }
+}
+
+#[cfg(feature = "derive")]
+mod titled {
+    use std::io;
+
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree, print_tree_titled};
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("request failed")]
+    struct Request {
+        #[dyn_err]
+        io: io::Error,
+    }
+
+    #[test]
+    fn indented_under_title() {
+        let err = Request::_tree(io::ErrorKind::UnexpectedEof.into());
+
+        let mut plain = String::new();
+        print_tree::<{ front_max_for_depth(10) }, _, _>(&err, &mut plain).unwrap();
+
+        let mut titled = String::new();
+        print_tree_titled::<{ front_max_for_depth(11) }, _, _>(
+            "=== Request 42 failed ===",
+            &err,
+            &mut titled,
+        )
+        .unwrap();
+
+        let mut lines = titled.lines();
+        assert_eq!(lines.next(), Some("=== Request 42 failed ==="));
+        assert_eq!(lines.next(), Some("│"));
+
+        // The tree is the sole source, shifted one level
+        let mut plain_lines = plain.lines();
+        assert_eq!(
+            lines.next().and_then(|line| line.strip_prefix("╰─▶ ")),
+            plain_lines.next()
+        );
+        for (line, plain_line) in lines.zip(plain_lines) {
+            assert_eq!(line.strip_prefix("    "), Some(plain_line));
+        }
+    }
+
+    #[test]
+    fn sequential() {
+        let mut out = String::new();
+        for idx in 0..2 {
+            let err = Request::_tree(io::ErrorKind::UnexpectedEof.into());
+            print_tree_titled::<{ front_max_for_depth(11) }, _, _>(
+                &format!("=== Request {idx} failed ==="),
+                &err,
+                &mut out,
+            )
+            .unwrap();
+            out.push('\n');
+        }
+
+        assert_eq!(out.matches("=== Request").count(), 2);
+        assert_eq!(out.matches("╰─▶ request failed").count(), 2);
+    }
+}
+
+#[cfg(feature = "derive_alloc")]
+mod numbered {
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree, print_tree_numbered};
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("{msg}")]
+    struct Node {
+        msg: &'static str,
+        #[tree_iter_err]
+        children: Vec<Node>,
+    }
+
+    fn node(msg: &'static str, children: Vec<Node>) -> Node {
+        Node::_tree(msg, children)
+    }
+
+    fn tree() -> Node {
+        node(
+            "root",
+            vec![
+                node(
+                    "first",
+                    vec![node("first a", vec![]), node("first\nb", vec![])],
+                ),
+                node("second", vec![node("second a", vec![])]),
+            ],
+        )
+    }
+
+    /// Message lines, with glyphs removed.
+    fn messages(out: &str) -> Vec<&str> {
+        out.lines()
+            .enumerate()
+            .filter_map(|(idx, line)| match line.split_once("▶ ") {
+                Some((_, msg)) => Some(msg),
+                None => (idx == 0).then_some(line),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dotted_positions() {
+        let mut out = String::new();
+        print_tree_numbered::<{ front_max_for_depth(10) }, _, _>(tree(), &mut out).unwrap();
+
+        assert_eq!(
+            messages(&out),
+            [
+                "1 root",
+                "1.1 first",
+                "1.1.1 first a",
+                "1.1.2 first",
+                "1.2 second",
+                "1.2.1 second a"
+            ]
+        );
+
+        // Only message lines change
+        let mut plain = String::new();
+        print_tree::<{ front_max_for_depth(10) }, _, _>(tree(), &mut plain).unwrap();
+        assert_eq!(out.lines().count(), plain.lines().count());
+        for (line, plain_line) in out.lines().zip(plain.lines()) {
+            let (glyphs, msg) = line.split_once("▶ ").unwrap_or(("", line));
+            let unnumbered = match msg.split_once(' ') {
+                Some((number, msg))
+                    if !number.is_empty()
+                        && number.chars().all(|c| c.is_ascii_digit() || c == '.') =>
+                {
+                    format!("{glyphs}{}{msg}", if glyphs.is_empty() { "" } else { "▶ " })
+                }
+                _ => line.to_string(),
+            };
+            assert_eq!(unnumbered, plain_line);
+        }
+        assert!(out.contains("│ b"));
+    }
+
+    #[test]
+    fn truncated() {
+        let mut out = String::new();
+        print_tree_numbered::<{ front_max_for_depth(2) }, _, _>(tree(), &mut out).unwrap();
+
+        let rendered: Vec<_> = messages(&out)
+            .into_iter()
+            .map(|msg| msg.trim_end_matches('.'))
+            .collect();
+        assert_eq!(
+            rendered,
+            ["1 root", "1.1 first", "1.2 second", "1.2.1 second a"]
+        );
+        assert!(out
+            .lines()
+            .last()
+            .is_some_and(|line| line.starts_with("-- tree truncated")));
+    }
+}
+
+#[cfg(all(
+    feature = "derive_alloc",
+    not(feature = "source_line"),
+    not(feature = "tracing"),
+    not(feature = "timestamp")
+))]
+mod tight {
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree_with, FormatOptions};
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("{msg}")]
+    struct Node {
+        msg: &'static str,
+        #[tree_iter_err]
+        children: Vec<Node>,
+    }
+
+    fn node(msg: &'static str, children: Vec<Node>) -> Node {
+        Node::_tree(msg, children)
+    }
+
+    fn nesting() -> Node {
+        node(
+            "root",
+            vec![
+                node(
+                    "first",
+                    vec![node("first a", vec![]), node("first\nb", vec![])],
+                ),
+                node("second", vec![node("second a", vec![])]),
+            ],
+        )
+    }
+
+    fn render(tight: bool) -> String {
+        let mut out = String::new();
+        print_tree_with::<{ front_max_for_depth(10) }, _, _>(
+            nesting(),
+            FormatOptions::new().tight(tight),
+            &mut out,
+        )
+        .unwrap();
+        out
+    }
+
+    #[test]
+    fn spacious() {
+        let expected_lines = "root
+│
+├─▶ first
+│   │
+│   ├─▶ first a
+│   │
+│   ╰─▶ first
+│       │ b
+│
+╰─▶ second
+    │
+    ╰─▶ second a";
+
+        assert_eq!(render(false), expected_lines);
+    }
+
+    #[test]
+    fn tight() {
+        let expected_lines = "root
+├─▶ first
+│   ├─▶ first a
+│   ╰─▶ first
+│       │ b
+╰─▶ second
+    ╰─▶ second a";
+
+        assert_eq!(render(true), expected_lines);
+    }
+}
+
+#[cfg(all(
+    feature = "derive_alloc",
+    not(feature = "source_line"),
+    not(feature = "tracing"),
+    not(feature = "timestamp")
+))]
+mod max_children {
+    use bare_err_tree::{
+        err_tree, front_max_for_depth, print_tree, print_tree_with, FormatOptions,
+    };
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("{msg}")]
+    struct Node {
+        msg: String,
+        #[tree_iter_err]
+        children: Vec<Node>,
+    }
+
+    fn leaves(name: &str) -> Vec<Node> {
+        (1..=10)
+            .map(|idx| Node::_tree(format!("{name} {idx}"), vec![]))
+            .collect()
+    }
+
+    /// Ten attempts, where the second has ten causes of its own.
+    fn retries() -> Node {
+        let mut attempts = leaves("attempt");
+        attempts[1] = Node::_tree("attempt 2".to_string(), leaves("cause"));
+        Node::_tree("gave up".to_string(), attempts)
+    }
+
+    fn render(tree: Node, options: FormatOptions) -> String {
+        let mut out = String::new();
+        print_tree_with::<{ front_max_for_depth(10) }, _, _>(tree, options, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn elided() {
+        let expected_lines = "gave up
+│
+├─▶ attempt 1
+│
+├─▶ attempt 2
+│   │
+│   ├─▶ cause 1
+│   │
+│   ├─▶ cause 2
+│   │
+│   ├─ … 6 similar errors elided
+│   │
+│   ├─▶ cause 9
+│   │
+│   ╰─▶ cause 10
+│
+├─ … 6 similar errors elided
+│
+├─▶ attempt 9
+│
+╰─▶ attempt 10";
+
+        let out = render(retries(), FormatOptions::new().max_children(Some(4)));
+        assert_eq!(out, expected_lines);
+    }
+
+    #[test]
+    fn uneven_split() {
+        let expected_lines = "gave up
+├─▶ attempt 1
+├─▶ attempt 2
+├─▶ attempt 3
+├─ … 5 similar errors elided
+├─▶ attempt 9
+╰─▶ attempt 10";
+
+        let tree = Node::_tree("gave up".to_string(), leaves("attempt"));
+        let options = FormatOptions::new().max_children(Some(5)).tight(true);
+        assert_eq!(render(tree, options), expected_lines);
+    }
+
+    #[test]
+    fn aligned() {
+        let expected_lines = "gave up
+├─▶ attempt 1
+├── … 8 similar errors elided
+╰─▶ attempt 10";
+
+        let tree = Node::_tree("gave up".to_string(), leaves("attempt"));
+        let options = FormatOptions::new()
+            .max_children(Some(2))
+            .tight(true)
+            .aligned(true);
+        assert_eq!(render(tree, options), expected_lines);
+    }
+
+    #[test]
+    fn all_elided() {
+        let tree = Node::_tree("gave up".to_string(), leaves("attempt"));
+        let options = FormatOptions::new().max_children(Some(0)).tight(true);
+        assert_eq!(
+            render(tree, options),
+            "gave up\n╰─ … 10 similar errors elided"
+        );
+    }
+
+    #[test]
+    fn within_limit() {
+        let mut full = String::new();
+        print_tree::<{ front_max_for_depth(10) }, _, _>(retries(), &mut full).unwrap();
+
+        let options = FormatOptions::new().max_children(Some(10));
+        assert_eq!(render(retries(), options), full);
+    }
+
+    #[test]
+    fn numbered_positions() {
+        let options = FormatOptions::new()
+            .max_children(Some(4))
+            .numbered(true)
+            .tight(true);
+        let out = render(retries(), options);
+        assert!(out.contains("├─▶ 1.2.2 cause 2\n│   ├─ … 6"), "{out}");
+        assert!(out.contains("├─▶ 1.9 attempt 9"), "{out}");
+        assert!(out.ends_with("╰─▶ 1.10 attempt 10"), "{out}");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_unaffected() {
+        let json = bare_err_tree::tree_to_json_string::<Node, _>(retries()).unwrap();
+        assert_eq!(json.matches("attempt ").count(), 10);
+        assert_eq!(json.matches("cause ").count(), 10);
+    }
+}
+
+mod max_nodes {
+    use core::fmt::{self, Display, Formatter};
+    use std::error::Error;
+
+    use bare_err_tree::{
+        front_max_for_depth, print_tree, print_tree_with, AsErrTree, ErrTree, FormatOptions,
+    };
+
+    const FRONT_MAX: usize = front_max_for_depth(13);
+
+    /// Reports three fresh sources one level down, until level zero.
+    #[derive(Debug, Clone, Copy)]
+    struct Fanout(usize);
+
+    impl Display for Fanout {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "level {}", self.0)
+        }
+    }
+
+    impl Error for Fanout {}
+
+    impl AsErrTree for Fanout {
+        fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+            let sources = match self.0 {
+                0 => [].as_slice(),
+                level => &[Fanout(level - 1); 3],
+            };
+            (func)(ErrTree::no_pkg(
+                self,
+                &mut sources.iter().map(|x| x as &dyn AsErrTree),
+            ))
+        }
+    }
+
+    fn render(tree: Fanout, options: FormatOptions) -> String {
+        let mut out = String::new();
+        print_tree_with::<FRONT_MAX, _, _>(tree, options, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn exhausted() {
+        let expected_lines = "level 2
+├─▶ level 1
+│   ├─▶ level 0
+│   ├─▶ level 0
+│   ╰─▶ level 0
+… node budget (5) exhausted";
+
+        let options = FormatOptions::new().max_nodes(5).tight(true);
+        assert_eq!(render(Fanout(2), options), expected_lines);
+    }
+
+    #[test]
+    fn bounded() {
+        // 3^12 leaves in full
+        let out = render(Fanout(12), FormatOptions::new().max_nodes(10_000));
+        assert_eq!(out.matches("level ").count(), 10_000);
+        assert!(out.ends_with("\n… node budget (10000) exhausted"), "{out}");
+        // Two lines per node, each at most 13 levels of front lines wide
+        assert!(out.len() < 10_000 * 256, "{}", out.len());
+    }
+
+    #[test]
+    fn zero_renders_root() {
+        let out = render(Fanout(1), FormatOptions::new().max_nodes(0));
+        assert_eq!(out, "level 1\n… node budget (1) exhausted");
+    }
+
+    #[test]
+    fn within_budget() {
+        let mut full = String::new();
+        print_tree::<FRONT_MAX, _, _>(Fanout(3), &mut full).unwrap();
+
+        // 1 + 3 + 9 + 27 nodes
+        assert_eq!(render(Fanout(3), FormatOptions::new().max_nodes(40)), full);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json() {
+        use bare_err_tree::{tree_to_json, tree_to_json_max_nodes};
+
+        let mut json = String::new();
+        tree_to_json_max_nodes::<Fanout, _, _>(Fanout(12), 100, &mut json).unwrap();
+        assert_eq!(json.matches("\"msg\"").count(), 101);
+        assert!(json.contains(r#"{"msg":"… node budget (100) exhausted"}"#));
+        assert!(json.ends_with("]}]}]}]}]}]}]}]}]}]}]}]}"), "{json}");
+        assert_eq!(json.matches('{').count(), json.matches('}').count());
+        assert_eq!(json.matches('[').count(), json.matches(']').count());
+
+        let mut full = String::new();
+        tree_to_json::<Fanout, _, _>(Fanout(2), &mut full).unwrap();
+        let mut budgeted = String::new();
+        tree_to_json_max_nodes::<Fanout, _, _>(Fanout(2), 13, &mut budgeted).unwrap();
+        assert_eq!(budgeted, full);
+    }
+}
+
+#[cfg(feature = "derive_alloc")]
+mod msg_filter {
+    use core::fmt::{self, Display, Formatter};
+    use std::error::Error;
+
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree, print_tree_with_filter};
+
+    const FRONT_MAX: usize = front_max_for_depth(10);
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("{msg}")]
+    struct Node {
+        msg: String,
+        #[tree_iter_err]
+        children: Vec<Node>,
+    }
+
+    #[err_tree(msg_field = context)]
+    #[derive(Debug)]
+    struct Overridden {
+        context: &'static str,
+    }
+
+    impl Error for Overridden {}
+    impl Display for Overridden {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "generic failure")
+        }
+    }
+
+    fn node(msg: &str, children: Vec<Node>) -> Node {
+        Node::_tree(msg.to_string(), children)
+    }
+
+    fn tree() -> Node {
+        node(
+            "connect to db token=abc",
+            vec![
+                node("auth failed token=abc", vec![]),
+                node("retry", vec![node("token=abc expired", vec![])]),
+            ],
+        )
+    }
+
+    fn redact(f: &mut dyn fmt::Write, err: &dyn Error) -> fmt::Result {
+        write!(f, "{}", err.to_string().replace("token=abc", "token=***"))
+    }
+
+    fn render_filtered(
+        tree: impl bare_err_tree::AsErrTree,
+        filter: &dyn Fn(&mut dyn fmt::Write, &dyn Error) -> fmt::Result,
+    ) -> String {
+        let mut out = String::new();
+        print_tree_with_filter::<FRONT_MAX, _, _>(tree, &mut out, filter).unwrap();
+        out
+    }
+
+    #[test]
+    fn every_node() {
+        let out = render_filtered(tree(), &redact);
+        assert!(!out.contains("token=abc"), "{out}");
+        assert_eq!(out.matches("token=***").count(), 3, "{out}");
+    }
+
+    #[test]
+    fn display_filter_matches_default() {
+        let mut expected = String::new();
+        print_tree::<FRONT_MAX, _, _>(tree(), &mut expected).unwrap();
+
+        assert_eq!(
+            render_filtered(tree(), &|f, err| write!(f, "{err}")),
+            expected
+        );
+    }
+
+    #[cfg(not(any(feature = "source_line", feature = "tracing", feature = "timestamp")))]
+    #[test]
+    fn filtered_output() {
+        assert_eq!(
+            render_filtered(tree(), &redact),
+            "connect to db token=***
+│
+├─▶ auth failed token=***
+│
+╰─▶ retry
+    │
+    ╰─▶ token=*** expired"
+        );
+    }
+
+    #[test]
+    fn sees_override() {
+        let out = render_filtered(Overridden::_tree("reading secret.toml"), &|f, err| {
+            write!(f, "{}", err.to_string().replace("secret", "***"))
+        });
+        assert!(out.starts_with("reading ***.toml"), "{out}");
+    }
+}
+
+#[cfg(all(
+    feature = "derive",
+    not(feature = "source_line"),
+    not(feature = "tracing"),
+    not(feature = "timestamp")
+))]
+mod slice_sources {
+    use core::fmt::{self, Debug, Formatter};
+    use std::{error::Error, io};
+
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree, AsErrTree};
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("{0}")]
+    struct Leaf(&'static str);
+
+    #[err_tree]
+    #[derive(thiserror::Error)]
+    #[error("aggregate")]
+    struct Aggregate<'a> {
+        #[tree_slice_err]
+        trees: &'a [&'a dyn AsErrTree],
+        #[dyn_slice_err]
+        errs: &'a [&'a dyn Error],
+    }
+
+    #[err_tree(SubsystemWrap)]
+    #[derive(thiserror::Error)]
+    enum Subsystem<'a> {
+        #[error("trees")]
+        #[tree_slice_err]
+        Trees(&'a [&'a dyn AsErrTree]),
+        #[error("errors")]
+        #[dyn_slice_err(collapse)]
+        Errors(&'a [&'a dyn Error]),
+    }
+
+    impl Debug for Aggregate<'_> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "Aggregate")
+        }
+    }
+
+    impl Debug for Subsystem<'_> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write!(f, "Subsystem")
+        }
+    }
+
+    fn render(tree: impl AsErrTree) -> String {
+        let mut out = String::new();
+        print_tree::<{ front_max_for_depth(10) }, _, _>(tree, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn struct_slices() {
+        let (first, second) = (Leaf::_tree("first"), Leaf::_tree("second"));
+        let io = io::Error::from(io::ErrorKind::UnexpectedEof);
+        let nested = Leaf::_tree("nested");
+
+        let trees: [&dyn AsErrTree; 2] = [&first, &second];
+        let errs: [&dyn Error; 2] = [&io, &nested];
+        let err = Aggregate::_tree(&trees, &errs);
+
+        let expected_lines = "aggregate
+│
+├─▶ first
+│
+├─▶ second
+│
+├─▶ unexpected end of file
+│
+╰─▶ nested";
+        assert_eq!(render(&err), expected_lines);
+    }
+
+    #[test]
+    fn enum_slices() {
+        let leaf = Leaf::_tree("leaf");
+        let trees: [&dyn AsErrTree; 1] = [&leaf];
+        let err = SubsystemWrap::from(Subsystem::Trees(&trees));
+        assert_eq!(render(&err), "trees\n│\n╰─▶ leaf");
+
+        let io = io::Error::from(io::ErrorKind::UnexpectedEof);
+        let errs: [&dyn Error; 1] = [&io];
+        let err = SubsystemWrap::from(Subsystem::Errors(&errs));
+        assert_eq!(render(&err), "errors\n│\n╰─▶ unexpected end of file");
+    }
 }
@@ -0,0 +1,121 @@
+use core::error::Error;
+
+use bare_err_tree::{
+    front_max_for_depth, print_tree, render_tree_to_sink, FmtSink, SinkError, TreeSink,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("outer é")]
+struct Outer(#[source] Inner);
+
+#[derive(Debug, Error)]
+#[error("inner 🦀")]
+struct Inner;
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+fn expected() -> String {
+    let mut out = String::new();
+    print_tree::<FRONT_MAX, _, _>(&Outer(Inner) as &dyn Error, &mut out).unwrap();
+    out
+}
+
+/// Raw byte sink, recording each write separately.
+#[derive(Default)]
+struct Chunks(Vec<Vec<u8>>);
+
+impl TreeSink for Chunks {
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), SinkError> {
+        self.0.push(bytes.to_vec());
+        Ok(())
+    }
+}
+
+#[test]
+fn raw_bytes() {
+    let mut sink = Chunks::default();
+    render_tree_to_sink::<FRONT_MAX, _, _>(&(&Outer(Inner) as &dyn Error), &mut sink).unwrap();
+
+    // Whole strings are passed through, so no write splits a char
+    for chunk in &sink.0 {
+        assert!(core::str::from_utf8(chunk).is_ok());
+    }
+    assert_eq!(sink.0.concat(), expected().as_bytes());
+}
+
+/// Forwards one byte per write, splitting every multibyte char.
+struct Bytewise<S>(S);
+
+impl<S: TreeSink> TreeSink for Bytewise<S> {
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), SinkError> {
+        bytes
+            .iter()
+            .try_for_each(|byte| self.0.write_bytes(core::slice::from_ref(byte)))
+    }
+}
+
+#[test]
+fn split_glyphs() {
+    let mut sink = Bytewise(FmtSink::new(String::new()));
+    render_tree_to_sink::<FRONT_MAX, _, _>(&(&Outer(Inner) as &dyn Error), &mut sink).unwrap();
+    assert_eq!(sink.0.finish().unwrap(), expected());
+
+    // Chunks that split a 4 byte char at every position
+    let crab = "🦀".as_bytes();
+    for split in 1..crab.len() {
+        let mut sink = FmtSink::new(String::new());
+        sink.write_bytes(b"a").unwrap();
+        sink.write_bytes(&crab[..split]).unwrap();
+        sink.write_bytes(&crab[split..]).unwrap();
+        sink.write_bytes(b"b").unwrap();
+        assert_eq!(sink.finish().unwrap(), "a🦀b");
+    }
+}
+
+#[test]
+fn invalid_utf8() {
+    let mut sink = FmtSink::new(String::new());
+    assert_eq!(sink.write_bytes(b"ok\xFFno"), Err(SinkError));
+
+    let mut sink = FmtSink::new(String::new());
+    sink.write_bytes(&"é".as_bytes()[..1]).unwrap();
+    assert_eq!(sink.write_bytes(b"x"), Err(SinkError));
+
+    // A char left incomplete at the end
+    let mut sink = FmtSink::new(String::new());
+    sink.write_bytes(&"─".as_bytes()[..2]).unwrap();
+    assert_eq!(sink.finish(), Err(SinkError));
+}
+
+#[test]
+fn failing_sink() {
+    struct Full;
+    impl TreeSink for Full {
+        fn write_bytes(&mut self, _: &[u8]) -> Result<(), SinkError> {
+            Err(SinkError)
+        }
+    }
+
+    assert_eq!(
+        render_tree_to_sink::<FRONT_MAX, _, _>(&(&Outer(Inner) as &dyn Error), Full),
+        Err(SinkError)
+    );
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use super::*;
+
+    use bare_err_tree::{render_json_to_sink, tree_to_json};
+
+    #[test]
+    fn matches_string() {
+        let mut expected = String::new();
+        tree_to_json::<dyn Error, _, _>(&Outer(Inner) as &dyn Error, &mut expected).unwrap();
+
+        let mut sink = Bytewise(FmtSink::new(String::new()));
+        render_json_to_sink(&(&Outer(Inner) as &dyn Error), &mut sink).unwrap();
+        assert_eq!(sink.0.finish().unwrap(), expected);
+    }
+}
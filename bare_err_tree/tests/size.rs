@@ -0,0 +1,162 @@
+#![cfg(all(feature = "derive", target_pointer_width = "64"))]
+
+use core::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    mem::size_of,
+};
+
+use bare_err_tree::{assert_err_tree_size, err_tree, front_max_for_depth, print_tree, ErrTreePkg};
+
+/// Expected [`ErrTreePkg`] size for the enabled features.
+const PKG: usize = if cfg!(all(
+    feature = "boxed",
+    any(feature = "tracing", feature = "timestamp")
+)) {
+    8
+} else {
+    let mut size = 0;
+    if cfg!(feature = "source_line") {
+        size += 8;
+    }
+    if cfg!(feature = "tracing") {
+        size += 40;
+    }
+    if cfg!(feature = "timestamp") {
+        size += 16;
+    }
+    size
+};
+
+#[err_tree]
+#[derive(Debug, Clone)]
+struct Leaf {
+    code: u64,
+}
+
+impl Error for Leaf {}
+impl Display for Leaf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code)
+    }
+}
+
+#[err_tree(SmallWrap)]
+#[derive(Debug)]
+enum Small {
+    Code(u32),
+    #[dyn_err]
+    Io(std::io::Error),
+}
+
+impl Error for Small {}
+impl Display for Small {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Code(code) => write!(f, "{code}"),
+            Self::Io(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+#[cfg(not(feature = "timestamp"))]
+assert_err_tree_size!(ErrTreePkg, max = 48);
+#[cfg(not(feature = "timestamp"))]
+assert_err_tree_size!(Leaf, max = 56);
+#[cfg(not(feature = "timestamp"))]
+assert_err_tree_size!(SmallWrap, max = 64);
+
+#[cfg(feature = "timestamp")]
+assert_err_tree_size!(ErrTreePkg, max = 64);
+#[cfg(feature = "timestamp")]
+assert_err_tree_size!(Leaf, max = 72);
+#[cfg(feature = "timestamp")]
+assert_err_tree_size!(SmallWrap, max = 80);
+
+#[test]
+fn pkg_size() {
+    assert_eq!(size_of::<ErrTreePkg>(), PKG);
+}
+
+#[test]
+fn fixture_sizes() {
+    let small = [
+        SmallWrap::from(Small::Code(0)),
+        SmallWrap::from(Small::Io(std::io::ErrorKind::Other.into())),
+    ];
+    assert_eq!(small[0].to_string(), "0");
+    assert_eq!(Leaf::_tree(1).to_string(), "1");
+
+    assert_eq!(size_of::<Leaf>(), size_of::<u64>() + PKG);
+    assert_eq!(size_of::<SmallWrap>(), size_of::<Small>() + PKG);
+}
+
+/// Inline and boxed packages render the same tree.
+#[test]
+fn render_independent_of_boxing() {
+    let leaf = Leaf::_tree(7);
+    let line = line!() - 1;
+
+    let mut out = String::new();
+    print_tree::<{ front_max_for_depth(2) }, _, _>(&leaf, &mut out).unwrap();
+
+    let mut cloned = String::new();
+    print_tree::<{ front_max_for_depth(2) }, _, _>(leaf.clone(), &mut cloned).unwrap();
+    assert_eq!(out, cloned);
+
+    let mut lines = out.lines();
+    let first = lines.next();
+    #[cfg(not(feature = "type_name"))]
+    assert_eq!(first, Some("7"));
+    #[cfg(feature = "type_name")]
+    assert!(first.is_some_and(|line| line.starts_with("7  ")), "{out}");
+    if cfg!(feature = "source_line") {
+        assert!(lines
+            .next()
+            .is_some_and(|loc| loc.contains(&format!("tests/size.rs:{line}:"))));
+    }
+    if !cfg!(any(
+        feature = "source_line",
+        feature = "tracing",
+        feature = "timestamp"
+    )) {
+        assert_eq!(lines.next(), None);
+    }
+}
+
+/// A chain of `depth` errors, through [`Error::source`].
+#[derive(Debug)]
+struct Chain(Option<Box<Chain>>);
+
+impl Error for Chain {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.as_deref().map(|next| next as _)
+    }
+}
+impl Display for Chain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "chain")
+    }
+}
+
+/// Render buffers are allocated once, not per level of depth.
+///
+/// A `FRONT_MAX` sized buffer per level would take ~5 MiB here with `tracing`.
+#[test]
+fn deep_render_stack() {
+    const DEPTH: usize = 100;
+
+    let chain = (1..DEPTH).fold(Chain(None), |next, _| Chain(Some(Box::new(next))));
+    let out = std::thread::Builder::new()
+        .stack_size(2 * 1024 * 1024)
+        .spawn(move || {
+            let mut out = String::new();
+            print_tree::<{ front_max_for_depth(1024) }, _, _>(&chain as &dyn Error, &mut out)
+                .unwrap();
+            out
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(out.matches("chain").count(), DEPTH);
+}
@@ -0,0 +1,80 @@
+use core::error::Error;
+
+use bare_err_tree::{front_max_for_depth, print_tree, render_tree_to_slice};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("outer é")]
+struct Outer(#[source] Inner);
+
+#[derive(Debug, Error)]
+#[error("inner")]
+struct Inner;
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+fn expected() -> String {
+    let mut out = String::new();
+    print_tree::<FRONT_MAX, _, _>(&Outer(Inner) as &dyn Error, &mut out).unwrap();
+    out
+}
+
+fn render(buf: &mut [u8]) -> Result<usize, usize> {
+    render_tree_to_slice::<FRONT_MAX, _>(&(&Outer(Inner) as &dyn Error), buf)
+}
+
+#[test]
+fn exact_fit() {
+    let expected = expected();
+    let mut buf = vec![0; expected.len()];
+    assert_eq!(render(&mut buf), Ok(expected.len()));
+    assert_eq!(buf, expected.as_bytes());
+
+    let mut larger = vec![0xFF; expected.len() + 4];
+    assert_eq!(render(&mut larger), Ok(expected.len()));
+    assert_eq!(&larger[..expected.len()], expected.as_bytes());
+    assert_eq!(&larger[expected.len()..], [0xFF; 4]);
+}
+
+#[test]
+fn undersized() {
+    let expected = expected();
+    let mut buf = vec![0; expected.len() - 1];
+    assert_eq!(render(&mut buf), Err(expected.len()));
+    assert_eq!(buf, expected.as_bytes()[..expected.len() - 1]);
+
+    assert_eq!(render(&mut []), Err(expected.len()));
+}
+
+#[test]
+fn char_boundary() {
+    // Cuts inside the two byte 'é'
+    let mut buf = [0xFF; "outer é".len() - 1];
+    assert_eq!(render(&mut buf), Err(expected().len()));
+    assert_eq!(&buf[.."outer ".len()], b"outer ");
+    assert_eq!(buf["outer ".len()..], [0xFF]);
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use core::error::Error;
+
+    use bare_err_tree::{render_json_to_slice, tree_to_json};
+
+    use super::{Inner, Outer};
+
+    #[test]
+    fn json_fit() {
+        let err = &Outer(Inner) as &dyn Error;
+        let mut expected = String::new();
+        tree_to_json::<&dyn Error, _, _>(err, &mut expected).unwrap();
+
+        let mut buf = vec![0; expected.len()];
+        assert_eq!(render_json_to_slice(&err, &mut buf), Ok(expected.len()));
+        assert_eq!(buf, expected.as_bytes());
+
+        let mut small = vec![0; expected.len() / 2];
+        assert_eq!(render_json_to_slice(&err, &mut small), Err(expected.len()));
+        assert_eq!(small, expected.as_bytes()[..expected.len() / 2]);
+    }
+}
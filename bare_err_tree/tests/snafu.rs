@@ -0,0 +1,75 @@
+#![cfg(all(feature = "snafu", not(feature = "unix_color")))]
+
+use std::io;
+
+use bare_err_tree::{front_max_for_depth, print_tree, snafu_tree, AsErrTree, ErrTree, ErrTreePkg};
+use snafu::{IntoError, Snafu};
+
+#[derive(Debug, Snafu)]
+enum Multi {
+    #[snafu(display("io failed"))]
+    Io {
+        source: io::Error,
+        #[snafu(implicit)]
+        pkg: ErrTreePkg,
+    },
+    #[snafu(display("both failed"))]
+    Both {
+        first: Box<Multi>,
+        second: Box<Multi>,
+    },
+    #[snafu(display("gave up"))]
+    GaveUp,
+}
+
+impl AsErrTree for Multi {
+    fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+        snafu_tree!(func, self, {
+            Multi::Io { source, pkg } => with pkg, [dyn source],
+            Multi::Both { first, second } => [tree &**first, tree &**second],
+            Multi::GaveUp => [],
+        })
+    }
+}
+
+fn print(err: impl AsErrTree) -> String {
+    let mut out = String::new();
+    print_tree::<{ front_max_for_depth(10) }, _, _>(err, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn multiple_sources() {
+    let err = Multi::Both {
+        first: Box::new(IoSnafu.into_error(io::ErrorKind::NotFound.into())),
+        second: Box::new(GaveUpSnafu.build()),
+    };
+
+    let out = print(&err);
+    let lines: Vec<_> = out
+        .lines()
+        .filter(|line| !line.contains(" at ") && line.contains(|c| !matches!(c, '│' | ' ')))
+        .collect();
+    assert_eq!(
+        lines,
+        [
+            "both failed",
+            "├─▶ io failed",
+            "│   ╰─▶ entity not found",
+            "╰─▶ gave up",
+        ]
+    );
+
+    #[cfg(feature = "source_line")]
+    assert_eq!(out.matches("tests/snafu.rs").count(), 1);
+}
+
+#[test]
+fn whatever() {
+    let err: snafu::Whatever = snafu::FromString::with_source(
+        Box::new(io::Error::from(io::ErrorKind::NotFound)) as Box<_>,
+        "while loading".to_string(),
+    );
+
+    assert_eq!(print(&err), "while loading\n│\n╰─▶ entity not found");
+}
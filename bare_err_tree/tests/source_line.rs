@@ -0,0 +1,89 @@
+#![cfg(all(
+    feature = "source_line",
+    feature = "derive_alloc",
+    not(any(
+        feature = "tracing",
+        feature = "timestamp",
+        feature = "hyperlinks",
+        feature = "unix_color",
+        feature = "type_name"
+    ))
+))]
+
+use bare_err_tree::{err_tree, print_tree_with, FormatOptions};
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("{msg}")]
+struct Node {
+    msg: &'static str,
+    #[tree_iter_err]
+    children: Vec<Node>,
+}
+
+/// Every error built here reports the same location.
+fn node(msg: &'static str, children: Vec<Node>) -> Node {
+    Node::_tree(msg, children)
+}
+
+fn tree() -> Node {
+    node(
+        "failed to sync",
+        vec![
+            node("failed to fetch", vec![node("timed out", vec![])]),
+            node("failed to push", vec![]),
+        ],
+    )
+}
+
+fn render(options: FormatOptions) -> String {
+    let mut out = String::new();
+    print_tree_with::<60, _, _>(tree(), options, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn dedup() {
+    let expected = "failed to sync
+├─ at bare_err_tree/tests/source_line.rs:26:5 (seen 4 times)
+│
+├─▶ failed to fetch
+│   │
+│   ╰─▶ timed out
+│
+╰─▶ failed to push";
+
+    assert_eq!(
+        render(FormatOptions::new().dedup_source_lines(true)),
+        expected
+    );
+}
+
+#[test]
+fn off_by_default() {
+    let out = render(FormatOptions::new());
+    assert_eq!(out.matches("source_line.rs:26:5").count(), 4);
+    assert!(!out.contains("seen"));
+}
+
+#[test]
+fn distinct_lines_kept() {
+    let tree = Node::_tree("outer", vec![node("inner", vec![])]);
+
+    let mut out = String::new();
+    print_tree_with::<60, _, _>(
+        tree,
+        FormatOptions::new().dedup_source_lines(true),
+        &mut out,
+    )
+    .unwrap();
+
+    assert_eq!(
+        out,
+        "outer
+├─ at bare_err_tree/tests/source_line.rs:71:16
+│
+╰─▶ inner
+    ╰─ at bare_err_tree/tests/source_line.rs:26:5"
+    );
+}
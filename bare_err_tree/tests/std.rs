@@ -1,8 +1,11 @@
 #![cfg(all(
     not(feature = "tracing"),
-    feature = "derive",
+    feature = "derive_alloc",
     feature = "source_line",
-    not(feature = "unix_color")
+    not(feature = "unix_color"),
+    not(feature = "hyperlinks"),
+    not(feature = "timestamp"),
+    not(feature = "type_name")
 ))]
 
 mod example {
@@ -0,0 +1,105 @@
+#![cfg(all(feature = "std_panic", feature = "derive_alloc"))]
+
+use std::{
+    env, io,
+    panic::{self, Location},
+    process::Command,
+    sync::Mutex,
+};
+
+use bare_err_tree::{err_tree, front_max_for_depth, print_tree, tree_unwrap, TreePanic};
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("request failed")]
+struct Request {
+    #[dyn_err]
+    io: io::Error,
+}
+
+fn request() -> Request {
+    Request::_tree(io::ErrorKind::TimedOut.into())
+}
+
+/// Payloads seen by the hook, as `(rendered, line)`.
+static SEEN: Mutex<Vec<(String, u32)>> = Mutex::new(Vec::new());
+
+#[test]
+fn hook_downcasts() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        match info.payload().downcast_ref::<TreePanic>() {
+            Some(tree_panic) => SEEN
+                .lock()
+                .unwrap()
+                .push((tree_panic.rendered.clone(), tree_panic.location.line())),
+            None => default_hook(info),
+        }
+    }));
+
+    let line = line!() + 2;
+    let payload = panic::catch_unwind(|| {
+        tree_unwrap::<FRONT_MAX, (), _>(Err(request()));
+    })
+    .unwrap_err();
+    let _ = panic::take_hook();
+
+    let mut expected = String::new();
+    print_tree::<FRONT_MAX, _, _>(request(), &mut expected).unwrap();
+    // Only the callsites of the two requests differ
+    let strip_lines = |tree: &str| -> Vec<String> {
+        tree.lines()
+            .filter(|line| !line.contains("tests/std_panic.rs"))
+            .map(str::to_string)
+            .collect()
+    };
+
+    let seen = SEEN.lock().unwrap().clone();
+    assert_eq!(seen.len(), 1);
+    assert_eq!(strip_lines(&seen[0].0), strip_lines(&expected));
+    assert_eq!(seen[0].1, line);
+
+    let tree_panic = payload.downcast::<TreePanic>().unwrap();
+    assert_eq!(tree_panic.location.file(), file!());
+    assert_eq!(tree_panic.rendered, seen[0].0);
+}
+
+#[test]
+fn display_matches_message() {
+    let location = Location::caller();
+    let tree_panic = TreePanic {
+        rendered: "request failed\n│\n╰─▶ timed out".to_string(),
+        location,
+    };
+    assert_eq!(
+        tree_panic.to_string(),
+        format!("Panic origin at: {location:#?}\nrequest failed\n│\n╰─▶ timed out")
+    );
+}
+
+#[test]
+fn tree_panic_hook_prints() {
+    // Stderr is captured within the harness, so check it from a child run
+    if env::var_os("STD_PANIC_CHILD").is_some() {
+        bare_err_tree::set_tree_panic_hook();
+        tree_unwrap::<FRONT_MAX, (), _>(Err(request()));
+    }
+
+    let out = Command::new(env::current_exe().unwrap())
+        .args(["tree_panic_hook_prints", "--exact", "--nocapture"])
+        .env("STD_PANIC_CHILD", "1")
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(
+        stderr.contains("' panicked at bare_err_tree/tests/std_panic.rs:"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("Panic origin at: Location {"), "{stderr}");
+    assert!(stderr.contains("╰─▶ timed out"), "{stderr}");
+    assert!(!stderr.contains("Box<dyn Any>"), "{stderr}");
+}
@@ -0,0 +1,64 @@
+#![cfg(all(feature = "derive_alloc", not(feature = "unix_color")))]
+
+use std::{io, mem::size_of};
+
+use bare_err_tree::{err_tree, print_tree, ErrTreePkg};
+
+/// Declares `$name` with identical fields and sources, stubbed when `$cfg`.
+macro_rules! job_err {
+    ($name:ident, $cfg:meta) => {
+        #[err_tree(stub_when($cfg))]
+        #[derive(Debug, thiserror::Error)]
+        #[error("job {id} failed")]
+        struct $name {
+            id: u32,
+            #[source]
+            #[dyn_err]
+            cause: io::Error,
+            #[dyn_iter_err]
+            retries: Vec<io::Error>,
+        }
+
+        impl $name {
+            #[track_caller]
+            fn new(id: u32) -> Self {
+                Self::_tree(
+                    id,
+                    io::ErrorKind::TimedOut.into(),
+                    vec![io::ErrorKind::ConnectionReset.into()],
+                )
+            }
+        }
+    };
+}
+
+job_err!(Tracked, any());
+job_err!(Stubbed, all());
+
+fn render<E: bare_err_tree::AsErrTree>(err: E) -> String {
+    let mut out = String::new();
+    print_tree::<60, _, _>(err, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn sizes() {
+    let fields = size_of::<(u32, io::Error, Vec<io::Error>)>();
+    assert_eq!(size_of::<Stubbed>(), fields);
+    assert!(size_of::<Tracked>() >= fields + size_of::<ErrTreePkg>());
+}
+
+#[test]
+fn stub_renders_as_dyn() {
+    let err = Stubbed::new(7);
+    assert_eq!(err.id, 7);
+    assert_eq!(err.retries.len(), 1);
+    assert_eq!(render(err), "job 7 failed\n│\n╰─▶ timed out");
+}
+
+#[test]
+fn tracked_renders_annotations() {
+    let out = render(Tracked::new(7));
+    assert!(out.starts_with("job 7 failed"), "{out}");
+    assert!(out.contains("connection reset"), "{out}");
+}
@@ -0,0 +1,171 @@
+#![cfg(all(feature = "timestamp", feature = "derive_alloc"))]
+
+use std::{thread::sleep, time::Duration};
+
+use bare_err_tree::{err_tree, front_max_for_depth, print_tree, print_tree_with, FormatOptions};
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("{msg}")]
+struct Node {
+    msg: &'static str,
+    #[tree_iter_err]
+    children: Vec<Node>,
+}
+
+/// Rendered timestamps, in output order.
+fn timestamps(out: &str) -> Vec<&str> {
+    out.lines()
+        .filter_map(|line| line.split_once("─ at ").map(|(_, at)| at))
+        .filter(|at| at.len() == "2024-05-02T10:31:04Z".len() && at.ends_with('Z'))
+        .collect()
+}
+
+#[test]
+fn leaf_before_root() {
+    let leaf = Node::_tree("leaf", vec![]);
+    let root = Node::_tree("root", vec![leaf]);
+
+    let mut out = String::new();
+    print_tree::<FRONT_MAX, _, _>(&root, &mut out).unwrap();
+
+    let times = timestamps(&out);
+    assert_eq!(times.len(), 2, "{out}");
+    // RFC 3339 times with four digit years sort lexicographically
+    assert!(times[0] >= times[1], "{out}");
+    assert!(times[0].starts_with("20"), "{out}");
+}
+
+#[cfg(not(any(feature = "tracing", feature = "source_line")))]
+#[test]
+fn layout() {
+    let root = Node::_tree("root", vec![Node::_tree("leaf", vec![])]);
+
+    let mut out = String::new();
+    print_tree::<FRONT_MAX, _, _>(&root, &mut out).unwrap();
+
+    let mut lines = out.lines();
+    assert_eq!(lines.next(), Some("root"));
+    assert!(lines.next().unwrap().starts_with("├─ at "));
+    assert_eq!(lines.next(), Some("│"));
+    assert_eq!(lines.next(), Some("╰─▶ leaf"));
+    assert!(lines.next().unwrap().starts_with("    ╰─ at "));
+    assert_eq!(lines.next(), None);
+}
+
+fn render_elapsed(root: &Node) -> String {
+    let mut out = String::new();
+    let options = FormatOptions::new().show_elapsed(true);
+    print_tree_with::<FRONT_MAX, _, _>(root, options, &mut out).unwrap();
+    out
+}
+
+/// Rendered elapsed times, in output order.
+fn elapsed(out: &str) -> Vec<&str> {
+    out.lines()
+        .filter_map(|line| line.split_once("─ ").map(|(_, rest)| rest))
+        .filter_map(|rest| rest.split_once(" before parent"))
+        .map(|(elapsed, _)| elapsed)
+        .collect()
+}
+
+#[test]
+fn elapsed_before_parent() {
+    let leaf = Node::_tree("leaf", vec![]);
+    sleep(Duration::from_millis(20));
+    let root = Node::_tree("root", vec![leaf]);
+
+    let out = render_elapsed(&root);
+    let times = elapsed(&out);
+    assert_eq!(times.len(), 1, "{out}");
+    match times[0].strip_suffix("ms") {
+        Some(millis) => assert!(millis.parse::<u64>().unwrap() >= 20, "{out}"),
+        None => assert!(
+            times[0].ends_with('s') && !times[0].ends_with("µs") && !times[0].ends_with("ns"),
+            "{out}"
+        ),
+    }
+
+    let mut plain = String::new();
+    print_tree::<FRONT_MAX, _, _>(&root, &mut plain).unwrap();
+    assert!(elapsed(&plain).is_empty(), "{plain}");
+}
+
+#[test]
+fn elapsed_clamped() {
+    let mut root = Node::_tree("root", vec![]);
+    sleep(Duration::from_millis(2));
+    root.children.push(Node::_tree("late", vec![]));
+
+    let out = render_elapsed(&root);
+    assert_eq!(elapsed(&out), ["0ns"], "{out}");
+    assert!(out.contains("(clamped, created "), "{out}");
+    assert!(out.ends_with(" after it)"), "{out}");
+}
+
+#[cfg(not(any(feature = "tracing", feature = "source_line")))]
+#[test]
+fn elapsed_layout() {
+    let root = Node::_tree("root", vec![Node::_tree("leaf", vec![])]);
+
+    let out = render_elapsed(&root);
+    let mut lines = out.lines();
+    assert_eq!(lines.next(), Some("root"));
+    assert!(lines.next().unwrap().starts_with("├─ at "));
+    assert_eq!(lines.next(), Some("│"));
+    assert_eq!(lines.next(), Some("╰─▶ leaf"));
+    assert!(lines.next().unwrap().starts_with("    ├─ at "));
+    let line = lines.next().unwrap();
+    assert!(line.starts_with("    ╰─ "), "{out}");
+    assert!(line.ends_with(" before parent"), "{out}");
+    assert_eq!(lines.next(), None);
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use bare_err_tree::{reconstruct_to_string, tree_to_json_string};
+
+    use super::*;
+
+    fn reconstruct_time(time: i64) -> String {
+        let json = format!("{{\"msg\":\"err\",\"time\":{time}}}");
+        reconstruct_to_string::<FRONT_MAX, _>(json).unwrap()
+    }
+
+    #[test]
+    fn formatting() {
+        assert_eq!(
+            reconstruct_time(1714645864),
+            "err\n╰─ at 2024-05-02T10:31:04Z"
+        );
+        assert_eq!(
+            reconstruct_time(951782400),
+            "err\n╰─ at 2000-02-29T00:00:00Z"
+        );
+        assert_eq!(reconstruct_time(0), "err\n╰─ at 1970-01-01T00:00:00Z");
+        assert_eq!(reconstruct_time(-1), "err\n╰─ at 1969-12-31T23:59:59Z");
+        assert_eq!(
+            reconstruct_time(253402300799),
+            "err\n╰─ at 9999-12-31T23:59:59Z"
+        );
+        assert_eq!(
+            reconstruct_time(-62135596800),
+            "err\n╰─ at 0001-01-01T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let root = Node::_tree("root", vec![Node::_tree("leaf", vec![])]);
+
+        let json = tree_to_json_string::<Node, _>(&root).unwrap();
+        assert_eq!(json.matches("\"time\":").count(), 2, "{json}");
+
+        let mut out = String::new();
+        print_tree::<FRONT_MAX, _, _>(&root, &mut out).unwrap();
+        let reconstructed = reconstruct_to_string::<FRONT_MAX, _>(&json).unwrap();
+        assert_eq!(timestamps(&reconstructed), timestamps(&out));
+    }
+}
@@ -1,8 +1,10 @@
 #![cfg(all(
     feature = "tracing",
-    feature = "derive",
+    feature = "derive_alloc",
     feature = "source_line",
-    not(feature = "unix_color")
+    not(feature = "unix_color"),
+    not(feature = "hyperlinks"),
+    not(feature = "timestamp")
 ))]
 
 mod example {
@@ -93,3 +95,209 @@ mod trailing {
         assert_eq!(gen_print(), expected_lines);
     }
 }
+
+mod dedup {
+    use bare_err_tree::{dedup_traces, err_tree, DedupTrace};
+    use tracing_error::ErrorLayer;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("outer")]
+    struct Outer {
+        #[tree_err]
+        #[source]
+        inner: Inner,
+    }
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("inner")]
+    struct Inner {}
+
+    #[tracing::instrument]
+    fn make_inner() -> Inner {
+        Inner::_tree()
+    }
+
+    #[tracing::instrument]
+    fn make_outer() -> Outer {
+        Outer::_tree(make_inner())
+    }
+
+    #[test]
+    fn new_and_repeated() {
+        let subscriber = tracing_subscriber::registry().with(ErrorLayer::default());
+        let err = tracing::subscriber::with_default(subscriber, make_outer);
+
+        let mut new = Vec::new();
+        let mut repeated = Vec::new();
+        dedup_traces::<10, _, _>(&err, |node, trace| {
+            match trace {
+                DedupTrace::New(frame) => new.push((node, frame.frame, frame.name.to_string())),
+                DedupTrace::Repeated(idx) => repeated.push((node, idx.to_vec())),
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(
+            new,
+            [
+                (0, 0, "make_outer".to_string()),
+                (1, 1, "make_inner".to_string())
+            ]
+        );
+        assert_eq!(repeated, [(1, vec![0])]);
+    }
+
+    #[test]
+    fn frame_limit() {
+        let subscriber = tracing_subscriber::registry().with(ErrorLayer::default());
+        let err = tracing::subscriber::with_default(subscriber, make_outer);
+
+        let mut frames = Vec::new();
+        dedup_traces::<1, _, _>(&err, |_, trace| {
+            if let DedupTrace::New(frame) = trace {
+                frames.push(frame.frame);
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(frames, [0, 1]);
+    }
+}
+
+mod aligned {
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree_with, FormatOptions};
+    use tracing_error::ErrorLayer;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("{msg}")]
+    struct Node {
+        msg: &'static str,
+        #[tree_iter_err]
+        children: Vec<Node>,
+    }
+
+    #[tracing::instrument]
+    fn make_root(attempt: u8) -> Node {
+        Node::_tree(
+            "root",
+            vec![Node::_tree("first", vec![]), Node::_tree("second", vec![])],
+        )
+    }
+
+    fn render(aligned: bool) -> String {
+        let subscriber = tracing_subscriber::registry().with(ErrorLayer::default());
+        let err = tracing::subscriber::with_default(subscriber, || make_root(3));
+
+        let mut out = String::new();
+        print_tree_with::<{ front_max_for_depth(10) }, _, _>(
+            &err,
+            // Split fields to check their continuation lines
+            FormatOptions::new().aligned(aligned).inline_fields(0),
+            &mut out,
+        )
+        .unwrap();
+        out
+    }
+
+    #[test]
+    fn default() {
+        let expected_lines = "root
+├─ at bare_err_tree/tests/tracing.rs:188:9
+│
+├─ tracing frame 0 => tracing::aligned::make_root with
+│    attempt=3
+│        at bare_err_tree/tests/tracing.rs:186
+│
+├─▶ first
+│   ├─ at bare_err_tree/tests/tracing.rs:190:18
+│   │
+│   ╰─ 1 duplicate tracing frame(s): [0]
+│
+╰─▶ second
+    ├─ at bare_err_tree/tests/tracing.rs:190:48
+    │
+    ╰─ 1 duplicate tracing frame(s): [0]";
+
+        assert_eq!(render(false), expected_lines);
+    }
+
+    #[test]
+    fn aligned() {
+        let expected_lines = "root
+├── at bare_err_tree/tests/tracing.rs:188:9
+│
+├── tracing frame 0 => tracing::aligned::make_root with
+│     attempt=3
+│         at bare_err_tree/tests/tracing.rs:186
+│
+├─▶ first
+│   ├── at bare_err_tree/tests/tracing.rs:190:18
+│   │
+│   ╰── 1 duplicate tracing frame(s): [0]
+│
+╰─▶ second
+    ├── at bare_err_tree/tests/tracing.rs:190:48
+    │
+    ╰── 1 duplicate tracing frame(s): [0]";
+
+        assert_eq!(render(true), expected_lines);
+    }
+}
+
+mod inline_fields {
+    use bare_err_tree::{err_tree, front_max_for_depth, print_tree_with, FormatOptions};
+    use tracing_error::ErrorLayer;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[err_tree]
+    #[derive(Debug, thiserror::Error)]
+    #[error("retry failed")]
+    struct RetryFailed {}
+
+    #[tracing::instrument]
+    fn retry(retries: u8) -> RetryFailed {
+        RetryFailed::_tree()
+    }
+
+    fn render(options: FormatOptions) -> String {
+        let subscriber = tracing_subscriber::registry().with(ErrorLayer::default());
+        let err = tracing::subscriber::with_default(subscriber, || retry(3));
+
+        let mut out = String::new();
+        print_tree_with::<{ front_max_for_depth(10) }, _, _>(&err, options, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn short_inline() {
+        let out = render(FormatOptions::new());
+        let frame = out
+            .lines()
+            .skip_while(|line| !line.contains("tracing frame"));
+        let frame: Vec<_> = frame.take(2).collect();
+        assert_eq!(
+            frame[0],
+            "├─ tracing frame 0 => tracing::inline_fields::retry with retries=3"
+        );
+        assert!(frame[1].starts_with("│        at "), "{out}");
+    }
+
+    #[test]
+    fn threshold() {
+        // "retries=3" is 9 characters
+        assert!(render(FormatOptions::new().inline_fields(10)).contains("with retries=3"));
+
+        let out = render(FormatOptions::new().inline_fields(9));
+        assert!(
+            out.contains("tracing::inline_fields::retry with\n│    retries=3"),
+            "{out}"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+#![cfg(all(feature = "type_name", feature = "derive"))]
+
+use core::fmt::{self, Display, Formatter};
+use std::{error::Error, io};
+
+use bare_err_tree::{
+    err_tree, front_max_for_depth, print_tree, AsErrTree, ErrTree, ErrTreePkg, WrapErr,
+};
+
+const FRONT_MAX: usize = front_max_for_depth(10);
+
+#[err_tree]
+#[derive(Debug, thiserror::Error)]
+#[error("missed class")]
+struct MissedClass {
+    #[dyn_err]
+    io: io::Error,
+}
+
+#[err_tree(ScheduleWrap)]
+#[derive(Debug, thiserror::Error)]
+enum Schedule {
+    #[error("schedule conflict")]
+    #[tree_err]
+    Conflict(MissedClass),
+}
+
+fn missed_class() -> MissedClass {
+    MissedClass::_tree(io::ErrorKind::UnexpectedEof.into())
+}
+
+fn render(tree: impl AsErrTree) -> String {
+    let mut out = String::new();
+    print_tree::<FRONT_MAX, _, _>(tree, &mut out).unwrap();
+    out
+}
+
+/// `msg` followed by the rendered `type_name`.
+fn named(msg: &str, type_name: &str) -> String {
+    if cfg!(feature = "unix_color") {
+        format!("{msg}  \x1b[2m[{type_name}]\x1b[0m")
+    } else {
+        format!("{msg}  [{type_name}]")
+    }
+}
+
+#[test]
+fn derived_nodes() {
+    let out = render(missed_class());
+    let mut lines = out.lines();
+    assert_eq!(
+        lines.next(),
+        Some(named("missed class", "type_name::MissedClass").as_str())
+    );
+    assert!(
+        out.lines()
+            .any(|line| line.ends_with("─▶ unexpected end of file")),
+        "{out}"
+    );
+}
+
+#[test]
+fn wrapped_type() {
+    let out = render(ScheduleWrap::from(Schedule::Conflict(missed_class())));
+    assert_eq!(
+        out.lines().next(),
+        Some(named("schedule conflict", "type_name::Schedule").as_str())
+    );
+    let missed = format!("─▶ {}", named("missed class", "type_name::MissedClass"));
+    assert!(out.lines().any(|line| line.ends_with(&missed)), "{out}");
+}
+
+#[test]
+fn plain_dyn() {
+    let err = io::Error::from(io::ErrorKind::UnexpectedEof);
+    assert_eq!(render(&err as &dyn Error), "unexpected end of file");
+    assert!(!render(WrapErr::tree(&err)).contains('['));
+}
+
+#[test]
+fn with_pkg_named() {
+    #[derive(Debug)]
+    struct Manual(ErrTreePkg);
+
+    impl Error for Manual {}
+    impl Display for Manual {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.write_str("manual")
+        }
+    }
+
+    impl AsErrTree for Manual {
+        fn as_err_tree(&self, func: &mut dyn FnMut(ErrTree<'_>)) {
+            (func)(ErrTree::with_pkg_named(
+                self,
+                &mut core::iter::empty(),
+                &self.0,
+                "my_crate::Manual",
+            ))
+        }
+    }
+
+    let out = render(Manual(ErrTreePkg::new()));
+    assert_eq!(
+        out.lines().next(),
+        Some(named("manual", "my_crate::Manual").as_str())
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json() {
+    use bare_err_tree::{reconstruct_to_string, tree_to_json_string};
+
+    let json = tree_to_json_string::<MissedClass, _>(missed_class()).unwrap();
+    assert!(
+        json.starts_with(r#"{"v":1,"msg":"missed class","type":"type_name::MissedClass""#),
+        "{json}"
+    );
+    assert_eq!(json.matches("\"type\"").count(), 1, "{json}");
+
+    let reconstructed = reconstruct_to_string::<FRONT_MAX, _>(&json).unwrap();
+    assert_eq!(reconstructed, render(missed_class()));
+}
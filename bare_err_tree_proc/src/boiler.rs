@@ -6,9 +6,9 @@
 
 use std::iter;
 
-use proc_macro::TokenStream;
+use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{Attribute, Generics, Ident, Meta};
+use syn::{ext::IdentExt, parse_quote, Attribute, Fields, Generics, Ident, Index, Meta};
 
 /// Derives intended to minimize friction introduced by the wrapper.
 ///
@@ -26,21 +26,21 @@ pub fn wrapper_boilerplate(
     let universal: TokenStream = quote! {
         #[automatically_derived]
         impl #impl_generics ::core::error::Error for #name_attribute #ty_generics #where_clause {
-            fn source(&self) -> Option<&(dyn ::core::error::Error + 'static)> {
+            fn source(&self) -> ::core::option::Option<&(dyn ::core::error::Error + 'static)> {
                 ::core::error::Error::source(&self.inner)
             }
         }
 
         #[automatically_derived]
         impl #impl_generics ::core::fmt::Debug for #name_attribute #ty_generics #where_clause {
-            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), ::core::fmt::Error> {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
                 ::core::fmt::Debug::fmt(&self.inner, f)
             }
         }
 
         #[automatically_derived]
         impl #impl_generics ::core::fmt::Display for #name_attribute #ty_generics #where_clause {
-            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> Result<(), ::core::fmt::Error> {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
                 ::core::fmt::Display::fmt(&self.inner, f)
             }
         }
@@ -49,7 +49,7 @@ pub fn wrapper_boilerplate(
         impl #impl_generics ::core::convert::From<#ident #ty_generics> for #name_attribute #ty_generics #where_clause {
             #[track_caller]
             fn from(inner: #ident #ty_generics) -> Self {
-                Self::_tree(inner)
+                <#name_attribute #ty_generics>::_tree(inner)
             }
         }
 
@@ -74,8 +74,7 @@ pub fn wrapper_boilerplate(
                 &mut self.inner
             }
         }
-    }
-    .into();
+    };
 
     // Look for viable extra derives
     let mut extra_derive = Vec::new();
@@ -96,12 +95,11 @@ pub fn wrapper_boilerplate(
     let extra_derive_tokens =
         extra_derive
             .into_iter()
-            .map(|extra| match extra.to_string().to_lowercase().as_str() {
+            .map(|extra| match extra.unraw().to_string().to_lowercase().as_str() {
                 "eq" => quote! {
                     #[automatically_derived]
                     impl #impl_generics ::core::cmp::Eq for #name_attribute #ty_generics #where_clause {}
-                }
-                .into(),
+                },
                 "partialeq" => quote! {
                     #[automatically_derived]
                     impl #impl_generics ::core::cmp::PartialEq<#name_attribute #ty_generics> for #name_attribute #ty_generics #where_clause {
@@ -109,8 +107,7 @@ pub fn wrapper_boilerplate(
                             self.inner == other.inner
                         }
                     }
-                }
-                .into(),
+                },
                 "ord" => quote! {
                     #[automatically_derived]
                     impl #impl_generics ::core::cmp::Ord for #name_attribute #ty_generics #where_clause {
@@ -118,55 +115,99 @@ pub fn wrapper_boilerplate(
                             <#ident #ty_generics #where_clause as ::core::cmp::Ord>::ord(self.inner, other.inner)
                         }
                     }
-                }
-                .into(),
+                },
                 "partialord" => quote! {
                     #[automatically_derived]
                     impl #impl_generics ::core::cmp::PartialOrd for #name_attribute #ty_generics #where_clause {
-                        fn partial_cmp(&self, other: &Self) -> Option<::core::cmp::Ordering> {
+                        fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
                             <#ident #ty_generics #where_clause as ::core::cmp::ParitalOrd>::partial_cmp(self.inner, other.inner)
                         }
                     }
-                }
-                .into(),
+                },
                 "clone" => quote! {
                     #[automatically_derived]
                     impl #impl_generics ::core::clone::Clone for #name_attribute #ty_generics #where_clause {
                         fn clone(&self) -> Self {
                             Self {
-                                inner: self.inner.clone(),
+                                inner: ::core::clone::Clone::clone(&self.inner),
                                 _err_tree_pkg: self._err_tree_pkg.clone()
                             }
                         }
                     }
-                }
-                .into(),
+                },
                 "hash" => quote! {
                     #[automatically_derived]
                     impl #impl_generics ::core::hash::Hash for #name_attribute #ty_generics #where_clause {
                         fn hash<H>(&self, state: &mut H)
                             where H: ::core::hash::Hasher
                         {
-                            self.inner.hash(state)
+                            ::core::hash::Hash::hash(&self.inner, state)
                         }
                     }
-                }
-                .into(),
+                },
                 "default" => quote! {
                     #[automatically_derived]
                     impl #impl_generics ::core::default::Default for #name_attribute #ty_generics #where_clause {
                         #[track_caller]
                         fn default() -> Self {
                             Self {
-                                inner: #ident ::default(),
+                                inner: ::core::default::Default::default(),
                                 _err_tree_pkg: ::bare_err_tree::ErrTreePkg::default(),
                             }
                         }
                     }
-                }
-                .into(),
-                _ => quote! {}.into(),
+                },
+                _ => quote! {},
             });
 
     TokenStream::from_iter(iter::once(universal).chain(extra_derive_tokens))
 }
+
+/// [`Debug`] as derived on the struct as written, without the hidden field.
+///
+/// Like the derive, each type parameter is bounded by [`Debug`].
+pub fn debug_without_pkg(ident: &Ident, generics: &Generics, fields: &Fields) -> TokenStream {
+    let mut generics = generics.clone();
+    let params: Vec<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause
+            .predicates
+            .push(parse_quote! { #param: ::core::fmt::Debug });
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let name = ident.unraw().to_string();
+    let body = match fields {
+        Fields::Named(fields) => {
+            let members = fields.named.iter().flat_map(|field| &field.ident);
+            let names = members.clone().map(|member| member.unraw().to_string());
+            quote! {
+                f.debug_struct(#name)
+                    #(.field(#names, &self.#members))*
+                    .finish()
+            }
+        }
+        Fields::Unnamed(fields) => {
+            let members = (0..fields.unnamed.len()).map(Index::from);
+            quote! {
+                f.debug_tuple(#name)
+                    #(.field(&self.#members))*
+                    .finish()
+            }
+        }
+        Fields::Unit => quote! { f.write_str(#name) },
+    };
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics ::core::fmt::Debug for #ident #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+    }
+}
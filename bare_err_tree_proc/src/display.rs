@@ -0,0 +1,163 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    punctuated::Punctuated, spanned::Spanned, token::Comma, Attribute, Data, Error, Expr, ExprLit,
+    Fields, Generics, Ident, Lit, LitStr, Meta, Path,
+};
+
+/// Generates [`Display`](`core::fmt::Display`) from doc comments, for
+/// `#[err_tree(display_from_doc)]`.
+///
+/// Structs use their first doc line, and enums the first doc line of each
+/// variant.
+pub fn doc_display(
+    ident: &Ident,
+    generics: &Generics,
+    attrs: &[Attribute],
+    data: &Data,
+    flag: Span,
+) -> syn::Result<TokenStream> {
+    let variant_attrs = match data {
+        Data::Enum(data) => data.variants.iter().flat_map(|v| &v.attrs).collect(),
+        _ => Vec::new(),
+    };
+    if let Some(conflict) = display_conflict(attrs.iter().chain(variant_attrs))? {
+        return Err(Error::new(
+            conflict,
+            "display_from_doc conflicts with this Display implementation",
+        ));
+    }
+
+    let body = match data {
+        Data::Struct(data) => {
+            let fmt = first_doc_line(attrs, flag)?;
+            let (pattern, fmt) = bind_fields(quote! { Self }, &data.fields, fmt);
+            quote! {
+                let #pattern = self;
+                ::core::write!(f, #fmt)
+            }
+        }
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let fmt = first_doc_line(&variant.attrs, variant.span())?;
+                    let variant_ident = &variant.ident;
+                    let (pattern, fmt) =
+                        bind_fields(quote! { Self::#variant_ident }, &variant.fields, fmt);
+                    Ok(quote! { #pattern => ::core::write!(f, #fmt), })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new(
+                flag,
+                "display_from_doc requires a struct or enum",
+            ))
+        }
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::core::fmt::Display for #ident #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+    })
+}
+
+/// Finds a `#[derive(Display)]` or `thiserror` `#[error(..)]` attribute.
+fn display_conflict<'a>(
+    attrs: impl IntoIterator<Item = &'a Attribute>,
+) -> syn::Result<Option<Span>> {
+    for attr in attrs {
+        if attr.path().is_ident("error") {
+            return Ok(Some(attr.span()));
+        }
+        if attr.path().is_ident("derive") {
+            let derives = attr.parse_args_with(Punctuated::<Path, Comma>::parse_terminated)?;
+            if let Some(display) = derives.iter().find(|path| {
+                path.segments
+                    .last()
+                    .is_some_and(|segment| segment.ident == "Display")
+            }) {
+                return Ok(Some(display.span()));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// The first doc line, trimmed, as a format string.
+fn first_doc_line(attrs: &[Attribute], missing: Span) -> syn::Result<LitStr> {
+    attrs
+        .iter()
+        .find_map(|attr| match &attr.meta {
+            Meta::NameValue(name_value) if name_value.path.is_ident("doc") => {
+                match &name_value.value {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(doc), ..
+                    }) => {
+                        let doc = doc.value();
+                        let line = doc.lines().next().unwrap_or_default().trim();
+                        Some(LitStr::new(line, attr.span()))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .ok_or_else(|| Error::new(missing, "display_from_doc requires a doc comment"))
+}
+
+/// Destructures `fields` from `path`, returning the pattern and a format
+/// string referring to the bindings.
+///
+/// Tuple fields are bound as `_0`, `_1`, ..., so `{0}` is rewritten to
+/// `{_0}` to be captured like a named field.
+fn bind_fields(path: TokenStream, fields: &Fields, fmt: LitStr) -> (TokenStream, LitStr) {
+    match fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().flat_map(|field| &field.ident);
+            (quote! { #path { #(#names,)* .. } }, fmt)
+        }
+        Fields::Unnamed(fields) => {
+            let names = (0..fields.unnamed.len()).map(|idx| format_ident!("_{idx}"));
+            let fmt = LitStr::new(&prefix_indices(&fmt.value()), fmt.span());
+            (quote! { #path ( #(#names,)* .. ) }, fmt)
+        }
+        Fields::Unit => (quote! { #path { .. } }, fmt),
+    }
+}
+
+/// Prefixes each `{<index>` argument with `_`, leaving `{{` escapes as is.
+fn prefix_indices(fmt: &str) -> String {
+    let mut prefixed = String::with_capacity(fmt.len());
+    let mut chars = fmt.chars().peekable();
+    while let Some(c) = chars.next() {
+        prefixed.push(c);
+        if c == '{' {
+            match chars.peek() {
+                Some('{') => prefixed.extend(chars.next()),
+                Some(next) if next.is_ascii_digit() => prefixed.push('_'),
+                _ => (),
+            }
+        }
+    }
+    prefixed
+}
@@ -4,8 +4,11 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-use quote::{quote, quote_spanned};
-use syn::{spanned::Spanned, DataEnum, DataStruct, Field, Ident, Type};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    ext::IdentExt, spanned::Spanned, Attribute, DataEnum, DataStruct, Field, Fields,
+    GenericArgument, Ident, Index, Member, Meta, Path, PathArguments, Type,
+};
 
 #[derive(Debug)]
 pub enum ErrType {
@@ -17,23 +20,245 @@ pub enum ErrType {
     DynIter,
     /// Known ErrTree, in a collection
     TreeIter,
+    /// `&dyn Error` items of a collection, e.g. `&[&dyn Error]`
+    DynSlice,
+    /// `&dyn AsErrTree` items of a collection, e.g. `&[&dyn AsErrTree]`
+    TreeSlice,
 }
 
 #[derive(Debug)]
 pub struct TreeErr {
     ident: Ident,
+    /// Field access, which differs from `ident` for tuple struct fields
+    member: Member,
     span: proc_macro2::Span,
     var: ErrType,
+    /// Collection is wrapped in an [`Option`]
+    optional: bool,
+    /// Span of the collection type, if not a fixed-size array or slice
+    dynamic: Option<proc_macro2::Span>,
+    /// Render the source with [`ErrTree::collapsed`]
+    collapse: bool,
+    /// Function returning the collection's items, in place of `.iter()`
+    with: Option<Path>,
+    /// Method returning the source, in place of the field
+    via: Option<Ident>,
+    /// Invalid annotation arguments
+    arg_error: Option<syn::Error>,
 }
 
 impl TreeErr {
     pub fn new(ident: Ident, span: proc_macro2::Span, var: ErrType) -> Self {
-        Self { ident, span, var }
+        Self {
+            member: Member::Named(ident.clone()),
+            ident,
+            span,
+            var,
+            optional: false,
+            dynamic: None,
+            collapse: false,
+            with: None,
+            via: None,
+            arg_error: None,
+        }
+    }
+
+    /// Access the field through `member`, e.g. a tuple struct index.
+    fn with_member(self, member: Member) -> Self {
+        Self { member, ..self }
+    }
+
+    /// Apply the annotation arguments.
+    fn with_args(self, args: Result<SourceArgs, syn::Error>) -> Self {
+        match args {
+            Ok(SourceArgs {
+                with: Some(with), ..
+            }) if !matches!(self.var, ErrType::DynIter | ErrType::TreeIter) => Self {
+                arg_error: Some(syn::Error::new(
+                    with.span(),
+                    "`with` is only valid on `dyn_iter_err` and `tree_iter_err`",
+                )),
+                ..self
+            },
+            Ok(SourceArgs { via: Some(via), .. })
+                if !matches!(self.var, ErrType::Dyn | ErrType::Tree) =>
+            {
+                Self {
+                    arg_error: Some(syn::Error::new(
+                        via.span(),
+                        "`via` is only valid on `dyn_err` and `tree_err`",
+                    )),
+                    ..self
+                }
+            }
+            // The accessor replaces the collection type checks
+            Ok(SourceArgs {
+                collapse,
+                with: Some(with),
+                via,
+            }) => Self {
+                collapse,
+                with: Some(with),
+                via,
+                optional: false,
+                dynamic: None,
+                ..self
+            },
+            Ok(SourceArgs {
+                collapse,
+                with,
+                via,
+            }) => Self {
+                collapse,
+                with,
+                via,
+                ..self
+            },
+            Err(e) => Self {
+                arg_error: Some(e),
+                ..self
+            },
+        }
+    }
+
+    /// Reject `via`, which needs a field on `self` to replace.
+    fn without_via(self) -> Self {
+        match &self.via {
+            Some(via) if self.arg_error.is_none() => Self {
+                arg_error: Some(syn::Error::new(
+                    via.span(),
+                    "`via` is only valid on struct fields",
+                )),
+                ..self
+            },
+            _ => self,
+        }
+    }
+
+    /// Reference to a single source, through `via` if given.
+    fn source_ref(&self, parent: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let member = &self.member;
+        match &self.via {
+            Some(via) => quote_spanned! { self.span=> #parent.#via() },
+            None => quote_spanned! { self.span=> & self.#member },
+        }
+    }
+
+    /// Compile error for invalid annotation arguments, or for dynamic
+    /// collections without `derive_alloc`.
+    fn alloc_error(&self) -> Option<proc_macro2::TokenStream> {
+        if let Some(e) = &self.arg_error {
+            return Some(e.to_compile_error());
+        }
+        match self.dynamic {
+            Some(span) if !cfg!(feature = "derive_alloc") => Some(
+                syn::Error::new(
+                    span,
+                    "dynamically sized collections require the `derive_alloc` feature; \
+                    use a fixed-size array or slice, or enable `derive_alloc`",
+                )
+                .into_compile_error(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Converts `&item` into a collapsed `&dyn AsErrTree`.
+    fn collapse_call(&self) -> proc_macro2::TokenStream {
+        match self.var {
+            ErrType::Dyn | ErrType::DynIter | ErrType::DynSlice => quote! {
+                (|x| ::bare_err_tree::Collapse::tree(::bare_err_tree::WrapErr::wrap(x)))
+            },
+            // See `wrap_call`
+            ErrType::TreeIter if self.with.is_some() => quote! {
+                (|x| ::bare_err_tree::Collapse::tree(x))
+            },
+            ErrType::Tree | ErrType::TreeIter | ErrType::TreeSlice => {
+                quote! { ::bare_err_tree::Collapse::tree }
+            }
+        }
+    }
+
+    /// Converts a generic error reference into an `ErrTree` source.
+    ///
+    /// A `with` iterator is opaque, so dropck needs it to outlive the
+    /// `ErrTree` borrow. The closure lets the item lifetime shorten to match.
+    fn wrap_call(&self) -> proc_macro2::TokenStream {
+        if self.with.is_some() {
+            quote! { (|x| ::bare_err_tree::WrapErr::tree(x)) }
+        } else {
+            quote! { ::bare_err_tree::WrapErr::tree }
+        }
+    }
+
+    /// Item function converting a known tree into `&dyn AsErrTree`.
+    ///
+    /// Named after the field with its span, so a field that does not
+    /// implement `AsErrTree` is reported there instead of at the cast.
+    fn tree_fn(&self) -> (Ident, proc_macro2::TokenStream) {
+        let span = self.span;
+        let name = match &self.member {
+            Member::Named(ident) => format_ident!("tree_err_{}", ident.unraw(), span = span),
+            Member::Unnamed(idx) => format_ident!("tree_err_{}", idx, span = span),
+        };
+        let def = quote_spanned! {
+            span=> #[allow(non_snake_case)]
+                fn #name<'a, T: ::bare_err_tree::AsErrTree + 'a>(
+                    err: &'a T,
+                ) -> &'a dyn ::bare_err_tree::AsErrTree {
+                    err
+                }
+        };
+        (name, def)
     }
+
+    /// Iterator over the collection items, flattening through an [`Option`].
+    fn iter_call(&self) -> proc_macro2::TokenStream {
+        if self.optional {
+            quote! { .iter().flatten() }
+        } else {
+            quote! { .iter() }
+        }
+    }
+
+    /// Iterator over the items of `field`, with `field_ref` as its reference.
+    fn iter_expr(
+        &self,
+        field: proc_macro2::TokenStream,
+        field_ref: proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let span = self.span;
+        match &self.with {
+            Some(with) => quote_spanned! {
+                span=> ::core::iter::IntoIterator::into_iter(#with(#field_ref))
+            },
+            None => {
+                let iter_call = self.iter_call();
+                quote_spanned! { span=> #field #iter_call }
+            }
+        }
+    }
+}
+
+/// Arguments to a source annotation.
+#[derive(Default)]
+struct SourceArgs {
+    collapse: bool,
+    with: Option<Path>,
+    via: Option<Ident>,
 }
 
 /// Generate the `with_pkg` call on all notated sources in a struct.
-pub fn gen_sources_struct(errs: &[TreeErr], foreign: bool) -> proc_macro2::TokenStream {
+///
+/// With `auto`, the only source is [`Error::source`](core::error::Error::source)
+/// in place of the notated sources.
+pub fn gen_sources_struct(
+    errs: &[TreeErr],
+    foreign: bool,
+    msg_field: Option<&Member>,
+    external_pkg: bool,
+    auto: bool,
+) -> proc_macro2::TokenStream {
     // Trivial name change covers both foreign and direct impl
     let parent = if foreign {
         quote! { self.inner }
@@ -41,56 +266,144 @@ pub fn gen_sources_struct(errs: &[TreeErr], foreign: bool) -> proc_macro2::Token
         quote! { self }
     };
 
-    let conv = |x, span| {
+    let conv = |x, source, span, (tree_fn, tree_def)| {
         quote_spanned! {
-            span=> let #x = & self.#x as &dyn ::bare_err_tree::AsErrTree;
-                let #x = core::iter::once(#x);
+            span=> #tree_def
+                let #x = #tree_fn(#source);
+                let #x = ::core::iter::once(#x);
         }
     };
 
-    let conv_dyn = |x, span| {
+    let conv_dyn = |x, source, span| {
+        quote_spanned! {
+            span=> let #x = ::bare_err_tree::WrapErr::tree(#source);
+                let #x = ::core::iter::once(#x);
+        }
+    };
+
+    let conv_dyn_iter = |x, span, iter, wrap_call| {
+        quote_spanned! {
+            span=> let #x = #iter
+                .map(#wrap_call);
+        }
+    };
+
+    // A bare `tree_fn` repeats the bound error at the macro call site
+    let conv_iter = |x, span, iter, (tree_fn, tree_def)| {
         quote_spanned! {
-            span=> let #x = ::bare_err_tree::WrapErr::tree(& self.#x);
-                let #x = core::iter::once(#x);
+            span=> #tree_def
+                #[allow(clippy::redundant_closure)]
+                let #x = #iter.map(|x| #tree_fn(x));
         }
     };
 
-    let conv_dyn_iter = |x, span| {
+    // Items are already erased, so they are chained as is
+    let conv_slice = |x, span, iter| {
         quote_spanned! {
-            span=> let #x = #parent.#x.iter()
-                .map(::bare_err_tree::WrapErr::tree);
+            span=> let #x = #iter.copied();
         }
     };
 
-    let conv_iter = |x, span| {
+    let conv_dyn_slice = |x, span, iter| {
         quote_spanned! {
-            span=> let #x = #parent.#x.iter().map(|x| x as &dyn ::bare_err_tree::AsErrTree);
+            span=> let #x = #iter.map(::bare_err_tree::WrapErr::tree_dyn);
         }
     };
 
+    let conv_collapse = |x, source, span, collapse_call| {
+        quote_spanned! {
+            span=> let #x = (#collapse_call)(#source);
+                let #x = ::core::iter::once(#x);
+        }
+    };
+
+    let conv_collapse_iter = |x, span, iter, collapse_call| {
+        quote_spanned! {
+            span=> let #x = #iter.map(#collapse_call);
+        }
+    };
+
+    let iter = |err: &TreeErr| {
+        let member = &err.member;
+        err.iter_expr(quote! { #parent.#member }, quote! { &#parent.#member })
+    };
+
     let gen_vars = errs.iter().map(|err| match err.var {
-        ErrType::Dyn => conv_dyn(&err.ident, err.span),
-        ErrType::Tree => conv(&err.ident, err.span),
-        ErrType::DynIter => conv_dyn_iter(&err.ident, err.span),
-        ErrType::TreeIter => conv_iter(&err.ident, err.span),
+        _ if err.alloc_error().is_some() => {
+            let (x, alloc_error) = (&err.ident, err.alloc_error());
+            quote! {
+                #alloc_error
+                let #x = ::core::iter::empty();
+            }
+        }
+        ErrType::Dyn | ErrType::Tree if err.collapse => conv_collapse(
+            &err.ident,
+            err.source_ref(&parent),
+            err.span,
+            err.collapse_call(),
+        ),
+        ErrType::DynIter | ErrType::TreeIter | ErrType::DynSlice | ErrType::TreeSlice
+            if err.collapse =>
+        {
+            conv_collapse_iter(&err.ident, err.span, iter(err), err.collapse_call())
+        }
+        ErrType::Dyn => conv_dyn(&err.ident, err.source_ref(&parent), err.span),
+        ErrType::Tree => conv(&err.ident, err.source_ref(&parent), err.span, err.tree_fn()),
+        ErrType::DynIter => conv_dyn_iter(&err.ident, err.span, iter(err), err.wrap_call()),
+        ErrType::TreeIter => conv_iter(&err.ident, err.span, iter(err), err.tree_fn()),
+        ErrType::DynSlice => conv_dyn_slice(&err.ident, err.span, iter(err)),
+        ErrType::TreeSlice => conv_slice(&err.ident, err.span, iter(err)),
     });
     let ids = errs.iter().map(|err| &err.ident);
 
+    let sources = if auto {
+        let source = if foreign {
+            quote! { ::core::error::Error::source(&self.inner) }
+        } else {
+            quote! { ::core::error::Error::source(self) }
+        };
+        quote! {
+            let source = #source;
+            let mut sources = &mut source
+                .as_ref()
+                .map(|x| x as &dyn ::bare_err_tree::AsErrTree)
+                .into_iter();
+        }
+    } else {
+        quote! {
+            #(#gen_vars)*
+            let mut sources = &mut ::core::iter::empty()#(.chain(#ids))*;
+        }
+    };
+
+    let msg_override = msg_field.map(|field| {
+        quote! {
+            .with_msg_override(::core::convert::AsRef::<str>::as_ref(&#parent.#field))
+        }
+    });
+
+    // An external package may be missing
+    let tree = if external_pkg {
+        quote! { ::bare_err_tree::ErrTree::with_pkg_opt(self, sources, _err_tree_pkg.as_ref()) }
+    } else {
+        quote! { ::bare_err_tree::ErrTree::with_pkg(self, sources, _err_tree_pkg) }
+    };
+
     quote! {
-        #(#gen_vars)*
-        let mut sources = &mut core::iter::empty()#(.chain(#ids))*;
+        #sources
 
-        (func)(::bare_err_tree::ErrTree::with_pkg(self, sources, _err_tree_pkg))
+        (func)(#tree #msg_override)
     }
 }
 
 /// Generate the `with_pkg` call on all notated sources in a enum.
 pub fn gen_sources_enum(errs: &[TreeErr], ident: &Ident) -> proc_macro2::TokenStream {
-    let conv = |x, span| {
+    let conv = |x, span, (tree_fn, tree_def)| {
         quote_spanned! {
             span=> #ident :: #x (x) => {
-                let x = x as &dyn ::bare_err_tree::AsErrTree;
-                let x = &mut core::iter::once(x);
+                #tree_def
+                let x = #tree_fn(x);
+                let x = &mut ::core::iter::once(x);
                 (func)(::bare_err_tree::ErrTree::with_pkg(self, x, _err_tree_pkg))
             },
         }
@@ -100,174 +413,347 @@ pub fn gen_sources_enum(errs: &[TreeErr], ident: &Ident) -> proc_macro2::TokenSt
         quote_spanned! {
             span=> #ident :: #x (x) => {
                 let x = ::bare_err_tree::WrapErr::tree(x);
-                let x = &mut core::iter::once(x);
+                let x = &mut ::core::iter::once(x);
                 (func)(::bare_err_tree::ErrTree::with_pkg(self, x, _err_tree_pkg))
             },
         }
     };
 
-    let conv_iter = |x, span| {
+    // See `gen_sources_struct`
+    let conv_iter = |x, span, iter, (tree_fn, tree_def)| {
+        quote_spanned! {
+            span=> #ident :: #x (x) => {
+                #tree_def
+                #[allow(clippy::redundant_closure)]
+                let x = &mut #iter.map(|z| #tree_fn(z));
+                (func)(::bare_err_tree::ErrTree::with_pkg(self, x, _err_tree_pkg))
+            }
+        }
+    };
+
+    let conv_iter_dyn = |x, span, iter, wrap_call| {
+        quote_spanned! {
+            span=> #ident :: #x (x) => {
+                let x = &mut #iter.map(#wrap_call);
+                (func)(::bare_err_tree::ErrTree::with_pkg(self, x, _err_tree_pkg))
+            }
+        }
+    };
+
+    let conv_slice = |x, span, iter| {
+        quote_spanned! {
+            span=> #ident :: #x (x) => {
+                let x = &mut #iter.copied();
+                (func)(::bare_err_tree::ErrTree::with_pkg(self, x, _err_tree_pkg))
+            }
+        }
+    };
+
+    let conv_dyn_slice = |x, span, iter| {
         quote_spanned! {
             span=> #ident :: #x (x) => {
-                let x = &mut x.iter().map(|z| z as &dyn AsErrTree);
+                let x = &mut #iter.map(::bare_err_tree::WrapErr::tree_dyn);
                 (func)(::bare_err_tree::ErrTree::with_pkg(self, x, _err_tree_pkg))
             }
         }
     };
 
-    let conv_iter_dyn = |x, span| {
+    let conv_collapse = |x, span, collapse_call| {
+        quote_spanned! {
+            span=> #ident :: #x (x) => {
+                let x = (#collapse_call)(x);
+                let x = &mut ::core::iter::once(x);
+                (func)(::bare_err_tree::ErrTree::with_pkg(self, x, _err_tree_pkg))
+            },
+        }
+    };
+
+    let conv_collapse_iter = |x, span, iter, collapse_call| {
         quote_spanned! {
             span=> #ident :: #x (x) => {
-                let x = &mut x.iter().map(::bare_err_tree::WrapErr::tree);
+                let x = &mut #iter.map(#collapse_call);
                 (func)(::bare_err_tree::ErrTree::with_pkg(self, x, _err_tree_pkg))
             }
         }
     };
 
+    let iter = |err: &TreeErr| err.iter_expr(quote! { x }, quote! { x });
+
     let gen_arms = errs.iter().map(|err| match err.var {
+        _ if err.alloc_error().is_some() => {
+            let (x, alloc_error) = (&err.ident, err.alloc_error());
+            quote! {
+                #ident :: #x (..) => { #alloc_error }
+            }
+        }
+        ErrType::Dyn | ErrType::Tree if err.collapse => {
+            conv_collapse(&err.ident, err.span, err.collapse_call())
+        }
+        ErrType::DynIter | ErrType::TreeIter | ErrType::DynSlice | ErrType::TreeSlice
+            if err.collapse =>
+        {
+            conv_collapse_iter(&err.ident, err.span, iter(err), err.collapse_call())
+        }
         ErrType::Dyn => conv_dyn(&err.ident, err.span),
-        ErrType::Tree => conv(&err.ident, err.span),
-        ErrType::DynIter => conv_iter_dyn(&err.ident, err.span),
-        ErrType::TreeIter => conv_iter(&err.ident, err.span),
+        ErrType::Tree => conv(&err.ident, err.span, err.tree_fn()),
+        ErrType::DynIter => conv_iter_dyn(&err.ident, err.span, iter(err), err.wrap_call()),
+        ErrType::TreeIter => conv_iter(&err.ident, err.span, iter(err), err.tree_fn()),
+        ErrType::DynSlice => conv_dyn_slice(&err.ident, err.span, iter(err)),
+        ErrType::TreeSlice => conv_slice(&err.ident, err.span, iter(err)),
     });
 
     quote! {
         let sources = match &self.inner {
             #(#gen_arms)*
-            _ => {
-                (func)(::bare_err_tree::ErrTree::with_pkg(self, &mut core::iter::empty(), _err_tree_pkg))
-            }
+            // Unannotated variants fall back to the enum's own source
+            _ => match ::core::error::Error::source(&self.inner) {
+                ::core::option::Option::Some(source) => {
+                    let x = &mut ::core::iter::once(&source as &dyn ::bare_err_tree::AsErrTree);
+                    (func)(::bare_err_tree::ErrTree::with_pkg(self, x, _err_tree_pkg))
+                }
+                ::core::option::Option::None => {
+                    (func)(::bare_err_tree::ErrTree::with_pkg(self, &mut ::core::iter::empty(), _err_tree_pkg))
+                }
+            },
         };
     }
 }
 
+/// Strips all layers of references from a type.
+fn strip_refs(mut ty: &Type) -> &Type {
+    while let Type::Reference(ty_ref) = ty {
+        ty = &ty_ref.elem;
+    }
+    ty
+}
+
+/// Returns the inner type of an [`Option`].
+fn option_inner(ty: &Type) -> Option<&Type> {
+    if let Type::Path(ty_path) = ty {
+        let last = ty_path.path.segments.last()?;
+        if last.ident == "Option" {
+            if let PathArguments::AngleBracketed(args) = &last.arguments {
+                return args.args.iter().find_map(|arg| match arg {
+                    GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                });
+            }
+        }
+    }
+    None
+}
+
 /// Parse iterator types.
 ///
-/// Distinguishes between sized and unsized arrays to generate the
-/// correct identity name and sizing types.
+/// Distinguishes between sized and unsized collections, as only fixed-size
+/// arrays and slices are valid without `derive_alloc`. Collections wrapped in
+/// an [`Option`] are flattened, contributing no sources when `None`.
 fn iter_parse(f: &Field, ident: Ident, var: ErrType) -> TreeErr {
-    let mut ty = f.ty.clone();
-    while let Type::Reference(ty_ref) = ty {
-        ty = *ty_ref.elem;
+    let ty = strip_refs(&f.ty);
+
+    let (optional, ty) = match option_inner(ty) {
+        Some(inner) => (true, strip_refs(inner)),
+        None => (false, ty),
+    };
+
+    let dynamic = (!matches!(ty, Type::Array(_) | Type::Slice(_))).then(|| f.ty.span());
+
+    TreeErr {
+        optional,
+        dynamic,
+        ..TreeErr::new(ident, f.span(), var)
+    }
+}
+
+const SOURCE_ATTRS: [&str; 6] = [
+    "dyn_err",
+    "tree_err",
+    "dyn_iter_err",
+    "tree_iter_err",
+    "dyn_slice_err",
+    "tree_slice_err",
+];
+
+/// Parses a source annotation into its name and arguments.
+///
+/// Accepts the namespaced form (`#[err_tree(tree_err)]`,
+/// `#[err_tree(tree_iter_err(collapse))]`), and unless `namespaced_only`, the
+/// bare form (`#[tree_err]`) and its argument form
+/// (`#[tree_iter_err(collapse, with = path::to::fn)]`).
+fn source_attr(
+    attr: &Attribute,
+    namespaced_only: bool,
+) -> Option<(&'static str, Result<SourceArgs, syn::Error>)> {
+    if attr.path().is_ident("err_tree") {
+        let nested = attr.parse_args::<Meta>().ok()?;
+        let name = SOURCE_ATTRS
+            .into_iter()
+            .find(|name| nested.path().is_ident(name))?;
+        return Some((name, source_args(&nested)?));
+    }
+    if namespaced_only {
+        return None;
     }
 
-    TreeErr::new(ident, f.span(), var)
+    let name = attr.path().segments.iter().find_map(|seg| {
+        let seg = seg.ident.unraw().to_string();
+        SOURCE_ATTRS.into_iter().find(|name| *name == seg)
+    })?;
+
+    Some((name, source_args(&attr.meta)?))
+}
+
+/// Parses the arguments of a source annotation, if it has a valid shape.
+fn source_args(meta: &Meta) -> Option<Result<SourceArgs, syn::Error>> {
+    match meta {
+        Meta::Path(_) => Some(Ok(SourceArgs::default())),
+        Meta::List(list) => {
+            let mut args = SourceArgs::default();
+            let parsed = list.parse_nested_meta(|meta| {
+                if meta.path.is_ident("collapse") {
+                    args.collapse = true;
+                    Ok(())
+                } else if meta.path.is_ident("with") {
+                    args.with = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("via") {
+                    args.via = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `collapse`, `with = path::to::fn`, or `via = method`"))
+                }
+            });
+            Some(parsed.map(|()| args))
+        }
+        Meta::NameValue(_) => None,
+    }
+}
+
+/// A source annotation in the `#[err_tree(...)]` form.
+pub fn is_namespaced_source(attr: &Attribute) -> bool {
+    attr.path().is_ident("err_tree") && source_attr(attr, true).is_some()
+}
+
+/// Whether `attrs` have a `thiserror` `#[source]` or `#[from]`, without an
+/// explicit annotation from this library.
+fn inferred_source(attrs: &[Attribute], namespaced_only: bool) -> bool {
+    attrs
+        .iter()
+        .all(|x| source_attr(x, namespaced_only).is_none())
+        && attrs
+            .iter()
+            .any(|x| x.path().is_ident("source") || x.path().is_ident("from"))
 }
 
 /// Finds all child error annotations on a struct.
-pub fn get_struct_macros(data: &DataStruct) -> impl Iterator<Item = TreeErr> + use<'_> {
-    data.fields.iter().flat_map(|f| {
-        f.attrs.iter().filter_map(|x| {
-            x.meta.require_path_only().ok().and_then(|y| {
-                y.segments
-                    .iter()
-                    .find_map(|seg| match seg.ident.to_string().as_str() {
-                        "dyn_err" => Some(TreeErr::new(
-                            f.ident.clone().unwrap(),
-                            f.span(),
-                            ErrType::Dyn,
-                        )),
-                        "tree_err" => Some(TreeErr::new(
-                            f.ident.clone().unwrap(),
-                            f.span(),
-                            ErrType::Tree,
-                        )),
-                        "dyn_iter_err" => {
-                            Some(iter_parse(f, f.ident.clone().unwrap(), ErrType::DynIter))
-                        }
-                        "tree_iter_err" => {
-                            Some(iter_parse(f, f.ident.clone().unwrap(), ErrType::TreeIter))
-                        }
-                        _ => None,
-                    })
+///
+/// With `infer_sources`, unannotated named fields with `#[source]` or
+/// `#[from]` are treated as `#[dyn_err]`. With `namespaced_only`, only
+/// `#[err_tree(...)]` annotations are read.
+pub fn get_struct_macros(
+    data: &DataStruct,
+    infer_sources: bool,
+    namespaced_only: bool,
+) -> impl Iterator<Item = TreeErr> + use<'_> {
+    data.fields.iter().enumerate().flat_map(move |(idx, f)| {
+        let inferred = (infer_sources && inferred_source(&f.attrs, namespaced_only))
+            .then(|| Some(TreeErr::new(f.ident.clone()?, f.span(), ErrType::Dyn)))
+            .flatten();
+
+        // Tuple fields are bound to a placeholder, as in the `_tree` arguments
+        let (ident, member) = match &f.ident {
+            Some(ident) => (ident.clone(), Member::Named(ident.clone())),
+            None => (
+                format_ident!("_{idx}"),
+                Member::Unnamed(Index {
+                    index: idx as u32,
+                    span: f.span(),
+                }),
+            ),
+        };
+
+        f.attrs
+            .iter()
+            .filter_map(move |x| {
+                let (name, args) = source_attr(x, namespaced_only)?;
+                let ident = ident.clone();
+                let err = match name {
+                    "dyn_err" => TreeErr::new(ident, f.span(), ErrType::Dyn),
+                    "tree_err" => TreeErr::new(ident, f.span(), ErrType::Tree),
+                    "dyn_iter_err" => iter_parse(f, ident, ErrType::DynIter),
+                    "dyn_slice_err" => iter_parse(f, ident, ErrType::DynSlice),
+                    "tree_slice_err" => iter_parse(f, ident, ErrType::TreeSlice),
+                    _ => iter_parse(f, ident, ErrType::TreeIter),
+                };
+                Some(err.with_member(member.clone()).with_args(args))
             })
-        })
+            .chain(inferred)
     })
 }
 
 /// Finds all child error annotations on an enum.
-pub fn get_enum_macros(data: &DataEnum) -> impl Iterator<Item = TreeErr> + use<'_> {
-    data.variants.iter().flat_map(|f| {
-        f.attrs.iter().filter_map(|x| {
-            x.meta.require_path_only().ok().and_then(|y| {
-                y.segments
-                    .iter()
-                    .find_map(|seg| match seg.ident.to_string().as_str() {
-                        "dyn_err" => Some(TreeErr::new(f.ident.clone(), f.span(), ErrType::Dyn)),
-                        "tree_err" => Some(TreeErr::new(f.ident.clone(), f.span(), ErrType::Tree)),
-                        "dyn_iter_err" => {
-                            if f.fields.len() == 1 {
-                                let field =
-                                    f.fields.iter().next().expect("Previously checked length");
-                                Some(iter_parse(field, f.ident.clone(), ErrType::DynIter))
-                            } else {
-                                Some(TreeErr::new(f.ident.clone(), f.span(), ErrType::DynIter))
-                            }
-                        }
-                        "tree_iter_err" => {
-                            if f.fields.len() == 1 {
-                                let field =
-                                    f.fields.iter().next().expect("Previously checked length");
-                                Some(iter_parse(field, f.ident.clone(), ErrType::TreeIter))
-                            } else {
-                                Some(TreeErr::new(f.ident.clone(), f.span(), ErrType::TreeIter))
-                            }
-                        }
-                        _ => None,
-                    })
+///
+/// With `infer_sources`, unannotated single field tuple variants with
+/// `#[source]` or `#[from]` are treated as `#[dyn_err]`. With
+/// `namespaced_only`, only `#[err_tree(...)]` annotations are read.
+pub fn get_enum_macros(
+    data: &DataEnum,
+    infer_sources: bool,
+    namespaced_only: bool,
+) -> impl Iterator<Item = TreeErr> + use<'_> {
+    data.variants.iter().flat_map(move |f| {
+        let inferred = (infer_sources
+            && f.attrs
+                .iter()
+                .all(|x| source_attr(x, namespaced_only).is_none())
+            && matches!(&f.fields, Fields::Unnamed(fields) if fields.unnamed.len() == 1)
+            && f.fields
+                .iter()
+                .any(|field| inferred_source(&field.attrs, namespaced_only)))
+        .then(|| TreeErr::new(f.ident.clone(), f.span(), ErrType::Dyn));
+
+        f.attrs
+            .iter()
+            .filter_map(move |x| {
+                let (name, args) = source_attr(x, namespaced_only)?;
+                let ident = f.ident.clone();
+                let single_field = (f.fields.len() == 1)
+                    .then(|| f.fields.iter().next().expect("Previously checked length"));
+                let err = match (name, single_field) {
+                    ("dyn_err", _) => TreeErr::new(ident, f.span(), ErrType::Dyn),
+                    ("tree_err", _) => TreeErr::new(ident, f.span(), ErrType::Tree),
+                    ("dyn_iter_err", Some(field)) => iter_parse(field, ident, ErrType::DynIter),
+                    ("dyn_iter_err", None) => TreeErr::new(ident, f.span(), ErrType::DynIter),
+                    ("dyn_slice_err", Some(field)) => iter_parse(field, ident, ErrType::DynSlice),
+                    ("dyn_slice_err", None) => TreeErr::new(ident, f.span(), ErrType::DynSlice),
+                    ("tree_slice_err", Some(field)) => iter_parse(field, ident, ErrType::TreeSlice),
+                    ("tree_slice_err", None) => TreeErr::new(ident, f.span(), ErrType::TreeSlice),
+                    (_, Some(field)) => iter_parse(field, ident, ErrType::TreeIter),
+                    (_, None) => TreeErr::new(ident, f.span(), ErrType::TreeIter),
+                };
+                Some(err.with_args(args).without_via())
             })
-        })
+            .chain(inferred)
     })
 }
 
 /// Remove this library's annotation, as they aren't actually valid macros.
-pub fn clean_struct_macros(data: &mut DataStruct) {
+///
+/// With `namespaced_only`, bare annotations belong to another macro and stay.
+pub fn clean_struct_macros(data: &mut DataStruct, namespaced_only: bool) {
     data.fields.iter_mut().for_each(|f| {
-        f.attrs = f
-            .attrs
-            .clone()
-            .into_iter()
-            .filter(|x| {
-                x.meta
-                    .require_path_only()
-                    .ok()
-                    .and_then(|y| {
-                        y.segments
-                            .iter()
-                            .any(|seg| {
-                                ["dyn_err", "tree_err", "dyn_iter_err", "tree_iter_err"]
-                                    .contains(&seg.ident.to_string().as_str())
-                            })
-                            .then_some(())
-                    })
-                    .is_none()
-            })
-            .collect();
+        f.attrs.retain(|x| {
+            source_attr(x, namespaced_only).is_none() && !x.path().is_ident("err_tree")
+        });
     });
 }
 
 /// Remove this library's annotation, as they aren't actually valid macros.
-pub fn clean_enum_macros(data: &mut DataEnum) {
+///
+/// With `namespaced_only`, bare annotations belong to another macro and stay.
+pub fn clean_enum_macros(data: &mut DataEnum, namespaced_only: bool) {
     data.variants.iter_mut().for_each(|f| {
-        f.attrs = f
-            .attrs
-            .clone()
-            .into_iter()
-            .filter(|x| {
-                x.meta
-                    .require_path_only()
-                    .ok()
-                    .and_then(|y| {
-                        y.segments
-                            .iter()
-                            .any(|seg| {
-                                ["dyn_err", "tree_err", "dyn_iter_err", "tree_iter_err"]
-                                    .contains(&seg.ident.to_string().as_str())
-                            })
-                            .then_some(())
-                    })
-                    .is_none()
-            })
-            .collect();
+        f.attrs
+            .retain(|x| source_attr(x, namespaced_only).is_none());
     });
 }
@@ -4,12 +4,317 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
+use proc_macro2::Span;
 use quote::format_ident;
-use syn::{punctuated::Punctuated, token::Comma, Field, Fields, Ident, Meta, Visibility};
+use syn::{
+    ext::IdentExt, punctuated::Punctuated, spanned::Spanned, token::Comma, Attribute, DataEnum,
+    Expr, ExprLit, Field, Fields, Ident, Index, Lit, LitStr, Member, Meta, Path, Visibility,
+};
+
+use crate::errtype::is_namespaced_source;
+
+/// Flags that are not a wrapper name.
+const FLAGS: [&str; 10] = [
+    "auto",
+    "builder",
+    "default",
+    "debug_summary",
+    "display_from_doc",
+    "external_pkg",
+    "hidden",
+    "infer_sources",
+    "namespaced_only",
+    "pkg_debug_skip",
+];
 
 /// Dig out the struct/enum name.
 pub fn name_attribute(args: &Punctuated<Meta, Comma>) -> Option<&proc_macro2::Ident> {
-    args.iter().find_map(|arg| arg.path().get_ident())
+    args.iter().find_map(|arg| match arg {
+        Meta::Path(path) if !FLAGS.iter().any(|flag| path.is_ident(flag)) => path.get_ident(),
+        _ => None,
+    })
+}
+
+/// Check for a flag (e.g. `builder`).
+pub fn flag(args: &Punctuated<Meta, Comma>, name: &str) -> Option<Span> {
+    args.iter().find_map(|arg| match arg {
+        Meta::Path(path) if path.is_ident(name) => Some(path.span()),
+        _ => None,
+    })
+}
+
+/// Find all fields marked `#[err_tree(default)]`.
+pub fn builder_defaults(fields: &Fields) -> syn::Result<Vec<Ident>> {
+    let mut defaults = Vec::new();
+    for field in fields {
+        // Namespaced source annotations share the attribute name
+        let field_args = field
+            .attrs
+            .iter()
+            .filter(|x| x.path().is_ident("err_tree") && !is_namespaced_source(x));
+        for attr in field_args {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `default` or a source annotation, e.g. `tree_err`"))
+                }
+            })?;
+
+            match &field.ident {
+                Some(ident) => defaults.push(ident.clone()),
+                None => {
+                    return Err(syn::Error::new(
+                        attr.span(),
+                        "field defaults require named fields",
+                    ))
+                }
+            }
+        }
+    }
+    Ok(defaults)
+}
+
+/// Dig out the field named by `msg_field = FIELD`, if present.
+pub fn msg_field(args: &Punctuated<Meta, Comma>) -> syn::Result<Option<Member>> {
+    for arg in args {
+        if let Meta::NameValue(name_value) = arg {
+            if name_value.path.is_ident("msg_field") {
+                let member = match &name_value.value {
+                    Expr::Path(path) => path.path.get_ident().cloned().map(Member::Named),
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Int(idx), ..
+                    }) => Some(Member::Unnamed(Index {
+                        index: idx.base10_parse()?,
+                        span: idx.span(),
+                    })),
+                    _ => None,
+                };
+                return member.map(Some).ok_or_else(|| {
+                    syn::Error::new(
+                        name_value.value.span(),
+                        "msg_field must be a field name or tuple index",
+                    )
+                });
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Dig out the hidden field name from `pkg_field = IDENT` or
+/// `pkg_field = "IDENT"`, if present.
+pub fn pkg_field(args: &Punctuated<Meta, Comma>) -> syn::Result<Option<Ident>> {
+    for arg in args {
+        if let Meta::NameValue(name_value) = arg {
+            if name_value.path.is_ident("pkg_field") {
+                return match &name_value.value {
+                    Expr::Path(path) => path.path.get_ident().cloned(),
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(name),
+                        ..
+                    }) => name.parse().ok(),
+                    _ => None,
+                }
+                .map(Some)
+                .ok_or_else(|| {
+                    syn::Error::new(
+                        name_value.value.span(),
+                        "pkg_field must be a field name, e.g. `pkg_field = meta`",
+                    )
+                });
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// The default hidden field name, with `_` appended until no named field
+/// uses it.
+pub fn default_pkg_field(fields: &Fields) -> Ident {
+    let mut name = String::from("_err_tree_pkg");
+    while fields
+        .iter()
+        .filter_map(|field| field.ident.as_ref())
+        .any(|ident| ident == &name)
+    {
+        name.push('_');
+    }
+    Ident::new(&name, Span::call_site())
+}
+
+/// Removes `Debug` from the derives in `attrs`, returning if it was present.
+///
+/// Derives left empty are removed entirely.
+pub fn take_debug_derive(attrs: &mut Vec<Attribute>) -> syn::Result<bool> {
+    let mut found = false;
+    let mut kept = Vec::with_capacity(attrs.len());
+    for mut attr in attrs.drain(..) {
+        if attr.path().is_ident("derive") {
+            let derives = attr.parse_args_with(Punctuated::<Path, Comma>::parse_terminated)?;
+            let before = derives.len();
+            let derives: Punctuated<Path, Comma> = derives
+                .into_iter()
+                .filter(|derive| {
+                    derive
+                        .segments
+                        .last()
+                        .is_none_or(|last| last.ident != "Debug")
+                })
+                .collect();
+            if derives.len() != before {
+                found = true;
+                if derives.is_empty() {
+                    continue;
+                }
+                attr.meta = syn::parse_quote! { derive(#derives) };
+            }
+        }
+        kept.push(attr);
+    }
+    *attrs = kept;
+    Ok(found)
+}
+
+/// Parse the error code from `code = "..."`, if present.
+pub fn err_code(args: &Punctuated<Meta, Comma>) -> syn::Result<Option<LitStr>> {
+    for arg in args {
+        if let Meta::NameValue(name_value) = arg {
+            if name_value.path.is_ident("code") {
+                return match &name_value.value {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(code),
+                        ..
+                    }) if !code.value().is_empty() => Ok(Some(code.clone())),
+                    _ => Err(syn::Error::new(
+                        name_value.value.span(),
+                        "code must be a non-empty string, e.g. `code = \"E0142\"`",
+                    )),
+                };
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Parse the wrapper visibility from `vis = "..."`, if present.
+pub fn wrapper_vis(args: &Punctuated<Meta, Comma>) -> syn::Result<Option<(Span, Visibility)>> {
+    for arg in args {
+        if let Meta::NameValue(name_value) = arg {
+            if name_value.path.is_ident("vis") {
+                return match &name_value.value {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(vis), ..
+                    }) => Ok(Some((name_value.path.span(), vis.parse()?))),
+                    _ => Err(syn::Error::new(
+                        name_value.value.span(),
+                        "vis must be a string, e.g. `vis = \"pub(crate)\"`",
+                    )),
+                };
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Parse the cfg predicate from `stub_when(...)` or `stub_when = "..."`, if
+/// present.
+pub fn stub_when(args: &Punctuated<Meta, Comma>) -> syn::Result<Option<(Span, Meta)>> {
+    for arg in args {
+        match arg {
+            Meta::List(list) if list.path.is_ident("stub_when") => {
+                return Ok(Some((list.path.span(), list.parse_args()?)));
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("stub_when") => {
+                return match &name_value.value {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(predicate),
+                        ..
+                    }) => Ok(Some((name_value.path.span(), predicate.parse()?))),
+                    _ => Err(syn::Error::new(
+                        name_value.value.span(),
+                        "stub_when must be a cfg predicate, e.g. `stub_when(feature = \"slim\")`",
+                    )),
+                };
+            }
+            _ => (),
+        }
+    }
+    Ok(None)
+}
+
+/// Parse the hidden field attributes from `pkg_attrs(...)`, if present.
+pub fn pkg_attrs(args: &Punctuated<Meta, Comma>) -> syn::Result<Option<(Span, Vec<Meta>)>> {
+    for arg in args {
+        if let Meta::List(list) = arg {
+            if list.path.is_ident("pkg_attrs") {
+                let attrs = list.parse_args_with(Punctuated::<Meta, Comma>::parse_terminated)?;
+                return Ok(Some((list.path.span(), attrs.into_iter().collect())));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Span of the `#[repr(C)]` attribute, if present.
+pub fn repr_c(attrs: &[Attribute]) -> Option<Span> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+        .find(|attr| {
+            attr.parse_args_with(Punctuated::<Meta, Comma>::parse_terminated)
+                .is_ok_and(|reprs| reprs.iter().any(|repr| repr.path().is_ident("C")))
+        })
+        .map(Spanned::span)
+}
+
+/// `* `Variant`: <first doc paragraph>` for each variant, if any are documented.
+pub fn variant_docs(data: &DataEnum) -> Vec<String> {
+    let lines: Vec<_> = data
+        .variants
+        .iter()
+        .map(|variant| {
+            let doc = variant
+                .attrs
+                .iter()
+                .filter_map(|attr| match &attr.meta {
+                    Meta::NameValue(name_value) if name_value.path.is_ident("doc") => {
+                        match &name_value.value {
+                            Expr::Lit(ExprLit {
+                                lit: Lit::Str(doc), ..
+                            }) => Some(doc.value()),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                })
+                // `split` rather than `lines`, so an empty `///` still ends a paragraph
+                .flat_map(|doc| {
+                    doc.split('\n')
+                        .map(|line| line.trim().to_owned())
+                        .collect::<Vec<_>>()
+                })
+                .skip_while(String::is_empty)
+                .take_while(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            (variant.ident.unraw(), doc)
+        })
+        .collect();
+
+    if lines.iter().all(|(_, doc)| doc.is_empty()) {
+        return Vec::new();
+    }
+    lines
+        .into_iter()
+        .map(|(ident, doc)| {
+            if doc.is_empty() {
+                format!("* `{ident}`")
+            } else {
+                format!("* `{ident}`: {doc}")
+            }
+        })
+        .collect()
 }
 
 #[derive(Debug)]
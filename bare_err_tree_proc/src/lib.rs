@@ -12,11 +12,12 @@
 extern crate proc_macro;
 use core::panic;
 
-use proc_macro::{Span, TokenStream};
-use quote::{quote, ToTokens};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{
-    parse::Parser, parse_macro_input, punctuated::Punctuated, token::Brace, Attribute, Data,
-    DataStruct, DeriveInput, Error, Field, Fields, FieldsNamed, Generics, Ident, Meta, Visibility,
+    ext::IdentExt, parse::Parser, punctuated::Punctuated, spanned::Spanned, token::Brace,
+    Attribute, Data, DataStruct, DeriveInput, Error, Field, Fields, FieldsNamed, Generics, Ident,
+    Member, Meta, Visibility,
 };
 
 mod errtype;
@@ -25,6 +26,10 @@ mod boiler;
 use boiler::*;
 mod fields;
 use fields::*;
+mod display;
+use display::*;
+#[cfg(feature = "test_util")]
+mod shape;
 
 /// Implements a type as an error tree.
 ///
@@ -37,10 +42,156 @@ use fields::*;
 /// occur after the `#[err_tree]` macro. The `_err_tree_pkg` field will
 /// otherwise be added late and break the derivation.
 ///
+/// The hidden field is the first field of a struct with named fields, and the
+/// last field of a tuple struct so the indices of existing fields are
+/// unchanged. Generics, `where` clauses, and defaults are left as written. The
+/// hidden field compares as equal and hashes as a no-op, so derived
+/// comparisons and hashes only depend on the user fields.
+///
+/// # Hidden Field Name
+/// When a struct already has a field named `_err_tree_pkg` (e.g. from
+/// generated code), the hidden field takes the first free name with `_`
+/// appended instead. `#[err_tree(pkg_field = name)]` or
+/// `#[err_tree(pkg_field = "name")]` picks the name of the hidden field in a
+/// struct with named fields. Tuple and unit structs have no field names to
+/// collide with.
+///
+/// `#[err_tree(pkg_debug_skip)]` replaces a `#[derive(Debug)]` placed after
+/// `#[err_tree]` with an impl that leaves out the hidden field. Each type
+/// parameter gains a [`Debug`](`core::fmt::Debug`) bound, as with the derive.
+///
+/// ```
+/// # use std::io;
+/// use bare_err_tree::err_tree;
+///
+/// #[err_tree(pkg_field = meta, pkg_debug_skip)]
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("code {_err_tree_pkg}")]
+/// struct Generated {
+///     _err_tree_pkg: u32,
+///     #[dyn_err]
+///     io: io::Error,
+/// }
+///
+/// let err = Generated::_tree(1, io::ErrorKind::TimedOut.into());
+/// assert_eq!(
+///     format!("{err:?}"),
+///     "Generated { _err_tree_pkg: 1, io: Kind(TimedOut) }"
+/// );
+/// ```
+///
+/// # FFI Layout
+/// Under `#[repr(C)]`, the hidden field is always the last field, so the
+/// offsets of the user fields match a C definition without it. C code may read
+/// the user fields through a pointer, but must not copy or allocate the struct
+/// by its own `sizeof`. A `#[repr(C)]` unit struct is a compile error, as the
+/// hidden field would turn it into a struct with a field.
+///
+/// `#[err_tree(pkg_attrs(...))]` adds attributes to the hidden field, such as
+/// a `cfg_attr` for FFI binding generators.
+///
+/// ```
+/// # use std::{io, mem::offset_of};
+/// use bare_err_tree::err_tree;
+///
+/// #[err_tree(pkg_attrs(doc = "Not visible to C"))]
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("code {code}")]
+/// #[repr(C)]
+/// struct FfiErr {
+///     code: u32,
+///     flags: u8,
+///     #[dyn_err]
+///     io: io::Error,
+/// }
+///
+/// assert_eq!(offset_of!(FfiErr, code), 0);
+/// assert_eq!(offset_of!(FfiErr, flags), 4);
+/// ```
+///
+/// # External Package
+/// `#[err_tree(external_pkg)]` adds no hidden field, leaving the struct
+/// exactly as written. The package is instead attached after construction
+/// with `ErrTreePkg::attach`, which stores it in a global table keyed by
+/// the error's address. Requires the `external_pkg` feature.
+///
+/// The error must not move between attaching and printing, so attach it once
+/// it is in its final place (e.g. boxed or handed to C). A moved or
+/// unattached error renders without metadata. The type gains a [`Drop`]
+/// impl that removes its entry, so fields can no longer be moved out by
+/// destructuring. Unit structs are a compile error, as zero-sized errors
+/// share addresses.
+///
+/// `Self::_tree` still takes all fields in order, but captures nothing. The
+/// package captures its callsite in `ErrTreePkg::new`.
+///
+/// ```
+/// # use std::{io, mem::size_of};
+/// use bare_err_tree::{err_tree, ErrTreePkg};
+///
+/// #[err_tree(external_pkg)]
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("code {code}")]
+/// #[repr(C)]
+/// struct FfiErr {
+///     code: u32,
+///     flags: u8,
+///     #[dyn_err]
+///     io: io::Error,
+/// }
+///
+/// let err = Box::new(FfiErr::_tree(1, 0, io::ErrorKind::TimedOut.into()));
+/// ErrTreePkg::new().attach(&*err);
+/// assert_eq!(size_of::<FfiErr>(), 16);
+/// ```
+///
+/// # Stubbing
+/// `#[err_tree(stub_when(predicate))]` or `#[err_tree(stub_when = "predicate")]`
+/// generates both a tracked and a stubbed struct, picked by
+/// `#[cfg(predicate)]` in the annotated crate. When the predicate holds, the
+/// hidden field is a zero-sized `UnitPkg` and `Self::_tree` captures
+/// nothing. `AsErrTree` then renders like a plain `dyn Error`, following
+/// [`Error::source`](`core::error::Error::source`) instead of the
+/// annotated sources. Constructors and field accesses are unchanged, so
+/// call sites compile under both configurations.
+///
+/// This keeps tree metadata out of e.g. release builds without adding a
+/// `cfg_attr` around `#[err_tree]`, which would also remove `Self::_tree`.
+/// Wrapped types and `external_pkg` are not supported.
+///
+/// ```
+/// # use std::{io, mem::size_of};
+/// use bare_err_tree::err_tree;
+///
+/// #[err_tree(stub_when(not(debug_assertions)))]
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("code {code}")]
+/// struct Slim {
+///     code: u32,
+///     #[dyn_err]
+///     io: io::Error,
+/// }
+///
+/// let _err = Slim::_tree(1, io::ErrorKind::TimedOut.into());
+/// if cfg!(not(debug_assertions)) {
+///     assert_eq!(size_of::<Slim>(), size_of::<(u32, io::Error)>());
+/// }
+/// ```
+///
 /// # `Self::_tree`
 /// This is an internal-use constructor that takes all struct fields in order.
 /// Use `#[track_caller]` on any functions calling `Self::_tree` to store the
 /// callsite correctly.
+///
+/// A struct literal (e.g. `Foo { num }`) fails with "missing field
+/// `_err_tree_pkg`", which is the hidden field. Call `Self::_tree` or a
+/// constructor wrapping it instead.
+///
+/// `#[err_tree(default)]` generates [`Default`](`core::default::Default`),
+/// filling every field with its own default through `Self::_tree`. This is an
+/// easy construction path for doc examples and tests. Any field type without
+/// [`Default`](`core::default::Default`) is a compile error at that field. It
+/// is opt-in, so it does not conflict with a user `Default`.
 /// [Open an issue or PR](<https://github.com/Bennett-Petzold/bare_err_tree>)
 /// if this hidden field degrades a struct's API (aside from requiring a
 /// constructor method).
@@ -85,8 +236,32 @@ use fields::*;
 /// }
 /// ```
 ///
+/// #### Default Example
+/// ```
+/// # #![cfg_attr(coverage, feature(coverage_attribute))]
+/// # use std::{error::Error, fmt::{self, Display, Formatter}};
+/// use bare_err_tree::err_tree;
+///
+/// #[err_tree(default)]
+/// #[derive(Debug)]
+/// struct Foo {
+///     path: String,
+///     attempts: u32,
+/// }
+/// # impl Error for Foo {}
+/// # impl Display for Foo {
+/// #     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "foo") }
+/// # }
+///
+/// fn main() {
+///     let err = Foo::default();
+///     assert_eq!((err.path.as_str(), err.attempts), ("", 0));
+/// }
+/// ```
+///
 /// # Field Annotations
-/// The macro needs annotations for underlying source fields.
+/// The macro needs annotations for underlying source fields. Sources render
+/// in field declaration order, regardless of annotation kind.
 ///
 /// #### Single Item
 /// * `tree_err`: Mark a field as a `ErrTree` implementing [`Error`](`core::error::Error`).
@@ -94,13 +269,126 @@ use fields::*;
 ///
 /// #### Collection
 /// `*_iter_err` works on any type with a `.iter()` method returning its items.
+/// A collection wrapped in an [`Option`] (e.g. `Option<Vec<E>>`) contributes
+/// its items when `Some`, and no sources when `None`.
 ///
 /// * `tree_iter_err`: Mark a field as a collection of `ErrTree` implementing [`Error`](`core::error::Error`)s.
 /// * `dyn_iter_err`: Mark a field as a collection of generic [`Error`](`core::error::Error`)s.
 ///
-/// `*_iter_err` does not allocate for arrays with a known length.
-/// The `derive_alloc` feature enables generation of allocating code to support
-/// dynamically sized collections.
+/// Collections of already erased references (e.g. `&[&dyn AsErrTree]` when
+/// aggregating from several subsystems) chain their items directly:
+///
+/// * `tree_slice_err`: Mark a field as a collection of `&dyn AsErrTree`.
+/// * `dyn_slice_err`: Mark a field as a collection of `&dyn Error`, rendered
+///   through `WrapErr::tree_dyn`.
+///
+/// `*_iter_err` on fixed-size arrays and slices works in any `no_std` crate.
+/// Dynamically sized collections (e.g. `Vec`) need the `derive_alloc` feature,
+/// which is enabled by default. Without it, annotating a dynamically sized
+/// collection is a compile error, so strictly no-alloc crates catch accidental
+/// usage early.
+///
+/// #### Collapsing
+/// Any of the annotations accept a `collapse` argument (e.g.
+/// `#[tree_err(collapse)]`). The source then renders as its message and a
+/// count of its nested errors, for opaque internals that are rarely useful to
+/// expand. See `FormatOptions::expand_collapsed` to render them in full.
+/// `tree_err(collapse)` requires a sized field type.
+///
+/// #### Custom Iteration
+/// Collections without an `.iter()` method take a `with` argument naming a
+/// function or method (e.g. `#[tree_iter_err(with = Errors::errors)]`). It is
+/// called with a reference to the field and returns an iterator (or any
+/// [`IntoIterator`]) of error references. `with` combines with `collapse`, and
+/// is only valid on `tree_iter_err` and `dyn_iter_err`.
+///
+/// ```
+/// # use std::io;
+/// use bare_err_tree::err_tree;
+///
+/// #[derive(Debug)]
+/// struct Errors(Vec<(u32, io::Error)>);
+///
+/// impl Errors {
+///     fn errors(&self) -> impl Iterator<Item = &io::Error> {
+///         self.0.iter().map(|(_, err)| err)
+///     }
+/// }
+///
+/// #[err_tree]
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("batch failed")]
+/// struct Batch {
+///     #[dyn_iter_err(with = Errors::errors)]
+///     errs: Errors,
+/// }
+/// ```
+///
+/// #### Accessor Methods
+/// A source only reachable through a method takes a `via` argument naming a
+/// method on the error (e.g. `#[tree_err(via = cause)]`), which is called as
+/// `self.cause()` in place of reading the field. It must return a reference to
+/// the source. `via` combines with `collapse`, and is only valid on `tree_err`
+/// and `dyn_err` struct fields.
+///
+/// ```
+/// # use std::io;
+/// use bare_err_tree::err_tree;
+///
+/// mod state {
+///     #[derive(Debug)]
+///     pub struct Connection {
+///         last_error: std::io::Error,
+///     }
+///
+///     impl Connection {
+///         pub fn new(last_error: std::io::Error) -> Self {
+///             Self { last_error }
+///         }
+///
+///         pub fn last_error(&self) -> &std::io::Error {
+///             &self.last_error
+///         }
+///     }
+/// }
+///
+/// #[err_tree]
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("connection lost")]
+/// struct Disconnected {
+///     #[dyn_err(via = cause)]
+///     conn: state::Connection,
+/// }
+///
+/// impl Disconnected {
+///     fn cause(&self) -> &io::Error {
+///         self.conn.last_error()
+///     }
+/// }
+/// ```
+///
+/// #### Namespaced Annotations
+/// Every annotation is also accepted inside `err_tree`, e.g.
+/// `#[err_tree(dyn_err)]` or `#[err_tree(tree_iter_err(collapse))]`. Prefer
+/// this form in crates that use other macros, as the bare form removes any
+/// attribute with the same name. `#[err_tree(namespaced_only)]` stops reading
+/// the bare form entirely, leaving attributes like `#[tree_err]` to the macro
+/// that defines them.
+///
+/// ```
+/// # use std::io;
+/// use bare_err_tree::err_tree;
+///
+/// #[err_tree(namespaced_only)]
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("upload failed")]
+/// struct Upload {
+///     #[err_tree(dyn_err)]
+///     io: io::Error,
+///     #[err_tree(dyn_iter_err(collapse))]
+///     retries: Vec<io::Error>,
+/// }
+/// ```
 ///
 /// #### Example
 /// ```
@@ -157,6 +445,331 @@ use fields::*;
 /// }
 /// ```
 ///
+/// # Overriding the Message
+/// `#[err_tree(msg_field = FIELD)]` prints the tree message from a field
+/// instead of [`Display`](`core::fmt::Display`). The field can be any type
+/// implementing [`AsRef<str>`](`core::convert::AsRef`). This keeps a generic
+/// `Display` for logs while the tree shows runtime context. Tuple structs use
+/// the field index (e.g. `msg_field = 0`). Not supported on enums.
+///
+/// #### Example
+/// ```
+/// # #![cfg_attr(coverage, feature(coverage_attribute))]
+/// # use std::{error::Error, fmt::{self, Debug, Display, Formatter}};
+/// use bare_err_tree::{err_tree, AsErrTree};
+///
+/// #[err_tree(msg_field = context)]
+/// #[derive(Debug)]
+/// struct Foo {
+///     context: String,
+/// }
+///
+/// impl Error for Foo {}
+/// impl Display for Foo {
+///     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+///         write!(f, "generic failure")
+///     }
+/// }
+///
+/// fn main() {
+///     let err = Foo::_tree("reading config.toml".to_string());
+///     assert_eq!(err.to_string(), "generic failure");
+/// }
+/// ```
+///
+/// # Builder Construction
+/// `#[err_tree(builder)]` additionally generates a private `<NAME>TreeBuilder`,
+/// created with `Self::_tree_builder()`. It has a setter per field and a
+/// `#[track_caller]` `build()` that captures the hidden field. Fields marked
+/// `#[err_tree(default)]` fall back to [`Default`](`core::default::Default`),
+/// and `build()` panics if any other field is unset. Only supported on
+/// structs with named fields, and not alongside `WRAPPER`.
+///
+/// #### Example
+/// ```
+/// # #![cfg_attr(coverage, feature(coverage_attribute))]
+/// # use std::{error::Error, fmt::{self, Debug, Display, Formatter}};
+/// use bare_err_tree::err_tree;
+///
+/// #[err_tree(builder)]
+/// #[derive(Debug)]
+/// struct Foo {
+///     path: String,
+///     attempts: u32,
+///     #[err_tree(default)]
+///     retryable: bool,
+/// }
+///
+/// impl Error for Foo {}
+/// impl Display for Foo {
+///     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+///         write!(f, "failed to read {} after {} attempts", self.path, self.attempts)
+///     }
+/// }
+///
+/// fn main() {
+///     let err = Foo::_tree_builder()
+///         .attempts(3)
+///         .path("config.toml".to_string())
+///         .build();
+///     assert!(!err.retryable);
+///     assert_eq!(err.to_string(), "failed to read config.toml after 3 attempts");
+/// }
+/// ```
+///
+/// # Debug Summary
+/// `#[err_tree(debug_summary)]` generates a `tree_summary` method, displaying
+/// the first line of each error with `bare_err_tree::compact_summary`. It is
+/// bounded in size and needs no allocation, for quick debugging output such as
+/// `eprintln!("{}", err.tree_summary())`. On wrapped types, the method is
+/// generated for the wrapper.
+///
+/// #### Example
+/// ```
+/// # #![cfg_attr(coverage, feature(coverage_attribute))]
+/// # use std::{error::Error, fmt::{self, Display, Formatter}, io};
+/// use bare_err_tree::err_tree;
+///
+/// #[err_tree(debug_summary)]
+/// #[derive(Debug)]
+/// struct Foo {
+///     #[dyn_err]
+///     io: io::Error,
+/// }
+/// # impl Error for Foo {}
+/// # impl Display for Foo {
+/// #     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "foo") }
+/// # }
+///
+/// fn main() {
+///     let err = Foo::_tree(io::ErrorKind::UnexpectedEof.into());
+///     assert_eq!(err.tree_summary().to_string(), "foo\n  unexpected end of file");
+/// }
+/// ```
+///
+/// # Error Code
+/// `#[err_tree(code = "E0142")]` gives the type an associated
+/// `ERR_CODE: &'static str`, rendered in brackets before the message and
+/// stored as `"code"` in JSON. On wrapped types, the constant is on the
+/// wrapped type. With the `code_registry` feature, the type is also added to
+/// `bare_err_tree::ERR_CODES`, which `bare_err_tree::assert_unique_err_codes`
+/// checks for shared codes.
+///
+/// #### Example
+/// ```
+/// # #![cfg_attr(coverage, feature(coverage_attribute))]
+/// # use std::{error::Error, fmt::{self, Display, Formatter}};
+/// use bare_err_tree::{err_tree, print_tree};
+///
+/// #[err_tree(code = "E0142")]
+/// #[derive(Debug)]
+/// struct NotFound {
+///     id: u32,
+/// }
+/// # impl Error for NotFound {}
+/// # impl Display for NotFound {
+/// #     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} not found", self.id) }
+/// # }
+///
+/// fn main() {
+///     assert_eq!(NotFound::ERR_CODE, "E0142");
+///
+///     let mut out = String::new();
+///     print_tree::<60, _, _>(NotFound::_tree(7), &mut out).unwrap();
+///     assert!(out.starts_with("[E0142] 7 not found"));
+/// }
+/// ```
+///
+/// #### Shared Code Example
+/// ```
+/// # #![cfg_attr(coverage, feature(coverage_attribute))]
+/// # use std::{error::Error, fmt::{self, Display, Formatter}};
+/// use std::panic;
+/// use bare_err_tree::err_tree;
+///
+/// #[err_tree(code = "E0142")]
+/// #[derive(Debug)]
+/// struct NotFound(u32);
+///
+/// #[err_tree(code = "E0142")]
+/// #[derive(Debug)]
+/// struct Missing(u32);
+/// # impl Error for NotFound {}
+/// # impl Display for NotFound {
+/// #     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} not found", self.0) }
+/// # }
+/// # impl Error for Missing {}
+/// # impl Display for Missing {
+/// #     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} missing", self.0) }
+/// # }
+///
+/// fn main() {
+///     # #[cfg(feature = "code_registry")]
+///     {
+///         let shared = panic::catch_unwind(|| bare_err_tree::assert_unique_err_codes!());
+///         let msg = shared.unwrap_err().downcast::<String>().unwrap();
+///         assert!(msg.starts_with("error code `E0142` is used by both"), "{msg}");
+///         assert!(msg.contains("::Missing`") && msg.contains("::NotFound`"), "{msg}");
+///     }
+/// }
+/// ```
+///
+/// # Type Name
+/// Every annotated type tags its node with [`core::any::type_name`], which
+/// `bare_err_tree` renders after the message with its `type_name` feature
+/// (e.g. `not found  [my_crate::NotFound]`). On wrapped types, this is the
+/// name of the wrapped type.
+///
+/// # Display From Doc
+/// `#[err_tree(display_from_doc)]` implements [`Display`](`core::fmt::Display`)
+/// with the first doc comment line as the format string, like `displaydoc`.
+/// Fields are interpolated by name (e.g. `{path}`), or by index on tuple
+/// structs (e.g. `{0}`). Enums use the first doc line of each variant. It is an
+/// error to combine this with `#[error(..)]` or a derived `Display`, and a
+/// manual `Display` implementation conflicts at compile time.
+///
+/// #### Example
+/// ```
+/// # #![cfg_attr(coverage, feature(coverage_attribute))]
+/// # use std::{error::Error, io};
+/// use bare_err_tree::err_tree;
+///
+/// /// Failed to read {path}
+/// #[err_tree(display_from_doc)]
+/// #[derive(Debug)]
+/// struct Foo {
+///     path: String,
+///     #[dyn_err]
+///     io: io::Error,
+/// }
+/// impl Error for Foo {}
+///
+/// fn main() {
+///     let err = Foo::_tree("config.toml".to_string(), io::ErrorKind::NotFound.into());
+///     assert_eq!(err.to_string(), "Failed to read config.toml");
+/// }
+/// ```
+///
+/// # Inferring Sources
+/// `#[err_tree(infer_sources)]` treats fields marked with `thiserror`'s
+/// `#[source]` or `#[from]` as `#[dyn_err]`. An explicit annotation on the
+/// field (or enum variant) takes precedence. On enums, only single field
+/// tuple variants are inferred. Without the flag, these attributes are
+/// ignored.
+///
+/// #### Example
+/// ```
+/// # #![cfg_attr(coverage, feature(coverage_attribute))]
+/// # use std::io;
+/// use bare_err_tree::{err_tree, front_max_for_depth, print_tree};
+///
+/// #[err_tree(infer_sources)]
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("failed to read")]
+/// struct Foo {
+///     #[source]
+///     io: io::Error,
+/// }
+///
+/// fn main() {
+///     let err = Foo::_tree(io::ErrorKind::NotFound.into());
+///     let mut out = String::new();
+///     print_tree::<{ front_max_for_depth(10) }, _, _>(&err, &mut out).unwrap();
+///     assert!(out.ends_with("╰─▶ entity not found"));
+/// }
+/// ```
+///
+/// # Automatic Sources
+/// `#[err_tree(auto)]` adds the hidden field and `Self::_tree`, but renders
+/// the type's [`Error::source`](`core::error::Error::source`) as its only
+/// source, without any field annotations. This is a first step for adopting
+/// `bare_err_tree` across many types: constructions capture their metadata
+/// now, and annotations can be added later where they matter.
+///
+/// Limitations compared to annotated sources:
+/// * Only one source is rendered, so multiple sources need annotations.
+/// * The source renders through its [`Error`](`core::error::Error`) impl, so
+///   its own metadata (e.g. its source line) and annotated sources are lost,
+///   even if it is also an `#[err_tree]` type.
+///
+/// Source annotations alongside `auto` are a compile error. On enums, `auto`
+/// makes every variant fall back to the enum's `Error::source`.
+///
+/// #### Example
+/// ```
+/// # #![cfg_attr(coverage, feature(coverage_attribute))]
+/// # use std::io;
+/// use bare_err_tree::{err_tree, front_max_for_depth, print_tree};
+///
+/// #[err_tree(auto)]
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("failed to read")]
+/// struct Foo {
+///     #[source]
+///     io: io::Error,
+/// }
+///
+/// fn main() {
+///     let err = Foo::_tree(io::ErrorKind::NotFound.into());
+///     let mut out = String::new();
+///     print_tree::<{ front_max_for_depth(10) }, _, _>(&err, &mut out).unwrap();
+///     assert!(out.ends_with("╰─▶ entity not found"));
+/// }
+/// ```
+///
+/// # Unit Structs
+/// Unit structs are constructed with `Self::_tree()`. On unit structs,
+/// `#[err_tree(default)]` also generates a `new` constructor alongside
+/// [`Default`](`core::default::Default`), both capturing the hidden field at
+/// their callsite. This is opt-in, so it does not conflict with a user `new`.
+///
+/// Annotated types can also be declared inside function bodies.
+///
+/// #### Example
+/// ```
+/// # #![cfg_attr(coverage, feature(coverage_attribute))]
+/// #![deny(warnings)]
+/// # use std::{error::Error, fmt::{self, Display, Formatter}};
+/// use bare_err_tree::err_tree;
+///
+/// fn main() {
+///     #[err_tree(default)]
+///     #[derive(Debug)]
+///     struct Unit;
+///
+///     #[err_tree]
+///     #[derive(Debug)]
+///     struct Outer {
+///         #[tree_err]
+///         unit: Unit,
+///     }
+///
+///     #[err_tree(EnumWrap)]
+///     #[derive(Debug)]
+///     enum Enum {
+///         #[tree_err]
+///         Outer(Outer),
+///     }
+///
+///     # impl Error for Unit {}
+///     # impl Error for Outer {}
+///     # impl Error for Enum {}
+///     # impl Display for Unit {
+///     #     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "unit") }
+///     # }
+///     # impl Display for Outer {
+///     #     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "outer") }
+///     # }
+///     # impl Display for Enum {
+///     #     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "enum") }
+///     # }
+///     // `new` and `Default` are generated by `#[err_tree(default)]`
+///     let _ = Unit::new();
+///     let err: EnumWrap = Enum::Outer(Outer::_tree(Unit::default())).into();
+///     assert_eq!(err.to_string(), "enum");
+/// }
+/// ```
+///
 /// # Generating a Wrapper
 /// `#[err_tree(WRAPPER)]` will generate a wrapper struct for storing metadata.
 /// Enums need this form, as a hidden field cannot be added to the enum.
@@ -166,6 +779,17 @@ use fields::*;
 /// Some derives are automatically re-derived for the wrapper; any other traits
 /// that need to be implemented for the wrapper can be written manually.
 ///
+/// The wrapper copies the type's docs and visibility. For enums, documented
+/// variants are also listed in a "Variants" section of the wrapper docs.
+/// `vis = "..."` sets a different wrapper visibility (e.g.
+/// `#[err_tree(FooWrap, vis = "pub(crate)")]`), and `hidden` marks the wrapper
+/// `#[doc(hidden)]`.
+///
+/// Enum variants without a source annotation fall back to the enum's
+/// [`Error::source`](`core::error::Error::source`), as a single generic source.
+/// A forgotten annotation then loses the cause's tree metadata, but not the
+/// cause itself.
+///
 /// #### Wrapper automatic re-derives
 // https://doc.rust-lang.org/rust-by-example/trait/derive.html
 /// [`Eq`](`core::cmp::Eq`), [`PartialEq`](`core::cmp::PartialEq`),
@@ -219,7 +843,7 @@ use fields::*;
 /// # Full Usage Example:
 /// ```
 /// # use std::{error::Error, fmt::{self, Debug, Display, Formatter}};
-/// use bare_err_tree::{err_tree, tree_unwrap};
+/// use bare_err_tree::{err_tree, front_max_for_depth, tree_unwrap};
 ///
 /// #[err_tree]
 /// #[derive(Debug)]
@@ -263,7 +887,7 @@ use fields::*;
 /// }
 ///
 /// const MAX_DEPTH: usize = 10;
-/// const MAX_CHARS: usize = MAX_DEPTH * 6;
+/// const MAX_CHARS: usize = front_max_for_depth(MAX_DEPTH);
 ///
 /// pub fn main() {
 ///     # let _ = std::panic::catch_unwind(|| {
@@ -275,45 +899,329 @@ use fields::*;
 /// }
 /// ```
 #[proc_macro_attribute]
-pub fn err_tree(args: TokenStream, input: TokenStream) -> TokenStream {
-    let args = parse_macro_input!(args with Punctuated::<Meta, syn::Token![,]>::parse_terminated);
+pub fn err_tree(
+    args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    expand_err_tree(args.into(), input.into()).into()
+}
+
+/// Asserts that an error tree has the given shape of messages.
+///
+/// Compares the `tree_shape` of the first argument, by reference, against a
+/// braced root message. A message followed by `=> [...]` has those sources, in
+/// order. A message without one has no sources. Locations are masked, so
+/// moving code does not break the assertion.
+///
+/// On mismatch, panics with both shapes listed one message per line.
+///
+/// ```rust
+/// # use std::{error::Error, io};
+/// use bare_err_tree::assert_tree_shape;
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("outer")]
+/// struct Outer(#[source] io::Error);
+///
+/// let err = Outer(io::Error::from(io::ErrorKind::UnexpectedEof));
+/// let err: &dyn Error = &err;
+///
+/// assert_tree_shape!(err, {
+///     "outer" => [
+///         "unexpected end of file",
+///     ]
+/// });
+/// ```
+#[cfg(feature = "test_util")]
+#[proc_macro]
+pub fn assert_tree_shape(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match syn::parse2::<shape::AssertShape>(input.into()) {
+        Ok(assert) => assert.into_token_stream(),
+        Err(e) => e.into_compile_error(),
+    }
+    .into()
+}
+
+/// [`err_tree`] on [`proc_macro2`] tokens, so it can run outside of a macro
+/// invocation.
+fn expand_err_tree(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = match Punctuated::<Meta, syn::Token![,]>::parse_terminated.parse2(args) {
+        Ok(args) => args,
+        Err(e) => return e.into_compile_error(),
+    };
 
     let name_attribute = name_attribute(&args);
+    let auto = flag(&args, "auto");
+    let builder = flag(&args, "builder");
+    let default = flag(&args, "default");
+    let debug_summary = flag(&args, "debug_summary").is_some();
+    let external_pkg = flag(&args, "external_pkg");
+    let hidden = flag(&args, "hidden");
+    let infer_sources = flag(&args, "infer_sources").is_some();
+    let namespaced_only = flag(&args, "namespaced_only").is_some();
+    let pkg_attrs = match pkg_attrs(&args) {
+        Ok(pkg_attrs) => pkg_attrs,
+        Err(e) => return e.into_compile_error(),
+    };
+    let pkg_field = match pkg_field(&args) {
+        Ok(pkg_field) => pkg_field,
+        Err(e) => return e.into_compile_error(),
+    };
+    let pkg_debug_skip = flag(&args, "pkg_debug_skip");
+    // Options naming or hiding the hidden field
+    let pkg_naming = pkg_field.as_ref().map(Ident::span).or(pkg_debug_skip);
+    let msg_field = match msg_field(&args) {
+        Ok(msg_field) => msg_field,
+        Err(e) => return e.into_compile_error(),
+    };
+    let wrapper_vis = match wrapper_vis(&args) {
+        Ok(wrapper_vis) => wrapper_vis,
+        Err(e) => return e.into_compile_error(),
+    };
+    let code = match err_code(&args) {
+        Ok(code) => code,
+        Err(e) => return e.into_compile_error(),
+    };
+    let stub_when = match stub_when(&args) {
+        Ok(stub_when) => stub_when,
+        Err(e) => return e.into_compile_error(),
+    };
+    let wrapper_only = hidden.or(wrapper_vis.as_ref().map(|(span, _)| *span));
+    let wrapper = WrapperOptions {
+        vis: wrapper_vis.map(|(_, vis)| vis),
+        hidden: hidden.is_some(),
+        variant_docs: Vec::new(),
+    };
 
     let DeriveInput {
-        attrs,
+        mut attrs,
         vis,
         ident,
         generics,
         mut data,
-    } = parse_macro_input!(input as DeriveInput);
+    } = match syn::parse2(input) {
+        Ok(input) => input,
+        Err(e) => return e.into_compile_error(),
+    };
+
+    let display = match flag(&args, "display_from_doc") {
+        Some(flag) => doc_display(&ident, &generics, &attrs, &data, flag)
+            .unwrap_or_else(Error::into_compile_error),
+        None => TokenStream::new(),
+    };
+
+    let code_const = code.as_ref().map(|code| {
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #ident #ty_generics #where_clause {
+                /// Stable code of this error, rendered before its message.
+                #[allow(dead_code)]
+                #vis const ERR_CODE: &'static str = #code;
+            }
+        }
+    });
+    let code_entry = code
+        .as_ref()
+        .filter(|_| cfg!(feature = "code_registry"))
+        .map(|code| {
+            quote! {
+                const _: () = {
+                    #[::bare_err_tree::__linkme::distributed_slice(::bare_err_tree::ERR_CODES)]
+                    #[linkme(crate = ::bare_err_tree::__linkme)]
+                    static ERR_CODE_ENTRY: (&'static str, &'static str) = (
+                        ::core::concat!(::core::module_path!(), "::", ::core::stringify!(#ident)),
+                        #code,
+                    );
+                };
+            }
+        });
 
     let generated = match data {
         // Only structs are directly valid for injecting the hidden field
         Data::Struct(ref mut data) => {
-            let errs: Vec<_> = get_struct_macros(data).collect();
+            let errs: Vec<_> = get_struct_macros(data, infer_sources, namespaced_only).collect();
+            let defaults = match builder_defaults(&data.fields) {
+                Ok(defaults) => defaults,
+                Err(e) => return e.into_compile_error(),
+            };
 
-            if let Some(name_attribute) = name_attribute {
-                foreign_err_tree(
-                    &ident,
+            if let (Some(auto), false) = (auto, errs.is_empty()) {
+                clean_struct_macros(data, namespaced_only);
+                auto_annotated(auto).into_compile_error()
+            } else if let (Some(default), None) = (defaults.first(), builder) {
+                clean_struct_macros(data, namespaced_only);
+                Error::new(
+                    default.span(),
+                    "field defaults require '#[err_tree(builder)]'",
+                )
+                .into_compile_error()
+            } else if let (Some(_), Some(builder)) = (name_attribute, builder) {
+                clean_struct_macros(data, namespaced_only);
+                Error::new(builder, "builder is not supported on wrapped types")
+                    .into_compile_error()
+            } else if let (Some(_), Some(default)) = (name_attribute, default) {
+                clean_struct_macros(data, namespaced_only);
+                Error::new(default, "default is not supported on wrapped types")
+                    .into_compile_error()
+            } else if let (None, Some(wrapper_only)) = (name_attribute, wrapper_only) {
+                clean_struct_macros(data, namespaced_only);
+                Error::new(wrapper_only, "vis and hidden require a wrapper").into_compile_error()
+            } else if let (Some(_), Some((pkg_attrs, _))) = (name_attribute, &pkg_attrs) {
+                clean_struct_macros(data, namespaced_only);
+                Error::new(*pkg_attrs, "pkg_attrs is not supported on wrapped types")
+                    .into_compile_error()
+            } else if let (Some(_), Some(external_pkg)) = (name_attribute, external_pkg) {
+                clean_struct_macros(data, namespaced_only);
+                Error::new(
+                    external_pkg,
+                    "external_pkg is not supported on wrapped types",
+                )
+                .into_compile_error()
+            } else if let (Some(_), Some((pkg_attrs, _))) = (external_pkg, &pkg_attrs) {
+                clean_struct_macros(data, namespaced_only);
+                Error::new(
+                    *pkg_attrs,
+                    "pkg_attrs is not supported with external_pkg, which adds no hidden field",
+                )
+                .into_compile_error()
+            } else if let (Some(external_pkg), false) =
+                (external_pkg, cfg!(feature = "external_pkg"))
+            {
+                clean_struct_macros(data, namespaced_only);
+                Error::new(
+                    external_pkg,
+                    "external_pkg requires the `external_pkg` feature",
+                )
+                .into_compile_error()
+            } else if let (Some(_), Some((stub_when, _))) = (name_attribute, &stub_when) {
+                clean_struct_macros(data, namespaced_only);
+                Error::new(*stub_when, "stub_when is not supported on wrapped types")
+                    .into_compile_error()
+            } else if let (Some(_), Some((stub_when, _))) = (external_pkg, &stub_when) {
+                clean_struct_macros(data, namespaced_only);
+                Error::new(
+                    *stub_when,
+                    "stub_when is not supported with external_pkg, which adds no hidden field",
+                )
+                .into_compile_error()
+            } else if let (Some(_), Some(pkg_naming)) = (name_attribute, pkg_naming) {
+                clean_struct_macros(data, namespaced_only);
+                Error::new(
+                    pkg_naming,
+                    "pkg_field and pkg_debug_skip are not supported on wrapped types",
+                )
+                .into_compile_error()
+            } else if let (Some(_), Some(pkg_naming)) = (external_pkg, pkg_naming) {
+                clean_struct_macros(data, namespaced_only);
+                Error::new(
+                    pkg_naming,
+                    "pkg_field and pkg_debug_skip are not supported with external_pkg, which adds no hidden field",
+                )
+                .into_compile_error()
+            } else if let (Some(pkg_field), Fields::Unnamed(_)) = (&pkg_field, &data.fields) {
+                clean_struct_macros(data, namespaced_only);
+                Error::new(
+                    pkg_field.span(),
+                    "pkg_field requires a struct with named fields",
+                )
+                .into_compile_error()
+            } else if let Some(taken) = pkg_field.as_ref().filter(|pkg_field| {
+                data.fields
+                    .iter()
+                    .any(|field| field.ident.as_ref() == Some(*pkg_field))
+            }) {
+                clean_struct_macros(data, namespaced_only);
+                Error::new(taken.span(), "pkg_field names an existing field").into_compile_error()
+            } else if let Some(name_attribute) = name_attribute {
+                clean_struct_macros(data, namespaced_only);
+                foreign_err_tree(
+                    &ident,
                     &vis,
                     &attrs,
                     name_attribute,
                     &generics,
                     &errs,
-                    Foreign::Struct,
+                    Foreign::Struct(msg_field.as_ref()),
+                    StructOptions {
+                        debug_summary,
+                        code: code.as_ref(),
+                        auto: auto.is_some(),
+                        ..StructOptions::default()
+                    },
+                    wrapper,
                 )
             } else {
-                clean_struct_macros(data);
-                err_tree_struct(&ident, &vis, &generics, data, &errs, Foreign::Not)
+                clean_struct_macros(data, namespaced_only);
+                if let Some(pkg_debug_skip) = pkg_debug_skip {
+                    match take_debug_derive(&mut attrs) {
+                        Ok(true) => (),
+                        Ok(false) => {
+                            return Error::new(
+                                pkg_debug_skip,
+                                "pkg_debug_skip requires `#[derive(Debug)]` after `#[err_tree]`",
+                            )
+                            .into_compile_error()
+                        }
+                        Err(e) => return e.into_compile_error(),
+                    }
+                }
+                err_tree_struct(
+                    &ident,
+                    &vis,
+                    &generics,
+                    data,
+                    &errs,
+                    Foreign::Not(msg_field.as_ref()),
+                    StructOptions {
+                        pkg_field: pkg_field.as_ref(),
+                        debug_skip: pkg_debug_skip.is_some(),
+                        builder: builder.map(|_| defaults.as_slice()),
+                        default: default.is_some(),
+                        debug_summary,
+                        repr_c: repr_c(&attrs),
+                        pkg_attrs: pkg_attrs.as_ref().map_or(&[], |(_, attrs)| attrs),
+                        external_pkg,
+                        code: code.as_ref(),
+                        auto: auto.is_some(),
+                        stub_when: stub_when.as_ref().map(|(_, predicate)| predicate),
+                    },
+                )
             }
         }
         // Enums can be handled by a generated wrapping struct
         Data::Enum(ref mut data) => {
-            let errs: Vec<_> = get_enum_macros(data).collect();
-            clean_enum_macros(data);
+            let errs: Vec<_> = get_enum_macros(data, infer_sources, namespaced_only).collect();
+            clean_enum_macros(data, namespaced_only);
+            let wrapper = WrapperOptions {
+                variant_docs: variant_docs(data),
+                ..wrapper
+            };
 
-            if let Some(name_attribute) = name_attribute {
+            if let (Some(auto), false) = (auto, errs.is_empty()) {
+                auto_annotated(auto).into_compile_error()
+            } else if let Some(msg_field) = msg_field {
+                Error::new(msg_field.span(), "msg_field is not supported on enum types")
+                    .into_compile_error()
+            } else if let Some(flag) = builder.or(default) {
+                Error::new(flag, "builder and default are not supported on enum types")
+                    .into_compile_error()
+            } else if let Some(pkg_naming) = pkg_naming {
+                Error::new(
+                    pkg_naming,
+                    "pkg_field and pkg_debug_skip are not supported on enum types",
+                )
+                .into_compile_error()
+            } else if let Some((pkg_attrs, _)) = pkg_attrs {
+                Error::new(pkg_attrs, "pkg_attrs is not supported on enum types")
+                    .into_compile_error()
+            } else if let Some(external_pkg) = external_pkg {
+                Error::new(external_pkg, "external_pkg is not supported on enum types")
+                    .into_compile_error()
+            } else if let Some((stub_when, _)) = stub_when {
+                Error::new(stub_when, "stub_when is not supported on enum types")
+                    .into_compile_error()
+            } else if let Some(name_attribute) = name_attribute {
                 foreign_err_tree(
                     &ident,
                     &vis,
@@ -322,26 +1230,28 @@ pub fn err_tree(args: TokenStream, input: TokenStream) -> TokenStream {
                     &generics,
                     &errs,
                     Foreign::Enum(&ident),
+                    StructOptions {
+                        debug_summary,
+                        code: code.as_ref(),
+                        ..StructOptions::default()
+                    },
+                    wrapper,
                 )
             } else {
-                TokenStream::from(
-                    Error::new(
-                        Span::call_site().into(),
+                Error::new(
+                        Span::call_site(),
                         "err_tree cannot implement directly on an enum type. Use '#[err_tree(WRAPPER)]'",
                     )
-                    .into_compile_error(),
-                )
+                    .into_compile_error()
             }
         }
         // This datatype is barely used -- mostly C interop -- so the lack of
         // functionality doesn't really matter. I've never seen a Union Error.
-        Data::Union(_) => TokenStream::from(
-            Error::new(
-                Span::call_site().into(),
-                "err_tree cannot be annotated on union types",
-            )
-            .into_compile_error(),
-        ),
+        Data::Union(_) => Error::new(
+            Span::call_site(),
+            "err_tree cannot be annotated on union types",
+        )
+        .into_compile_error(),
     };
 
     TokenStream::from_iter([
@@ -352,28 +1262,42 @@ pub fn err_tree(args: TokenStream, input: TokenStream) -> TokenStream {
             generics,
             data,
         }
-        .into_token_stream()
-        .into(),
+        .into_token_stream(),
         generated,
+        display,
+        code_const.unwrap_or_default(),
+        code_entry.unwrap_or_default(),
     ])
 }
 
 #[derive(Debug)]
 enum Foreign<'a> {
-    /// Direct struct generation
-    Not,
+    /// Direct struct generation, with an optional `msg_field`
+    Not(Option<&'a Member>),
     /// Wrapper around a struct, doesn't need a defined ident
-    Struct,
+    Struct(Option<&'a Member>),
     /// Wrapper around an enum, needs an enum ident for pattern matching
     Enum(&'a Ident),
 }
 
+/// Wrapper-only options for [`foreign_err_tree`].
+#[derive(Debug, Default)]
+struct WrapperOptions {
+    /// Visibility of the wrapper, instead of the wrapped type's
+    vis: Option<Visibility>,
+    /// Hide the wrapper from docs
+    hidden: bool,
+    /// Lines of a "Variants" docs section, from the enum variant docs
+    variant_docs: Vec<String>,
+}
+
 /// Generate a foreign wrapper.
 ///
 /// Boilerplates a wrapper notice into docs, copies all struct docs, creates
 /// automatic Deref and From impls, and re-derives known trivial methods.
 ///
 /// Concludes with a call to [`err_tree_struct`].
+#[allow(clippy::too_many_arguments)]
 fn foreign_err_tree(
     ident: &Ident,
     vis: &Visibility,
@@ -382,8 +1306,21 @@ fn foreign_err_tree(
     generics: &Generics,
     errs: &[TreeErr],
     foreign_type: Foreign,
+    options: StructOptions<'_>,
+    wrapper: WrapperOptions,
 ) -> TokenStream {
-    let (_, ty_generics, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let vis = wrapper.vis.as_ref().unwrap_or(vis);
+    let hidden = wrapper.hidden.then(|| quote! { #[doc(hidden)] });
+    let variant_docs = &wrapper.variant_docs;
+    let variants_section = (!variant_docs.is_empty()).then(|| {
+        quote! {
+            #[doc = ""]
+            #[doc = "# Variants"]
+            #[doc = ""]
+            #(#[doc = #variant_docs])*
+        }
+    });
 
     let doc_attrs = attrs.iter().filter(|x| {
         if let Ok(x) = x.meta.require_name_value() {
@@ -397,18 +1334,26 @@ fn foreign_err_tree(
         }
     });
 
-    let ident_link = format!("Wrapper for [`{ident}`] generated by [`bare_err_tree`].");
+    // Intra-doc links do not resolve with the raw identifier prefix
+    let ident_link = format!(
+        "Wrapper for [`{}`] generated by [`bare_err_tree`].",
+        ident.unraw()
+    );
     let wrapper_struct: TokenStream = quote! {
         #[doc = #ident_link]
         ///
         #(#doc_attrs)*
-        #vis struct #name_attribute #generics {
+        #variants_section
+        #hidden
+        #vis struct #name_attribute #generics #where_clause {
             inner: #ident #ty_generics,
         }
-    }
-    .into();
+    };
 
-    let mut wrapper_struct = parse_macro_input!(wrapper_struct as DeriveInput);
+    let mut wrapper_struct = match syn::parse2::<DeriveInput>(wrapper_struct) {
+        Ok(wrapper_struct) => wrapper_struct,
+        Err(e) => return e.into_compile_error(),
+    };
 
     if let Data::Struct(ref mut wrapper_struct_data) = &mut wrapper_struct.data {
         let boilerplate = wrapper_boilerplate(ident, generics, attrs, name_attribute);
@@ -419,9 +1364,10 @@ fn foreign_err_tree(
             wrapper_struct_data,
             errs,
             foreign_type,
+            options,
         );
         TokenStream::from_iter([
-            wrapper_struct.to_token_stream().into(),
+            wrapper_struct.to_token_stream(),
             boilerplate,
             generated_impl,
         ])
@@ -430,6 +1376,44 @@ fn foreign_err_tree(
     }
 }
 
+/// Optional generated constructors and methods.
+#[derive(Debug, Default)]
+struct StructOptions<'a> {
+    /// The defaulted fields, when a builder is requested
+    builder: Option<&'a [Ident]>,
+    /// Generate [`Default`], and `new` for a unit struct
+    default: bool,
+    /// Generate a `tree_summary` method
+    debug_summary: bool,
+    /// Span of `#[repr(C)]`, which places the hidden field last
+    repr_c: Option<Span>,
+    /// Extra attributes on the hidden field
+    pkg_attrs: &'a [Meta],
+    /// Name of the hidden field, instead of `_err_tree_pkg`
+    pkg_field: Option<&'a Ident>,
+    /// Implement [`Debug`] without the hidden field, in place of a derive
+    debug_skip: bool,
+    /// Span of `external_pkg`, which replaces the hidden field with
+    /// `ErrTreePkg::attach`
+    external_pkg: Option<Span>,
+    /// Error code from `code = "..."`
+    code: Option<&'a syn::LitStr>,
+    /// Walk [`Error::source`](core::error::Error::source) instead of
+    /// notated sources
+    auto: bool,
+    /// cfg predicate from `stub_when`, under which the hidden field is a
+    /// `UnitPkg`
+    stub_when: Option<&'a Meta>,
+}
+
+/// Error for source annotations alongside `auto`.
+fn auto_annotated(auto: Span) -> Error {
+    Error::new(
+        auto,
+        "auto walks Error::source in place of source annotations; remove `auto` or the annotations",
+    )
+}
+
 /// Injects `_err_tree_pkg`, the `_tree` constructor, and the `_as_err_tree`
 /// impl.
 fn err_tree_struct(
@@ -439,147 +1423,1267 @@ fn err_tree_struct(
     data: &mut DataStruct,
     errs: &[TreeErr],
     foreign: Foreign<'_>,
+    options: StructOptions<'_>,
 ) -> TokenStream {
+    let StructOptions {
+        builder,
+        default,
+        debug_summary,
+        repr_c,
+        pkg_attrs,
+        pkg_field,
+        debug_skip,
+        external_pkg,
+        code,
+        auto,
+        stub_when,
+    } = options;
+    let pkg_ident = pkg_field
+        .cloned()
+        .unwrap_or_else(|| default_pkg_field(&data.fields));
+    if let (Some(_), Fields::Unnamed(_) | Fields::Unit) = (builder, &data.fields) {
+        return Error::new(
+            Span::call_site(),
+            "builder requires a struct with named fields",
+        )
+        .into_compile_error();
+    }
+    if let (Some(external_pkg), Fields::Unit) = (external_pkg, &data.fields) {
+        return Error::new(
+            external_pkg,
+            "external_pkg cannot tell unit struct errors apart by address",
+        )
+        .into_compile_error();
+    }
+    if let (Some(repr_c), Fields::Unit) = (repr_c, &data.fields) {
+        return Error::new(
+            repr_c,
+            "err_tree cannot keep the layout of a repr(C) unit struct, which gains a hidden field",
+        )
+        .into_compile_error();
+    }
+
     let FieldsStrip {
         bounds: field_bounds,
         idents: field_names,
     } = strip_fields(&data.fields);
 
+    // Named after the wrapped type, rather than the generated wrapper
+    let type_name = match foreign {
+        Foreign::Not(_) => quote! { ::core::any::type_name::<Self>() },
+        Foreign::Struct(_) | Foreign::Enum(_) => {
+            quote! { ::core::any::type_name_of_val(&self.inner) }
+        }
+    };
+
     // Generate the with_pkg call on all notated sources
     let sources = match foreign {
-        Foreign::Not => gen_sources_struct(errs, false),
-        Foreign::Struct => gen_sources_struct(errs, true),
+        Foreign::Not(msg_field) => {
+            gen_sources_struct(errs, false, msg_field, external_pkg.is_some(), auto)
+        }
+        Foreign::Struct(msg_field) => gen_sources_struct(errs, true, msg_field, false, auto),
         Foreign::Enum(ident) => gen_sources_enum(errs, ident),
     };
+    let with_code = code.map(|code| quote! { .with_code(#code) });
+    // Lifetimes and type parameters may not be `'static`, which downcasting needs
+    let downcastable = (generics.lifetimes().next().is_none()
+        && generics.type_params().next().is_none())
+    .then(|| match foreign {
+        Foreign::Not(_) => quote! { .downcastable(self) },
+        Foreign::Struct(_) | Foreign::Enum(_) => quote! { .downcastable(&self.inner) },
+    });
+    let sources = quote! {
+        // Unannotated, so `tree` may borrow from `self` for `downcastable`
+        let func = &mut |tree| {
+            (func)(::bare_err_tree::ErrTree::with_type_name(tree, #type_name) #with_code #downcastable)
+        };
+        #sources
+    };
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    match &mut data.fields {
-        // Struct with fields like { a: usize, b: usize }
-        Fields::Named(fields) => {
-            // Insert the pkg field
-            let field_ident = proc_macro2::Ident::new("_err_tree_pkg", Span::call_site().into());
-            fields.named.push(
-                Field::parse_named
-                    .parse2(quote! { #field_ident: ::bare_err_tree::ErrTreePkg })
-                    .unwrap(),
-            );
-            let field_ident = field_ident.into_token_stream();
+    let debug_summary = debug_summary.then(|| {
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #ident #ty_generics #where_clause {
+                /// Compact summary of the error tree, from `bare_err_tree::compact_summary`.
+                #[allow(dead_code)]
+                #vis fn tree_summary(&self) -> impl ::core::fmt::Display + '_ {
+                    ::bare_err_tree::compact_summary(self)
+                }
+            }
+        }
+    });
 
-            quote! {
+    // External packages are attached after construction, and looked up by
+    // address instead of through a field
+    let pkg_init = external_pkg
+        .is_none()
+        .then(|| pkg_init(&pkg_ident, stub_when));
+    let pkg_member = external_pkg.is_none().then(|| quote! { #pkg_ident });
+    let pkg_lookup = quote! { &::bare_err_tree::ErrTreePkg::attached(self) };
+    let external_drop = external_pkg.map(|_| {
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics ::core::ops::Drop for #ident #ty_generics #where_clause {
+                fn drop(&mut self) {
+                    ::bare_err_tree::ErrTreePkg::detach(&*self);
+                }
+            }
+        }
+    });
+
+    let debug_impl = debug_skip.then(|| debug_without_pkg(ident, generics, &data.fields));
+
+    let pkg_doc = quote! {
+        #[doc = "Error tree metadata, injected by `#[err_tree]`."]
+        #[doc = ""]
+        #[doc = "Construct the type with `Self::_tree` (or a constructor calling it), which"]
+        #[doc = "fills this in, instead of a struct literal."]
+    };
+    let tree_doc = format!(
+        "Constructs [`{}`] from its fields in order{}.",
+        ident.unraw(),
+        if external_pkg.is_none() {
+            ", capturing the error tree package here"
+        } else {
+            ""
+        }
+    );
+
+    // Every field from its own `Default`, with errors at the field
+    let fields_default = default.then(|| {
+        let values = field_bounds.iter().map(|field| {
+            let ty = &field.ty;
+            quote_spanned! {ty.span()=> <#ty as ::core::default::Default>::default() }
+        });
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics ::core::default::Default for #ident #ty_generics #where_clause {
+                #[track_caller]
+                fn default() -> Self {
+                    Self::_tree(#(#values),*)
+                }
+            }
+        }
+    });
+
+    let as_err_tree_impl = |pkg: &TokenStream| {
+        let tree_impl = quote! {
+            #[automatically_derived]
+            impl #impl_generics ::bare_err_tree::AsErrTree for #ident #ty_generics #where_clause {
+                #[track_caller]
+                fn as_err_tree(&self, func: &mut dyn FnMut(::bare_err_tree::ErrTree<'_>)) {
+                    let _err_tree_pkg = #pkg;
+                    #sources
+                }
+            }
+        };
+
+        // Matches the `dyn Error` impl, without requiring `'static`
+        match stub_when {
+            None => tree_impl,
+            Some(predicate) => quote! {
+                #[cfg(not(#predicate))]
+                #tree_impl
+
+                #[cfg(#predicate)]
                 #[automatically_derived]
                 impl #impl_generics ::bare_err_tree::AsErrTree for #ident #ty_generics #where_clause {
-                    #[track_caller]
                     fn as_err_tree(&self, func: &mut dyn FnMut(::bare_err_tree::ErrTree<'_>)) {
-                        let _err_tree_pkg = &self.#field_ident;
-                        #sources
+                        let source = ::core::error::Error::source(self);
+                        let mut sources = source
+                            .as_ref()
+                            .map(|e| e as &dyn ::bare_err_tree::AsErrTree)
+                            .into_iter();
+                        (func)(::bare_err_tree::ErrTree::no_pkg(self, &mut sources))
                     }
                 }
+            },
+        }
+    };
+
+    let generated = match &mut data.fields {
+        // Struct with fields like { a: usize, b: usize }
+        Fields::Named(fields) => {
+            // Insert the pkg field first, ahead of all user fields, unless
+            // that would move the user fields in a C layout
+            let field_ident = &pkg_ident;
+            let pkg = match external_pkg {
+                Some(_) => pkg_lookup,
+                None => {
+                    let prefix = quote! { #pkg_doc #(#[#pkg_attrs])* #field_ident: };
+                    for (idx, field) in pkg_field_decls(&prefix, stub_when).into_iter().enumerate()
+                    {
+                        let field = Field::parse_named.parse2(field).unwrap();
+                        if repr_c.is_some() {
+                            fields.named.push(field);
+                        } else {
+                            fields.named.insert(idx, field);
+                        }
+                    }
+                    quote! { &self.#field_ident }
+                }
+            };
+
+            let builder = builder
+                .map(|defaults| {
+                    gen_builder(
+                        ident,
+                        generics,
+                        &field_bounds,
+                        defaults,
+                        external_pkg.is_none().then_some(&pkg_ident),
+                        stub_when,
+                    )
+                })
+                .unwrap_or_default();
+
+            let tree_impl = as_err_tree_impl(&pkg);
+            quote! {
+                #builder
+
+                #tree_impl
 
                 #[automatically_derived]
                 impl #impl_generics #ident #ty_generics #where_clause {
+                    #[doc = #tree_doc]
                     #[track_caller]
                     #[allow(clippy::too_many_arguments)]
+                    #[allow(dead_code)]
                     fn _tree(#field_bounds) -> Self {
-                        let #field_ident = ::bare_err_tree::ErrTreePkg::new();
+                        #pkg_init
                         Self {
                             #(#field_names,)*
-                            #field_ident
+                            #pkg_member
                         }
                     }
                 }
+
+                #fields_default
+                #external_drop
             }
-            .into()
         }
         // Struct with fields like ( usize, usize )
         Fields::Unnamed(fields) => {
-            // Insert the pkg field
+            // Insert the pkg field last, keeping the user field indices
             let prev_len = syn::Index::from(fields.unnamed.len());
-            fields.unnamed.push(
-                Field::parse_unnamed
-                    .parse2(quote! { ::bare_err_tree::ErrTreePkg })
-                    .unwrap(),
-            );
-
-            quote! {
-                #[automatically_derived]
-                impl #impl_generics ::bare_err_tree::AsErrTree for #ident #ty_generics #where_clause {
-                    #[track_caller]
-                    fn as_err_tree(&self, func: &mut dyn FnMut(::bare_err_tree::ErrTree<'_>)) {
-                        let _err_tree_pkg = &self.#prev_len;
-                        #sources
+            let pkg = match external_pkg {
+                Some(_) => pkg_lookup,
+                None => {
+                    let prefix = quote! { #pkg_doc #(#[#pkg_attrs])* };
+                    for field in pkg_field_decls(&prefix, stub_when) {
+                        fields
+                            .unnamed
+                            .push(Field::parse_unnamed.parse2(field).unwrap());
                     }
+                    quote! { &self.#prev_len }
                 }
+            };
+
+            let tree_impl = as_err_tree_impl(&pkg);
+            quote! {
+                #tree_impl
 
                 #[automatically_derived]
                 impl #impl_generics #ident #ty_generics #where_clause {
+                    #[doc = #tree_doc]
                     #[track_caller]
                     #[allow(clippy::too_many_arguments)]
+                    #[allow(dead_code)]
                     fn _tree(#field_bounds) -> Self {
-                        let _err_tree_pkg = ::bare_err_tree::ErrTreePkg::new();
+                        #pkg_init
                         Self (
                             #(#field_names,)*
-                            _err_tree_pkg
+                            #pkg_member
                         )
                     }
                 }
+
+                #fields_default
+                #external_drop
             }
-            .into()
         }
         // Transmutes a unit struct into a named struct for pkg injection
-        // Adds new and default methods for easy construction
+        // Optionally adds new and default methods for easy construction
         Fields::Unit => {
             // Insert the pkg field
-            let field_ident = proc_macro2::Ident::new("_err_tree_pkg", Span::call_site().into());
-            let mut named = Punctuated::default();
-            named.push(
-                Field::parse_named
-                    .parse2(quote! { #field_ident: ::bare_err_tree::ErrTreePkg })
-                    .unwrap(),
-            );
+            let field_ident = &pkg_ident;
+            let prefix = quote! { #pkg_doc #(#[#pkg_attrs])* #field_ident: };
+            let named = pkg_field_decls(&prefix, stub_when)
+                .into_iter()
+                .map(|field| Field::parse_named.parse2(field).unwrap())
+                .collect();
             let field_ident = field_ident.into_token_stream();
             data.fields = Fields::Named(FieldsNamed {
                 brace_token: Brace::default(),
                 named,
             });
 
-            quote! {
-                #[automatically_derived]
-                impl #impl_generics ::bare_err_tree::AsErrTree for #ident #ty_generics #where_clause {
-                    #[track_caller]
-                    fn as_err_tree(&self, func: &mut dyn FnMut(::bare_err_tree::ErrTree<'_>)) {
-                        let _err_tree_pkg = &self.#field_ident;
-                        #sources
+            let default = default.then(|| {
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::core::default::Default for #ident #ty_generics #where_clause {
+                        #[track_caller]
+                        fn default() -> Self {
+                            Self::_tree()
+                        }
+                    }
+
+                    #[automatically_derived]
+                    impl #impl_generics #ident #ty_generics #where_clause {
+                        #[track_caller]
+                        #[allow(dead_code)]
+                        #vis fn new() -> Self {
+                            Self::_tree()
+                        }
                     }
                 }
+            });
+
+            let tree_impl = as_err_tree_impl(&quote! { &self.#field_ident });
+            quote! {
+                #tree_impl
 
                 #[automatically_derived]
                 impl #impl_generics #ident #ty_generics #where_clause {
+                    #[doc = #tree_doc]
                     #[track_caller]
+                    #[allow(dead_code)]
                     fn _tree() -> Self {
-                        let #field_ident = ::bare_err_tree::ErrTreePkg::new();
+                        #pkg_init
                         Self {
                             #field_ident
                         }
                     }
                 }
 
-                #[automatically_derived]
-                impl #impl_generics ::core::default::Default for #ident #ty_generics #where_clause {
-                    #[track_caller]
-                    fn default() -> Self {
-                        Self::_tree()
-                    }
+                #default
+            }
+        }
+    };
+
+    quote! {
+        #generated
+        #debug_impl
+        #debug_summary
+    }
+}
+
+/// Declarations of the hidden field, as `prefix` (its attributes and name)
+/// followed by its type, once for each side of `stub_when`.
+fn pkg_field_decls(prefix: &TokenStream, stub_when: Option<&Meta>) -> Vec<TokenStream> {
+    match stub_when {
+        None => vec![quote! { #prefix ::bare_err_tree::ErrTreePkg }],
+        Some(predicate) => vec![
+            quote! { #[cfg(not(#predicate))] #prefix ::bare_err_tree::ErrTreePkg },
+            quote! { #[cfg(#predicate)] #prefix ::bare_err_tree::UnitPkg },
+        ],
+    }
+}
+
+/// Binds `pkg_ident` to a new package, or to `()` under `stub_when`.
+fn pkg_init(pkg_ident: &Ident, stub_when: Option<&Meta>) -> TokenStream {
+    match stub_when {
+        None => quote! { let #pkg_ident = ::bare_err_tree::ErrTreePkg::new(); },
+        Some(predicate) => quote! {
+            #[cfg(not(#predicate))]
+            let #pkg_ident = ::bare_err_tree::ErrTreePkg::new();
+            #[cfg(#predicate)]
+            let #pkg_ident: ::bare_err_tree::UnitPkg = ();
+        },
+    }
+}
+
+/// Generates `<ident>TreeBuilder` and the `_tree_builder` constructor.
+fn gen_builder(
+    ident: &Ident,
+    generics: &Generics,
+    fields: &Punctuated<Field, syn::Token![,]>,
+    defaults: &[Ident],
+    pkg_field: Option<&Ident>,
+    stub_when: Option<&Meta>,
+) -> proc_macro2::TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let builder_ident = format_ident!("{}TreeBuilder", ident.unraw());
+
+    let field_names: Vec<_> = fields.iter().flat_map(|f| &f.ident).collect();
+    let field_types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    let field_values = field_names.iter().map(|name| {
+        if defaults.contains(name) {
+            quote! { self.#name.unwrap_or_default() }
+        } else {
+            let missing = format!("missing field `{}`", name.unraw());
+            quote! { ::core::option::Option::expect(self.#name, #missing) }
+        }
+    });
+
+    let pkg_init = pkg_field.map(|pkg_field| pkg_init(pkg_field, stub_when));
+    let pkg_member = pkg_field.map(|pkg_field| quote! { #pkg_field });
+
+    let builder_doc = format!("Builder for [`{}`].", ident.unraw());
+    let capture = if pkg_field.is_some() {
+        ", capturing the error tree package here"
+    } else {
+        ""
+    };
+    let build_doc = format!(
+        "Constructs [`{}`]{capture}.\n\n\
+        Panics if a field without `#[err_tree(default)]` is unset.",
+        ident.unraw()
+    );
+
+    quote! {
+        #[doc = #builder_doc]
+        struct #builder_ident #generics #where_clause {
+            #(#field_names: ::core::option::Option<#field_types>,)*
+        }
+
+        #[automatically_derived]
+        #[allow(dead_code)]
+        impl #impl_generics #builder_ident #ty_generics #where_clause {
+            #(
+                fn #field_names(mut self, #field_names: #field_types) -> Self {
+                    self.#field_names = ::core::option::Option::Some(#field_names);
+                    self
                 }
+            )*
 
-                #[automatically_derived]
-                impl #impl_generics #ident #ty_generics #where_clause {
-                    #[track_caller]
-                    #vis fn new() -> Self {
-                        Self::_tree()
+            #[doc = #build_doc]
+            #[track_caller]
+            fn build(self) -> #ident #ty_generics {
+                #pkg_init
+                #ident {
+                    #(#field_names: #field_values,)*
+                    #pkg_member
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #[allow(dead_code)]
+            fn _tree_builder() -> #builder_ident #ty_generics {
+                #builder_ident {
+                    #(#field_names: ::core::option::Option::None,)*
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use super::expand_err_tree;
+
+    /// Space separated tokens of the expansion.
+    fn expand(args: proc_macro2::TokenStream, input: proc_macro2::TokenStream) -> Vec<String> {
+        expand_err_tree(args, input)
+            .to_string()
+            .split_whitespace()
+            .map(str::to_owned)
+            .collect()
+    }
+
+    #[test]
+    fn deterministic() {
+        let inputs = [
+            (
+                quote! {},
+                quote! {
+                    #[derive(Debug)]
+                    struct Foo {
+                        #[dyn_err]
+                        io: std::io::Error,
+                        code: u8,
+                        #[tree_iter_err]
+                        children: Vec<Foo>,
+                        #[tree_err(collapse)]
+                        nested: Bar,
+                    }
+                },
+            ),
+            (
+                quote! { builder },
+                quote! {
+                    struct Foo {
+                        #[err_tree(default)]
+                        code: u8,
+                        #[dyn_iter_err]
+                        errs: [std::io::Error; 2],
+                    }
+                },
+            ),
+            (
+                quote! { FooWrap },
+                quote! {
+                    #[derive(Debug, Clone)]
+                    enum Foo {
+                        #[dyn_err]
+                        Io(std::io::Error),
+                        #[tree_err]
+                        Bar(Bar),
+                        Other,
+                    }
+                },
+            ),
+            (
+                quote! { builder },
+                quote! {
+                    struct Invalid(u8);
+                },
+            ),
+        ];
+
+        for (args, input) in inputs {
+            assert_eq!(
+                expand(args.clone(), input.clone()),
+                expand(args, input),
+                "expansion differs between identical inputs"
+            );
+        }
+    }
+
+    #[test]
+    fn layout_errors() {
+        let cases = [
+            (
+                quote! {},
+                quote! {
+                    #[repr(C)]
+                    struct Foo;
+                },
+                "err_tree cannot keep the layout of a repr(C) unit struct",
+            ),
+            (
+                quote! { FooWrap, pkg_attrs(allow(unused)) },
+                quote! {
+                    struct Foo(u8);
+                },
+                "pkg_attrs is not supported on wrapped types",
+            ),
+            (
+                quote! { pkg_attrs(allow(unused)) },
+                quote! {
+                    enum Foo {
+                        Bar,
+                    }
+                },
+                "pkg_attrs is not supported on enum types",
+            ),
+        ];
+
+        for (args, input, msg) in cases {
+            let expanded = expand_err_tree(args, input);
+            assert!(
+                expanded.to_string().contains(msg),
+                "missing {msg:?} in {expanded}"
+            );
+        }
+    }
+
+    #[test]
+    fn external_pkg_errors() {
+        let mut cases = vec![
+            (
+                quote! { FooWrap, external_pkg },
+                quote! {
+                    struct Foo(u8);
+                },
+                "external_pkg is not supported on wrapped types",
+            ),
+            (
+                quote! { FooWrap, external_pkg },
+                quote! {
+                    enum Foo {
+                        Bar,
+                    }
+                },
+                "external_pkg is not supported on enum types",
+            ),
+            (
+                quote! { external_pkg, pkg_attrs(allow(unused)) },
+                quote! {
+                    struct Foo(u8);
+                },
+                "pkg_attrs is not supported with external_pkg",
+            ),
+        ];
+        if cfg!(feature = "external_pkg") {
+            cases.push((
+                quote! { external_pkg },
+                quote! {
+                    struct Foo;
+                },
+                "external_pkg cannot tell unit struct errors apart by address",
+            ));
+        } else {
+            cases.push((
+                quote! { external_pkg },
+                quote! {
+                    struct Foo(u8);
+                },
+                "external_pkg requires the `external_pkg` feature",
+            ));
+        }
+
+        for (args, input, msg) in cases {
+            let expanded = expand_err_tree(args, input);
+            assert!(
+                expanded.to_string().contains(msg),
+                "missing {msg:?} in {expanded}"
+            );
+        }
+    }
+
+    #[test]
+    fn stub_when() {
+        for args in [
+            quote! { stub_when(feature = "slim") },
+            quote! { stub_when = "feature = \"slim\"" },
+        ] {
+            let expanded = expand_err_tree(
+                args,
+                quote! {
+                    struct Foo(#[dyn_err] std::io::Error);
+                },
+            )
+            .to_string();
+            for cfg in [
+                r#"# [cfg (not (feature = "slim"))]"#,
+                r#"# [cfg (feature = "slim")]"#,
+            ] {
+                assert_eq!(expanded.matches(cfg).count(), 3, "{cfg} in {expanded}");
+            }
+            assert!(
+                expanded.contains(":: bare_err_tree :: UnitPkg"),
+                "{expanded}"
+            );
+            assert!(expanded.contains("ErrTree :: no_pkg (self"), "{expanded}");
+        }
+
+        let cases = [
+            (
+                quote! { FooWrap, stub_when(feature = "slim") },
+                quote! {
+                    struct Foo(u8);
+                },
+                "stub_when is not supported on wrapped types",
+            ),
+            (
+                quote! { FooWrap, stub_when(feature = "slim") },
+                quote! {
+                    enum Foo {
+                        Bar,
                     }
+                },
+                "stub_when is not supported on enum types",
+            ),
+            (
+                quote! { external_pkg, stub_when(feature = "slim") },
+                quote! {
+                    struct Foo(u8);
+                },
+                "stub_when is not supported with external_pkg",
+            ),
+            (
+                quote! { stub_when = 1 },
+                quote! {
+                    struct Foo(u8);
+                },
+                "stub_when must be a cfg predicate",
+            ),
+        ];
+
+        for (args, input, msg) in cases {
+            let expanded = expand_err_tree(args, input);
+            assert!(
+                expanded.to_string().contains(msg),
+                "missing {msg:?} in {expanded}"
+            );
+        }
+    }
+
+    #[test]
+    fn err_code() {
+        let expanded = expand_err_tree(
+            quote! { code = "E0142" },
+            quote! {
+                struct Foo(u8);
+            },
+        )
+        .to_string();
+        assert!(
+            expanded.contains(r#"const ERR_CODE : & 'static str = "E0142""#),
+            "{expanded}"
+        );
+        assert!(expanded.contains(r#"with_code ("E0142")"#), "{expanded}");
+        assert_eq!(
+            expanded.contains("distributed_slice (:: bare_err_tree :: ERR_CODES)"),
+            cfg!(feature = "code_registry"),
+            "{expanded}"
+        );
+
+        for args in [quote! { code = 142 }, quote! { code = "" }] {
+            let expanded = expand_err_tree(
+                args,
+                quote! {
+                    struct Foo(u8);
+                },
+            );
+            assert!(
+                expanded
+                    .to_string()
+                    .contains("code must be a non-empty string"),
+                "{expanded}"
+            );
+        }
+    }
+
+    #[test]
+    fn auto_sources() {
+        let expanded = expand_err_tree(
+            quote! { auto },
+            quote! {
+                struct Foo {
+                    #[source]
+                    io: std::io::Error,
+                }
+            },
+        )
+        .to_string();
+        assert!(
+            expanded.contains(":: core :: error :: Error :: source (self)"),
+            "{expanded}"
+        );
+
+        for input in [
+            quote! {
+                struct Foo {
+                    #[dyn_err]
+                    io: std::io::Error,
                 }
+            },
+            quote! {
+                enum Foo {
+                    #[dyn_err]
+                    Io(std::io::Error),
+                }
+            },
+        ] {
+            let expanded = expand_err_tree(quote! { FooWrap, auto }, input);
+            assert!(
+                expanded
+                    .to_string()
+                    .contains("auto walks Error::source in place of source annotations"),
+                "{expanded}"
+            );
+        }
+    }
+
+    #[test]
+    fn field_defaults() {
+        let input = quote! {
+            struct Foo {
+                path: String,
+                #[dyn_err]
+                io: std::io::Error,
             }
-            .into()
+        };
+        let default_impl = ":: core :: default :: Default for Foo";
+
+        let expanded = expand_err_tree(quote! { default }, input.clone()).to_string();
+        assert!(expanded.contains(default_impl), "{expanded}");
+        assert!(
+            expanded.contains("Self :: _tree (< String as :: core :: default :: Default > :: default () , < std :: io :: Error as :: core :: default :: Default > :: default ())"),
+            "{expanded}"
+        );
+        assert!(!expanded.contains("fn new"), "{expanded}");
+
+        let expanded = expand_err_tree(quote! {}, input).to_string();
+        assert!(!expanded.contains(default_impl), "{expanded}");
+
+        let expanded = expand_err_tree(quote! { default }, quote! { struct Foo(u8, u16); });
+        assert!(
+            expanded.to_string().contains(
+                "Self :: _tree (< u8 as :: core :: default :: Default > :: default () , < u16 as :: core :: default :: Default > :: default ())"
+            ),
+            "{expanded}"
+        );
+    }
+
+    #[test]
+    fn pkg_field() {
+        let input = quote! {
+            #[derive(Debug, Clone)]
+            struct Foo {
+                _err_tree_pkg: u32,
+            }
+        };
+
+        let expanded =
+            expand_err_tree(quote! { pkg_field = meta, pkg_debug_skip }, input).to_string();
+        assert!(expanded.contains("meta : :: bare_err_tree"), "{expanded}");
+        assert!(expanded.contains("_err_tree_pkg : u32"), "{expanded}");
+        assert!(expanded.contains("# [derive (Clone)]"), "{expanded}");
+        assert!(
+            expanded.contains(":: core :: fmt :: Debug for Foo"),
+            "{expanded}"
+        );
+        assert!(
+            !expanded.contains(". field (\"meta\""),
+            "pkg field in Debug: {expanded}"
+        );
+
+        let expanded = expand_err_tree(
+            quote! { pkg_field = "meta" },
+            quote! {
+                struct Foo {
+                    bar: u8,
+                }
+            },
+        )
+        .to_string();
+        assert!(expanded.contains("meta : :: bare_err_tree"), "{expanded}");
+
+        // The default name steps aside for user fields
+        let expanded = expand_err_tree(
+            quote! {},
+            quote! {
+                struct Foo {
+                    _err_tree_pkg: u8,
+                    _err_tree_pkg_: u16,
+                }
+            },
+        )
+        .to_string();
+        assert!(
+            expanded.contains("_err_tree_pkg__ : :: bare_err_tree"),
+            "{expanded}"
+        );
+        assert!(expanded.contains("& self . _err_tree_pkg__"), "{expanded}");
+
+        let cases = [
+            (
+                quote! { pkg_field = 1 },
+                quote! {
+                    struct Foo {
+                        bar: u8,
+                    }
+                },
+                "pkg_field must be a field name",
+            ),
+            (
+                quote! { pkg_field = "bar" },
+                quote! {
+                    struct Foo {
+                        bar: u8,
+                    }
+                },
+                "pkg_field names an existing field",
+            ),
+            (
+                quote! { pkg_field = meta },
+                quote! {
+                    struct Foo(u8);
+                },
+                "pkg_field requires a struct with named fields",
+            ),
+            (
+                quote! { pkg_debug_skip },
+                quote! {
+                    struct Foo(u8);
+                },
+                "pkg_debug_skip requires `#[derive(Debug)]` after `#[err_tree]`",
+            ),
+            (
+                quote! { FooWrap, pkg_debug_skip },
+                quote! {
+                    #[derive(Debug)]
+                    struct Foo(u8);
+                },
+                "pkg_field and pkg_debug_skip are not supported on wrapped types",
+            ),
+            (
+                quote! { pkg_field = meta },
+                quote! {
+                    enum Foo {
+                        Bar,
+                    }
+                },
+                "pkg_field and pkg_debug_skip are not supported on enum types",
+            ),
+            (
+                quote! { external_pkg, pkg_field = meta },
+                quote! {
+                    struct Foo {
+                        bar: u8,
+                    }
+                },
+                "pkg_field and pkg_debug_skip are not supported with external_pkg",
+            ),
+        ];
+        for (args, input, msg) in cases {
+            let expanded = expand_err_tree(args, input);
+            assert!(
+                expanded.to_string().contains(msg),
+                "missing {msg:?} in {expanded}"
+            );
         }
     }
+
+    #[test]
+    fn with_on_single_item() {
+        let expanded = expand_err_tree(
+            quote! {},
+            quote! {
+                struct Foo {
+                    #[dyn_err(with = Foo::errors)]
+                    io: std::io::Error,
+                }
+            },
+        );
+        assert!(
+            expanded
+                .to_string()
+                .contains("`with` is only valid on `dyn_iter_err` and `tree_iter_err`"),
+            "{expanded}"
+        );
+    }
+
+    #[test]
+    fn slice_sources() {
+        let expanded = expand_err_tree(
+            quote! {},
+            quote! {
+                struct Foo<'a> {
+                    #[tree_slice_err]
+                    trees: &'a [&'a dyn bare_err_tree::AsErrTree],
+                    #[dyn_slice_err]
+                    errs: &'a [&'a dyn core::error::Error],
+                }
+            },
+        )
+        .to_string();
+        assert!(
+            expanded.contains("let trees = self . trees . iter () . copied () ;"),
+            "{expanded}"
+        );
+        assert!(
+            expanded.contains(
+                "let errs = self . errs . iter () . map (:: bare_err_tree :: WrapErr :: tree_dyn) ;"
+            ),
+            "{expanded}"
+        );
+        assert!(!expanded.contains("slice_err"), "{expanded}");
+
+        let expanded = expand_err_tree(
+            quote! {},
+            quote! {
+                struct Foo<'a> {
+                    #[dyn_slice_err(with = Foo::errors)]
+                    errs: &'a [&'a dyn core::error::Error],
+                }
+            },
+        );
+        assert!(
+            expanded
+                .to_string()
+                .contains("`with` is only valid on `dyn_iter_err` and `tree_iter_err`"),
+            "{expanded}"
+        );
+    }
+
+    #[test]
+    fn namespaced_annotations() {
+        let bare = expand_err_tree(
+            quote! {},
+            quote! {
+                struct Foo {
+                    #[dyn_err]
+                    io: std::io::Error,
+                    #[tree_iter_err(collapse)]
+                    children: Vec<Foo>,
+                }
+            },
+        )
+        .to_string();
+        let namespaced = expand_err_tree(
+            quote! {},
+            quote! {
+                struct Foo {
+                    #[err_tree(dyn_err)]
+                    io: std::io::Error,
+                    #[err_tree(tree_iter_err(collapse))]
+                    children: Vec<Foo>,
+                }
+            },
+        )
+        .to_string();
+        assert_eq!(namespaced, bare);
+
+        // Another macro's `tree_err` is left alone
+        let expanded = expand_err_tree(
+            quote! { namespaced_only },
+            quote! {
+                struct Foo {
+                    #[tree_err]
+                    other: Other,
+                    #[err_tree(dyn_err)]
+                    io: std::io::Error,
+                }
+            },
+        )
+        .to_string();
+        assert!(expanded.contains("# [tree_err] other"), "{expanded}");
+        assert!(!expanded.contains("err_tree (dyn_err)"), "{expanded}");
+        assert!(!expanded.contains("self . other"), "{expanded}");
+        assert!(expanded.contains("self . io"), "{expanded}");
+
+        let expanded = expand_err_tree(
+            quote! { FooWrap },
+            quote! {
+                enum Foo {
+                    #[err_tree(tree_err)]
+                    Bar(Bar),
+                }
+            },
+        )
+        .to_string();
+        assert!(!expanded.contains("err_tree (tree_err)"), "{expanded}");
+        assert!(expanded.contains("Foo :: Bar (x) =>"), "{expanded}");
+    }
+
+    #[test]
+    fn via_sources() {
+        let expanded = expand_err_tree(
+            quote! {},
+            quote! {
+                struct Foo {
+                    #[tree_err(via = cause)]
+                    state: State,
+                    #[dyn_err(collapse, via = io)]
+                    conn: Conn,
+                }
+            },
+        )
+        .to_string();
+        assert!(
+            expanded.contains("tree_err_state (self . cause ())"),
+            "{expanded}"
+        );
+        assert!(expanded.contains("(self . io ())"), "{expanded}");
+        assert!(!expanded.contains("self . state"), "{expanded}");
+        assert!(!expanded.contains("self . conn"), "{expanded}");
+
+        let error = |args, input| expand_err_tree(args, input).to_string();
+        assert!(error(
+            quote! {},
+            quote! {
+                struct Foo {
+                    #[tree_iter_err(via = causes)]
+                    errs: Vec<Foo>,
+                }
+            },
+        )
+        .contains("`via` is only valid on `dyn_err` and `tree_err`"));
+        assert!(error(
+            quote! { FooWrap },
+            quote! {
+                enum Foo {
+                    #[dyn_err(via = cause)]
+                    Bar(Bar),
+                }
+            },
+        )
+        .contains("`via` is only valid on struct fields"));
+    }
+
+    #[test]
+    fn display_from_doc_errors() {
+        let cases = [
+            (
+                quote! {
+                    /// Doc message
+                    #[derive(Debug, thiserror::Error)]
+                    #[error("attribute message")]
+                    struct Foo;
+                },
+                "display_from_doc conflicts with this Display implementation",
+            ),
+            (
+                quote! {
+                    /// Doc message
+                    #[derive(Debug, derive_more::Display)]
+                    struct Foo;
+                },
+                "display_from_doc conflicts with this Display implementation",
+            ),
+            (
+                quote! {
+                    #[derive(Debug)]
+                    enum Foo {
+                        /// Documented
+                        First,
+                        Second,
+                    }
+                },
+                "display_from_doc requires a doc comment",
+            ),
+        ];
+
+        for (input, msg) in cases {
+            let expanded = expand_err_tree(quote! { FooWrap, display_from_doc }, input);
+            assert!(
+                expanded.to_string().contains(msg),
+                "missing {msg:?} in {expanded}"
+            );
+        }
+    }
+
+    #[test]
+    fn wrapper_docs() {
+        let expanded = expand_err_tree(
+            quote! { FooWrap, vis = "pub(crate)", hidden },
+            quote! {
+                /// Type docs
+                pub enum Foo {
+                    /// Reading failed.
+                    ///
+                    /// Later paragraphs are left out.
+                    #[dyn_err]
+                    Io(std::io::Error),
+                    /// Timed out
+                    /// after a while
+                    Timeout,
+                    Other,
+                }
+            },
+        )
+        .to_string();
+
+        for expected in [
+            r##"# [doc = r" Type docs"] # [doc = ""] # [doc = "# Variants"] # [doc = ""]"##,
+            r#"# [doc = "* `Io`: Reading failed."]"#,
+            r#"# [doc = "* `Timeout`: Timed out after a while"]"#,
+            r#"# [doc = "* `Other`"] # [doc (hidden)] pub (crate) struct FooWrap"#,
+            "pub enum Foo",
+        ] {
+            assert!(
+                expanded.contains(expected),
+                "missing {expected:?} in {expanded}"
+            );
+        }
+        assert!(!expanded.contains("Reading failed. Later"));
+
+        // Undocumented variants add no section, and the wrapper keeps the type visibility
+        let expanded = expand_err_tree(
+            quote! { FooWrap },
+            quote! {
+                pub enum Foo {
+                    Other,
+                }
+            },
+        )
+        .to_string();
+        assert!(!expanded.contains("Variants"));
+        assert!(!expanded.contains("hidden"));
+        assert!(expanded.contains("pub struct FooWrap"));
+
+        let expanded = expand_err_tree(quote! { hidden }, quote! { struct Foo; }).to_string();
+        assert!(expanded.contains("vis and hidden require a wrapper"));
+    }
+
+    #[test]
+    fn reorder_is_local() {
+        let original = expand(
+            quote! {},
+            quote! {
+                struct Foo {
+                    #[dyn_err]
+                    io: std::io::Error,
+                    first: u8,
+                    second: u16,
+                }
+            },
+        );
+        let reordered = expand(
+            quote! {},
+            quote! {
+                struct Foo {
+                    #[dyn_err]
+                    io: std::io::Error,
+                    second: u16,
+                    first: u8,
+                }
+            },
+        );
+
+        assert_eq!(original.len(), reordered.len());
+        assert_ne!(original, reordered);
+
+        let swapped = ["first", "second", "u8", "u16"];
+        for (lhs, rhs) in original.iter().zip(&reordered) {
+            assert!(
+                lhs == rhs || (swapped.contains(&lhs.as_str()) && swapped.contains(&rhs.as_str())),
+                "{lhs} changed to {rhs}"
+            );
+        }
+    }
+
+    #[test]
+    fn wrapper_where_clause() {
+        let expanded = expand_err_tree(
+            quote! { FooWrap },
+            quote! {
+                struct Foo<T = std::io::Error>(#[dyn_err] T)
+                where
+                    T: std::error::Error + 'static;
+            },
+        )
+        .to_string();
+
+        let wrapper = expanded
+            .split_once("struct FooWrap")
+            .map(|(_, wrapper)| wrapper.trim_start())
+            .unwrap();
+        assert!(
+            wrapper.starts_with(
+                "< T = std :: io :: Error > where T : std :: error :: Error + 'static"
+            ),
+            "{wrapper}"
+        );
+        assert!(!expanded.contains("# [dyn_err]"), "{expanded}");
+        assert!(expanded.contains("self . 0"), "{expanded}");
+    }
+
+    #[cfg(feature = "test_util")]
+    #[test]
+    fn shape_errors() {
+        let error = |input: proc_macro2::TokenStream| {
+            syn::parse2::<crate::shape::AssertShape>(input)
+                .err()
+                .map(|e| e.to_string())
+        };
+
+        assert_eq!(error(quote! { err, { "a" => ["b", "c" => []] } }), None);
+        assert_eq!(error(quote! { err, { "a" }, }), None);
+        assert_eq!(
+            error(quote! { err, { a } }).as_deref(),
+            Some("expected an error message string literal")
+        );
+        assert_eq!(
+            error(quote! { err, { "a" => [b] } }).as_deref(),
+            Some("expected an error message string literal")
+        );
+        assert_eq!(
+            error(quote! { err, { "a", "b" } }).as_deref(),
+            Some("expected a single root error")
+        );
+        assert_eq!(
+            error(quote! { err, { "a" }, extra }).as_deref(),
+            Some("unexpected tokens after the tree shape")
+        );
+    }
 }
@@ -0,0 +1,92 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{
+    braced, bracketed,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Error, Expr, LitStr, Token,
+};
+
+/// Input of `assert_tree_shape!`: `err, { "msg" => [ ... ] }`.
+pub struct AssertShape {
+    err: Expr,
+    root: ShapeNode,
+}
+
+/// An expected error message, with its sources when followed by `=> [...]`.
+struct ShapeNode {
+    msg: LitStr,
+    children: Punctuated<ShapeNode, Token![,]>,
+}
+
+impl Parse for AssertShape {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let err = input.parse()?;
+        input.parse::<Token![,]>()?;
+
+        let content;
+        braced!(content in input);
+        let root = content.parse()?;
+        if !content.is_empty() {
+            return Err(content.error("expected a single root error"));
+        }
+
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+        }
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens after the tree shape"));
+        }
+        Ok(Self { err, root })
+    }
+}
+
+impl Parse for ShapeNode {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let msg = input
+            .parse()
+            .map_err(|e| Error::new(e.span(), "expected an error message string literal"))?;
+
+        let children = if input.peek(Token![=>]) {
+            input.parse::<Token![=>]>()?;
+            let content;
+            bracketed!(content in input);
+            Punctuated::parse_terminated(&content)?
+        } else {
+            Punctuated::new()
+        };
+        Ok(Self { msg, children })
+    }
+}
+
+impl ToTokens for ShapeNode {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self { msg, children } = self;
+        let children = children.iter();
+        tokens.extend(quote! {
+            ::bare_err_tree::Shape::new(#msg, [#(#children),*])
+        });
+    }
+}
+
+impl ToTokens for AssertShape {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self { err, root } = self;
+        tokens.extend(quote! {
+            match (&::bare_err_tree::tree_shape(&#err), &#root) {
+                (actual, expected) => ::core::assert!(
+                    *actual == *expected,
+                    "error tree shape mismatch\n  actual:\n{}\nexpected:\n{}",
+                    actual,
+                    expected,
+                ),
+            }
+        });
+    }
+}